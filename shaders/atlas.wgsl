@@ -0,0 +1,49 @@
+struct Viewport {
+    size: vec2<f32>,
+}
+
+struct QuadInput {
+    @location(0) corner: vec2<f32>,
+}
+
+struct InstanceInput {
+    // physical pixels from the top left corner of the window
+    @location(1) rect: vec4<f32>,
+    @location(2) uv: vec4<f32>,
+    @location(3) page: u32,
+    @location(4) opacity: f32,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) @interpolate(flat) page: u32,
+    @location(2) opacity: f32,
+};
+
+@group(0) @binding(0)
+var atlas: texture_2d_array<f32>;
+@group(0) @binding(1)
+var atlas_sampler: sampler;
+
+@group(1) @binding(0)
+var<uniform> viewport: Viewport;
+
+@vertex
+fn vs_main(quad: QuadInput, instance: InstanceInput) -> VertexOutput {
+    var output: VertexOutput;
+    let position = mix(instance.rect.xy, instance.rect.zw, quad.corner);
+
+    let clip = position / viewport.size * 2.0 - 1.0;
+    output.clip_position = vec4<f32>(clip.x, -clip.y, 0.0, 1.0);
+    output.uv = mix(instance.uv.xy, instance.uv.zw, quad.corner);
+    output.page = instance.page;
+    output.opacity = instance.opacity;
+    return output;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(atlas, atlas_sampler, in.uv, in.page);
+    return vec4<f32>(color.rgb, color.a * in.opacity);
+}
@@ -0,0 +1,20 @@
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+};
+
+@group(0) @binding(0)
+var source: texture_2d<f32>;
+
+// a single triangle covering the whole target
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    var output: VertexOutput;
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    output.clip_position = vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+    return output;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return textureLoad(source, vec2<i32>(in.clip_position.xy), 0);
+}
@@ -0,0 +1,43 @@
+struct Blur {
+    direction: vec2<f32>,
+    sigma: f32,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+};
+
+const MAX_RADIUS: i32 = 64;
+
+@group(0) @binding(0)
+var source: texture_2d<f32>;
+@group(0) @binding(1)
+var<uniform> blur: Blur;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    var output: VertexOutput;
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    output.clip_position = vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+    return output;
+}
+
+// one axis of a separable gaussian, run once horizontally and once vertically
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let size = vec2<i32>(textureDimensions(source)) - vec2<i32>(1);
+    let center = vec2<i32>(in.clip_position.xy);
+    let direction = vec2<i32>(blur.direction);
+    let radius = min(i32(ceil(blur.sigma * 3.0)), MAX_RADIUS);
+    let falloff = 1.0 / (2.0 * blur.sigma * blur.sigma);
+
+    var sum = vec4<f32>(0.0);
+    var total = 0.0;
+    for (var i = -radius; i <= radius; i++) {
+        let weight = exp(-f32(i * i) * falloff);
+        let coords = clamp(center + direction * i, vec2<i32>(0), size);
+        sum += textureLoad(source, coords, 0) * weight;
+        total += weight;
+    }
+    return sum / total;
+}
@@ -0,0 +1,75 @@
+struct Composite {
+    opacity: f32,
+    blend: u32,
+    backdrop_blur: u32,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+};
+
+const BLEND_MULTIPLY: u32 = 1u;
+const BLEND_SCREEN: u32 = 2u;
+const BLEND_OVERLAY: u32 = 3u;
+
+@group(0) @binding(0)
+var layer: texture_2d<f32>;
+@group(0) @binding(1)
+var backdrop: texture_2d<f32>;
+@group(0) @binding(2)
+var blurred_backdrop: texture_2d<f32>;
+@group(0) @binding(3)
+var<uniform> composite: Composite;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    var output: VertexOutput;
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    output.clip_position = vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+    return output;
+}
+
+fn unpremultiply(color: vec4<f32>) -> vec3<f32> {
+    if color.a <= 0.0 {
+        return vec3<f32>(0.0);
+    }
+    return color.rgb / color.a;
+}
+
+// separable blend functions from the compositing spec, on straight colors
+fn blend(backdrop: vec3<f32>, source: vec3<f32>) -> vec3<f32> {
+    switch composite.blend {
+        case BLEND_MULTIPLY: {
+            return backdrop * source;
+        }
+        case BLEND_SCREEN: {
+            return backdrop + source - backdrop * source;
+        }
+        case BLEND_OVERLAY: {
+            let low = 2.0 * backdrop * source;
+            let high = 1.0 - 2.0 * (1.0 - backdrop) * (1.0 - source);
+            return select(high, low, backdrop <= vec3<f32>(0.5));
+        }
+        default: {
+            return source;
+        }
+    }
+}
+
+// both the layer and the backdrop are premultiplied, the result replaces the backdrop
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let coords = vec2<i32>(in.clip_position.xy);
+    let source = textureLoad(layer, coords, 0) * composite.opacity;
+    var base = textureLoad(backdrop, coords, 0);
+
+    if composite.backdrop_blur == 1u {
+        // the backdrop is blurred wherever the layer drew anything, however transparent
+        let mask = min(textureLoad(layer, coords, 0).a * 255.0, 1.0);
+        base = mix(base, textureLoad(blurred_backdrop, coords, 0), mask);
+    }
+
+    let mixed = blend(unpremultiply(base), unpremultiply(source));
+    let color = (1.0 - base.a) * source.rgb + (1.0 - source.a) * base.rgb + source.a * base.a * mixed;
+    return vec4<f32>(color, source.a + base.a * (1.0 - source.a));
+}
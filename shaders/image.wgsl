@@ -0,0 +1,40 @@
+struct Viewport {
+    size: vec2<f32>,
+}
+
+struct VertexInput {
+    // physical pixels from the top left corner of the window
+    @location(0) position: vec2<f32>,
+    @location(1) uv: vec2<f32>,
+    @location(2) opacity: f32,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) opacity: f32,
+};
+
+@group(0) @binding(0)
+var image: texture_2d<f32>;
+@group(0) @binding(1)
+var image_sampler: sampler;
+
+@group(1) @binding(0)
+var<uniform> viewport: Viewport;
+
+@vertex
+fn vs_main(input: VertexInput) -> VertexOutput {
+    var output: VertexOutput;
+    let clip = input.position / viewport.size * 2.0 - 1.0;
+    output.clip_position = vec4<f32>(clip.x, -clip.y, 0.0, 1.0);
+    output.uv = input.uv;
+    output.opacity = input.opacity;
+    return output;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(image, image_sampler, in.uv);
+    return vec4<f32>(color.rgb, color.a * in.opacity);
+}
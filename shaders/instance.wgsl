@@ -26,5 +26,5 @@ fn vs_main(input: VertexInput, instance: InstanceInput) -> VertexOutput {
 
 @fragment
 fn fs_main(out: VertexOutput) -> @location(0) vec4<f32> {
-    return vec4<f32>(out.color, 0.0);
+    return vec4<f32>(out.color, 1.0);
 }
\ No newline at end of file
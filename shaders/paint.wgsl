@@ -0,0 +1,116 @@
+struct Viewport {
+    size: vec2<f32>,
+}
+
+struct VertexInput {
+    // physical pixels from the top left corner of the window
+    @location(0) position: vec2<f32>,
+    @location(1) color: vec3<f32>,
+}
+
+struct PaintUniform {
+    params: vec4<f32>,
+    kind: u32,
+    spread: u32,
+    stop_count: u32,
+    offsets: array<vec4<f32>, 4>,
+    colors: array<vec4<f32>, 16>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+};
+
+const KIND_SOLID: u32 = 0u;
+const KIND_LINEAR: u32 = 1u;
+const KIND_RADIAL: u32 = 2u;
+const KIND_CONIC: u32 = 3u;
+const SPREAD_REPEAT: u32 = 1u;
+const SPREAD_REFLECT: u32 = 2u;
+const TAU: f32 = 6.2831853;
+
+@group(0) @binding(0)
+var<uniform> paint: PaintUniform;
+
+@group(1) @binding(0)
+var<uniform> viewport: Viewport;
+
+@vertex
+fn vs_main(input: VertexInput) -> VertexOutput {
+    var output: VertexOutput;
+    let clip = input.position / viewport.size * 2.0 - 1.0;
+    output.clip_position = vec4<f32>(clip.x, -clip.y, 0.0, 1.0);
+    return output;
+}
+
+fn stop_offset(index: u32) -> f32 {
+    return paint.offsets[index / 4u][index % 4u];
+}
+
+// position along the gradient for a pixel, before the spread mode is applied
+fn gradient_position(p: vec2<f32>) -> f32 {
+    switch paint.kind {
+        case KIND_LINEAR: {
+            let start = paint.params.xy;
+            let direction = paint.params.zw - start;
+            let length_squared = dot(direction, direction);
+            if length_squared == 0.0 {
+                return 0.0;
+            }
+            return dot(p - start, direction) / length_squared;
+        }
+        case KIND_RADIAL: {
+            if paint.params.z <= 0.0 {
+                return 1.0;
+            }
+            return length(p - paint.params.xy) / paint.params.z;
+        }
+        case KIND_CONIC: {
+            let d = p - paint.params.xy;
+            let turn = (atan2(d.y, d.x) - paint.params.z) / TAU;
+            return turn - floor(turn);
+        }
+        default: {
+            return 0.0;
+        }
+    }
+}
+
+fn spread(t: f32) -> f32 {
+    switch paint.spread {
+        case SPREAD_REPEAT: {
+            return t - floor(t);
+        }
+        case SPREAD_REFLECT: {
+            let half = t * 0.5;
+            return 1.0 - abs((half - floor(half)) * 2.0 - 1.0);
+        }
+        default: {
+            return clamp(t, 0.0, 1.0);
+        }
+    }
+}
+
+// colors are premultiplied and linear, so they are interpolated without dark fringes
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    if paint.stop_count == 0u {
+        return vec4<f32>(0.0);
+    }
+
+    let t = spread(gradient_position(in.clip_position.xy));
+    if t <= stop_offset(0u) {
+        return paint.colors[0];
+    }
+
+    for (var i = 1u; i < paint.stop_count; i++) {
+        let offset = stop_offset(i);
+        if t <= offset {
+            let previous = stop_offset(i - 1u);
+            let amount = (t - previous) / max(offset - previous, 0.00001);
+            return mix(paint.colors[i - 1u], paint.colors[i], amount);
+        }
+    }
+
+    return paint.colors[paint.stop_count - 1u];
+}
@@ -0,0 +1,201 @@
+struct Viewport {
+    size: vec2<f32>,
+}
+
+struct QuadInput {
+    @location(0) corner: vec2<f32>,
+}
+
+struct InstanceInput {
+    // x, y, width, height in physical pixels
+    @location(1) rect: vec4<f32>,
+    // top left, top right, bottom right, bottom left
+    @location(2) radii: vec4<f32>,
+    @location(3) color: vec4<f32>,
+    @location(4) border_color: vec4<f32>,
+    @location(5) shadow_color: vec4<f32>,
+    // offset x, offset y, blur radius, spread
+    @location(6) shadow: vec4<f32>,
+    @location(7) border_width: f32,
+    @location(8) kind: u32,
+    // arcs: start angle, sweep, stroke width
+    @location(9) params: vec4<f32>,
+    // multiplies the whole shape including border and shadow
+    @location(10) opacity: f32,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) position: vec2<f32>,
+    @location(1) @interpolate(flat) rect: vec4<f32>,
+    @location(2) @interpolate(flat) radii: vec4<f32>,
+    @location(3) @interpolate(flat) color: vec4<f32>,
+    @location(4) @interpolate(flat) border_color: vec4<f32>,
+    @location(5) @interpolate(flat) shadow_color: vec4<f32>,
+    @location(6) @interpolate(flat) shadow: vec4<f32>,
+    @location(7) @interpolate(flat) border_width: f32,
+    @location(8) @interpolate(flat) kind: u32,
+    @location(9) @interpolate(flat) params: vec4<f32>,
+    @location(10) @interpolate(flat) opacity: f32,
+};
+
+const KIND_ROUNDED_RECT: u32 = 0u;
+const KIND_ELLIPSE: u32 = 1u;
+const KIND_ARC: u32 = 2u;
+const TAU: f32 = 6.2831853;
+
+@group(0) @binding(0)
+var<uniform> viewport: Viewport;
+
+@vertex
+fn vs_main(quad: QuadInput, instance: InstanceInput) -> VertexOutput {
+    var output: VertexOutput;
+
+    // the quad covers the shape and everything its shadow can reach, plus a pixel for anti aliasing
+    let shadow_reach = instance.shadow.w + instance.shadow.z * 1.5;
+    let shadow_min = instance.rect.xy + instance.shadow.xy - shadow_reach;
+    let shadow_max = instance.rect.xy + instance.rect.zw + instance.shadow.xy + shadow_reach;
+    var bounds_min = instance.rect.xy;
+    var bounds_max = instance.rect.xy + instance.rect.zw;
+    if instance.shadow_color.a > 0.0 {
+        bounds_min = min(bounds_min, shadow_min);
+        bounds_max = max(bounds_max, shadow_max);
+    }
+    bounds_min -= vec2<f32>(1.0);
+    bounds_max += vec2<f32>(1.0);
+
+    // quad corners have y pointing up while pixels have it pointing down
+    let position = mix(bounds_min, bounds_max, vec2<f32>(quad.corner.x, 1.0 - quad.corner.y));
+    let clip = position / viewport.size * 2.0 - 1.0;
+
+    output.clip_position = vec4<f32>(clip.x, -clip.y, 0.0, 1.0);
+    output.position = position;
+    output.rect = instance.rect;
+    output.radii = instance.radii;
+    output.color = instance.color;
+    output.border_color = instance.border_color;
+    output.shadow_color = instance.shadow_color;
+    output.shadow = instance.shadow;
+    output.border_width = instance.border_width;
+    output.kind = instance.kind;
+    output.params = instance.params;
+    output.opacity = instance.opacity;
+    return output;
+}
+
+// signed distance to a box centered on the origin with a radius per corner, y pointing down
+fn rounded_box(p: vec2<f32>, half_size: vec2<f32>, radii: vec4<f32>) -> f32 {
+    let side = select(radii.xw, radii.yz, p.x > 0.0);
+    let radius = min(select(side.x, side.y, p.y > 0.0), min(half_size.x, half_size.y));
+    let q = abs(p) - half_size + radius;
+    return min(max(q.x, q.y), 0.0) + length(max(q, vec2<f32>(0.0))) - radius;
+}
+
+// approximate distance to an ellipse, exact for circles
+fn ellipse(p: vec2<f32>, radii: vec2<f32>) -> f32 {
+    let k0 = length(p / radii);
+    let k1 = length(p / (radii * radii));
+    if k1 == 0.0 {
+        return -min(radii.x, radii.y);
+    }
+    return k0 * (k0 - 1.0) / k1;
+}
+
+// circular arc with round caps, angles are clockwise from the positive x axis
+fn arc(p: vec2<f32>, radius: f32, start: f32, sweep: f32, width: f32) -> f32 {
+    let angle = atan2(p.y, p.x) - start;
+    let relative = angle - floor(angle / TAU) * TAU;
+    if relative <= sweep {
+        return abs(length(p) - radius) - width * 0.5;
+    }
+
+    let first = radius * vec2<f32>(cos(start), sin(start));
+    let last = radius * vec2<f32>(cos(start + sweep), sin(start + sweep));
+    return min(length(p - first), length(p - last)) - width * 0.5;
+}
+
+// grow moves the edge outwards, which is how shadows are spread
+fn shape_distance(p: vec2<f32>, half_size: vec2<f32>, radii: vec4<f32>, kind: u32, params: vec4<f32>, grow: f32) -> f32 {
+    switch kind {
+        case KIND_ELLIPSE: {
+            return ellipse(p, max(half_size + grow, vec2<f32>(0.001)));
+        }
+        case KIND_ARC: {
+            let radius = min(half_size.x, half_size.y) - params.z * 0.5;
+            return arc(p, radius, params.x, params.y, max(params.z + grow * 2.0, 0.0));
+        }
+        default: {
+            return rounded_box(p, max(half_size + grow, vec2<f32>(0.0)), max(radii + grow, vec4<f32>(0.0)));
+        }
+    }
+}
+
+fn erf(x: f32) -> f32 {
+    let s = sign(x);
+    let a = abs(x);
+    var r = 1.0 + (0.278393 + (0.230389 + 0.078108 * (a * a)) * a) * a;
+    r = r * r;
+    return s - s / (r * r);
+}
+
+fn premultiply(color: vec4<f32>) -> vec4<f32> {
+    return vec4<f32>(color.rgb * color.a, color.a);
+}
+
+fn over(top: vec4<f32>, bottom: vec4<f32>) -> vec4<f32> {
+    return top + bottom * (1.0 - top.a);
+}
+
+// width of the anti-aliased edge, the distance changes by about one per pixel unless the shape is
+// scaled or very thin, so it is measured instead of assumed
+fn edge_width(distance: f32) -> f32 {
+    return max(length(vec2<f32>(dpdx(distance), dpdy(distance))), 0.0001);
+}
+
+// analytic coverage of a pixel by the area where the distance is negative
+fn edge_coverage(distance: f32, width: f32) -> f32 {
+    return clamp(0.5 - distance / width, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let half_size = in.rect.zw * 0.5;
+    let center = in.rect.xy + half_size;
+    let distance = shape_distance(in.position - center, half_size, in.radii, in.kind, in.params, 0.0);
+    // derivatives have to be taken outside of branches
+    let aa = edge_width(distance);
+
+    var result = vec4<f32>(0.0);
+
+    if in.shadow_color.a > 0.0 {
+        let shadow_distance = shape_distance(
+            in.position - center - in.shadow.xy,
+            half_size,
+            in.radii,
+            in.kind,
+            in.params,
+            in.shadow.w
+        );
+
+        // the blur radius covers two standard deviations of the gaussian
+        let sigma = in.shadow.z * 0.5;
+        var coverage = edge_coverage(shadow_distance, aa);
+        if sigma > 0.0 {
+            coverage = 0.5 - 0.5 * erf(shadow_distance / (sigma * 1.4142135));
+        }
+
+        // the shadow is only visible around the shape, not through it
+        let outside = 1.0 - edge_coverage(distance, aa);
+        let alpha = in.shadow_color.a * coverage * outside;
+        result = vec4<f32>(in.shadow_color.rgb * alpha, alpha);
+    }
+
+    let coverage = edge_coverage(distance, aa);
+    var shape = premultiply(in.color);
+    if in.border_width > 0.0 {
+        let inner = edge_coverage(distance + in.border_width, aa);
+        shape = mix(premultiply(in.border_color), shape, inner);
+    }
+
+    return over(shape * coverage, result) * in.opacity;
+}
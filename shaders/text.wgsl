@@ -0,0 +1,41 @@
+struct Viewport {
+    size: vec2<f32>,
+}
+
+struct VertexInput {
+    // physical pixels from the top left corner of the window
+    @location(0) position: vec2<f32>,
+    @location(1) uv: vec2<f32>,
+    @location(2) color: vec3<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec3<f32>,
+};
+
+@group(0) @binding(0)
+var atlas: texture_2d<f32>;
+@group(0) @binding(1)
+var atlas_sampler: sampler;
+
+@group(1) @binding(0)
+var<uniform> viewport: Viewport;
+
+@vertex
+fn vs_main(input: VertexInput) -> VertexOutput {
+    var output: VertexOutput;
+    let clip = input.position / viewport.size * 2.0 - 1.0;
+    output.clip_position = vec4<f32>(clip.x, -clip.y, 0.0, 1.0);
+    output.uv = input.uv;
+    output.color = input.color;
+    return output;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // the atlas only stores coverage, so it becomes the alpha of the glyph
+    let coverage = textureSample(atlas, atlas_sampler, in.uv).r;
+    return vec4<f32>(in.color, coverage);
+}
@@ -0,0 +1,254 @@
+// a spring counts as settled once it is this close to its target
+const SPRING_PRECISION: f32 = 0.001;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Easing {
+    Linear,
+    // control points of a css cubic-bezier() curve, x values are clamped between 0 and 1
+    CubicBezier(f32, f32, f32, f32),
+    // a damped spring moving from 0 to 1, its motion is stretched over the duration of the animation
+    Spring { stiffness: f32, damping: f32, mass: f32 }
+}
+
+impl Easing {
+    pub const EASE: Easing = Easing::CubicBezier(0.25, 0.1, 0.25, 1.0);
+    pub const EASE_IN: Easing = Easing::CubicBezier(0.42, 0.0, 1.0, 1.0);
+    pub const EASE_OUT: Easing = Easing::CubicBezier(0.0, 0.0, 0.58, 1.0);
+    pub const EASE_IN_OUT: Easing = Easing::CubicBezier(0.42, 0.0, 0.58, 1.0);
+
+    pub fn spring(stiffness: f32, damping: f32) -> Self {
+        Easing::Spring { stiffness, damping, mass: 1.0 }
+    }
+
+    // maps progress between 0 and 1 to eased progress, springs and some curves overshoot
+    pub fn ease(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match *self {
+            Easing::Linear => t,
+            Easing::CubicBezier(x1, y1, x2, y2) => cubic_bezier(x1.clamp(0.0, 1.0), y1, x2.clamp(0.0, 1.0), y2, t),
+            Easing::Spring { stiffness, damping, mass } => {
+                if t >= 1.0 {
+                    return 1.0;
+                }
+                let spring = SpringCurve::new(stiffness, damping, mass);
+                spring.position(t * spring.settle_time())
+            }
+        }
+    }
+
+    // css timing function names, cubic-bezier(x1, y1, x2, y2) and spring(stiffness, damping[, mass])
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim().to_ascii_lowercase();
+        match value.as_str() {
+            "linear" => return Some(Easing::Linear),
+            "ease" => return Some(Easing::EASE),
+            "ease-in" => return Some(Easing::EASE_IN),
+            "ease-out" => return Some(Easing::EASE_OUT),
+            "ease-in-out" => return Some(Easing::EASE_IN_OUT),
+            _ => {}
+        }
+
+        let (function, arguments) = value.strip_suffix(')')?.split_once('(')?;
+        let arguments = arguments
+            .split(',')
+            .map(|argument| argument.trim().parse::<f32>().ok())
+            .collect::<Option<Vec<_>>>()?;
+
+        match (function.trim(), arguments.as_slice()) {
+            ("cubic-bezier", &[x1, y1, x2, y2]) => Some(Easing::CubicBezier(x1, y1, x2, y2)),
+            ("spring", &[stiffness, damping]) => Some(Easing::spring(stiffness, damping)),
+            ("spring", &[stiffness, damping, mass]) => Some(Easing::Spring { stiffness, damping, mass }),
+            _ => None
+        }
+    }
+
+    // how long a spring takes to settle in seconds, the natural duration for a spring animation
+    pub fn settle_time(&self) -> Option<f32> {
+        match *self {
+            Easing::Spring { stiffness, damping, mass } => Some(SpringCurve::new(stiffness, damping, mass).settle_time()),
+            _ => None
+        }
+    }
+}
+
+impl Default for Easing {
+    fn default() -> Self {
+        Easing::EASE
+    }
+}
+
+fn cubic_bezier(x1: f32, y1: f32, x2: f32, y2: f32, x: f32) -> f32 {
+    // one dimensional bezier with the end points fixed at 0 and 1
+    let curve = |a: f32, b: f32, s: f32| {
+        let inverse = 1.0 - s;
+        3.0 * inverse * inverse * s * a + 3.0 * inverse * s * s * b + s * s * s
+    };
+    let slope = |a: f32, b: f32, s: f32| {
+        let inverse = 1.0 - s;
+        3.0 * inverse * inverse * a + 6.0 * inverse * s * (b - a) + 3.0 * s * s * (1.0 - b)
+    };
+
+    // newton's method converges quickly on most curves, bisection catches flat spots
+    let mut s = x;
+    for _ in 0..8 {
+        let error = curve(x1, x2, s) - x;
+        if error.abs() < 1e-6 {
+            return curve(y1, y2, s);
+        }
+        let derivative = slope(x1, x2, s);
+        if derivative.abs() < 1e-6 {
+            break;
+        }
+        s -= error / derivative;
+    }
+
+    let (mut low, mut high) = (0.0, 1.0);
+    s = x;
+    for _ in 0..32 {
+        let value = curve(x1, x2, s);
+        if (value - x).abs() < 1e-6 {
+            break;
+        }
+        if value < x {
+            low = s;
+        } else {
+            high = s;
+        }
+        s = (low + high) / 2.0;
+    }
+    curve(y1, y2, s)
+}
+
+// closed form solution of a damped harmonic oscillator released at 0 with its rest position at 1
+struct SpringCurve {
+    // undamped angular frequency
+    omega: f32,
+    // damping ratio, 1 is critically damped
+    zeta: f32
+}
+
+impl SpringCurve {
+    fn new(stiffness: f32, damping: f32, mass: f32) -> Self {
+        let stiffness = stiffness.max(0.001);
+        let mass = mass.max(0.001);
+        SpringCurve {
+            omega: (stiffness / mass).sqrt(),
+            zeta: damping.max(0.0) / (2.0 * (stiffness * mass).sqrt())
+        }
+    }
+
+    fn position(&self, time: f32) -> f32 {
+        let SpringCurve { omega, zeta } = *self;
+
+        if zeta < 1.0 {
+            let damped = omega * (1.0 - zeta * zeta).sqrt();
+            let envelope = (-zeta * omega * time).exp();
+            1.0 - envelope * ((damped * time).cos() + zeta * omega / damped * (damped * time).sin())
+        } else if zeta == 1.0 {
+            1.0 - (-omega * time).exp() * (1.0 + omega * time)
+        } else {
+            let root = omega * (zeta * zeta - 1.0).sqrt();
+            let fast = -zeta * omega - root;
+            let slow = -zeta * omega + root;
+            1.0 - (fast * (slow * time).exp() - slow * (fast * time).exp()) / (fast - slow)
+        }
+    }
+
+    fn settle_time(&self) -> f32 {
+        // the slowest decaying term decides when the motion becomes invisible
+        let decay = if self.zeta < 1.0 {
+            self.zeta * self.omega
+        } else {
+            self.omega * (self.zeta - (self.zeta * self.zeta - 1.0).sqrt())
+        };
+        let mut time = (1.0 / SPRING_PRECISION).ln() / decay.max(0.001);
+
+        // near critical damping the decay is slowed down by a linear term, so the estimate is stretched
+        for _ in 0..32 {
+            if (1.0 - self.position(time)).abs() <= SPRING_PRECISION {
+                break;
+            }
+            time *= 1.1;
+        }
+        time
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-3
+    }
+
+    #[test]
+    fn cubic_bezier_matches_css_curves() {
+        for easing in [Easing::EASE, Easing::EASE_IN, Easing::EASE_OUT, Easing::EASE_IN_OUT] {
+            assert!(close(easing.ease(0.0), 0.0));
+            assert!(close(easing.ease(1.0), 1.0));
+        }
+        // reference values of the css timing functions
+        assert!(close(Easing::EASE.ease(0.25), 0.4094));
+        assert!(close(Easing::EASE_IN_OUT.ease(0.5), 0.5));
+        assert!(close(Easing::CubicBezier(0.0, 0.0, 1.0, 1.0).ease(0.3), 0.3));
+    }
+
+    #[test]
+    fn cubic_bezier_solves_flat_and_overshooting_curves() {
+        // the x curve has no slope at the start, which newton's method cannot follow
+        let flat = Easing::CubicBezier(1.0, 0.0, 1.0, 0.0);
+        assert!(flat.ease(0.5) < 0.2);
+        assert!(close(flat.ease(1.0), 1.0));
+
+        let back = Easing::CubicBezier(0.3, -0.5, 0.7, 1.5);
+        assert!(back.ease(0.1) < 0.0);
+        assert!(back.ease(0.9) > 1.0);
+    }
+
+    #[test]
+    fn parses_css_timing_functions() {
+        assert_eq!(Easing::parse(" Ease-In-Out "), Some(Easing::EASE_IN_OUT));
+        assert_eq!(Easing::parse("linear"), Some(Easing::Linear));
+        assert_eq!(Easing::parse("cubic-bezier(0.1, 0.7, 1.0, 0.1)"), Some(Easing::CubicBezier(0.1, 0.7, 1.0, 0.1)));
+        assert_eq!(Easing::parse("spring(170, 26)"), Some(Easing::spring(170.0, 26.0)));
+        assert_eq!(Easing::parse("spring(170, 26, 2)"), Some(Easing::Spring { stiffness: 170.0, damping: 26.0, mass: 2.0 }));
+
+        assert_eq!(Easing::parse("cubic-bezier(0.1, 0.7, 1.0)"), None);
+        assert_eq!(Easing::parse("spring(stiff, 26)"), None);
+        assert_eq!(Easing::parse("bounce"), None);
+    }
+
+    #[test]
+    fn springs_start_at_zero_and_end_at_one() {
+        for easing in [Easing::spring(170.0, 26.0), Easing::spring(100.0, 5.0), Easing::spring(100.0, 60.0)] {
+            assert!(close(easing.ease(0.0), 0.0));
+            assert_eq!(easing.ease(1.0), 1.0);
+        }
+    }
+
+    #[test]
+    fn only_underdamped_springs_overshoot() {
+        let sample = |easing: Easing| (1..100).map(move |i| easing.ease(i as f32 / 100.0));
+
+        assert!(sample(Easing::spring(100.0, 5.0)).any(|value| value > 1.0));
+
+        let overdamped: Vec<f32> = sample(Easing::spring(100.0, 60.0)).collect();
+        assert!(overdamped.iter().all(|value| *value <= 1.0));
+        assert!(overdamped.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+
+    #[test]
+    fn springs_settle_within_precision() {
+        for (stiffness, damping) in [(170.0, 26.0), (100.0, 5.0), (100.0, 20.0), (100.0, 60.0)] {
+            let curve = SpringCurve::new(stiffness, damping, 1.0);
+            let time = curve.settle_time();
+            assert!((1.0 - curve.position(time)).abs() <= SPRING_PRECISION, "{} {}", stiffness, damping);
+        }
+
+        let soft = Easing::spring(50.0, 14.0).settle_time().unwrap();
+        let stiff = Easing::spring(400.0, 40.0).settle_time().unwrap();
+        assert!(stiff < soft);
+        assert_eq!(Easing::Linear.settle_time(), None);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,299 @@
+pub mod easing;
+pub mod physics;
+pub mod scheduler;
+pub mod transition;
+
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+use crate::animation::easing::Easing;
+use crate::animation::physics::Simulation;
+use crate::graphics::color::Color;
+use crate::graphics::instance::{InstanceData, InstanceId};
+use crate::graphics::sdf::SdfInstance;
+use crate::render::wgpu::{SdfId, WgpuRenderer};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum AnimationTarget {
+    Instance(InstanceId),
+    Sdf(SdfId)
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Property {
+    Position,
+    Scale,
+    Color,
+    Opacity,
+    CornerRadius
+}
+
+impl Property {
+    // flat instances only have a position and a scale
+    pub fn is_supported(self, target: AnimationTarget) -> bool {
+        match target {
+            AnimationTarget::Instance(_) => matches!(self, Property::Position | Property::Scale),
+            AnimationTarget::Sdf(_) => true
+        }
+    }
+}
+
+// positions are in the units of the target, clip space for flat instances and physical pixels for
+// sdf shapes, where scale grows the shape around its center and corner radii are per corner
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PropertyValue {
+    Position([f32; 2]),
+    Scale([f32; 2]),
+    Color(Color),
+    Opacity(f32),
+    CornerRadius([f32; 4])
+}
+
+impl PropertyValue {
+    pub fn property(&self) -> Property {
+        match self {
+            PropertyValue::Position(_) => Property::Position,
+            PropertyValue::Scale(_) => Property::Scale,
+            PropertyValue::Color(_) => Property::Color,
+            PropertyValue::Opacity(_) => Property::Opacity,
+            PropertyValue::CornerRadius(_) => Property::CornerRadius
+        }
+    }
+
+    // values of different properties do not mix, the start value is kept instead
+    pub fn lerp(&self, other: &PropertyValue, t: f32) -> PropertyValue {
+        let mix = |a: f32, b: f32| a + (b - a) * t;
+
+        match (*self, *other) {
+            (PropertyValue::Position(a), PropertyValue::Position(b)) => {
+                PropertyValue::Position([mix(a[0], b[0]), mix(a[1], b[1])])
+            }
+            (PropertyValue::Scale(a), PropertyValue::Scale(b)) => {
+                PropertyValue::Scale([mix(a[0], b[0]), mix(a[1], b[1])])
+            }
+            (PropertyValue::Color(a), PropertyValue::Color(b)) => PropertyValue::Color(a.lerp_oklch(b, t)),
+            (PropertyValue::Opacity(a), PropertyValue::Opacity(b)) => PropertyValue::Opacity(mix(a, b)),
+            (PropertyValue::CornerRadius(a), PropertyValue::CornerRadius(b)) => {
+                PropertyValue::CornerRadius(std::array::from_fn(|i| mix(a[i], b[i])))
+            }
+            (value, _) => value
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum AnimationError {
+    Unsupported(AnimationTarget, Property),
+    // every keyframe has to animate the same property
+    MixedProperties,
+    NoKeyframes
+}
+
+impl Display for AnimationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AnimationError::Unsupported(target, property) => write!(f, "{:?} cannot animate {:?}", target, property),
+            AnimationError::MixedProperties => write!(f, "keyframes animate different properties"),
+            AnimationError::NoKeyframes => write!(f, "animation has no keyframes")
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Keyframe {
+    // position in the animation between 0 and 1
+    pub offset: f32,
+    pub value: PropertyValue,
+    // easing of the segment that ends at this keyframe, the animation easing when missing
+    pub easing: Option<Easing>
+}
+
+impl Keyframe {
+    pub fn new(offset: f32, value: PropertyValue) -> Self {
+        Keyframe { offset, value, easing: None }
+    }
+
+    pub fn with_easing(self, easing: Easing) -> Self {
+        Keyframe { easing: Some(easing), ..self }
+    }
+}
+
+// without a keyframe at offset 0 the animation starts from the value the target has when it starts
+#[derive(Clone, Debug, PartialEq)]
+pub struct Animation {
+    pub target: AnimationTarget,
+    pub keyframes: Vec<Keyframe>,
+    pub duration: Duration,
+    pub delay: Duration,
+    pub easing: Easing
+}
+
+impl Animation {
+    pub fn to(target: AnimationTarget, value: PropertyValue, duration: Duration) -> Self {
+        Animation::keyframes(target, vec![Keyframe::new(1.0, value)], duration)
+    }
+
+    pub fn keyframes(target: AnimationTarget, mut keyframes: Vec<Keyframe>, duration: Duration) -> Self {
+        keyframes.sort_by(|a, b| a.offset.total_cmp(&b.offset));
+        Animation {
+            target,
+            keyframes,
+            duration,
+            delay: Duration::ZERO,
+            easing: Easing::default()
+        }
+    }
+
+    pub fn from(mut self, value: PropertyValue) -> Self {
+        self.keyframes.retain(|k| k.offset > 0.0);
+        self.keyframes.insert(0, Keyframe::new(0.0, value));
+        self
+    }
+
+    pub fn easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    pub fn property(&self) -> Option<Property> {
+        self.keyframes.first().map(|k| k.value.property())
+    }
+
+    pub fn validate(&self) -> Result<Property, AnimationError> {
+        let property = self.property().ok_or(AnimationError::NoKeyframes)?;
+        if self.keyframes.iter().any(|k| k.value.property() != property) {
+            return Err(AnimationError::MixedProperties);
+        }
+        if !property.is_supported(self.target) {
+            return Err(AnimationError::Unsupported(self.target, property));
+        }
+        Ok(property)
+    }
+
+    // value at progress between 0 and 1, start is used for the implicit keyframe at offset 0
+    pub fn sample(&self, start: PropertyValue, progress: f32) -> PropertyValue {
+        let mut previous = Keyframe::new(0.0, start);
+        for keyframe in &self.keyframes {
+            if progress <= keyframe.offset {
+                let span = keyframe.offset - previous.offset;
+                if span <= 0.0 {
+                    return keyframe.value;
+                }
+                let easing = keyframe.easing.unwrap_or(self.easing);
+                let t = easing.ease((progress - previous.offset) / span);
+                return previous.value.lerp(&keyframe.value, t);
+            }
+            previous = *keyframe;
+        }
+        previous.value
+    }
+}
+
+// a physics simulation driving a property, value turns the simulated position into the property
+pub struct SimulatedAnimation {
+    pub target: AnimationTarget,
+    pub simulation: Box<dyn Simulation>,
+    pub value: Box<dyn Fn(f32) -> PropertyValue + Send>
+}
+
+impl SimulatedAnimation {
+    pub fn new(
+        target: AnimationTarget,
+        simulation: impl Simulation + 'static,
+        value: impl Fn(f32) -> PropertyValue + Send + 'static
+    ) -> Self {
+        SimulatedAnimation {
+            target,
+            simulation: Box::new(simulation),
+            value: Box::new(value)
+        }
+    }
+
+    pub fn validate(&self) -> Result<Property, AnimationError> {
+        let property = (self.value)(self.simulation.position()).property();
+        if !property.is_supported(self.target) {
+            return Err(AnimationError::Unsupported(self.target, property));
+        }
+        Ok(property)
+    }
+}
+
+// the state of a target before an animation touches it, scale is relative to it for sdf shapes
+#[derive(Copy, Clone, Debug)]
+pub(crate) enum TargetState {
+    Instance(InstanceData),
+    Sdf(SdfInstance)
+}
+
+impl TargetState {
+    pub(crate) fn read(renderer: &WgpuRenderer, target: AnimationTarget) -> Option<TargetState> {
+        match target {
+            AnimationTarget::Instance(id) => renderer.instance_data(id).map(TargetState::Instance),
+            AnimationTarget::Sdf(id) => renderer.sdf(id).map(TargetState::Sdf)
+        }
+    }
+
+    pub(crate) fn value(&self, property: Property) -> PropertyValue {
+        match (self, property) {
+            (TargetState::Instance(data), Property::Position) => PropertyValue::Position(data.position),
+            (TargetState::Instance(data), Property::Scale) => PropertyValue::Scale(data.scale),
+            (TargetState::Sdf(sdf), Property::Position) => PropertyValue::Position([sdf.rect[0], sdf.rect[1]]),
+            (TargetState::Sdf(_), Property::Scale) => PropertyValue::Scale([1.0, 1.0]),
+            (TargetState::Sdf(sdf), Property::Color) => {
+                let [r, g, b, a] = sdf.color;
+                PropertyValue::Color(Color::from_linear(r, g, b, a))
+            }
+            (TargetState::Sdf(sdf), Property::Opacity) => PropertyValue::Opacity(sdf.opacity),
+            (TargetState::Sdf(sdf), Property::CornerRadius) => PropertyValue::CornerRadius(sdf.radii),
+            (TargetState::Instance(_), Property::Color) => PropertyValue::Color(Color::WHITE),
+            (TargetState::Instance(_), Property::Opacity) => PropertyValue::Opacity(1.0),
+            (TargetState::Instance(_), Property::CornerRadius) => PropertyValue::CornerRadius([0.0; 4])
+        }
+    }
+}
+
+// writes a single property, the renderer only uploads the entry of the changed target,
+// returns false once the target is gone
+pub(crate) fn apply(renderer: &mut WgpuRenderer, target: AnimationTarget, base: &TargetState, value: PropertyValue) -> bool {
+    match target {
+        AnimationTarget::Instance(id) => {
+            let Some(mut data) = renderer.instance_data(id) else {
+                return false;
+            };
+            match value {
+                PropertyValue::Position(position) => data.position = position,
+                PropertyValue::Scale(scale) => data.scale = scale,
+                _ => return true
+            }
+            renderer.update_instance(id, data);
+        }
+        AnimationTarget::Sdf(id) => {
+            let Some(mut sdf) = renderer.sdf(id) else {
+                return false;
+            };
+            match value {
+                PropertyValue::Position(position) => {
+                    sdf.rect[0] = position[0];
+                    sdf.rect[1] = position[1];
+                }
+                PropertyValue::Scale(scale) => {
+                    let TargetState::Sdf(base) = base else {
+                        return true;
+                    };
+                    let center = [sdf.rect[0] + sdf.rect[2] / 2.0, sdf.rect[1] + sdf.rect[3] / 2.0];
+                    let size = [(base.rect[2] * scale[0]).max(0.0), (base.rect[3] * scale[1]).max(0.0)];
+                    sdf.rect = [center[0] - size[0] / 2.0, center[1] - size[1] / 2.0, size[0], size[1]];
+                }
+                PropertyValue::Color(color) => sdf.color = color.to_linear(),
+                PropertyValue::Opacity(opacity) => sdf.opacity = opacity.clamp(0.0, 1.0),
+                PropertyValue::CornerRadius(radii) => sdf.radii = radii.map(|r| r.max(0.0))
+            }
+            renderer.update_sdf(id, sdf);
+        }
+    }
+    true
+}
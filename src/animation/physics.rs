@@ -0,0 +1,522 @@
+// simulations advance in fixed steps so they behave the same at any frame rate
+pub const FIXED_TIMESTEP: f32 = 1.0 / 240.0;
+// at most this many steps are taken per frame, a long stall is skipped instead of replayed
+const MAX_STEPS_PER_FRAME: u32 = 240;
+// how strongly content resists being pulled past its edge, the value ios uses
+const RUBBER_BAND_COEFFICIENT: f32 = 0.55;
+// velocity that inertia loses per second, as an exponential decay rate
+pub const DEFAULT_FRICTION: f32 = 2.0;
+const DEFAULT_STIFFNESS: f32 = 170.0;
+// how long release velocity is averaged over, in seconds
+const VELOCITY_WINDOW: f32 = 0.1;
+
+// one dimensional motion that is advanced in fixed steps
+pub trait Simulation: Send {
+    fn step(&mut self, dt: f32);
+    fn position(&self) -> f32;
+    fn velocity(&self) -> f32;
+    fn is_done(&self) -> bool;
+}
+
+// feeds frame times into a simulation in fixed steps and carries the remainder to the next frame
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct FixedTimestep {
+    pub step: f32,
+    accumulator: f32
+}
+
+impl FixedTimestep {
+    pub fn new(step: f32) -> Self {
+        FixedTimestep { step: step.max(0.0001), accumulator: 0.0 }
+    }
+
+    // returns the number of steps taken
+    pub fn advance(&mut self, elapsed: f32, simulation: &mut dyn Simulation) -> u32 {
+        self.accumulator += elapsed.max(0.0);
+
+        let mut steps = 0;
+        while self.accumulator >= self.step && !simulation.is_done() {
+            simulation.step(self.step);
+            self.accumulator -= self.step;
+            steps += 1;
+
+            if steps == MAX_STEPS_PER_FRAME {
+                self.accumulator = 0.0;
+                break;
+            }
+        }
+        steps
+    }
+}
+
+impl Default for FixedTimestep {
+    fn default() -> Self {
+        FixedTimestep::new(FIXED_TIMESTEP)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Spring {
+    pub position: f32,
+    pub velocity: f32,
+    pub target: f32,
+    pub stiffness: f32,
+    pub damping: f32,
+    pub mass: f32,
+    // the spring rests once it is this close to the target and slower than this per second
+    pub precision: f32
+}
+
+impl Spring {
+    pub fn new(position: f32, target: f32, stiffness: f32, damping: f32) -> Self {
+        Spring {
+            position,
+            velocity: 0.0,
+            target,
+            stiffness: stiffness.max(0.0),
+            damping: damping.max(0.0),
+            mass: 1.0,
+            precision: 0.01
+        }
+    }
+
+    // the fastest spring that does not overshoot
+    pub fn critically_damped(position: f32, target: f32, stiffness: f32) -> Self {
+        let stiffness = stiffness.max(0.0);
+        Spring::new(position, target, stiffness, 2.0 * stiffness.sqrt())
+    }
+
+    pub fn with_velocity(self, velocity: f32) -> Self {
+        Spring { velocity, ..self }
+    }
+}
+
+impl Default for Spring {
+    fn default() -> Self {
+        Spring::critically_damped(0.0, 0.0, DEFAULT_STIFFNESS)
+    }
+}
+
+impl Simulation for Spring {
+    // semi implicit euler, stable for any reasonable stiffness at the fixed timestep
+    fn step(&mut self, dt: f32) {
+        let force = -self.stiffness * (self.position - self.target) - self.damping * self.velocity;
+        self.velocity += force / self.mass.max(0.001) * dt;
+        self.position += self.velocity * dt;
+
+        if self.is_done() {
+            self.position = self.target;
+            self.velocity = 0.0;
+        }
+    }
+
+    fn position(&self) -> f32 {
+        self.position
+    }
+
+    fn velocity(&self) -> f32 {
+        self.velocity
+    }
+
+    fn is_done(&self) -> bool {
+        (self.position - self.target).abs() < self.precision && self.velocity.abs() < self.precision * 10.0
+    }
+}
+
+// keeps moving after a fling and slows down exponentially
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Inertia {
+    pub position: f32,
+    pub velocity: f32,
+    pub friction: f32,
+    // stops below this speed per second
+    pub min_velocity: f32
+}
+
+impl Inertia {
+    pub fn new(position: f32, velocity: f32) -> Self {
+        Inertia {
+            position,
+            velocity,
+            friction: DEFAULT_FRICTION,
+            min_velocity: 1.0
+        }
+    }
+
+    pub fn with_friction(self, friction: f32) -> Self {
+        Inertia { friction: friction.max(0.0), ..self }
+    }
+
+    // where the motion ends, useful for snapping to items before the fling is over
+    pub fn resting_position(&self) -> f32 {
+        if self.friction <= 0.0 {
+            return self.position;
+        }
+        self.position + self.velocity / self.friction
+    }
+}
+
+impl Simulation for Inertia {
+    // the exact solution of the decay over one step, so the distance does not depend on the step size
+    fn step(&mut self, dt: f32) {
+        let decay = (-self.friction * dt).exp();
+        if self.friction > 0.0 {
+            self.position += self.velocity * (1.0 - decay) / self.friction;
+        } else {
+            self.position += self.velocity * dt;
+        }
+        self.velocity *= decay;
+
+        if self.is_done() {
+            self.velocity = 0.0;
+        }
+    }
+
+    fn position(&self) -> f32 {
+        self.position
+    }
+
+    fn velocity(&self) -> f32 {
+        self.velocity
+    }
+
+    fn is_done(&self) -> bool {
+        self.velocity.abs() < self.min_velocity
+    }
+}
+
+// how far content moves when pulled past its edge by overshoot, approaching dimension the further it is
+// pulled, dimension is usually the size of the viewport
+pub fn rubber_band(overshoot: f32, dimension: f32) -> f32 {
+    if dimension <= 0.0 {
+        return 0.0;
+    }
+    let distance = overshoot.abs();
+    let resisted = (1.0 - 1.0 / (distance * RUBBER_BAND_COEFFICIENT / dimension + 1.0)) * dimension;
+    resisted.copysign(overshoot)
+}
+
+// estimates the release velocity of a drag from its recent positions
+#[derive(Clone, Debug, Default)]
+pub struct VelocityTracker {
+    // seconds and positions
+    samples: Vec<(f32, f32)>
+}
+
+impl VelocityTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, time: f32, position: f32) {
+        self.samples.push((time, position));
+        self.samples.retain(|(sample_time, _)| time - sample_time <= VELOCITY_WINDOW);
+    }
+
+    pub fn velocity(&self) -> f32 {
+        match (self.samples.first(), self.samples.last()) {
+            (Some(&(start_time, start)), Some(&(end_time, end))) if end_time > start_time => {
+                (end - start) / (end_time - start_time)
+            }
+            _ => 0.0
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ScrollPhase {
+    Idle,
+    Dragging,
+    // inertia after a fling
+    Coasting,
+    // springing back inside the bounds after an overscroll
+    Bouncing
+}
+
+// scroll offset along one axis with inertia, rubber banding past the edges and a spring back
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Scroller {
+    pub min: f32,
+    pub max: f32,
+    // size of the visible area, which scales the rubber band
+    pub viewport: f32,
+    pub friction: f32,
+    pub stiffness: f32,
+    phase: ScrollPhase,
+    // where the drag would be without resistance at the edges
+    drag_position: f32,
+    inertia: Inertia,
+    spring: Spring
+}
+
+impl Scroller {
+    pub fn new(min: f32, max: f32, viewport: f32) -> Self {
+        Scroller {
+            min,
+            max: max.max(min),
+            viewport,
+            friction: DEFAULT_FRICTION,
+            stiffness: DEFAULT_STIFFNESS,
+            phase: ScrollPhase::Idle,
+            drag_position: min,
+            inertia: Inertia::new(min, 0.0),
+            spring: Spring::critically_damped(min, min, DEFAULT_STIFFNESS)
+        }
+    }
+
+    pub fn phase(&self) -> ScrollPhase {
+        self.phase
+    }
+
+    pub fn set_bounds(&mut self, min: f32, max: f32, viewport: f32) {
+        self.min = min;
+        self.max = max.max(min);
+        self.viewport = viewport;
+        if self.phase == ScrollPhase::Idle && self.overshoot() != 0.0 {
+            self.bounce(0.0);
+        }
+    }
+
+    // jumps without any motion, clamped to the bounds
+    pub fn scroll_to(&mut self, position: f32) {
+        let position = position.clamp(self.min, self.max);
+        self.phase = ScrollPhase::Idle;
+        self.drag_position = position;
+        self.inertia = Inertia::new(position, 0.0);
+        self.spring.position = position;
+        self.spring.velocity = 0.0;
+    }
+
+    // discrete scrolling like mouse wheel notches and keys, stops any running motion
+    pub fn scroll_by(&mut self, delta: f32) {
+        let position = self.position();
+        self.scroll_to(position + delta);
+    }
+
+    // moves with the pointer, resisting more the further it is pulled past an edge
+    pub fn drag_by(&mut self, delta: f32) {
+        if self.phase != ScrollPhase::Dragging {
+            self.drag_position = self.unresisted(self.position());
+            self.phase = ScrollPhase::Dragging;
+        }
+        self.drag_position += delta;
+
+        let position = if self.drag_position < self.min {
+            self.min + rubber_band(self.drag_position - self.min, self.viewport)
+        } else if self.drag_position > self.max {
+            self.max + rubber_band(self.drag_position - self.max, self.viewport)
+        } else {
+            self.drag_position
+        };
+        self.inertia = Inertia::new(position, 0.0);
+    }
+
+    // moves the position by delta without interrupting a drag or fling, used when content above the
+    // viewport changes size and what is visible should stay in place
+    pub fn shift(&mut self, delta: f32) {
+        self.drag_position += delta;
+        self.inertia.position += delta;
+        self.spring.position += delta;
+        if self.phase == ScrollPhase::Idle {
+            let position = self.position();
+            self.scroll_to(position);
+        }
+    }
+
+    // ends a drag, flinging with the release velocity in units per second
+    pub fn release(&mut self, velocity: f32) {
+        if self.overshoot() != 0.0 {
+            self.bounce(velocity);
+        } else {
+            self.inertia = Inertia::new(self.position(), velocity).with_friction(self.friction);
+            self.phase = if self.inertia.is_done() { ScrollPhase::Idle } else { ScrollPhase::Coasting };
+        }
+    }
+
+    // how far the position is outside the bounds, negative before the start
+    pub fn overshoot(&self) -> f32 {
+        let position = self.position();
+        if position < self.min {
+            position - self.min
+        } else if position > self.max {
+            position - self.max
+        } else {
+            0.0
+        }
+    }
+
+    fn bounce(&mut self, velocity: f32) {
+        let position = self.position();
+        let target = position.clamp(self.min, self.max);
+        self.spring = Spring::critically_damped(position, target, self.stiffness).with_velocity(velocity);
+        self.phase = ScrollPhase::Bouncing;
+    }
+
+    // the drag position that the rubber band turns into position
+    fn unresisted(&self, position: f32) -> f32 {
+        let inverse = |overshoot: f32| {
+            let distance = overshoot.abs().min(self.viewport * 0.999);
+            let raw = self.viewport * distance / (RUBBER_BAND_COEFFICIENT * (self.viewport - distance));
+            raw.copysign(overshoot)
+        };
+
+        if self.viewport <= 0.0 {
+            position
+        } else if position < self.min {
+            self.min + inverse(position - self.min)
+        } else if position > self.max {
+            self.max + inverse(position - self.max)
+        } else {
+            position
+        }
+    }
+}
+
+impl Simulation for Scroller {
+    fn step(&mut self, dt: f32) {
+        match self.phase {
+            ScrollPhase::Coasting => {
+                self.inertia.step(dt);
+                // a fling that reaches an edge carries its momentum into the overscroll
+                if self.overshoot() != 0.0 {
+                    self.bounce(self.inertia.velocity);
+                } else if self.inertia.is_done() {
+                    self.phase = ScrollPhase::Idle;
+                }
+            }
+            ScrollPhase::Bouncing => {
+                self.spring.step(dt);
+                if self.spring.is_done() {
+                    let position = self.spring.position;
+                    self.scroll_to(position);
+                }
+            }
+            ScrollPhase::Idle | ScrollPhase::Dragging => {}
+        }
+    }
+
+    fn position(&self) -> f32 {
+        match self.phase {
+            ScrollPhase::Bouncing => self.spring.position,
+            _ => self.inertia.position
+        }
+    }
+
+    fn velocity(&self) -> f32 {
+        match self.phase {
+            ScrollPhase::Bouncing => self.spring.velocity,
+            ScrollPhase::Coasting => self.inertia.velocity,
+            ScrollPhase::Idle | ScrollPhase::Dragging => 0.0
+        }
+    }
+
+    // a drag is not done, it waits for release
+    fn is_done(&self) -> bool {
+        self.phase == ScrollPhase::Idle
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // runs the simulation at 60 frames per second until it is done or the time is up
+    fn run(simulation: &mut dyn Simulation, seconds: f32) {
+        let mut timestep = FixedTimestep::default();
+        let mut elapsed = 0.0;
+        while !simulation.is_done() && elapsed < seconds {
+            timestep.advance(1.0 / 60.0, simulation);
+            elapsed += 1.0 / 60.0;
+        }
+    }
+
+    #[test]
+    fn springs_settle_on_their_target() {
+        let mut spring = Spring::new(0.0, 100.0, 170.0, 10.0);
+        run(&mut spring, 10.0);
+        assert!(spring.is_done());
+        assert_eq!(spring.position, 100.0);
+        assert_eq!(spring.velocity, 0.0);
+    }
+
+    #[test]
+    fn critically_damped_springs_do_not_overshoot() {
+        let mut spring = Spring::critically_damped(0.0, 100.0, 300.0);
+        let mut timestep = FixedTimestep::default();
+        while !spring.is_done() {
+            timestep.advance(1.0 / 60.0, &mut spring);
+            assert!(spring.position <= 100.0);
+        }
+    }
+
+    #[test]
+    fn inertia_rests_where_predicted() {
+        let mut inertia = Inertia::new(10.0, 2000.0).with_friction(4.0);
+        let predicted = inertia.resting_position();
+        run(&mut inertia, 30.0);
+        assert!(inertia.is_done());
+        // it stops once slower than min_velocity, which leaves at most min_velocity / friction to go
+        assert!((inertia.position - predicted).abs() <= inertia.min_velocity / inertia.friction);
+    }
+
+    #[test]
+    fn long_stalls_are_skipped() {
+        let mut inertia = Inertia::new(0.0, 1_000_000.0).with_friction(0.0);
+        let mut timestep = FixedTimestep::default();
+        assert_eq!(timestep.advance(10.0, &mut inertia), MAX_STEPS_PER_FRAME);
+        assert_eq!(timestep.advance(0.0, &mut inertia), 0);
+    }
+
+    #[test]
+    fn rubber_band_resists_more_the_further_it_is_pulled() {
+        assert_eq!(rubber_band(0.0, 500.0), 0.0);
+        assert_eq!(rubber_band(100.0, 0.0), 0.0);
+        assert_eq!(rubber_band(-100.0, 500.0), -rubber_band(100.0, 500.0));
+
+        let near = rubber_band(100.0, 500.0);
+        let far = rubber_band(1000.0, 500.0);
+        assert!(near > 0.0 && near < 100.0);
+        assert!(far > near && far - near < 900.0);
+        assert!(rubber_band(1_000_000.0, 500.0) < 500.0);
+    }
+
+    #[test]
+    fn flings_coast_to_a_stop() {
+        let mut scroller = Scroller::new(0.0, 10_000.0, 500.0);
+        scroller.release(1000.0);
+        assert_eq!(scroller.phase(), ScrollPhase::Coasting);
+
+        run(&mut scroller, 30.0);
+        assert_eq!(scroller.phase(), ScrollPhase::Idle);
+        assert!((scroller.position() - 1000.0 / DEFAULT_FRICTION).abs() <= 1.0);
+    }
+
+    #[test]
+    fn overscroll_bounces_back_to_the_edge() {
+        let mut scroller = Scroller::new(0.0, 1000.0, 500.0);
+        scroller.drag_by(-200.0);
+        assert!(scroller.position() < 0.0 && scroller.position() > -200.0);
+
+        scroller.release(0.0);
+        assert_eq!(scroller.phase(), ScrollPhase::Bouncing);
+        run(&mut scroller, 10.0);
+        assert_eq!(scroller.phase(), ScrollPhase::Idle);
+        assert_eq!(scroller.position(), 0.0);
+    }
+
+    #[test]
+    fn flings_past_the_edge_bounce_back() {
+        let mut scroller = Scroller::new(0.0, 100.0, 500.0);
+        scroller.release(5000.0);
+        run(&mut scroller, 0.5);
+        assert_eq!(scroller.phase(), ScrollPhase::Bouncing);
+
+        run(&mut scroller, 10.0);
+        assert_eq!(scroller.phase(), ScrollPhase::Idle);
+        assert_eq!(scroller.position(), 100.0);
+    }
+}
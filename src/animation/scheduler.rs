@@ -0,0 +1,413 @@
+use std::future::{Future, IntoFuture};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{watch, Notify};
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+use crate::animation::physics::FixedTimestep;
+use crate::animation::{apply, Animation, AnimationError, AnimationTarget, Property, SimulatedAnimation, TargetState};
+use crate::render::wgpu::WgpuRenderer;
+use crate::render::Renderer;
+
+pub const DEFAULT_FRAME_RATE: u32 = 60;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct AnimationId(pub u64);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AnimationOutcome {
+    Finished,
+    // cancelled explicitly, replaced by another animation of the same property or the target was removed
+    Cancelled
+}
+
+enum Motion {
+    Tween(Animation),
+    Simulated {
+        animation: SimulatedAnimation,
+        clock: FixedTimestep,
+        last: Option<Instant>
+    }
+}
+
+struct ScheduledAnimation {
+    id: AnimationId,
+    target: AnimationTarget,
+    motion: Motion,
+    property: Property,
+    // waits for this animation to finish before starting
+    after: Option<AnimationId>,
+    started: Option<Instant>,
+    // captured once the delay is over, so the animation starts from wherever the target is then
+    base: Option<TargetState>,
+    done: watch::Sender<Option<AnimationOutcome>>
+}
+
+impl ScheduledAnimation {
+    fn key(&self) -> (AnimationTarget, Property) {
+        (self.target, self.property)
+    }
+}
+
+#[derive(Default)]
+struct SchedulerState {
+    animations: Vec<ScheduledAnimation>,
+    next_id: u64
+}
+
+impl SchedulerState {
+    fn schedule(
+        &mut self,
+        motion: Motion,
+        after: Option<AnimationId>
+    ) -> Result<(AnimationId, watch::Receiver<Option<AnimationOutcome>>), AnimationError> {
+        let (target, property) = match &motion {
+            Motion::Tween(animation) => (animation.target, animation.validate()?),
+            Motion::Simulated { animation, .. } => (animation.target, animation.validate()?)
+        };
+        let id = AnimationId(self.next_id);
+        self.next_id += 1;
+
+        let (done, receiver) = watch::channel(None);
+        self.animations.push(ScheduledAnimation {
+            id,
+            target,
+            motion,
+            property,
+            after,
+            started: None,
+            base: None,
+            done
+        });
+        Ok((id, receiver))
+    }
+
+    fn start(&mut self, id: AnimationId, now: Instant) {
+        let Some(animation) = self.animations.iter_mut().find(|a| a.id == id) else {
+            return;
+        };
+        animation.after = None;
+        animation.started = Some(now);
+
+        // a newer animation of the same property takes over
+        let key = animation.key();
+        let replaced: Vec<_> = self.animations
+            .iter()
+            .filter(|a| a.id != id && a.started.is_some() && a.key() == key)
+            .map(|a| a.id)
+            .collect();
+        for replaced in replaced {
+            self.finish(replaced, AnimationOutcome::Cancelled, now);
+        }
+    }
+
+    // chained animations start when their predecessor finishes and are cancelled with it
+    fn finish(&mut self, id: AnimationId, outcome: AnimationOutcome, now: Instant) {
+        let Some(index) = self.animations.iter().position(|a| a.id == id) else {
+            return;
+        };
+        let animation = self.animations.remove(index);
+        animation.done.send_replace(Some(outcome));
+
+        let chained: Vec<_> = self.animations
+            .iter()
+            .filter(|a| a.after == Some(id))
+            .map(|a| a.id)
+            .collect();
+        for next in chained {
+            match outcome {
+                AnimationOutcome::Finished => self.start(next, now),
+                AnimationOutcome::Cancelled => self.finish(next, AnimationOutcome::Cancelled, now)
+            }
+        }
+    }
+}
+
+// runs animations on the tokio runtime, waking up once per frame while any are active
+#[derive(Clone)]
+pub struct FrameScheduler {
+    state: Arc<Mutex<SchedulerState>>,
+    wake: Arc<Notify>,
+    frame_interval: Duration
+}
+
+impl FrameScheduler {
+    pub fn new(frame_rate: u32) -> Self {
+        FrameScheduler {
+            state: Arc::new(Mutex::new(SchedulerState::default())),
+            wake: Arc::new(Notify::new()),
+            frame_interval: Duration::from_secs(1) / frame_rate.max(1)
+        }
+    }
+
+    pub fn animate(&self, animation: Animation) -> Result<AnimationHandle, AnimationError> {
+        let (id, done) = self.state.lock().unwrap().schedule(Motion::Tween(animation), None)?;
+        self.wake.notify_one();
+        Ok(self.handle(id, done))
+    }
+
+    // runs the simulation until it comes to rest, in fixed steps regardless of the frame rate
+    pub fn simulate(&self, animation: SimulatedAnimation) -> Result<AnimationHandle, AnimationError> {
+        let motion = Motion::Simulated {
+            animation,
+            clock: FixedTimestep::default(),
+            last: None
+        };
+        let (id, done) = self.state.lock().unwrap().schedule(motion, None)?;
+        self.wake.notify_one();
+        Ok(self.handle(id, done))
+    }
+
+    pub fn cancel(&self, id: AnimationId) {
+        self.state.lock().unwrap().finish(id, AnimationOutcome::Cancelled, Instant::now());
+    }
+
+    // cancels everything running on the target, for when it is removed
+    pub fn cancel_target(&self, target: AnimationTarget) {
+        let mut state = self.state.lock().unwrap();
+        let ids: Vec<_> = state.animations.iter().filter(|a| a.target == target).map(|a| a.id).collect();
+        for id in ids {
+            state.finish(id, AnimationOutcome::Cancelled, Instant::now());
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.state.lock().unwrap().animations.is_empty()
+    }
+
+    // advances every animation to now, returns whether another frame is needed
+    pub fn tick(&self, renderer: &mut WgpuRenderer, now: Instant) -> bool {
+        let mut state = self.state.lock().unwrap();
+
+        let starting: Vec<_> = state.animations
+            .iter()
+            .filter(|a| a.after.is_none() && a.started.is_none())
+            .map(|a| a.id)
+            .collect();
+        for id in starting {
+            state.start(id, now);
+        }
+
+        let mut finished = Vec::new();
+        let mut lost = Vec::new();
+        for scheduled in state.animations.iter_mut() {
+            let Some(started) = scheduled.started else {
+                continue;
+            };
+            if let Motion::Tween(animation) = &scheduled.motion {
+                if now.saturating_duration_since(started) < animation.delay {
+                    continue;
+                }
+            }
+
+            if scheduled.base.is_none() {
+                scheduled.base = TargetState::read(renderer, scheduled.target);
+            }
+            let Some(base) = scheduled.base else {
+                lost.push(scheduled.id);
+                continue;
+            };
+
+            let (value, done) = match &mut scheduled.motion {
+                Motion::Tween(animation) => {
+                    let elapsed = now.saturating_duration_since(started) - animation.delay;
+                    let progress = if animation.duration.is_zero() {
+                        1.0
+                    } else {
+                        (elapsed.as_secs_f32() / animation.duration.as_secs_f32()).min(1.0)
+                    };
+                    (animation.sample(base.value(scheduled.property), progress), progress >= 1.0)
+                }
+                Motion::Simulated { animation, clock, last } => {
+                    let elapsed = now.saturating_duration_since(last.unwrap_or(started));
+                    *last = Some(now);
+                    clock.advance(elapsed.as_secs_f32(), animation.simulation.as_mut());
+                    ((animation.value)(animation.simulation.position()), animation.simulation.is_done())
+                }
+            };
+
+            if !apply(renderer, scheduled.target, &base, value) {
+                lost.push(scheduled.id);
+            } else if done {
+                finished.push(scheduled.id);
+            }
+        }
+
+        for id in finished {
+            state.finish(id, AnimationOutcome::Finished, now);
+        }
+        for id in lost {
+            state.finish(id, AnimationOutcome::Cancelled, now);
+        }
+
+        !state.animations.is_empty()
+    }
+
+    // renders a frame at the frame rate while animations are running and sleeps otherwise
+    pub fn run(&self, renderer: Arc<tokio::sync::Mutex<WgpuRenderer<'static>>>) -> JoinHandle<()> {
+        let scheduler = self.clone();
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(scheduler.frame_interval);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+            loop {
+                if !scheduler.is_active() {
+                    scheduler.wake.notified().await;
+                    interval.reset_immediately();
+                }
+                interval.tick().await;
+
+                let mut renderer = renderer.lock().await;
+                scheduler.tick(&mut renderer, Instant::now());
+                renderer.render();
+            }
+        })
+    }
+
+    fn handle(&self, id: AnimationId, done: watch::Receiver<Option<AnimationOutcome>>) -> AnimationHandle {
+        AnimationHandle {
+            id,
+            scheduler: self.clone(),
+            done
+        }
+    }
+}
+
+impl Default for FrameScheduler {
+    fn default() -> Self {
+        FrameScheduler::new(DEFAULT_FRAME_RATE)
+    }
+}
+
+// awaiting the handle waits for the animation to end, dropping it leaves the animation running
+pub struct AnimationHandle {
+    pub id: AnimationId,
+    scheduler: FrameScheduler,
+    done: watch::Receiver<Option<AnimationOutcome>>
+}
+
+impl AnimationHandle {
+    pub fn cancel(&self) {
+        self.scheduler.cancel(self.id);
+    }
+
+    pub fn outcome(&self) -> Option<AnimationOutcome> {
+        *self.done.borrow()
+    }
+
+    // starts the animation once this one finishes, it is cancelled if this one is
+    pub fn then(&self, animation: Animation) -> Result<AnimationHandle, AnimationError> {
+        let mut state = self.scheduler.state.lock().unwrap();
+        let pending = state.animations.iter().any(|a| a.id == self.id);
+
+        let after = if pending { Some(self.id) } else { None };
+        let (id, done) = state.schedule(Motion::Tween(animation), after)?;
+        if self.outcome() == Some(AnimationOutcome::Cancelled) {
+            state.finish(id, AnimationOutcome::Cancelled, Instant::now());
+        }
+        drop(state);
+
+        self.scheduler.wake.notify_one();
+        Ok(self.scheduler.handle(id, done))
+    }
+
+    pub async fn finished(&self) -> AnimationOutcome {
+        let mut done = self.done.clone();
+        let outcome = match done.wait_for(Option::is_some).await {
+            Ok(outcome) => *outcome,
+            // the scheduler is gone, so the animation never completes
+            Err(_) => None
+        };
+        outcome.unwrap_or(AnimationOutcome::Cancelled)
+    }
+}
+
+impl IntoFuture for AnimationHandle {
+    type Output = AnimationOutcome;
+    type IntoFuture = Pin<Box<dyn Future<Output = AnimationOutcome> + Send>>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        Box::pin(async move { self.finished().await })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::animation::PropertyValue;
+    use crate::graphics::instance::InstanceId;
+    use crate::render::wgpu::SdfId;
+
+    fn fade(sdf: u32, opacity: f32) -> Animation {
+        Animation::to(AnimationTarget::Sdf(SdfId(sdf)), PropertyValue::Opacity(opacity), Duration::from_millis(100))
+    }
+
+    #[test]
+    fn newer_animations_of_a_property_replace_older_ones() {
+        let mut state = SchedulerState::default();
+        let now = Instant::now();
+        let (first, first_done) = state.schedule(Motion::Tween(fade(0, 0.0)), None).unwrap();
+        let (second, _) = state.schedule(Motion::Tween(fade(0, 1.0)), None).unwrap();
+        let (other, _) = state.schedule(Motion::Tween(fade(1, 1.0)), None).unwrap();
+        assert!(first < second && second < other);
+
+        state.start(first, now);
+        state.start(other, now);
+        state.start(second, now);
+
+        assert_eq!(*first_done.borrow(), Some(AnimationOutcome::Cancelled));
+        assert_eq!(state.animations.iter().map(|a| a.id).collect::<Vec<_>>(), vec![second, other]);
+    }
+
+    #[test]
+    fn chained_animations_start_when_their_predecessor_finishes() {
+        let mut state = SchedulerState::default();
+        let now = Instant::now();
+        let (first, _) = state.schedule(Motion::Tween(fade(0, 0.0)), None).unwrap();
+        let (next, next_done) = state.schedule(Motion::Tween(fade(0, 1.0)), Some(first)).unwrap();
+        state.start(first, now);
+
+        state.finish(first, AnimationOutcome::Finished, now);
+        let next = state.animations.iter().find(|a| a.id == next).unwrap();
+        assert_eq!(next.started, Some(now));
+        assert_eq!(*next_done.borrow(), None);
+    }
+
+    #[test]
+    fn cancelling_cancels_the_whole_chain() {
+        let scheduler = FrameScheduler::default();
+        let first = scheduler.animate(fade(0, 0.0)).unwrap();
+        let second = first.then(fade(0, 1.0)).unwrap();
+        let third = second.then(fade(0, 0.5)).unwrap();
+
+        first.cancel();
+        assert_eq!(first.outcome(), Some(AnimationOutcome::Cancelled));
+        assert_eq!(second.outcome(), Some(AnimationOutcome::Cancelled));
+        assert_eq!(third.outcome(), Some(AnimationOutcome::Cancelled));
+        assert!(!scheduler.is_active());
+    }
+
+    #[test]
+    fn chaining_after_an_ended_animation() {
+        let scheduler = FrameScheduler::default();
+        let cancelled = scheduler.animate(fade(0, 0.0)).unwrap();
+        cancelled.cancel();
+        assert_eq!(cancelled.then(fade(0, 1.0)).unwrap().outcome(), Some(AnimationOutcome::Cancelled));
+
+        let finished = scheduler.animate(fade(1, 0.0)).unwrap();
+        scheduler.state.lock().unwrap().finish(finished.id, AnimationOutcome::Finished, Instant::now());
+        let next = finished.then(fade(1, 1.0)).unwrap();
+        assert_eq!(next.outcome(), None);
+        assert!(scheduler.state.lock().unwrap().animations.iter().any(|a| a.id == next.id && a.after.is_none()));
+    }
+
+    #[test]
+    fn invalid_animations_are_rejected() {
+        let scheduler = FrameScheduler::default();
+        let flat = Animation::to(AnimationTarget::Instance(InstanceId(0)), PropertyValue::Opacity(0.0), Duration::ZERO);
+        assert!(matches!(scheduler.animate(flat), Err(AnimationError::Unsupported(..))));
+        assert!(!scheduler.is_active());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,322 @@
+use std::fmt::{Display, Formatter};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use winit::dpi::PhysicalSize;
+use crate::animation::easing::Easing;
+use crate::animation::scheduler::{AnimationHandle, FrameScheduler};
+use crate::animation::{Animation, AnimationError, AnimationTarget, Property, PropertyValue};
+use crate::designer::point::Point;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TransitionError {
+    UnknownProperty(String),
+    InvalidTiming(String),
+    MissingDuration(String),
+    // the target was never declared or has been removed
+    UnknownTarget(AnimationTarget),
+    Animation(AnimationError)
+}
+
+impl Display for TransitionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TransitionError::UnknownProperty(value) => write!(f, "unknown transition property \"{}\"", value),
+            TransitionError::InvalidTiming(value) => write!(f, "invalid transition timing \"{}\"", value),
+            TransitionError::MissingDuration(value) => write!(f, "transition \"{}\" has no duration", value),
+            TransitionError::UnknownTarget(target) => write!(f, "{:?} has no transitions declared", target),
+            TransitionError::Animation(e) => write!(f, "could not start transition: {}", e)
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Transition {
+    // none transitions every property
+    pub property: Option<Property>,
+    pub duration: Duration,
+    pub easing: Easing,
+    pub delay: Duration
+}
+
+impl Transition {
+    pub fn new(property: Option<Property>, duration: Duration) -> Self {
+        Transition {
+            property,
+            duration,
+            easing: Easing::EASE,
+            delay: Duration::ZERO
+        }
+    }
+
+    // follows css: "opacity 200ms ease-out, position 1s spring(170, 26) 50ms", an optional
+    // "transition:" prefix is ignored, the first time is the duration and the second the delay
+    pub fn parse(declaration: &str) -> Result<Vec<Transition>, TransitionError> {
+        let declaration = declaration.trim();
+        let declaration = declaration.strip_prefix("transition:").unwrap_or(declaration);
+
+        split_outside_parens(declaration, |c| c == ',')
+            .into_iter()
+            .filter(|item| !item.trim().is_empty())
+            .map(Transition::parse_single)
+            .collect()
+    }
+
+    fn parse_single(item: &str) -> Result<Transition, TransitionError> {
+        let tokens = split_outside_parens(item, char::is_whitespace);
+        let mut tokens = tokens.into_iter().filter(|token| !token.is_empty());
+
+        let name = tokens.next().unwrap_or_default();
+        let property = match name.to_ascii_lowercase().as_str() {
+            "all" => None,
+            "position" | "translate" => Some(Property::Position),
+            "scale" => Some(Property::Scale),
+            "color" | "background-color" => Some(Property::Color),
+            "opacity" => Some(Property::Opacity),
+            "corner-radius" | "border-radius" => Some(Property::CornerRadius),
+            _ => return Err(TransitionError::UnknownProperty(name.to_string()))
+        };
+
+        let mut times = Vec::new();
+        let mut easing = None;
+        for token in tokens {
+            if let Some(time) = parse_time(token) {
+                times.push(time);
+            } else if let Some(parsed) = Easing::parse(token) {
+                easing = Some(parsed);
+            } else {
+                return Err(TransitionError::InvalidTiming(token.to_string()));
+            }
+        }
+
+        let Some(&duration) = times.first() else {
+            return Err(TransitionError::MissingDuration(item.trim().to_string()));
+        };
+        if times.len() > 2 {
+            return Err(TransitionError::InvalidTiming(item.trim().to_string()));
+        }
+
+        Ok(Transition {
+            property,
+            duration,
+            easing: easing.unwrap_or(Easing::EASE),
+            delay: times.get(1).copied().unwrap_or(Duration::ZERO)
+        })
+    }
+
+    pub fn applies_to(&self, property: Property) -> bool {
+        self.property.is_none_or(|p| p == property)
+    }
+}
+
+fn parse_time(token: &str) -> Option<Duration> {
+    let seconds = if let Some(value) = token.strip_suffix("ms") {
+        value.parse::<f64>().ok()? / 1000.0
+    } else {
+        token.strip_suffix('s')?.parse::<f64>().ok()?
+    };
+    (seconds >= 0.0).then(|| Duration::from_secs_f64(seconds))
+}
+
+// splits on separators that are not inside a function like cubic-bezier()
+fn split_outside_parens(value: &str, separator: impl Fn(char) -> bool) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+
+    for (index, c) in value.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            c if depth == 0 && separator(c) => {
+                parts.push(&value[start..index]);
+                start = index + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&value[start..]);
+    parts
+}
+
+struct TransitionEntry {
+    target: AnimationTarget,
+    transitions: Vec<Transition>,
+    // where layout puts the target, its position follows when the window is resized
+    anchor: Option<Point>
+}
+
+impl TransitionEntry {
+    // the last matching declaration wins, like in css
+    fn transition(&self, property: Property) -> Option<Transition> {
+        self.transitions.iter().rev().find(|t| t.applies_to(property)).copied()
+    }
+}
+
+#[derive(Default)]
+struct TransitionState {
+    entries: Vec<TransitionEntry>
+}
+
+// shares the scheduler the app runs, so it can only be made from one
+#[derive(Clone)]
+pub struct TransitionManager {
+    state: Arc<Mutex<TransitionState>>,
+    scheduler: FrameScheduler
+}
+
+impl TransitionManager {
+    pub fn new(scheduler: FrameScheduler) -> Self {
+        TransitionManager {
+            state: Arc::new(Mutex::new(TransitionState::default())),
+            scheduler
+        }
+    }
+
+    pub fn declare(&self, target: AnimationTarget, declaration: &str) -> Result<TransitionHandle, TransitionError> {
+        let transitions = Transition::parse(declaration)?;
+
+        let mut state = self.state.lock().unwrap();
+        state.entries.retain(|e| e.target != target);
+        state.entries.push(TransitionEntry {
+            target,
+            transitions,
+            anchor: None
+        });
+
+        Ok(TransitionHandle {
+            target,
+            manager: self.clone()
+        })
+    }
+
+    pub fn remove(&self, target: AnimationTarget) {
+        self.state.lock().unwrap().entries.retain(|e| e.target != target);
+    }
+
+    // moves every anchored target to where layout puts it in the new window size,
+    // through its position transition when it has one, the first target that could not be moved is reported
+    pub fn relayout(&self, size: PhysicalSize<u32>) -> Result<(), TransitionError> {
+        let moves: Vec<_> = self.state
+            .lock()
+            .unwrap()
+            .entries
+            .iter()
+            .filter_map(|e| e.anchor.as_ref().map(|anchor| (e.target, anchor_position(e.target, anchor, size))))
+            .collect();
+
+        let mut result = Ok(());
+        for (target, position) in moves {
+            if let Err(e) = self.set(target, PropertyValue::Position(position)) {
+                result = result.and(Err(e));
+            }
+        }
+        result
+    }
+
+    fn set(&self, target: AnimationTarget, value: PropertyValue) -> Result<AnimationHandle, TransitionError> {
+        let transition = {
+            let state = self.state.lock().unwrap();
+            let entry = state.entries
+                .iter()
+                .find(|e| e.target == target)
+                .ok_or(TransitionError::UnknownTarget(target))?;
+            entry.transition(value.property())
+        };
+
+        // without a transition the value is still set through the scheduler, so it replaces
+        // a transition that is still running instead of being overwritten by it on the next frame
+        let animation = match transition {
+            Some(transition) => Animation::to(target, value, transition.duration)
+                .easing(transition.easing)
+                .delay(transition.delay),
+            None => Animation::to(target, value, Duration::ZERO)
+        };
+
+        self.scheduler.animate(animation).map_err(TransitionError::Animation)
+    }
+}
+
+fn anchor_position(target: AnimationTarget, anchor: &Point, size: PhysicalSize<u32>) -> [f32; 2] {
+    match target {
+        AnimationTarget::Instance(_) => anchor.get_screen_position(size),
+        AnimationTarget::Sdf(_) => anchor.get_pixel_position(size)
+    }
+}
+
+// updates made through the handle interpolate from the current to the new value
+pub struct TransitionHandle {
+    target: AnimationTarget,
+    manager: TransitionManager
+}
+
+impl TransitionHandle {
+    pub fn target(&self) -> AnimationTarget {
+        self.target
+    }
+
+    // the new value is written on the next frame, the returned animation can be awaited
+    // to know when the transition is over
+    pub fn set(&self, value: PropertyValue) -> Result<AnimationHandle, TransitionError> {
+        self.manager.set(self.target, value)
+    }
+
+    pub fn set_transitions(&self, declaration: &str) -> Result<(), TransitionError> {
+        let transitions = Transition::parse(declaration)?;
+        let mut state = self.manager.state.lock().unwrap();
+        if let Some(entry) = state.entries.iter_mut().find(|e| e.target == self.target) {
+            entry.transitions = transitions;
+        }
+        Ok(())
+    }
+
+    // lets layout place the target, it moves to the anchor and follows it when the window is resized
+    pub fn anchor(&self, anchor: Point, size: PhysicalSize<u32>) -> Result<AnimationHandle, TransitionError> {
+        let position = anchor_position(self.target, &anchor, size);
+        {
+            let mut state = self.manager.state.lock().unwrap();
+            if let Some(entry) = state.entries.iter_mut().find(|e| e.target == self.target) {
+                entry.anchor = Some(anchor);
+            }
+        }
+        self.set(PropertyValue::Position(position))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_a_list_of_transitions() {
+        let transitions = Transition::parse("transition: opacity 200ms ease-out, position 1s spring(170, 26) 50ms").unwrap();
+        assert_eq!(transitions, vec![
+            Transition {
+                property: Some(Property::Opacity),
+                duration: Duration::from_millis(200),
+                easing: Easing::EASE_OUT,
+                delay: Duration::ZERO
+            },
+            Transition {
+                property: Some(Property::Position),
+                duration: Duration::from_secs(1),
+                easing: Easing::spring(170.0, 26.0),
+                delay: Duration::from_millis(50)
+            }
+        ]);
+    }
+
+    #[test]
+    fn all_applies_to_every_property() {
+        let transitions = Transition::parse("all 0.5s").unwrap();
+        assert_eq!(transitions[0].easing, Easing::EASE);
+        assert!(transitions[0].applies_to(Property::Color));
+    }
+
+    #[test]
+    fn rejects_invalid_declarations() {
+        assert_eq!(Transition::parse("width 1s"), Err(TransitionError::UnknownProperty("width".to_string())));
+        assert_eq!(Transition::parse("opacity ease"), Err(TransitionError::MissingDuration("opacity ease".to_string())));
+        assert_eq!(Transition::parse("opacity 1s 2s 3s"), Err(TransitionError::InvalidTiming("opacity 1s 2s 3s".to_string())));
+        assert_eq!(Transition::parse("opacity -1s"), Err(TransitionError::InvalidTiming("-1s".to_string())));
+    }
+}
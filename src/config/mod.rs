@@ -1,12 +1,60 @@
-#[derive(Clone, Debug)]
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use serde::Deserialize;
+use crate::animation::scheduler::DEFAULT_FRAME_RATE;
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
 pub struct ShuiqiOptions {
-    pub(crate) resize_interval: u128
+    pub resize_interval: u64,
+    pub key_bindings: Vec<KeyBindingOptions>,
+    // 1, 2, 4 or 8, lowered to what the adapter supports
+    pub msaa_samples: u32,
+    // how often running animations are advanced and drawn
+    pub frame_rate: u32
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct KeyBindingOptions {
+    pub keys: String,
+    pub command: String,
+    #[serde(default)]
+    pub scope: Option<String>
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(std::io::Error),
+    Parse(toml::de::Error)
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::Io(e) => write!(f, "could not read config file: {}", e),
+            ConfigError::Parse(e) => write!(f, "could not parse config file: {}", e)
+        }
+    }
+}
+
+impl ShuiqiOptions {
+    pub fn parse(contents: &str) -> Result<Self, ConfigError> {
+        toml::from_str(contents).map_err(ConfigError::Parse)
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let contents = std::fs::read_to_string(path).map_err(ConfigError::Io)?;
+        Self::parse(&contents)
+    }
 }
 
 impl Default for ShuiqiOptions {
     fn default() -> Self {
         ShuiqiOptions {
-            resize_interval: 250
+            resize_interval: 250,
+            key_bindings: vec![],
+            msaa_samples: 1,
+            frame_rate: DEFAULT_FRAME_RATE
         }
     }
-}
\ No newline at end of file
+}
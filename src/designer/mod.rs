@@ -1,11 +1,20 @@
 pub mod point;
 
 use crate::designer::point::{get_measurement_screen_percentage, Measurement, Point};
-use crate::graphics::instance::Shape;
+use crate::graphics::atlas::AtlasHandle;
+use crate::graphics::color::Color;
+use crate::graphics::image::{Image, ImageError, ImageFit, Sampling, TextureHandle};
+use crate::graphics::instance::{InstanceId, Shape};
 use crate::graphics::Vertex;
-use crate::render::Renderer;
-use crate::render::wgpu::WgpuRenderer;
+use crate::graphics::paint::Paint;
+use crate::graphics::path::{FillRule, Path, PathError, StrokeStyle, Tessellator};
+use crate::graphics::sdf::{ArcSegment, RoundedRect, SdfInstance, ShapeStyle};
+use crate::render::wgpu::{AtlasQuadId, ImageId, PaintId, SdfId, TextId, WgpuRenderer};
+use crate::scene::Rect;
+use crate::text::font::Font;
+use crate::text::layout::{layout_text, TextStyle};
 
+#[derive(Default)]
 pub struct Designer;
 
 impl Designer {
@@ -19,11 +28,10 @@ impl Designer {
         position: Point,
         width: Measurement,
         height: Measurement,
-        color: [f32; 3]
-    ) {
+        color: Color
+    ) -> InstanceId {
+        let color = color.to_linear_rgb();
         let position_percentages = position.get_screen_position(renderer.size);
-        let pos_x = position_percentages[0];
-        let pos_y = position_percentages[1];
 
         let width_percentage = get_measurement_screen_percentage(&width, renderer.size.width);
         let height_percentage = get_measurement_screen_percentage(&height, renderer.size.height);
@@ -55,14 +63,12 @@ impl Designer {
             bottom_left,
             top_right,
         ];
-        println!("{:?}", vertices);
 
         let indices: Vec<u16> = vec![
             1, 3, 0,
             0, 2, 1
         ];
 
-        println!("{:?}", indices);
         let shape = renderer.create_shape(
             Shape {
                 vertices,
@@ -82,7 +88,7 @@ impl Designer {
         point: Point,
         width: Measurement,
         height: Measurement
-    ) {
+    ) -> InstanceId {
         let point_measurements = point.get_screen_position(renderer.size);
         let x = point_measurements[0];
         let y = point_measurements[1];
@@ -141,48 +147,126 @@ impl Designer {
         )
     }
 
-    fn get_counter_clockwise_index_order(vertexes: &Vec<Vertex>) -> Vec<u16> {
-        if vertexes.is_empty() {
-            return vec![];
-        }
-
-        let centroid = {
-            let sum = vertexes.iter().fold([0.0, 0.0], |[sx, sy], v| {
-                [sx + v.position[0], sy + v.position[1]]
-            });
-            let len = vertexes.len() as f32;
-            [sum[0] / len, sum[1] / len]
-        };
-
-        let mut indices_with_angles: Vec<(usize, f32)> = vertexes
-            .iter()
-            .enumerate()
-            .map(|(i, v)| {
-                let dx = v.position[0] - centroid[0];
-                let dy = v.position[1] - centroid[1];
-                let angle = dy.atan2(dx);
-                (i, angle)
-            })
-            .collect();
-
-        indices_with_angles.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
-
-        indices_with_angles.into_iter().map(|(i, _)| i as u16).collect()
-    }
-
-    fn max(a: f32, b: f32) -> f32 {
-        if a > b {
-            a
-        } else {
-            b
-        }
-    }
-
-    fn min(a: f32, b: f32) -> f32 {
-        if a < b {
-            a
-        } else {
-            b
-        }
+    pub fn create_text(
+        &self,
+        renderer: &mut WgpuRenderer,
+        font: &Font,
+        text: &str,
+        position: Point,
+        style: &TextStyle,
+        color: Color
+    ) -> Option<TextId> {
+        let origin = position.get_pixel_position(renderer.size);
+        let layout = layout_text(font, text, style, renderer.size);
+
+        renderer.add_text(font, &layout.glyphs, origin, style.size, color.to_linear_rgb())
+    }
+
+    // draws a flat rectangle given in physical pixels from the top left corner of the window
+    pub fn create_rect(&self, renderer: &mut WgpuRenderer, rect: Rect, color: Color) -> InstanceId {
+        let shape = renderer.create_shape(Shape::rect(rect.to_clip(renderer.size), color.to_linear_rgb()));
+        renderer.add_instance(shape, [0.0, 0.0], [1.0, 1.0])
+    }
+
+    // decodes an image file and uploads it, the texture can then be drawn any number of times
+    pub fn load_image(&self, renderer: &WgpuRenderer, path: impl AsRef<std::path::Path>) -> Result<TextureHandle, ImageError> {
+        renderer.create_texture(&Image::from_file(path)?)
+    }
+
+    pub fn create_image(
+        &self,
+        renderer: &mut WgpuRenderer,
+        texture: &TextureHandle,
+        bounds: Rect,
+        fit: ImageFit,
+        sampling: Sampling
+    ) -> ImageId {
+        let (dest, uv) = fit.place([texture.width as f32, texture.height as f32], bounds);
+        renderer.add_image(texture, dest, uv, sampling, 1.0)
+    }
+
+    // draws an image packed into the shared texture atlas, batched with every other atlas image
+    pub fn create_icon(
+        &self,
+        renderer: &mut WgpuRenderer,
+        handle: &AtlasHandle,
+        bounds: Rect,
+        fit: ImageFit
+    ) -> Option<AtlasQuadId> {
+        let [width, height] = renderer.atlas_region(handle)?.size;
+        let (dest, uv) = fit.place([width as f32, height as f32], bounds);
+        renderer.add_atlas_quad(handle, dest, uv, 1.0)
+    }
+
+    // draws a rectangle with rounded corners, an optional border and an optional drop shadow
+    pub fn create_rounded_rect(&self, renderer: &mut WgpuRenderer, rect: &RoundedRect) -> SdfId {
+        renderer.add_sdf(SdfInstance::rounded_rect(rect))
+    }
+
+    pub fn update_rounded_rect(&self, renderer: &mut WgpuRenderer, id: SdfId, rect: &RoundedRect) {
+        renderer.update_sdf(id, SdfInstance::rounded_rect(rect))
+    }
+
+    pub fn create_circle(&self, renderer: &mut WgpuRenderer, center: [f32; 2], radius: f32, style: &ShapeStyle) -> SdfId {
+        let bounds = Rect::new(center[0] - radius, center[1] - radius, radius * 2.0, radius * 2.0);
+        renderer.add_sdf(SdfInstance::ellipse(bounds, style))
+    }
+
+    // draws the ellipse inscribed in the bounds
+    pub fn create_ellipse(&self, renderer: &mut WgpuRenderer, bounds: Rect, style: &ShapeStyle) -> SdfId {
+        renderer.add_sdf(SdfInstance::ellipse(bounds, style))
+    }
+
+    pub fn create_arc(&self, renderer: &mut WgpuRenderer, arc: &ArcSegment) -> SdfId {
+        renderer.add_sdf(SdfInstance::arc(arc))
+    }
+
+    // fills any path, including concave and self intersecting ones
+    pub fn create_path_fill(
+        &self,
+        renderer: &mut WgpuRenderer,
+        path: &Path,
+        rule: FillRule,
+        color: Color
+    ) -> Result<InstanceId, PathError> {
+        let shape = Tessellator::new().fill(path, rule, color.to_linear_rgb())?;
+        Ok(renderer.add_pixel_shape(shape))
+    }
+
+    pub fn create_path_stroke(
+        &self,
+        renderer: &mut WgpuRenderer,
+        path: &Path,
+        style: &StrokeStyle,
+        color: Color
+    ) -> Result<InstanceId, PathError> {
+        let shape = Tessellator::new().stroke(path, style, color.to_linear_rgb())?;
+        Ok(renderer.add_pixel_shape(shape))
+    }
+
+    pub fn create_painted_rect(&self, renderer: &mut WgpuRenderer, rect: Rect, paint: &Paint) -> PaintId {
+        renderer.add_painted_shape(Shape::rect([rect.x, rect.y, rect.right(), rect.bottom()], [0.0; 3]), paint)
+    }
+
+    pub fn create_painted_path_fill(
+        &self,
+        renderer: &mut WgpuRenderer,
+        path: &Path,
+        rule: FillRule,
+        paint: &Paint
+    ) -> Result<PaintId, PathError> {
+        let shape = Tessellator::new().fill(path, rule, [0.0; 3])?;
+        Ok(renderer.add_painted_shape(shape, paint))
+    }
+
+    pub fn create_painted_path_stroke(
+        &self,
+        renderer: &mut WgpuRenderer,
+        path: &Path,
+        style: &StrokeStyle,
+        paint: &Paint
+    ) -> Result<PaintId, PathError> {
+        let shape = Tessellator::new().stroke(path, style, [0.0; 3])?;
+        Ok(renderer.add_painted_shape(shape, paint))
     }
 }
\ No newline at end of file
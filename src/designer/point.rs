@@ -16,9 +16,17 @@ impl Point {
             get_measurement_screen_percentage(&self.y, screen_size.height)
         ]
     }
+
+    // position in physical pixels measured from the top left corner of the window
+    pub fn get_pixel_position(&self, screen_size: PhysicalSize<u32>) -> [f32; 2] {
+        [
+            get_measurement_pixels(&self.x, screen_size.width),
+            get_measurement_pixels(&self.y, screen_size.height)
+        ]
+    }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub enum Measurement {
     Pixels(f32),
     Percentage(f32)
@@ -33,4 +41,14 @@ pub fn get_measurement_screen_percentage(
         Measurement::Pixels(value) => value / screen_size as f32,
         Measurement::Percentage(percent) => percent / 50.0 - 1.0
     }
-}
\ No newline at end of file
+}
+
+pub fn get_measurement_pixels(
+    measurement: &Measurement,
+    screen_size: u32
+) -> f32 {
+    match measurement {
+        Measurement::Pixels(value) => *value,
+        Measurement::Percentage(percent) => percent / 100.0 * screen_size as f32
+    }
+}
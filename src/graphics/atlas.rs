@@ -0,0 +1,349 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::sync::{Arc, Weak};
+use bytemuck::{Pod, Zeroable};
+use wgpu::{BindGroup, BindGroupLayout, Device, Queue, Texture};
+use crate::graphics::image::Image;
+use crate::graphics::packer::ShelfPacker;
+
+pub const ATLAS_PAGE_SIZE: u32 = 1024;
+pub const ATLAS_PAGES: u32 = 4;
+// every image is surrounded by a copy of its edge pixels, so linear filtering at the border
+// blends with the image itself instead of its neighbour
+const EXTRUDE: u32 = 1;
+
+#[derive(Debug, PartialEq)]
+pub enum AtlasError {
+    Empty,
+    TooLarge { width: u32, height: u32 },
+    Full { width: u32, height: u32 }
+}
+
+impl Display for AtlasError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AtlasError::Empty => write!(f, "image has no pixels"),
+            AtlasError::TooLarge { width, height } => {
+                write!(f, "a {}x{} image does not fit on a {}px atlas page", width, height, ATLAS_PAGE_SIZE)
+            }
+            AtlasError::Full { width, height } => write!(f, "texture atlas is full, could not fit a {}x{} image", width, height)
+        }
+    }
+}
+
+// every page is one layer of a single array texture, so all of them share one bind group
+#[derive(Clone, Debug)]
+pub struct AtlasHandle {
+    key: Arc<u64>
+}
+
+impl AtlasHandle {
+    pub fn key(&self) -> u64 {
+        *self.key
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct AtlasRegion {
+    pub page: u32,
+    // [u0, v0, u1, v1] in normalized texture coordinates of the page
+    pub uv: [f32; 4],
+    pub size: [u32; 2]
+}
+
+struct AtlasEntry {
+    // top left corner of the extruded rectangle on the page
+    position: [u32; 2],
+    region: AtlasRegion,
+    // entries are evicted once every handle pointing at them has been dropped
+    owner: Weak<u64>
+}
+
+pub struct TextureAtlas {
+    texture: Texture,
+    bind_group: BindGroup,
+    packers: Vec<ShelfPacker>,
+    entries: HashMap<u64, AtlasEntry>,
+    next_key: u64
+}
+
+impl TextureAtlas {
+    pub fn new(device: &Device, layout: &BindGroupLayout) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Texture Atlas"),
+            size: wgpu::Extent3d {
+                width: ATLAS_PAGE_SIZE,
+                height: ATLAS_PAGE_SIZE,
+                depth_or_array_layers: ATLAS_PAGES,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor {
+            dimension: Some(wgpu::TextureViewDimension::D2Array),
+            ..Default::default()
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Texture Atlas Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let bind_group = crate::graphics::pipeline::create_texture_bind_group(
+            device,
+            layout,
+            &view,
+            &sampler
+        );
+
+        TextureAtlas {
+            texture,
+            bind_group,
+            packers: (0..ATLAS_PAGES).map(|_| ShelfPacker::new(ATLAS_PAGE_SIZE, ATLAS_PAGE_SIZE, 0)).collect(),
+            entries: HashMap::new(),
+            next_key: 0
+        }
+    }
+
+    pub fn bind_group(&self) -> &BindGroup {
+        &self.bind_group
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // packs the image into the first page with room for it, evicting unused entries when all pages are full;
+    // images larger than a page should be uploaded as their own texture instead
+    pub fn insert(&mut self, queue: &Queue, image: &Image) -> Result<AtlasHandle, AtlasError> {
+        if image.width == 0 || image.height == 0 {
+            return Err(AtlasError::Empty);
+        }
+        let width = image.width + EXTRUDE * 2;
+        let height = image.height + EXTRUDE * 2;
+        if !self.packers[0].fits(width, height) {
+            return Err(AtlasError::TooLarge { width: image.width, height: image.height });
+        }
+
+        let allocation = self.allocate(width, height).or_else(|| {
+            if self.evict_unused(queue) > 0 {
+                self.allocate(width, height)
+            } else {
+                None
+            }
+        });
+        let Some((page, [x, y])) = allocation else {
+            return Err(AtlasError::Full { width: image.width, height: image.height });
+        };
+
+        self.write(queue, page, [x, y, width, height], &extrude(image));
+
+        let page_size = ATLAS_PAGE_SIZE as f32;
+        let [left, top] = [x + EXTRUDE, y + EXTRUDE];
+        let region = AtlasRegion {
+            page,
+            uv: [
+                left as f32 / page_size,
+                top as f32 / page_size,
+                (left + image.width) as f32 / page_size,
+                (top + image.height) as f32 / page_size
+            ],
+            size: [image.width, image.height]
+        };
+
+        let key = Arc::new(self.next_key);
+        self.next_key += 1;
+        self.entries.insert(*key, AtlasEntry {
+            position: [x, y],
+            region,
+            owner: Arc::downgrade(&key)
+        });
+
+        Ok(AtlasHandle { key })
+    }
+
+    pub fn get(&self, handle: &AtlasHandle) -> Option<AtlasRegion> {
+        self.entries.get(&handle.key()).map(|e| e.region)
+    }
+
+    pub fn remove(&mut self, queue: &Queue, handle: &AtlasHandle) {
+        if let Some(entry) = self.entries.remove(&handle.key()) {
+            self.free(queue, entry);
+        }
+    }
+
+    // frees the space of every entry whose handles have all been dropped
+    pub fn evict_unused(&mut self, queue: &Queue) -> usize {
+        let unused: Vec<u64> = self.entries
+            .iter()
+            .filter(|(_, e)| e.owner.strong_count() == 0)
+            .map(|(key, _)| *key)
+            .collect();
+
+        for key in &unused {
+            if let Some(entry) = self.entries.remove(key) {
+                self.free(queue, entry);
+            }
+        }
+        unused.len()
+    }
+
+    fn allocate(&mut self, width: u32, height: u32) -> Option<(u32, [u32; 2])> {
+        self.packers
+            .iter_mut()
+            .enumerate()
+            .find_map(|(page, packer)| packer.allocate(width, height).map(|p| (page as u32, p)))
+    }
+
+    // freed space is cleared so a smaller image packed into it later is not surrounded by old pixels
+    fn free(&mut self, queue: &Queue, entry: AtlasEntry) {
+        let [width, height] = entry.region.size.map(|s| s + EXTRUDE * 2);
+        let [x, y] = entry.position;
+        self.write(queue, entry.region.page, [x, y, width, height], &vec![0; (width * height * 4) as usize]);
+        self.packers[entry.region.page as usize].deallocate(entry.position, width);
+    }
+
+    fn write(&self, queue: &Queue, page: u32, [x, y, width, height]: [u32; 4], pixels: &[u8]) {
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &self.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d { x, y, z: page },
+                aspect: wgpu::TextureAspect::All,
+            },
+            pixels,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(width * 4),
+                rows_per_image: Some(height),
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+}
+
+// copies the image with its outermost rows and columns repeated EXTRUDE times on every side
+fn extrude(image: &Image) -> Vec<u8> {
+    let width = (image.width + EXTRUDE * 2) as usize;
+    let height = (image.height + EXTRUDE * 2) as usize;
+    let mut pixels = Vec::with_capacity(width * height * 4);
+
+    for y in 0..height {
+        let source_y = y.saturating_sub(EXTRUDE as usize).min(image.height as usize - 1);
+        for x in 0..width {
+            let source_x = x.saturating_sub(EXTRUDE as usize).min(image.width as usize - 1);
+            let offset = (source_y * image.width as usize + source_x) * 4;
+            pixels.extend_from_slice(&image.pixels[offset..offset + 4]);
+        }
+    }
+    pixels
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+pub struct AtlasInstance {
+    // rectangle in physical pixels as [left, top, right, bottom]
+    pub rect: [f32; 4],
+    pub uv: [f32; 4],
+    pub page: u32,
+    pub opacity: f32
+}
+
+impl AtlasInstance {
+    pub fn new(rect: [f32; 4], region: AtlasRegion, uv: [f32; 4], opacity: f32) -> Self {
+        let [u0, v0, u1, v1] = region.uv;
+        let width = u1 - u0;
+        let height = v1 - v0;
+
+        AtlasInstance {
+            rect,
+            uv: [
+                u0 + uv[0] * width,
+                v0 + uv[1] * height,
+                u0 + uv[2] * width,
+                v0 + uv[3] * height
+            ],
+            page: region.page,
+            opacity
+        }
+    }
+
+    pub fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<AtlasInstance>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &[
+                wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 1,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
+                    shader_location: 2,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 8]>() as wgpu::BufferAddress,
+                    shader_location: 3,
+                    format: wgpu::VertexFormat::Uint32,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 9]>() as wgpu::BufferAddress,
+                    shader_location: 4,
+                    format: wgpu::VertexFormat::Float32,
+                }
+            ]
+        }
+    }
+}
+
+// corners of the unit quad every atlas instance is stretched over
+pub const QUAD_CORNERS: [[f32; 2]; 4] = [
+    [0.0, 1.0],
+    [1.0, 1.0],
+    [1.0, 0.0],
+    [0.0, 0.0]
+];
+pub const QUAD_INDICES: [u16; 6] = [0, 1, 2, 0, 2, 3];
+
+pub fn quad_corner_desc() -> wgpu::VertexBufferLayout<'static> {
+    wgpu::VertexBufferLayout {
+        array_stride: std::mem::size_of::<[f32; 2]>() as wgpu::BufferAddress,
+        step_mode: wgpu::VertexStepMode::Vertex,
+        attributes: &[
+            wgpu::VertexAttribute {
+                offset: 0,
+                shader_location: 0,
+                format: wgpu::VertexFormat::Float32x2,
+            }
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extrusion_repeats_the_edges() {
+        let image = Image::from_rgba(2, 1, vec![1, 1, 1, 1, 2, 2, 2, 2]).unwrap();
+        let pixels = extrude(&image);
+        let reds: Vec<u8> = pixels.chunks(4).map(|p| p[0]).collect();
+        assert_eq!(reds, vec![1, 1, 2, 2, 1, 1, 2, 2, 1, 1, 2, 2]);
+    }
+}
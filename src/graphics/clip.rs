@@ -0,0 +1,68 @@
+use crate::graphics::path::{FillRule, Path};
+use crate::scene::Rect;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ClipId(pub u32);
+
+// rectangles only need a scissor rect, every other clip also goes through the stencil buffer
+#[derive(Clone, Debug, PartialEq)]
+pub enum Clip {
+    Rect(Rect),
+    // radii are top left, top right, bottom right, bottom left
+    RoundedRect(Rect, [f32; 4]),
+    Path(Path, FillRule)
+}
+
+impl Clip {
+    // area the clip can never draw outside of, None when it is not known
+    pub fn bounds(&self) -> Option<Rect> {
+        match self {
+            Clip::Rect(rect) | Clip::RoundedRect(rect, _) => Some(*rect),
+            Clip::Path(..) => None
+        }
+    }
+
+    // whether the clip lets the point through, outlines included
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        match self.outline() {
+            Some((path, rule)) => path.contains(x, y, rule),
+            None => self.bounds().is_some_and(|bounds| bounds.contains(x, y))
+        }
+    }
+
+    pub fn is_rect(&self) -> bool {
+        match self {
+            Clip::Rect(_) => true,
+            Clip::RoundedRect(_, radii) => radii.iter().all(|r| *r <= 0.0),
+            Clip::Path(..) => false
+        }
+    }
+
+    // outline written into the stencil buffer for clips that are not plain rectangles
+    pub fn outline(&self) -> Option<(Path, FillRule)> {
+        if self.is_rect() {
+            return None;
+        }
+
+        match self {
+            Clip::RoundedRect(rect, radii) => Some((Path::rounded_rect(*rect, *radii), FillRule::NonZero)),
+            Clip::Path(path, rule) => Some((path.clone(), *rule)),
+            Clip::Rect(_) => None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_clips_with_corners_or_paths_need_an_outline() {
+        let rect = Rect::new(0.0, 0.0, 10.0, 10.0);
+        assert!(Clip::Rect(rect).outline().is_none());
+        assert!(Clip::RoundedRect(rect, [0.0; 4]).outline().is_none());
+        assert!(Clip::RoundedRect(rect, [0.0, 2.0, 0.0, 0.0]).outline().is_some());
+        assert!(Clip::Path(Path::new(), FillRule::EvenOdd).outline().is_some());
+        assert_eq!(Clip::Path(Path::new(), FillRule::EvenOdd).bounds(), None);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,529 @@
+use std::fmt::{Display, Formatter};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ColorError {
+    InvalidHex(String),
+    UnknownName(String),
+    InvalidFunction(String)
+}
+
+impl Display for ColorError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ColorError::InvalidHex(value) => write!(f, "invalid hex color \"{}\"", value),
+            ColorError::UnknownName(value) => write!(f, "unknown color name \"{}\"", value),
+            ColorError::InvalidFunction(value) => write!(f, "invalid color function \"{}\"", value)
+        }
+    }
+}
+
+// components are srgb encoded between 0 and 1 with straight alpha, the way colors are written in css,
+// the renderer works in linear light so colors are converted with to_linear before they are uploaded
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Color {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32
+}
+
+impl Color {
+    pub const TRANSPARENT: Color = Color::new(0.0, 0.0, 0.0, 0.0);
+    pub const BLACK: Color = Color::new(0.0, 0.0, 0.0, 1.0);
+    pub const WHITE: Color = Color::new(1.0, 1.0, 1.0, 1.0);
+
+    pub const fn new(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Color { r, g, b, a }
+    }
+
+    pub fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Color::rgba(r, g, b, 1.0)
+    }
+
+    pub fn rgba(r: u8, g: u8, b: u8, a: f32) -> Self {
+        Color::new(r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0, a.clamp(0.0, 1.0))
+    }
+
+    pub fn from_linear(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Color::new(linear_to_srgb(r), linear_to_srgb(g), linear_to_srgb(b), a)
+    }
+
+    // #rgb, #rgba, #rrggbb or #rrggbbaa, the leading # is optional
+    pub fn hex(value: &str) -> Result<Self, ColorError> {
+        let invalid = || ColorError::InvalidHex(value.to_string());
+        let digits = value.trim().trim_start_matches('#');
+        if !digits.is_ascii() {
+            return Err(invalid());
+        }
+
+        let channel = |index: usize, width: usize| -> Result<u8, ColorError> {
+            let part = &digits[index * width..(index + 1) * width];
+            let parsed = u8::from_str_radix(part, 16).map_err(|_| invalid())?;
+            // a single digit is repeated, so f becomes ff
+            Ok(if width == 1 { parsed * 17 } else { parsed })
+        };
+
+        let (width, count) = match digits.len() {
+            3 => (1, 3),
+            4 => (1, 4),
+            6 => (2, 3),
+            8 => (2, 4),
+            _ => return Err(invalid())
+        };
+
+        let alpha = if count == 4 { channel(3, width)? as f32 / 255.0 } else { 1.0 };
+        Ok(Color::rgba(channel(0, width)?, channel(1, width)?, channel(2, width)?, alpha))
+    }
+
+    // css named colors, case insensitive
+    pub fn named(name: &str) -> Option<Self> {
+        let name = name.trim().to_ascii_lowercase();
+        if name == "transparent" {
+            return Some(Color::TRANSPARENT);
+        }
+
+        NAMED_COLORS
+            .iter()
+            .find(|(candidate, _)| *candidate == name)
+            .map(|(_, value)| Color::rgb((value >> 16) as u8, (value >> 8) as u8, *value as u8))
+    }
+
+    // hue in degrees, saturation and lightness between 0 and 1
+    pub fn hsl(hue: f32, saturation: f32, lightness: f32) -> Self {
+        Color::hsla(hue, saturation, lightness, 1.0)
+    }
+
+    pub fn hsla(hue: f32, saturation: f32, lightness: f32, alpha: f32) -> Self {
+        let saturation = saturation.clamp(0.0, 1.0);
+        let lightness = lightness.clamp(0.0, 1.0);
+        let hue = hue.rem_euclid(360.0);
+
+        let amount = saturation * lightness.min(1.0 - lightness);
+        let channel = |n: f32| {
+            let k = (n + hue / 30.0) % 12.0;
+            lightness - amount * (k - 3.0).min(9.0 - k).clamp(-1.0, 1.0)
+        };
+
+        Color::new(channel(0.0), channel(8.0), channel(4.0), alpha.clamp(0.0, 1.0))
+    }
+
+    // perceptual lightness between 0 and 1, chroma usually below 0.4 and hue in degrees,
+    // colors outside of srgb are clipped
+    pub fn oklch(lightness: f32, chroma: f32, hue: f32) -> Self {
+        Color::oklcha(lightness, chroma, hue, 1.0)
+    }
+
+    pub fn oklcha(lightness: f32, chroma: f32, hue: f32, alpha: f32) -> Self {
+        let hue = hue.to_radians();
+        let a = chroma.max(0.0) * hue.cos();
+        let b = chroma.max(0.0) * hue.sin();
+
+        let l = (lightness + 0.396_337_78 * a + 0.215_803_76 * b).powi(3);
+        let m = (lightness - 0.105_561_346 * a - 0.063_854_17 * b).powi(3);
+        let s = (lightness - 0.089_484_18 * a - 1.291_485_5 * b).powi(3);
+
+        Color::from_linear(
+            (4.076_741_7 * l - 3.307_711_6 * m + 0.230_969_94 * s).clamp(0.0, 1.0),
+            (-1.268_438 * l + 2.609_757_4 * m - 0.341_319_38 * s).clamp(0.0, 1.0),
+            (-0.004_196_086 * l - 0.703_418_6 * m + 1.707_614_7 * s).clamp(0.0, 1.0),
+            alpha.clamp(0.0, 1.0)
+        )
+    }
+
+    // lightness, chroma and hue in degrees
+    pub fn to_oklch(self) -> [f32; 3] {
+        let [r, g, b, _] = self.to_linear();
+
+        let l = (0.412_221_46 * r + 0.536_332_55 * g + 0.051_445_995 * b).cbrt();
+        let m = (0.211_903_5 * r + 0.680_699_5 * g + 0.107_396_96 * b).cbrt();
+        let s = (0.088_302_46 * r + 0.281_718_85 * g + 0.629_978_7 * b).cbrt();
+
+        let lightness = 0.210_454_26 * l + 0.793_617_8 * m - 0.004_072_047 * s;
+        let a = 1.977_998_5 * l - 2.428_592_2 * m + 0.450_593_7 * s;
+        let b = 0.025_904_037 * l + 0.782_771_77 * m - 0.808_675_77 * s;
+
+        [lightness, (a * a + b * b).sqrt(), b.atan2(a).to_degrees().rem_euclid(360.0)]
+    }
+
+    // accepts hex, css names and the rgb(), rgba(), hsl(), hsla() and oklch() functions
+    // with either comma or space separated arguments
+    pub fn parse(value: &str) -> Result<Self, ColorError> {
+        let trimmed = value.trim();
+        if trimmed.starts_with('#') {
+            return Color::hex(trimmed);
+        }
+
+        let Some((function, arguments)) = trimmed.strip_suffix(')').and_then(|rest| rest.split_once('(')) else {
+            return Color::named(trimmed).ok_or_else(|| ColorError::UnknownName(value.to_string()));
+        };
+
+        let invalid = || ColorError::InvalidFunction(value.to_string());
+        let arguments = arguments
+            .split(|c: char| c == ',' || c == '/' || c.is_whitespace())
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>();
+        if arguments.len() != 3 && arguments.len() != 4 {
+            return Err(invalid());
+        }
+
+        let alpha = match arguments.get(3) {
+            Some(part) => parse_number(part, 1.0).ok_or_else(invalid)?,
+            None => 1.0
+        };
+
+        match function.trim().to_ascii_lowercase().as_str() {
+            "rgb" | "rgba" => {
+                let channel = |part: &str| parse_number(part, 255.0).map(|value| (value / 255.0).clamp(0.0, 1.0));
+                Ok(Color::new(
+                    channel(arguments[0]).ok_or_else(invalid)?,
+                    channel(arguments[1]).ok_or_else(invalid)?,
+                    channel(arguments[2]).ok_or_else(invalid)?,
+                    alpha.clamp(0.0, 1.0)
+                ))
+            }
+            "hsl" | "hsla" => Ok(Color::hsla(
+                parse_angle(arguments[0]).ok_or_else(invalid)?,
+                parse_number(arguments[1], 100.0).ok_or_else(invalid)? / 100.0,
+                parse_number(arguments[2], 100.0).ok_or_else(invalid)? / 100.0,
+                alpha
+            )),
+            "oklch" => Ok(Color::oklcha(
+                parse_number(arguments[0], 1.0).ok_or_else(invalid)?,
+                // 100% chroma is 0.4 in css
+                parse_number(arguments[1], 0.4).ok_or_else(invalid)?,
+                parse_angle(arguments[2]).ok_or_else(invalid)?,
+                alpha
+            )),
+            _ => Err(invalid())
+        }
+    }
+
+    pub fn with_alpha(self, a: f32) -> Self {
+        Color { a, ..self }
+    }
+
+    // linear light with straight alpha, what the shaders expect
+    pub fn to_linear(self) -> [f32; 4] {
+        [srgb_to_linear(self.r), srgb_to_linear(self.g), srgb_to_linear(self.b), self.a]
+    }
+
+    // for the flat color pipelines that have no alpha
+    pub fn to_linear_rgb(self) -> [f32; 3] {
+        let [r, g, b, _] = self.to_linear();
+        [r, g, b]
+    }
+
+    pub fn to_premultiplied_linear(self) -> [f32; 4] {
+        let [r, g, b, a] = self.to_linear();
+        [r * a, g * a, b * a, a]
+    }
+
+    // mixes in linear light with premultiplied alpha, like blending on the gpu
+    pub fn lerp(self, other: Color, t: f32) -> Color {
+        let from = self.to_premultiplied_linear();
+        let to = other.to_premultiplied_linear();
+        let [r, g, b, a] = std::array::from_fn(|index| from[index] + (to[index] - from[index]) * t);
+
+        if a <= 0.0 {
+            return Color::TRANSPARENT;
+        }
+        Color::from_linear(r / a, g / a, b / a, a.clamp(0.0, 1.0))
+    }
+
+    // mixes in oklch along the shorter way around the hue circle, keeps the lightness even,
+    // which suits animations between distinct colors better than lerp
+    pub fn lerp_oklch(self, other: Color, t: f32) -> Color {
+        let [from_lightness, from_chroma, mut from_hue] = self.to_oklch();
+        let [to_lightness, to_chroma, mut to_hue] = other.to_oklch();
+
+        // grays have no meaningful hue, so they take the hue of the other color
+        const ACHROMATIC: f32 = 0.0001;
+        if from_chroma < ACHROMATIC {
+            from_hue = to_hue;
+        }
+        if to_chroma < ACHROMATIC {
+            to_hue = from_hue;
+        }
+
+        let mut hue_delta = to_hue - from_hue;
+        if hue_delta > 180.0 {
+            hue_delta -= 360.0;
+        } else if hue_delta < -180.0 {
+            hue_delta += 360.0;
+        }
+
+        Color::oklcha(
+            from_lightness + (to_lightness - from_lightness) * t,
+            from_chroma + (to_chroma - from_chroma) * t,
+            from_hue + hue_delta * t,
+            self.a + (other.a - self.a) * t
+        )
+    }
+
+    pub fn to_hex(self) -> String {
+        let byte = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+        if self.a >= 1.0 {
+            format!("#{:02x}{:02x}{:02x}", byte(self.r), byte(self.g), byte(self.b))
+        } else {
+            format!("#{:02x}{:02x}{:02x}{:02x}", byte(self.r), byte(self.g), byte(self.b), byte(self.a))
+        }
+    }
+}
+
+impl std::str::FromStr for Color {
+    type Err = ColorError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Color::parse(value)
+    }
+}
+
+pub fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+pub fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.003_130_8 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+// plain numbers are returned as they are, percentages are scaled so 100% is the given value
+fn parse_number(part: &str, percent_of: f32) -> Option<f32> {
+    match part.strip_suffix('%') {
+        Some(percent) => percent.parse::<f32>().ok().map(|value| value / 100.0 * percent_of),
+        None => part.parse::<f32>().ok()
+    }
+}
+
+// degrees unless another css unit is given
+fn parse_angle(part: &str) -> Option<f32> {
+    if let Some(value) = part.strip_suffix("deg") {
+        value.parse().ok()
+    } else if let Some(value) = part.strip_suffix("grad") {
+        value.parse::<f32>().ok().map(|value| value * 0.9)
+    } else if let Some(value) = part.strip_suffix("rad") {
+        value.parse::<f32>().ok().map(f32::to_degrees)
+    } else if let Some(value) = part.strip_suffix("turn") {
+        value.parse::<f32>().ok().map(|value| value * 360.0)
+    } else {
+        part.parse().ok()
+    }
+}
+
+const NAMED_COLORS: &[(&str, u32)] = &[
+    ("aliceblue", 0xf0f8ff),
+    ("antiquewhite", 0xfaebd7),
+    ("aqua", 0x00ffff),
+    ("aquamarine", 0x7fffd4),
+    ("azure", 0xf0ffff),
+    ("beige", 0xf5f5dc),
+    ("bisque", 0xffe4c4),
+    ("black", 0x000000),
+    ("blanchedalmond", 0xffebcd),
+    ("blue", 0x0000ff),
+    ("blueviolet", 0x8a2be2),
+    ("brown", 0xa52a2a),
+    ("burlywood", 0xdeb887),
+    ("cadetblue", 0x5f9ea0),
+    ("chartreuse", 0x7fff00),
+    ("chocolate", 0xd2691e),
+    ("coral", 0xff7f50),
+    ("cornflowerblue", 0x6495ed),
+    ("cornsilk", 0xfff8dc),
+    ("crimson", 0xdc143c),
+    ("cyan", 0x00ffff),
+    ("darkblue", 0x00008b),
+    ("darkcyan", 0x008b8b),
+    ("darkgoldenrod", 0xb8860b),
+    ("darkgray", 0xa9a9a9),
+    ("darkgreen", 0x006400),
+    ("darkgrey", 0xa9a9a9),
+    ("darkkhaki", 0xbdb76b),
+    ("darkmagenta", 0x8b008b),
+    ("darkolivegreen", 0x556b2f),
+    ("darkorange", 0xff8c00),
+    ("darkorchid", 0x9932cc),
+    ("darkred", 0x8b0000),
+    ("darksalmon", 0xe9967a),
+    ("darkseagreen", 0x8fbc8f),
+    ("darkslateblue", 0x483d8b),
+    ("darkslategray", 0x2f4f4f),
+    ("darkslategrey", 0x2f4f4f),
+    ("darkturquoise", 0x00ced1),
+    ("darkviolet", 0x9400d3),
+    ("deeppink", 0xff1493),
+    ("deepskyblue", 0x00bfff),
+    ("dimgray", 0x696969),
+    ("dimgrey", 0x696969),
+    ("dodgerblue", 0x1e90ff),
+    ("firebrick", 0xb22222),
+    ("floralwhite", 0xfffaf0),
+    ("forestgreen", 0x228b22),
+    ("fuchsia", 0xff00ff),
+    ("gainsboro", 0xdcdcdc),
+    ("ghostwhite", 0xf8f8ff),
+    ("gold", 0xffd700),
+    ("goldenrod", 0xdaa520),
+    ("gray", 0x808080),
+    ("green", 0x008000),
+    ("greenyellow", 0xadff2f),
+    ("grey", 0x808080),
+    ("honeydew", 0xf0fff0),
+    ("hotpink", 0xff69b4),
+    ("indianred", 0xcd5c5c),
+    ("indigo", 0x4b0082),
+    ("ivory", 0xfffff0),
+    ("khaki", 0xf0e68c),
+    ("lavender", 0xe6e6fa),
+    ("lavenderblush", 0xfff0f5),
+    ("lawngreen", 0x7cfc00),
+    ("lemonchiffon", 0xfffacd),
+    ("lightblue", 0xadd8e6),
+    ("lightcoral", 0xf08080),
+    ("lightcyan", 0xe0ffff),
+    ("lightgoldenrodyellow", 0xfafad2),
+    ("lightgray", 0xd3d3d3),
+    ("lightgreen", 0x90ee90),
+    ("lightgrey", 0xd3d3d3),
+    ("lightpink", 0xffb6c1),
+    ("lightsalmon", 0xffa07a),
+    ("lightseagreen", 0x20b2aa),
+    ("lightskyblue", 0x87cefa),
+    ("lightslategray", 0x778899),
+    ("lightslategrey", 0x778899),
+    ("lightsteelblue", 0xb0c4de),
+    ("lightyellow", 0xffffe0),
+    ("lime", 0x00ff00),
+    ("limegreen", 0x32cd32),
+    ("linen", 0xfaf0e6),
+    ("magenta", 0xff00ff),
+    ("maroon", 0x800000),
+    ("mediumaquamarine", 0x66cdaa),
+    ("mediumblue", 0x0000cd),
+    ("mediumorchid", 0xba55d3),
+    ("mediumpurple", 0x9370db),
+    ("mediumseagreen", 0x3cb371),
+    ("mediumslateblue", 0x7b68ee),
+    ("mediumspringgreen", 0x00fa9a),
+    ("mediumturquoise", 0x48d1cc),
+    ("mediumvioletred", 0xc71585),
+    ("midnightblue", 0x191970),
+    ("mintcream", 0xf5fffa),
+    ("mistyrose", 0xffe4e1),
+    ("moccasin", 0xffe4b5),
+    ("navajowhite", 0xffdead),
+    ("navy", 0x000080),
+    ("oldlace", 0xfdf5e6),
+    ("olive", 0x808000),
+    ("olivedrab", 0x6b8e23),
+    ("orange", 0xffa500),
+    ("orangered", 0xff4500),
+    ("orchid", 0xda70d6),
+    ("palegoldenrod", 0xeee8aa),
+    ("palegreen", 0x98fb98),
+    ("paleturquoise", 0xafeeee),
+    ("palevioletred", 0xdb7093),
+    ("papayawhip", 0xffefd5),
+    ("peachpuff", 0xffdab9),
+    ("peru", 0xcd853f),
+    ("pink", 0xffc0cb),
+    ("plum", 0xdda0dd),
+    ("powderblue", 0xb0e0e6),
+    ("purple", 0x800080),
+    ("rebeccapurple", 0x663399),
+    ("red", 0xff0000),
+    ("rosybrown", 0xbc8f8f),
+    ("royalblue", 0x4169e1),
+    ("saddlebrown", 0x8b4513),
+    ("salmon", 0xfa8072),
+    ("sandybrown", 0xf4a460),
+    ("seagreen", 0x2e8b57),
+    ("seashell", 0xfff5ee),
+    ("sienna", 0xa0522d),
+    ("silver", 0xc0c0c0),
+    ("skyblue", 0x87ceeb),
+    ("slateblue", 0x6a5acd),
+    ("slategray", 0x708090),
+    ("slategrey", 0x708090),
+    ("snow", 0xfffafa),
+    ("springgreen", 0x00ff7f),
+    ("steelblue", 0x4682b4),
+    ("tan", 0xd2b48c),
+    ("teal", 0x008080),
+    ("thistle", 0xd8bfd8),
+    ("tomato", 0xff6347),
+    ("turquoise", 0x40e0d0),
+    ("violet", 0xee82ee),
+    ("wheat", 0xf5deb3),
+    ("white", 0xffffff),
+    ("whitesmoke", 0xf5f5f5),
+    ("yellow", 0xffff00),
+    ("yellowgreen", 0x9acd32)
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: Color, b: Color) {
+        let close = [a.r - b.r, a.g - b.g, a.b - b.b, a.a - b.a].iter().all(|d| d.abs() < 0.002);
+        assert!(close, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn hex_round_trips() {
+        assert_eq!(Color::hex("#f80"), Ok(Color::rgb(255, 136, 0)));
+        assert_eq!(Color::hex("f808"), Color::hex("#ff880088"));
+        for value in ["#1e90ff", "#1e90ff80", "#000000", "#ffffff00"] {
+            assert_eq!(Color::hex(value).unwrap().to_hex(), value);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_hex() {
+        for value in ["#12345", "#ggg", "#ééé", ""] {
+            assert_eq!(Color::hex(value), Err(ColorError::InvalidHex(value.to_string())));
+        }
+    }
+
+    #[test]
+    fn parses_names_and_functions() {
+        let blue = Color::hex("#1e90ff").unwrap();
+        assert_eq!(Color::parse("DodgerBlue"), Ok(blue));
+        assert_eq!(Color::parse("transparent"), Ok(Color::TRANSPARENT));
+        assert_eq!(Color::parse("rgb(30, 144, 255)"), Ok(blue));
+        assert_eq!(Color::parse("rgb(30 144 255 / 50%)"), Ok(blue.with_alpha(0.5)));
+        assert_close(Color::parse("hsl(120, 100%, 50%)").unwrap(), Color::named("lime").unwrap());
+        assert_close(Color::parse("hsla(0.5turn 100% 50% / 0.25)").unwrap(), Color::new(0.0, 1.0, 1.0, 0.25));
+        assert_close(Color::parse("oklch(100% 0 0)").unwrap(), Color::WHITE);
+    }
+
+    #[test]
+    fn reports_what_could_not_be_parsed() {
+        assert_eq!(Color::parse("blurple"), Err(ColorError::UnknownName("blurple".to_string())));
+        assert_eq!(Color::parse("rgb(1, 2)"), Err(ColorError::InvalidFunction("rgb(1, 2)".to_string())));
+        assert_eq!(Color::parse("lab(50 20 30)"), Err(ColorError::InvalidFunction("lab(50 20 30)".to_string())));
+        assert_eq!(Color::parse("rgb(a, b, c)"), Err(ColorError::InvalidFunction("rgb(a, b, c)".to_string())));
+    }
+
+    #[test]
+    fn oklch_round_trips() {
+        for value in ["#1e90ff", "#ff8800", "#336633", "#808080", "#ffffff"] {
+            let color = Color::hex(value).unwrap();
+            let [lightness, chroma, hue] = color.to_oklch();
+            assert_close(Color::oklch(lightness, chroma, hue), color);
+        }
+
+        let [lightness, chroma, hue] = Color::oklch(0.7, 0.1, 30.0).to_oklch();
+        assert!((lightness - 0.7).abs() < 0.001);
+        assert!((chroma - 0.1).abs() < 0.001);
+        assert!((hue - 30.0).abs() < 0.5);
+    }
+}
@@ -0,0 +1,232 @@
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use std::sync::Arc;
+use wgpu::{BindGroup, BindGroupLayout, Device, Queue, Texture};
+use crate::scene::Rect;
+
+#[derive(Debug)]
+pub enum ImageError {
+    Io(std::io::Error),
+    Decode(image::ImageError),
+    Empty,
+    // the pixel data does not hold width * height rgba pixels
+    SizeMismatch { expected: Option<usize>, actual: usize },
+    TooLarge { width: u32, height: u32, max: u32 }
+}
+
+impl Display for ImageError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImageError::Io(e) => write!(f, "could not read image file: {}", e),
+            ImageError::Decode(e) => write!(f, "could not decode image: {}", e),
+            ImageError::Empty => write!(f, "image has no pixels"),
+            ImageError::SizeMismatch { expected: Some(expected), actual } => {
+                write!(f, "pixel data is {} bytes, the image size needs {}", actual, expected)
+            }
+            ImageError::SizeMismatch { expected: None, actual } => {
+                write!(f, "pixel data is {} bytes, the image size does not fit in memory", actual)
+            }
+            ImageError::TooLarge { width, height, max } => {
+                write!(f, "image is {}x{}, textures can be at most {}x{}", width, height, max, max)
+            }
+        }
+    }
+}
+
+// decoded image in straight alpha rgba8
+#[derive(Clone, Debug)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>
+}
+
+impl Image {
+    // the format (png, jpeg or webp) is detected from the data itself
+    pub fn decode(bytes: &[u8]) -> Result<Self, ImageError> {
+        let decoded = image::load_from_memory(bytes).map_err(ImageError::Decode)?.to_rgba8();
+
+        Ok(Image {
+            width: decoded.width(),
+            height: decoded.height(),
+            pixels: decoded.into_raw()
+        })
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ImageError> {
+        let bytes = std::fs::read(path).map_err(ImageError::Io)?;
+        Self::decode(&bytes)
+    }
+
+    pub fn from_rgba(width: u32, height: u32, pixels: Vec<u8>) -> Result<Self, ImageError> {
+        let expected = (width as usize).checked_mul(height as usize).and_then(|n| n.checked_mul(4));
+        if expected != Some(pixels.len()) {
+            return Err(ImageError::SizeMismatch { expected, actual: pixels.len() });
+        }
+        Ok(Image { width, height, pixels })
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Sampling {
+    Linear,
+    Nearest
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ImageFit {
+    // scales the image to fit inside the bounds, keeping its aspect ratio
+    Contain,
+    // scales the image to cover the bounds, cropping what does not fit
+    Cover,
+    // stretches the image to the bounds
+    Fill,
+    // keeps the natural size, centered and cropped to the bounds
+    None
+}
+
+impl ImageFit {
+    // returns the destination rectangle and the [u0, v0, u1, v1] part of the image shown in it
+    pub fn place(self, image_size: [f32; 2], bounds: Rect) -> (Rect, [f32; 4]) {
+        let [width, height] = image_size;
+        if width <= 0.0 || height <= 0.0 {
+            return (bounds, [0.0, 0.0, 1.0, 1.0]);
+        }
+
+        match self {
+            ImageFit::Fill => (bounds, [0.0, 0.0, 1.0, 1.0]),
+            ImageFit::Contain => {
+                let scale = (bounds.width / width).min(bounds.height / height);
+                (centered(bounds, width * scale, height * scale), [0.0, 0.0, 1.0, 1.0])
+            }
+            ImageFit::Cover => {
+                let scale = (bounds.width / width).max(bounds.height / height);
+                let u = (1.0 - bounds.width / (width * scale)) / 2.0;
+                let v = (1.0 - bounds.height / (height * scale)) / 2.0;
+                (bounds, [u, v, 1.0 - u, 1.0 - v])
+            }
+            ImageFit::None => {
+                let shown_width = width.min(bounds.width);
+                let shown_height = height.min(bounds.height);
+                let u = (1.0 - shown_width / width) / 2.0;
+                let v = (1.0 - shown_height / height) / 2.0;
+                (centered(bounds, shown_width, shown_height), [u, v, 1.0 - u, 1.0 - v])
+            }
+        }
+    }
+}
+
+fn centered(bounds: Rect, width: f32, height: f32) -> Rect {
+    Rect::new(
+        bounds.x + (bounds.width - width) / 2.0,
+        bounds.y + (bounds.height - height) / 2.0,
+        width,
+        height
+    )
+}
+
+pub struct GpuTexture {
+    texture: Texture,
+    linear: BindGroup,
+    nearest: BindGroup,
+    pub width: u32,
+    pub height: u32
+}
+
+// textures are shared between every draw that shows them
+pub type TextureHandle = Arc<GpuTexture>;
+
+impl GpuTexture {
+    pub fn new(device: &Device, queue: &Queue, layout: &BindGroupLayout, image: &Image) -> Result<Self, ImageError> {
+        if image.width == 0 || image.height == 0 {
+            return Err(ImageError::Empty);
+        }
+        let max = device.limits().max_texture_dimension_2d;
+        if image.width > max || image.height > max {
+            return Err(ImageError::TooLarge { width: image.width, height: image.height, max });
+        }
+
+        let size = wgpu::Extent3d {
+            width: image.width,
+            height: image.height,
+            depth_or_array_layers: 1,
+        };
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Image Texture"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            &image.pixels,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(image.width * 4),
+                rows_per_image: Some(image.height),
+            },
+            size,
+        );
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let bind_group = |filter: wgpu::FilterMode| {
+            let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+                label: Some("Image Sampler"),
+                mag_filter: filter,
+                min_filter: filter,
+                ..Default::default()
+            });
+            crate::graphics::pipeline::create_texture_bind_group(device, layout, &view, &sampler)
+        };
+
+        Ok(GpuTexture {
+            linear: bind_group(wgpu::FilterMode::Linear),
+            nearest: bind_group(wgpu::FilterMode::Nearest),
+            texture,
+            width: image.width,
+            height: image.height
+        })
+    }
+
+    pub fn bind_group(&self, sampling: Sampling) -> &BindGroup {
+        match sampling {
+            Sampling::Linear => &self.linear,
+            Sampling::Nearest => &self.nearest
+        }
+    }
+
+    pub fn texture(&self) -> &Texture {
+        &self.texture
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rgba_data_must_match_the_size() {
+        assert!(Image::from_rgba(2, 2, vec![0; 16]).is_ok());
+        assert!(matches!(
+            Image::from_rgba(2, 2, vec![0; 12]),
+            Err(ImageError::SizeMismatch { expected: Some(16), actual: 12 })
+        ));
+    }
+
+    #[test]
+    fn huge_sizes_do_not_overflow() {
+        assert!(Image::from_rgba(u32::MAX, u32::MAX, vec![0; 4]).is_err());
+        assert!(Image::from_rgba(65536, 65536, vec![]).is_err());
+    }
+}
@@ -1,7 +1,12 @@
 use bytemuck::{Pod, Zeroable};
 use wgpu::Buffer;
+use winit::dpi::PhysicalSize;
+use crate::graphics::layer::DrawScope;
 use crate::graphics::Vertex;
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct InstanceId(pub u32);
+
 #[repr(C)]
 #[derive(Copy, Clone, Debug, Pod, Zeroable)]
 pub struct InstanceData {
@@ -48,12 +53,70 @@ pub struct ShapeData {
 
 pub struct ObjectInstance {
     pub shape: ShapeData,
-    pub data: InstanceData
+    pub data: InstanceData,
+    pub scope: DrawScope,
+    // position in the draw order, content added later is drawn on top
+    pub order: u64,
+    // the shape in physical pixels for instances that are projected again on resize
+    pub pixels: Option<Shape>
 }
 
 impl ObjectInstance {
     pub fn new(shape: ShapeData, data: InstanceData) -> Self {
-        ObjectInstance { shape, data }
+        ObjectInstance { shape, data, scope: DrawScope::default(), order: 0, pixels: None }
     }
 }
 
+impl Shape {
+    // builds a quad from a clip space or pixel rectangle given as [left, top, right, bottom]
+    pub fn rect(rect: [f32; 4], color: [f32; 3]) -> Shape {
+        let [left, top, right, bottom] = rect;
+
+        Shape {
+            vertices: vec![
+                Vertex::new([left, bottom], color),
+                Vertex::new([right, bottom], color),
+                Vertex::new([right, top], color),
+                Vertex::new([left, top], color)
+            ],
+            indices: vec![0, 1, 2, 0, 2, 3]
+        }
+    }
+
+    // builds a rectangular ring from a clip space rectangle given as [left, top, right, bottom]
+    pub fn outline(rect: [f32; 4], thickness: [f32; 2], color: [f32; 3]) -> Shape {
+        let [left, top, right, bottom] = rect;
+        let [tx, ty] = thickness;
+
+        let edges = [
+            [left - tx, top + ty, right + tx, top],
+            [left - tx, bottom, right + tx, bottom - ty],
+            [left - tx, top, left, bottom],
+            [right, top, right + tx, bottom]
+        ];
+
+        let mut vertices = Vec::with_capacity(16);
+        let mut indices = Vec::with_capacity(24);
+        for [l, t, r, b] in edges {
+            let base = vertices.len() as u16;
+            vertices.push(Vertex::new([l, b], color));
+            vertices.push(Vertex::new([r, b], color));
+            vertices.push(Vertex::new([r, t], color));
+            vertices.push(Vertex::new([l, t], color));
+            indices.extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+        }
+
+        Shape { vertices, indices }
+    }
+
+    // maps a shape in physical pixels from the top left corner of the window to clip space
+    pub fn to_clip(&self, screen_size: PhysicalSize<u32>) -> Shape {
+        let [width, height] = [screen_size.width as f32, screen_size.height as f32];
+        let vertices = self.vertices
+            .iter()
+            .map(|v| Vertex::new([v.position[0] / width * 2.0 - 1.0, 1.0 - v.position[1] / height * 2.0], v.color))
+            .collect();
+
+        Shape { vertices, indices: self.indices.clone() }
+    }
+}
@@ -0,0 +1,116 @@
+use bytemuck::{Pod, Zeroable};
+use wgpu::{Device, Texture, TextureView};
+use winit::dpi::PhysicalSize;
+use crate::graphics::clip::ClipId;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LayerId(pub u32);
+
+// the layer and clip content was added under
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct DrawScope {
+    pub layer: Option<LayerId>,
+    pub clip: Option<ClipId>
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum BlendMode {
+    #[default]
+    Normal,
+    Multiply,
+    Screen,
+    Overlay
+}
+
+// blur amounts are the standard deviation of the gaussian in physical pixels, 0 turns them off
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LayerOptions {
+    pub opacity: f32,
+    pub blend: BlendMode,
+    // blurs the content of the layer
+    pub blur: f32,
+    // blurs whatever is behind the layer where the layer has content
+    pub backdrop_blur: f32
+}
+
+impl LayerOptions {
+    pub fn new(opacity: f32) -> Self {
+        LayerOptions {
+            opacity,
+            blend: BlendMode::Normal,
+            blur: 0.0,
+            backdrop_blur: 0.0
+        }
+    }
+}
+
+// offscreen texture the size of the window, used for the scene and for layers
+pub struct RenderTarget {
+    pub texture: Texture,
+    pub view: TextureView
+}
+
+impl RenderTarget {
+    pub fn new(device: &Device, size: PhysicalSize<u32>, label: &str) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d {
+                width: size.width.max(1),
+                height: size.height.max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Bgra8UnormSrgb,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC
+                | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        RenderTarget { texture, view }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+pub struct BlurUniform {
+    // one pixel along the axis being blurred
+    pub direction: [f32; 2],
+    pub sigma: f32,
+    _padding: f32
+}
+
+impl BlurUniform {
+    pub fn new(direction: [f32; 2], sigma: f32) -> Self {
+        BlurUniform { direction, sigma, _padding: 0.0 }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+pub struct CompositeUniform {
+    pub opacity: f32,
+    pub blend: u32,
+    pub backdrop_blur: u32,
+    _padding: u32
+}
+
+impl CompositeUniform {
+    pub fn new(options: &LayerOptions) -> Self {
+        CompositeUniform {
+            opacity: options.opacity.clamp(0.0, 1.0),
+            blend: match options.blend {
+                BlendMode::Normal => 0,
+                BlendMode::Multiply => 1,
+                BlendMode::Screen => 2,
+                BlendMode::Overlay => 3
+            },
+            backdrop_blur: (options.backdrop_blur > 0.0) as u32,
+            _padding: 0
+        }
+    }
+}
@@ -2,6 +2,15 @@ use bytemuck::{Pod, Zeroable};
 
 pub mod pipeline;
 pub mod instance;
+pub mod packer;
+pub mod image;
+pub mod atlas;
+pub mod sdf;
+pub mod path;
+pub mod paint;
+pub mod clip;
+pub mod layer;
+pub mod color;
 
 #[repr(C)]
 #[derive(Copy, Clone, Debug, Pod, Zeroable)]
@@ -35,23 +44,95 @@ impl Vertex {
     }
 }
 
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+pub struct TextVertex {
+    pub position: [f32; 2],
+    pub uv: [f32; 2],
+    pub color: [f32; 3]
+}
+
+impl TextVertex {
+    pub fn new(position: [f32; 2], uv: [f32; 2], color: [f32; 3]) -> Self {
+        TextVertex { position, uv, color }
+    }
+
+    fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<TextVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &[
+                wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 0,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 2]>() as wgpu::BufferAddress,
+                    shader_location: 1,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
+                    shader_location: 2,
+                    format: wgpu::VertexFormat::Float32x3,
+                }
+            ]
+        }
+    }
+}
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+pub struct ImageVertex {
+    pub position: [f32; 2],
+    pub uv: [f32; 2],
+    pub opacity: f32
+}
+
+impl ImageVertex {
+    pub fn new(position: [f32; 2], uv: [f32; 2], opacity: f32) -> Self {
+        ImageVertex { position, uv, opacity }
+    }
+
+    fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<ImageVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &[
+                wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 0,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 2]>() as wgpu::BufferAddress,
+                    shader_location: 1,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
+                    shader_location: 2,
+                    format: wgpu::VertexFormat::Float32,
+                }
+            ]
+        }
+    }
+}
 
 const COLOR: [f32; 3] = [0.5, 0.5, 0.5];
 
 pub fn square() -> (&'static [Vertex], &'static [u16]) {
-    let vertices: &[Vertex] = &[
+    let vertices: &'static [Vertex] = &[
         Vertex { position: [-0.8, 0.8], color: COLOR },
         Vertex { position: [0.8, 0.8], color: COLOR },
         Vertex { position: [0.8, -0.8], color: COLOR },
         Vertex { position: [-0.8, -0.8], color: COLOR }
     ];
 
-    let indices: &[u16] = &[
+    let indices: &'static [u16] = &[
         1, 0, 3,
         3, 2, 1
     ];
 
-    unsafe {
-        (std::mem::transmute(vertices), std::mem::transmute(indices))
-    }
+    (vertices, indices)
 }
\ No newline at end of file
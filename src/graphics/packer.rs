@@ -0,0 +1,150 @@
+// a simple shelf packer: rectangles are placed left to right on horizontal shelves,
+// and a new shelf is opened below the last one when nothing fits
+struct Shelf {
+    y: u32,
+    height: u32,
+    cursor: u32,
+    // freed [x, width] spans left of the cursor, sorted by x
+    free: Vec<[u32; 2]>
+}
+
+impl Shelf {
+    fn fits(&self, width: u32, shelf_width: u32) -> bool {
+        shelf_width - self.cursor >= width || self.free.iter().any(|[_, w]| *w >= width)
+    }
+
+    fn take(&mut self, width: u32) -> u32 {
+        if let Some(index) = self.free.iter().position(|[_, w]| *w >= width) {
+            let [x, w] = self.free[index];
+            if w == width {
+                self.free.remove(index);
+            } else {
+                self.free[index] = [x + width, w - width];
+            }
+            return x;
+        }
+
+        let x = self.cursor;
+        self.cursor += width;
+        x
+    }
+
+    fn release(&mut self, x: u32, width: u32) {
+        let index = self.free.partition_point(|[fx, _]| *fx < x);
+        self.free.insert(index, [x, width]);
+
+        // neighbouring spans are merged so wide rectangles can reuse the space
+        if index + 1 < self.free.len() && x + width == self.free[index + 1][0] {
+            self.free[index][1] += self.free.remove(index + 1)[1];
+        }
+        if index > 0 && self.free[index - 1][0] + self.free[index - 1][1] == x {
+            self.free[index - 1][1] += self.free.remove(index)[1];
+        }
+
+        if let Some(&[last_x, last_width]) = self.free.last() {
+            if last_x + last_width == self.cursor {
+                self.cursor = last_x;
+                self.free.pop();
+            }
+        }
+    }
+}
+
+pub struct ShelfPacker {
+    width: u32,
+    height: u32,
+    padding: u32,
+    shelves: Vec<Shelf>
+}
+
+impl ShelfPacker {
+    pub fn new(width: u32, height: u32, padding: u32) -> Self {
+        ShelfPacker {
+            width,
+            height,
+            padding,
+            shelves: vec![]
+        }
+    }
+
+    // whether a rectangle of this size could ever be packed, even into an empty packer
+    pub fn fits(&self, width: u32, height: u32) -> bool {
+        width + self.padding <= self.width && height + self.padding <= self.height
+    }
+
+    pub fn allocate(&mut self, width: u32, height: u32) -> Option<[u32; 2]> {
+        let padded_width = width + self.padding;
+        let padded_height = height + self.padding;
+        if !self.fits(width, height) {
+            return None;
+        }
+
+        // prefer the shelf that wastes the least vertical space
+        let shelf_width = self.width;
+        let best = self.shelves
+            .iter_mut()
+            .filter(|s| s.height >= padded_height && s.fits(padded_width, shelf_width))
+            .min_by_key(|s| s.height - padded_height);
+
+        if let Some(shelf) = best {
+            return Some([shelf.take(padded_width), shelf.y]);
+        }
+
+        let y = self.shelves.last().map(|s| s.y + s.height).unwrap_or(0);
+        if y + padded_height > self.height {
+            return None;
+        }
+
+        self.shelves.push(Shelf {
+            y,
+            height: padded_height,
+            cursor: padded_width,
+            free: vec![]
+        });
+        Some([0, y])
+    }
+
+    // gives back a rectangle returned by allocate, empty shelves at the bottom are closed again
+    pub fn deallocate(&mut self, position: [u32; 2], width: u32) {
+        let Some(shelf) = self.shelves.iter_mut().find(|s| s.y == position[1]) else {
+            return;
+        };
+        shelf.release(position[0], width + self.padding);
+
+        while self.shelves.last().is_some_and(|s| s.cursor == 0) {
+            self.shelves.pop();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.shelves.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn freed_space_is_reused() {
+        let mut packer = ShelfPacker::new(100, 100, 0);
+        let a = packer.allocate(50, 100).unwrap();
+        let b = packer.allocate(50, 100).unwrap();
+        assert_eq!(packer.allocate(10, 10), None);
+
+        packer.deallocate(a, 50);
+        assert_eq!(packer.allocate(50, 50), Some(a));
+        packer.deallocate(b, 50);
+        packer.deallocate(a, 50);
+        assert_eq!(packer.allocate(100, 100), Some([0, 0]));
+    }
+
+    #[test]
+    fn adjacent_spans_merge() {
+        let mut packer = ShelfPacker::new(100, 10, 0);
+        let spans: Vec<[u32; 2]> = (0..4).map(|_| packer.allocate(25, 10).unwrap()).collect();
+        packer.deallocate(spans[1], 25);
+        packer.deallocate(spans[2], 25);
+        assert_eq!(packer.allocate(50, 10), Some(spans[1]));
+    }
+}
@@ -0,0 +1,174 @@
+use bytemuck::{Pod, Zeroable};
+use crate::graphics::color::Color;
+
+pub const MAX_GRADIENT_STOPS: usize = 16;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GradientStop {
+    // position along the gradient between 0 and 1
+    pub offset: f32,
+    pub color: Color
+}
+
+impl GradientStop {
+    pub fn new(offset: f32, color: Color) -> Self {
+        GradientStop { offset, color }
+    }
+}
+
+// how the gradient continues outside of the 0 to 1 range
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum SpreadMode {
+    #[default]
+    Pad,
+    Repeat,
+    Reflect
+}
+
+// points are in physical pixels from the top left corner of the window, angles in radians clockwise
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum GradientKind {
+    Linear { start: [f32; 2], end: [f32; 2] },
+    Radial { center: [f32; 2], radius: f32 },
+    Conic { center: [f32; 2], angle: f32 }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gradient {
+    pub kind: GradientKind,
+    pub stops: Vec<GradientStop>,
+    pub spread: SpreadMode
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Paint {
+    Solid(Color),
+    Gradient(Gradient)
+}
+
+impl Paint {
+    pub fn linear(start: [f32; 2], end: [f32; 2], stops: Vec<GradientStop>) -> Self {
+        Paint::gradient(GradientKind::Linear { start, end }, stops)
+    }
+
+    pub fn radial(center: [f32; 2], radius: f32, stops: Vec<GradientStop>) -> Self {
+        Paint::gradient(GradientKind::Radial { center, radius }, stops)
+    }
+
+    pub fn conic(center: [f32; 2], angle: f32, stops: Vec<GradientStop>) -> Self {
+        Paint::gradient(GradientKind::Conic { center, angle }, stops)
+    }
+
+    fn gradient(kind: GradientKind, stops: Vec<GradientStop>) -> Self {
+        Paint::Gradient(Gradient {
+            kind,
+            stops,
+            spread: SpreadMode::Pad
+        })
+    }
+
+    pub fn with_spread(self, spread: SpreadMode) -> Self {
+        match self {
+            Paint::Gradient(gradient) => Paint::Gradient(Gradient { spread, ..gradient }),
+            solid => solid
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+pub struct PaintUniform {
+    // linear: start and end, radial: center and radius, conic: center and angle
+    pub params: [f32; 4],
+    pub kind: u32,
+    pub spread: u32,
+    pub stop_count: u32,
+    _padding: u32,
+    // four offsets per entry to satisfy uniform array alignment
+    pub offsets: [[f32; 4]; MAX_GRADIENT_STOPS / 4],
+    // premultiplied linear colors
+    pub colors: [[f32; 4]; MAX_GRADIENT_STOPS]
+}
+
+impl PaintUniform {
+    pub fn new(paint: &Paint) -> Self {
+        let mut uniform = PaintUniform::zeroed();
+
+        let stops = match paint {
+            Paint::Solid(color) => vec![GradientStop::new(0.0, *color)],
+            Paint::Gradient(gradient) => {
+                uniform.spread = match gradient.spread {
+                    SpreadMode::Pad => 0,
+                    SpreadMode::Repeat => 1,
+                    SpreadMode::Reflect => 2
+                };
+                (uniform.kind, uniform.params) = match gradient.kind {
+                    GradientKind::Linear { start, end } => (1, [start[0], start[1], end[0], end[1]]),
+                    GradientKind::Radial { center, radius } => (2, [center[0], center[1], radius, 0.0]),
+                    GradientKind::Conic { center, angle } => (3, [center[0], center[1], angle, 0.0])
+                };
+
+                let mut stops = gradient.stops.clone();
+                stops.sort_by(|a, b| a.offset.total_cmp(&b.offset));
+                stops.truncate(MAX_GRADIENT_STOPS);
+                stops
+            }
+        };
+
+        uniform.stop_count = stops.len() as u32;
+        for (index, stop) in stops.iter().enumerate() {
+            uniform.offsets[index / 4][index % 4] = stop.offset.clamp(0.0, 1.0);
+            uniform.colors[index] = stop.color.to_premultiplied_linear();
+        }
+
+        uniform
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphics::color::srgb_to_linear;
+
+    #[test]
+    fn solid_paint_is_a_single_premultiplied_stop() {
+        let uniform = PaintUniform::new(&Paint::Solid(Color::new(1.0, 0.5, 0.0, 0.5)));
+        assert_eq!(uniform.kind, 0);
+        assert_eq!(uniform.stop_count, 1);
+        assert_eq!(uniform.colors[0], [0.5, srgb_to_linear(0.5) * 0.5, 0.0, 0.5]);
+    }
+
+    #[test]
+    fn gradient_parameters_are_packed_by_kind() {
+        let stops = vec![GradientStop::new(0.0, Color::BLACK), GradientStop::new(1.0, Color::WHITE)];
+
+        let linear = PaintUniform::new(&Paint::linear([1.0, 2.0], [3.0, 4.0], stops.clone()));
+        assert_eq!((linear.kind, linear.params), (1, [1.0, 2.0, 3.0, 4.0]));
+
+        let radial = PaintUniform::new(&Paint::radial([5.0, 6.0], 7.0, stops.clone()).with_spread(SpreadMode::Reflect));
+        assert_eq!((radial.kind, radial.params, radial.spread), (2, [5.0, 6.0, 7.0, 0.0], 2));
+
+        let conic = PaintUniform::new(&Paint::conic([0.0, 1.0], 0.5, stops).with_spread(SpreadMode::Repeat));
+        assert_eq!((conic.kind, conic.params, conic.spread), (3, [0.0, 1.0, 0.5, 0.0], 1));
+    }
+
+    #[test]
+    fn stops_are_sorted_and_clamped() {
+        let paint = Paint::linear([0.0, 0.0], [1.0, 0.0], vec![
+            GradientStop::new(1.5, Color::WHITE),
+            GradientStop::new(-0.5, Color::BLACK),
+            GradientStop::new(0.5, Color::new(1.0, 0.0, 0.0, 1.0))
+        ]);
+        let uniform = PaintUniform::new(&paint);
+
+        assert_eq!(uniform.stop_count, 3);
+        assert_eq!(uniform.offsets[0][..3], [0.0, 0.5, 1.0]);
+        assert_eq!(uniform.colors[1], [1.0, 0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn spread_only_applies_to_gradients() {
+        let solid = Paint::Solid(Color::WHITE);
+        assert_eq!(solid.clone().with_spread(SpreadMode::Repeat), solid);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,482 @@
+use std::fmt::{Display, Formatter};
+use lyon_tessellation::geom::Angle;
+use lyon_tessellation::math::{point, vector, Point};
+use lyon_tessellation::path::builder::SvgPathBuilder;
+use lyon_tessellation::path::iterator::PathIterator;
+use lyon_tessellation::path::{ArcFlags, PathEvent};
+use lyon_tessellation::{
+    BuffersBuilder, FillOptions, FillTessellator, FillVertex, StrokeOptions, StrokeTessellator, StrokeVertex,
+    TessellationError, VertexBuffers
+};
+use crate::graphics::instance::Shape;
+use crate::graphics::Vertex;
+use crate::scene::Rect;
+
+const DEFAULT_TOLERANCE: f32 = 0.25;
+// patterns repeating more often than this along a path are drawn solid, they could not be told apart
+// from a solid line and would need more vertices than a shape can index
+const MAX_DASH_CYCLES: f32 = 16384.0;
+
+#[derive(Debug)]
+pub enum PathError {
+    Tessellation(TessellationError)
+}
+
+impl Display for PathError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PathError::Tessellation(e) => write!(f, "could not tessellate path: {:?}", e)
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum PathCommand {
+    MoveTo([f32; 2]),
+    LineTo([f32; 2]),
+    QuadTo([f32; 2], [f32; 2]),
+    CubicTo([f32; 2], [f32; 2], [f32; 2]),
+    ArcTo {
+        radii: [f32; 2],
+        rotation: f32,
+        large_arc: bool,
+        sweep: bool,
+        to: [f32; 2]
+    },
+    Close
+}
+
+// points are in physical pixels from the top left corner of the window
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Path {
+    commands: Vec<PathCommand>
+}
+
+impl Path {
+    pub fn new() -> Self {
+        Path { commands: vec![] }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    pub fn move_to(mut self, x: f32, y: f32) -> Self {
+        self.commands.push(PathCommand::MoveTo([x, y]));
+        self
+    }
+
+    pub fn line_to(mut self, x: f32, y: f32) -> Self {
+        self.commands.push(PathCommand::LineTo([x, y]));
+        self
+    }
+
+    pub fn quad_to(mut self, control: [f32; 2], to: [f32; 2]) -> Self {
+        self.commands.push(PathCommand::QuadTo(control, to));
+        self
+    }
+
+    pub fn cubic_to(mut self, control1: [f32; 2], control2: [f32; 2], to: [f32; 2]) -> Self {
+        self.commands.push(PathCommand::CubicTo(control1, control2, to));
+        self
+    }
+
+    // elliptical arc like the svg "A" command, rotation is in radians
+    pub fn arc_to(mut self, radii: [f32; 2], rotation: f32, large_arc: bool, sweep: bool, to: [f32; 2]) -> Self {
+        self.commands.push(PathCommand::ArcTo { radii, rotation, large_arc, sweep, to });
+        self
+    }
+
+    pub fn close(mut self) -> Self {
+        self.commands.push(PathCommand::Close);
+        self
+    }
+
+    // radii are top left, top right, bottom right, bottom left and shrink to fit the rectangle
+    pub fn rounded_rect(rect: Rect, radii: [f32; 4]) -> Self {
+        let limit = rect.width.min(rect.height) / 2.0;
+        let [top_left, top_right, bottom_right, bottom_left] = radii.map(|r| r.clamp(0.0, limit.max(0.0)));
+        let (left, top, right, bottom) = (rect.x, rect.y, rect.right(), rect.bottom());
+
+        Path::new()
+            .move_to(left + top_left, top)
+            .line_to(right - top_right, top)
+            .arc_to([top_right; 2], 0.0, false, true, [right, top + top_right])
+            .line_to(right, bottom - bottom_right)
+            .arc_to([bottom_right; 2], 0.0, false, true, [right - bottom_right, bottom])
+            .line_to(left + bottom_left, bottom)
+            .arc_to([bottom_left; 2], 0.0, false, true, [left, bottom - bottom_left])
+            .line_to(left, top + top_left)
+            .arc_to([top_left; 2], 0.0, false, true, [left + top_left, top])
+            .close()
+    }
+
+    // whether the point is inside the filled path, open sub paths are closed like when filling
+    pub fn contains(&self, x: f32, y: f32, rule: FillRule) -> bool {
+        let mut winding = 0;
+        let mut cross = |from: Point, to: Point| {
+            // counts edges crossing the horizontal ray to the right of the point, by direction
+            if (from.y <= y) != (to.y <= y) {
+                let t = (y - from.y) / (to.y - from.y);
+                if from.x + t * (to.x - from.x) > x {
+                    winding += if to.y > from.y { 1 } else { -1 };
+                }
+            }
+        };
+
+        for event in self.build().iter().flattened(DEFAULT_TOLERANCE) {
+            match event {
+                PathEvent::Line { from, to } => cross(from, to),
+                PathEvent::End { last, first, .. } => cross(last, first),
+                _ => {}
+            }
+        }
+
+        match rule {
+            FillRule::NonZero => winding != 0,
+            FillRule::EvenOdd => winding % 2 != 0
+        }
+    }
+
+    fn build(&self) -> lyon_tessellation::path::Path {
+        let mut builder = lyon_tessellation::path::Path::svg_builder();
+        let to_point = |p: [f32; 2]| point(p[0], p[1]);
+
+        for command in &self.commands {
+            match *command {
+                PathCommand::MoveTo(to) => {
+                    builder.move_to(to_point(to));
+                }
+                PathCommand::LineTo(to) => {
+                    builder.line_to(to_point(to));
+                }
+                PathCommand::QuadTo(control, to) => {
+                    builder.quadratic_bezier_to(to_point(control), to_point(to));
+                }
+                PathCommand::CubicTo(control1, control2, to) => {
+                    builder.cubic_bezier_to(to_point(control1), to_point(control2), to_point(to));
+                }
+                PathCommand::ArcTo { radii, rotation, large_arc, sweep, to } => {
+                    builder.arc_to(
+                        vector(radii[0], radii[1]),
+                        Angle::radians(rotation),
+                        ArcFlags { large_arc, sweep },
+                        to_point(to)
+                    );
+                }
+                PathCommand::Close => builder.close()
+            }
+        }
+
+        builder.build()
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FillRule {
+    NonZero,
+    EvenOdd
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LineJoin {
+    Miter,
+    Round,
+    Bevel
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LineCap {
+    Butt,
+    Round,
+    Square
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct StrokeStyle {
+    pub width: f32,
+    pub join: LineJoin,
+    pub cap: LineCap,
+    pub miter_limit: f32,
+    // alternating dash and gap lengths, an odd list is repeated twice like in svg
+    pub dashes: Vec<f32>,
+    pub dash_offset: f32
+}
+
+impl StrokeStyle {
+    pub fn new(width: f32) -> Self {
+        StrokeStyle {
+            width,
+            join: LineJoin::Miter,
+            cap: LineCap::Butt,
+            miter_limit: 4.0,
+            dashes: vec![],
+            dash_offset: 0.0
+        }
+    }
+}
+
+pub struct Tessellator {
+    fill: FillTessellator,
+    stroke: StrokeTessellator,
+    // maximum distance in pixels between curves and the line segments approximating them
+    pub tolerance: f32
+}
+
+impl Default for Tessellator {
+    fn default() -> Self {
+        Tessellator::new()
+    }
+}
+
+impl Tessellator {
+    pub fn new() -> Self {
+        Tessellator {
+            fill: FillTessellator::new(),
+            stroke: StrokeTessellator::new(),
+            tolerance: DEFAULT_TOLERANCE
+        }
+    }
+
+    // works for concave and self intersecting paths, open sub paths are closed implicitly;
+    // the shape stays in physical pixels like the path
+    pub fn fill(
+        &mut self,
+        path: &Path,
+        rule: FillRule,
+        color: [f32; 3]
+    ) -> Result<Shape, PathError> {
+        let options = FillOptions::tolerance(self.tolerance).with_fill_rule(match rule {
+            FillRule::NonZero => lyon_tessellation::FillRule::NonZero,
+            FillRule::EvenOdd => lyon_tessellation::FillRule::EvenOdd
+        });
+
+        let mut buffers: VertexBuffers<Vertex, u16> = VertexBuffers::new();
+        self.fill.tessellate_path(
+            &path.build(),
+            &options,
+            &mut BuffersBuilder::new(&mut buffers, |v: FillVertex| {
+                Vertex::new(v.position().to_array(), color)
+            })
+        ).map_err(PathError::Tessellation)?;
+
+        Ok(into_shape(buffers))
+    }
+
+    pub fn stroke(
+        &mut self,
+        path: &Path,
+        style: &StrokeStyle,
+        color: [f32; 3]
+    ) -> Result<Shape, PathError> {
+        let cap = match style.cap {
+            LineCap::Butt => lyon_tessellation::LineCap::Butt,
+            LineCap::Round => lyon_tessellation::LineCap::Round,
+            LineCap::Square => lyon_tessellation::LineCap::Square
+        };
+        let options = StrokeOptions::tolerance(self.tolerance)
+            .with_line_width(style.width)
+            .with_line_cap(cap)
+            .with_line_join(match style.join {
+                LineJoin::Miter => lyon_tessellation::LineJoin::Miter,
+                LineJoin::Round => lyon_tessellation::LineJoin::Round,
+                LineJoin::Bevel => lyon_tessellation::LineJoin::Bevel
+            })
+            .with_miter_limit(style.miter_limit.max(StrokeOptions::MINIMUM_MITER_LIMIT));
+
+        let mut path = path.build();
+        if !style.dashes.is_empty() {
+            path = dash(&path, &style.dashes, style.dash_offset, self.tolerance);
+        }
+
+        let mut buffers: VertexBuffers<Vertex, u16> = VertexBuffers::new();
+        self.stroke.tessellate_path(
+            &path,
+            &options,
+            &mut BuffersBuilder::new(&mut buffers, |v: StrokeVertex| {
+                Vertex::new(v.position().to_array(), color)
+            })
+        ).map_err(PathError::Tessellation)?;
+
+        Ok(into_shape(buffers))
+    }
+}
+
+// the instance pipeline culls clockwise triangles, so every triangle is turned counter clockwise on screen,
+// which is clockwise in pixels since their y axis points down
+fn into_shape(buffers: VertexBuffers<Vertex, u16>) -> Shape {
+    let vertices = buffers.vertices;
+    let mut indices = buffers.indices;
+
+    for triangle in indices.chunks_exact_mut(3) {
+        let [a, b, c] = [triangle[0], triangle[1], triangle[2]].map(|i| vertices[i as usize].position);
+        let area = (b[0] - a[0]) * (c[1] - a[1]) - (c[0] - a[0]) * (b[1] - a[1]);
+        if area > 0.0 {
+            triangle.swap(1, 2);
+        }
+    }
+
+    Shape { vertices, indices }
+}
+
+// splits the path into the dashes of the pattern, restarting the pattern for every sub path
+fn dash(path: &lyon_tessellation::path::Path, pattern: &[f32], offset: f32, tolerance: f32) -> lyon_tessellation::path::Path {
+    if pattern.iter().sum::<f32>() <= 0.0 || pattern.iter().any(|d| *d < 0.0 || !d.is_finite()) {
+        return path.clone();
+    }
+
+    let pattern: Vec<f32> = if pattern.len() % 2 == 1 {
+        pattern.repeat(2)
+    } else {
+        pattern.to_vec()
+    };
+    let total: f32 = pattern.iter().sum();
+    let length: f32 = path.iter().flattened(tolerance).map(|event| match event {
+        PathEvent::Line { from, to } => (to - from).length(),
+        PathEvent::End { last, first, close: true } => (first - last).length(),
+        _ => 0.0
+    }).sum();
+    if length / total > MAX_DASH_CYCLES {
+        return path.clone();
+    }
+
+    let mut dasher = Dasher {
+        builder: lyon_tessellation::path::Path::builder(),
+        pattern: &pattern,
+        index: 0,
+        remaining: 0.0,
+        drawing: false
+    };
+
+    for event in path.iter().flattened(tolerance) {
+        match event {
+            PathEvent::Begin { .. } => dasher.restart(offset.rem_euclid(total)),
+            PathEvent::Line { from, to } => dasher.walk(from, to),
+            PathEvent::End { last, first, close } => {
+                if close {
+                    dasher.walk(last, first);
+                }
+                dasher.finish();
+            }
+            _ => {}
+        }
+    }
+
+    dasher.builder.build()
+}
+
+struct Dasher<'a> {
+    builder: lyon_tessellation::path::path::Builder,
+    pattern: &'a [f32],
+    index: usize,
+    remaining: f32,
+    drawing: bool
+}
+
+impl Dasher<'_> {
+    fn restart(&mut self, mut offset: f32) {
+        self.finish();
+        self.index = 0;
+        while offset >= self.pattern[self.index] {
+            offset -= self.pattern[self.index];
+            self.index = (self.index + 1) % self.pattern.len();
+        }
+        self.remaining = self.pattern[self.index] - offset;
+    }
+
+    fn walk(&mut self, from: Point, to: Point) {
+        let length = (to - from).length();
+        let mut walked = 0.0;
+
+        while length - walked > f32::EPSILON {
+            let step = self.remaining.min(length - walked);
+            // even entries of the pattern are dashes, odd ones are gaps
+            if self.index.is_multiple_of(2) {
+                if !self.drawing {
+                    self.builder.begin(from.lerp(to, walked / length));
+                    self.drawing = true;
+                }
+                self.builder.line_to(from.lerp(to, (walked + step) / length));
+            }
+
+            walked += step;
+            self.remaining -= step;
+            if self.remaining <= f32::EPSILON {
+                self.finish();
+                self.index = (self.index + 1) % self.pattern.len();
+                self.remaining = self.pattern[self.index];
+            }
+        }
+    }
+
+    fn finish(&mut self) {
+        if self.drawing {
+            self.builder.end(false);
+            self.drawing = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(length: f32) -> Path {
+        Path::new().move_to(0.0, 0.0).line_to(length, 0.0)
+    }
+
+    fn dashed(length: f32, dashes: Vec<f32>) -> Shape {
+        let style = StrokeStyle { dashes, ..StrokeStyle::new(2.0) };
+        Tessellator::new().stroke(&line(length), &style, [0.0; 3]).unwrap()
+    }
+
+    #[test]
+    fn dashes_split_the_stroke() {
+        let solid = dashed(100.0, vec![]);
+        let dashes = dashed(100.0, vec![10.0, 10.0]);
+        assert_eq!(dashes.vertices.len(), solid.vertices.len() * 5);
+    }
+
+    #[test]
+    fn rounded_corners_are_outside() {
+        let path = Path::rounded_rect(Rect::new(0.0, 0.0, 100.0, 100.0), [20.0; 4]);
+        assert!(path.contains(50.0, 50.0, FillRule::NonZero));
+        assert!(path.contains(1.0, 50.0, FillRule::NonZero));
+        assert!(!path.contains(1.0, 1.0, FillRule::NonZero));
+        assert!(!path.contains(150.0, 50.0, FillRule::NonZero));
+    }
+
+    #[test]
+    fn fill_rules_differ_inside_overlaps() {
+        let square = |path: Path, offset: f32, size: f32| path
+            .move_to(offset, offset)
+            .line_to(offset + size, offset)
+            .line_to(offset + size, offset + size)
+            .line_to(offset, offset + size)
+            .close();
+        let path = square(square(Path::new(), 0.0, 100.0), 25.0, 50.0);
+        assert!(path.contains(50.0, 50.0, FillRule::NonZero));
+        assert!(!path.contains(50.0, 50.0, FillRule::EvenOdd));
+        assert!(path.contains(10.0, 50.0, FillRule::EvenOdd));
+    }
+
+    #[test]
+    fn tiny_dashes_draw_solid() {
+        let solid = dashed(1000.0, vec![]);
+        let dashes = dashed(1000.0, vec![1e-6, 1e-6]);
+        assert_eq!(dashes.vertices.len(), solid.vertices.len());
+    }
+
+    #[test]
+    fn triangles_face_the_screen_once_projected() {
+        let path = Path::new().move_to(10.0, 10.0).line_to(90.0, 10.0).line_to(50.0, 90.0).close();
+        let shape = Tessellator::new().fill(&path, FillRule::NonZero, [0.0; 3]).unwrap();
+        assert!(shape.vertices.iter().all(|v| v.position[0] >= 10.0 && v.position[1] <= 90.0));
+
+        let projected = shape.to_clip(winit::dpi::PhysicalSize::new(100, 100));
+        for triangle in projected.indices.chunks_exact(3) {
+            let [a, b, c] = [triangle[0], triangle[1], triangle[2]].map(|i| projected.vertices[i as usize].position);
+            assert!((b[0] - a[0]) * (c[1] - a[1]) - (c[0] - a[0]) * (b[1] - a[1]) > 0.0);
+        }
+    }
+}
@@ -1,8 +1,43 @@
-use wgpu::{Device, RenderPipeline};
+use wgpu::{BindGroup, BindGroupLayout, Buffer, Device, RenderPipeline, Sampler, ShaderModule, TextureView};
+use crate::graphics::atlas::{quad_corner_desc, AtlasInstance};
 use crate::graphics::instance::InstanceData;
-use crate::graphics::Vertex;
+use crate::graphics::sdf::SdfInstance;
+use crate::graphics::{ImageVertex, TextVertex, Vertex};
 
-pub fn create_instance_pipeline(device: &Device) -> RenderPipeline {
+pub const STENCIL_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Stencil8;
+
+// content is only drawn where the stencil value equals the reference, which is the number of stencil clips applied
+fn content_stencil_state() -> Option<wgpu::DepthStencilState> {
+    Some(stencil_state(wgpu::CompareFunction::Equal, wgpu::StencilOperation::Keep, 0))
+}
+
+fn stencil_state(
+    compare: wgpu::CompareFunction,
+    pass_op: wgpu::StencilOperation,
+    write_mask: u32
+) -> wgpu::DepthStencilState {
+    let face = wgpu::StencilFaceState {
+        compare,
+        fail_op: wgpu::StencilOperation::Keep,
+        depth_fail_op: wgpu::StencilOperation::Keep,
+        pass_op,
+    };
+
+    wgpu::DepthStencilState {
+        format: STENCIL_FORMAT,
+        depth_write_enabled: false,
+        depth_compare: wgpu::CompareFunction::Always,
+        stencil: wgpu::StencilState {
+            front: face,
+            back: face,
+            read_mask: 0xff,
+            write_mask,
+        },
+        bias: wgpu::DepthBiasState::default(),
+    }
+}
+
+pub fn create_instance_pipeline(device: &Device, sample_count: u32) -> RenderPipeline {
     let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
         label: Some("Shader"),
         source: wgpu::ShaderSource::Wgsl(include_str!("../../shaders/instance.wgsl").into()),
@@ -46,9 +81,9 @@ pub fn create_instance_pipeline(device: &Device) -> RenderPipeline {
             polygon_mode: wgpu::PolygonMode::Fill,
             conservative: false,
         },
-        depth_stencil: None,
+        depth_stencil: content_stencil_state(),
         multisample: wgpu::MultisampleState {
-            count: 1,
+            count: sample_count,
             mask: !0,
             alpha_to_coverage_enabled: false,
         },
@@ -99,6 +134,381 @@ pub fn create_vertex_pipeline(device: &Device) -> RenderPipeline {
             polygon_mode: wgpu::PolygonMode::Fill,
             conservative: false,
         },
+        depth_stencil: content_stencil_state(),
+        multisample: wgpu::MultisampleState {
+            count: 1,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+        cache: None,
+    })
+}
+
+pub fn create_texture_bind_group_layout(device: &Device) -> BindGroupLayout {
+    create_sampled_bind_group_layout(device, wgpu::TextureViewDimension::D2)
+}
+
+pub fn create_texture_array_bind_group_layout(device: &Device) -> BindGroupLayout {
+    create_sampled_bind_group_layout(device, wgpu::TextureViewDimension::D2Array)
+}
+
+fn create_sampled_bind_group_layout(device: &Device, view_dimension: wgpu::TextureViewDimension) -> BindGroupLayout {
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("Texture Bind Group Layout"),
+        entries: &[
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension,
+                    multisampled: false,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            }
+        ],
+    })
+}
+
+pub fn create_texture_bind_group(
+    device: &Device,
+    layout: &BindGroupLayout,
+    view: &TextureView,
+    sampler: &Sampler
+) -> BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Texture Bind Group"),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::Sampler(sampler),
+            }
+        ],
+    })
+}
+
+// glyph quads are positioned in pixels and mapped to clip space with the viewport, so text keeps its place on resize
+pub fn create_text_pipeline(device: &Device, texture_layout: &BindGroupLayout, viewport_layout: &BindGroupLayout, sample_count: u32) -> RenderPipeline {
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Text Shader"),
+        source: wgpu::ShaderSource::Wgsl(include_str!("../../shaders/text.wgsl").into()),
+    });
+
+    create_textured_pipeline(device, &[texture_layout, viewport_layout], &shader, &[TextVertex::desc()], sample_count, "Text Render Pipeline")
+}
+
+// images and atlas quads are positioned in pixels and mapped with the viewport like text
+pub fn create_image_pipeline(device: &Device, texture_layout: &BindGroupLayout, viewport_layout: &BindGroupLayout, sample_count: u32) -> RenderPipeline {
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Image Shader"),
+        source: wgpu::ShaderSource::Wgsl(include_str!("../../shaders/image.wgsl").into()),
+    });
+
+    create_textured_pipeline(device, &[texture_layout, viewport_layout], &shader, &[ImageVertex::desc()], sample_count, "Image Render Pipeline")
+}
+
+pub fn create_atlas_pipeline(device: &Device, atlas_layout: &BindGroupLayout, viewport_layout: &BindGroupLayout, sample_count: u32) -> RenderPipeline {
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Atlas Shader"),
+        source: wgpu::ShaderSource::Wgsl(include_str!("../../shaders/atlas.wgsl").into()),
+    });
+
+    create_textured_pipeline(
+        device,
+        &[atlas_layout, viewport_layout],
+        &shader,
+        &[quad_corner_desc(), AtlasInstance::desc()],
+        sample_count,
+        "Atlas Render Pipeline"
+    )
+}
+
+// a single uniform buffer, used for the viewport size and for paints
+pub fn create_uniform_bind_group_layout(device: &Device) -> BindGroupLayout {
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("Uniform Bind Group Layout"),
+        entries: &[
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }
+        ],
+    })
+}
+
+pub fn create_uniform_bind_group(device: &Device, layout: &BindGroupLayout, buffer: &Buffer) -> BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Uniform Bind Group"),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            }
+        ],
+    })
+}
+
+// shapes are drawn as signed distance fields, so their colors come out premultiplied by coverage
+pub fn create_sdf_pipeline(device: &Device, viewport_layout: &BindGroupLayout, sample_count: u32) -> RenderPipeline {
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("SDF Shader"),
+        source: wgpu::ShaderSource::Wgsl(include_str!("../../shaders/sdf.wgsl").into()),
+    });
+
+    create_blended_pipeline(
+        device,
+        &[viewport_layout],
+        &shader,
+        &[quad_corner_desc(), SdfInstance::desc()],
+        wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING,
+        sample_count,
+        "SDF Render Pipeline"
+    )
+}
+
+// paints are evaluated per pixel in linear space and come out premultiplied, shapes are in pixels like text
+pub fn create_paint_pipeline(device: &Device, paint_layout: &BindGroupLayout, viewport_layout: &BindGroupLayout, sample_count: u32) -> RenderPipeline {
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Paint Shader"),
+        source: wgpu::ShaderSource::Wgsl(include_str!("../../shaders/paint.wgsl").into()),
+    });
+
+    create_blended_pipeline(
+        device,
+        &[paint_layout, viewport_layout],
+        &shader,
+        &[Vertex::desc()],
+        wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING,
+        sample_count,
+        "Paint Render Pipeline"
+    )
+}
+
+// increments the stencil where the clip outline covers pixels that passed every outer clip
+pub fn create_clip_pipeline(device: &Device, sample_count: u32) -> RenderPipeline {
+    create_stencil_pipeline(
+        device,
+        wgpu::CompareFunction::Equal,
+        wgpu::StencilOperation::IncrementClamp,
+        sample_count,
+        "Clip Render Pipeline"
+    )
+}
+
+// sets the stencil back to the reference wherever it is drawn, used to undo clips
+pub fn create_stencil_reset_pipeline(device: &Device, sample_count: u32) -> RenderPipeline {
+    create_stencil_pipeline(
+        device,
+        wgpu::CompareFunction::Always,
+        wgpu::StencilOperation::Replace,
+        sample_count,
+        "Stencil Reset Render Pipeline"
+    )
+}
+
+fn create_stencil_pipeline(
+    device: &Device,
+    compare: wgpu::CompareFunction,
+    pass_op: wgpu::StencilOperation,
+    sample_count: u32,
+    label: &str
+) -> RenderPipeline {
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Clip Shader"),
+        source: wgpu::ShaderSource::Wgsl(include_str!("../../shaders/clip.wgsl").into()),
+    });
+
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some(label),
+        bind_group_layouts: &[],
+        push_constant_ranges: &[],
+    });
+
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some(label),
+        layout: Some(&pipeline_layout),
+        vertex: wgpu::VertexState {
+            module: &shader,
+            entry_point: "vs_main",
+            compilation_options: Default::default(),
+            buffers: &[Vertex::desc()],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &shader,
+            entry_point: "fs_main",
+            compilation_options: Default::default(),
+            targets: &[Some(wgpu::ColorTargetState {
+                format: wgpu::TextureFormat::Bgra8UnormSrgb,
+                blend: None,
+                write_mask: wgpu::ColorWrites::empty(),
+            })],
+        }),
+        multiview: None,
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: None,
+            unclipped_depth: false,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            conservative: false,
+        },
+        depth_stencil: Some(stencil_state(compare, pass_op, 0xff)),
+        multisample: wgpu::MultisampleState {
+            count: sample_count,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+        cache: None,
+    })
+}
+
+// post processing reads whole textures with textureLoad, followed by an optional uniform buffer
+pub fn create_post_bind_group_layout(device: &Device, textures: u32, uniform: bool) -> BindGroupLayout {
+    let mut entries: Vec<wgpu::BindGroupLayoutEntry> = (0..textures)
+        .map(|binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        })
+        .collect();
+
+    if uniform {
+        entries.push(wgpu::BindGroupLayoutEntry {
+            binding: textures,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        });
+    }
+
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("Post Bind Group Layout"),
+        entries: &entries,
+    })
+}
+
+pub fn create_post_bind_group(
+    device: &Device,
+    layout: &BindGroupLayout,
+    textures: &[&TextureView],
+    uniform: Option<&Buffer>
+) -> BindGroup {
+    let mut entries: Vec<wgpu::BindGroupEntry> = textures
+        .iter()
+        .enumerate()
+        .map(|(binding, view)| wgpu::BindGroupEntry {
+            binding: binding as u32,
+            resource: wgpu::BindingResource::TextureView(view),
+        })
+        .collect();
+
+    if let Some(buffer) = uniform {
+        entries.push(wgpu::BindGroupEntry {
+            binding: textures.len() as u32,
+            resource: buffer.as_entire_binding(),
+        });
+    }
+
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Post Bind Group"),
+        layout,
+        entries: &entries,
+    })
+}
+
+// copies the rendered scene to the surface
+pub fn create_blit_pipeline(device: &Device, layout: &BindGroupLayout) -> RenderPipeline {
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Blit Shader"),
+        source: wgpu::ShaderSource::Wgsl(include_str!("../../shaders/blit.wgsl").into()),
+    });
+
+    create_post_pipeline(device, layout, &shader, "Blit Render Pipeline")
+}
+
+pub fn create_blur_pipeline(device: &Device, layout: &BindGroupLayout) -> RenderPipeline {
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Blur Shader"),
+        source: wgpu::ShaderSource::Wgsl(include_str!("../../shaders/blur.wgsl").into()),
+    });
+
+    create_post_pipeline(device, layout, &shader, "Blur Render Pipeline")
+}
+
+// blends a layer onto a copy of the scene behind it and replaces the scene with the result
+pub fn create_composite_pipeline(device: &Device, layout: &BindGroupLayout) -> RenderPipeline {
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Composite Shader"),
+        source: wgpu::ShaderSource::Wgsl(include_str!("../../shaders/composite.wgsl").into()),
+    });
+
+    create_post_pipeline(device, layout, &shader, "Composite Render Pipeline")
+}
+
+fn create_post_pipeline(device: &Device, layout: &BindGroupLayout, shader: &ShaderModule, label: &str) -> RenderPipeline {
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some(label),
+        bind_group_layouts: &[layout],
+        push_constant_ranges: &[],
+    });
+
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some(label),
+        layout: Some(&pipeline_layout),
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: "vs_main",
+            compilation_options: Default::default(),
+            buffers: &[],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            entry_point: "fs_main",
+            compilation_options: Default::default(),
+            targets: &[Some(wgpu::ColorTargetState {
+                format: wgpu::TextureFormat::Bgra8UnormSrgb,
+                blend: Some(wgpu::BlendState::REPLACE),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        multiview: None,
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: None,
+            unclipped_depth: false,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            conservative: false,
+        },
         depth_stencil: None,
         multisample: wgpu::MultisampleState {
             count: 1,
@@ -107,4 +517,77 @@ pub fn create_vertex_pipeline(device: &Device) -> RenderPipeline {
         },
         cache: None,
     })
+}
+
+fn create_textured_pipeline(
+    device: &Device,
+    bind_group_layouts: &[&BindGroupLayout],
+    shader: &ShaderModule,
+    vertex_layouts: &[wgpu::VertexBufferLayout<'static>],
+    sample_count: u32,
+    label: &str
+) -> RenderPipeline {
+    create_blended_pipeline(
+        device,
+        bind_group_layouts,
+        shader,
+        vertex_layouts,
+        wgpu::BlendState::ALPHA_BLENDING,
+        sample_count,
+        label
+    )
+}
+
+fn create_blended_pipeline(
+    device: &Device,
+    bind_group_layouts: &[&BindGroupLayout],
+    shader: &ShaderModule,
+    vertex_layouts: &[wgpu::VertexBufferLayout<'static>],
+    blend: wgpu::BlendState,
+    sample_count: u32,
+    label: &str
+) -> RenderPipeline {
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some(label),
+        bind_group_layouts,
+        push_constant_ranges: &[],
+    });
+
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some(label),
+        layout: Some(&pipeline_layout),
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: "vs_main",
+            compilation_options: Default::default(),
+            buffers: vertex_layouts,
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            entry_point: "fs_main",
+            compilation_options: Default::default(),
+            targets: &[Some(wgpu::ColorTargetState {
+                format: wgpu::TextureFormat::Bgra8UnormSrgb,
+                blend: Some(blend),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        multiview: None,
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: None,
+            unclipped_depth: false,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            conservative: false,
+        },
+        depth_stencil: content_stencil_state(),
+        multisample: wgpu::MultisampleState {
+            count: sample_count,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+        cache: None,
+    })
 }
\ No newline at end of file
@@ -0,0 +1,320 @@
+use bytemuck::{Pod, Zeroable};
+use crate::graphics::color::Color;
+use crate::scene::Rect;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Border {
+    pub width: f32,
+    pub color: Color
+}
+
+impl Border {
+    pub fn new(width: f32, color: Color) -> Self {
+        Border { width, color }
+    }
+}
+
+// behaves like a css box-shadow: the shadow is the shape moved by offset, grown by spread and blurred
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BoxShadow {
+    pub offset: [f32; 2],
+    pub blur: f32,
+    pub spread: f32,
+    pub color: Color
+}
+
+impl BoxShadow {
+    pub fn new(offset: [f32; 2], blur: f32, spread: f32, color: Color) -> Self {
+        BoxShadow { offset, blur, spread, color }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RoundedRect {
+    pub bounds: Rect,
+    // top left, top right, bottom right, bottom left in physical pixels
+    pub radii: [f32; 4],
+    pub color: Color,
+    pub border: Option<Border>,
+    pub shadow: Option<BoxShadow>
+}
+
+impl RoundedRect {
+    pub fn new(bounds: Rect, radius: f32, color: Color) -> Self {
+        RoundedRect {
+            bounds,
+            radii: [radius; 4],
+            color,
+            border: None,
+            shadow: None
+        }
+    }
+}
+
+// fill and stroke of circles and ellipses, a transparent fill leaves only the stroke
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ShapeStyle {
+    pub fill: Color,
+    pub stroke: Option<Border>,
+    pub shadow: Option<BoxShadow>
+}
+
+impl ShapeStyle {
+    pub fn fill(color: Color) -> Self {
+        ShapeStyle {
+            fill: color,
+            stroke: None,
+            shadow: None
+        }
+    }
+
+    pub fn stroke(width: f32, color: Color) -> Self {
+        ShapeStyle {
+            fill: Color::TRANSPARENT,
+            stroke: Some(Border::new(width, color)),
+            shadow: None
+        }
+    }
+}
+
+// angles are in radians, clockwise from the positive x axis since y points down
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ArcSegment {
+    pub center: [f32; 2],
+    // radius of the middle of the stroke
+    pub radius: f32,
+    pub start_angle: f32,
+    pub end_angle: f32,
+    pub width: f32,
+    pub color: Color
+}
+
+impl ArcSegment {
+    pub fn new(center: [f32; 2], radius: f32, start_angle: f32, end_angle: f32, width: f32, color: Color) -> Self {
+        ArcSegment { center, radius, start_angle, end_angle, width, color }
+    }
+}
+
+#[repr(u32)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SdfKind {
+    RoundedRect = 0,
+    Ellipse = 1,
+    Arc = 2
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+pub struct SdfInstance {
+    // x, y, width, height in physical pixels
+    pub rect: [f32; 4],
+    pub radii: [f32; 4],
+    // colors are linear with straight alpha
+    pub color: [f32; 4],
+    pub border_color: [f32; 4],
+    pub shadow_color: [f32; 4],
+    // offset x, offset y, blur radius, spread
+    pub shadow: [f32; 4],
+    pub border_width: f32,
+    pub kind: u32,
+    // arcs: start angle, sweep, stroke width
+    pub params: [f32; 4],
+    pub opacity: f32
+}
+
+impl SdfInstance {
+    pub fn rounded_rect(rect: &RoundedRect) -> Self {
+        let border = rect.border.unwrap_or(Border::new(0.0, Color::TRANSPARENT));
+        let shadow = rect.shadow.unwrap_or(BoxShadow::new([0.0, 0.0], 0.0, 0.0, Color::TRANSPARENT));
+        let bounds = rect.bounds;
+
+        SdfInstance {
+            rect: [bounds.x, bounds.y, bounds.width, bounds.height],
+            radii: rect.radii.map(|r| r.max(0.0)),
+            color: rect.color.to_linear(),
+            border_color: border.color.to_linear(),
+            shadow_color: shadow.color.to_linear(),
+            shadow: [shadow.offset[0], shadow.offset[1], shadow.blur.max(0.0), shadow.spread],
+            border_width: border.width.max(0.0),
+            kind: SdfKind::RoundedRect as u32,
+            params: [0.0; 4],
+            opacity: 1.0
+        }
+    }
+
+    pub fn ellipse(bounds: Rect, style: &ShapeStyle) -> Self {
+        let mut rect = RoundedRect::new(bounds, 0.0, style.fill);
+        rect.border = style.stroke;
+        rect.shadow = style.shadow;
+
+        SdfInstance {
+            kind: SdfKind::Ellipse as u32,
+            ..SdfInstance::rounded_rect(&rect)
+        }
+    }
+
+    pub fn arc(arc: &ArcSegment) -> Self {
+        // the arc runs clockwise from start to end, so 350° to 10° is a 20° arc crossing 0°
+        let start = arc.start_angle.rem_euclid(std::f32::consts::TAU);
+        let span = arc.end_angle - arc.start_angle;
+        let sweep = if span.abs() >= std::f32::consts::TAU {
+            std::f32::consts::TAU
+        } else {
+            span.rem_euclid(std::f32::consts::TAU)
+        };
+        let width = arc.width.max(0.0);
+        let extent = arc.radius.max(0.0) + width / 2.0;
+        let bounds = Rect::new(arc.center[0] - extent, arc.center[1] - extent, extent * 2.0, extent * 2.0);
+
+        SdfInstance {
+            kind: SdfKind::Arc as u32,
+            params: [start, sweep, width, 0.0],
+            ..SdfInstance::rounded_rect(&RoundedRect::new(bounds, 0.0, arc.color))
+        }
+    }
+
+    // mirrors the shader so hit testing agrees with what is drawn, shadows are not part of the shape
+    pub fn distance(&self, x: f32, y: f32) -> f32 {
+        let [left, top, width, height] = self.rect;
+        let half = [width / 2.0, height / 2.0];
+        let p = [x - left - half[0], y - top - half[1]];
+
+        if self.kind == SdfKind::Ellipse as u32 {
+            let radii = [half[0].max(0.001), half[1].max(0.001)];
+            let k0 = (p[0] / radii[0]).hypot(p[1] / radii[1]);
+            let k1 = (p[0] / (radii[0] * radii[0])).hypot(p[1] / (radii[1] * radii[1]));
+            if k1 == 0.0 {
+                return -radii[0].min(radii[1]);
+            }
+            k0 * (k0 - 1.0) / k1
+        } else if self.kind == SdfKind::Arc as u32 {
+            let [start, sweep, stroke, _] = self.params;
+            let radius = half[0].min(half[1]) - stroke / 2.0;
+            let relative = (p[1].atan2(p[0]) - start).rem_euclid(std::f32::consts::TAU);
+            if relative <= sweep {
+                return (p[0].hypot(p[1]) - radius).abs() - stroke / 2.0;
+            }
+
+            let cap = |angle: f32| (p[0] - radius * angle.cos()).hypot(p[1] - radius * angle.sin());
+            cap(start).min(cap(start + sweep)) - stroke / 2.0
+        } else {
+            let [top_left, top_right, bottom_right, bottom_left] = self.radii;
+            let corner = match (p[0] > 0.0, p[1] > 0.0) {
+                (false, false) => top_left,
+                (true, false) => top_right,
+                (true, true) => bottom_right,
+                (false, true) => bottom_left
+            };
+            let radius = corner.min(half[0]).min(half[1]);
+            let q = [p[0].abs() - half[0] + radius, p[1].abs() - half[1] + radius];
+            q[0].max(q[1]).min(0.0) + q[0].max(0.0).hypot(q[1].max(0.0)) - radius
+        }
+    }
+
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        self.distance(x, y) <= 0.0
+    }
+
+    pub fn desc() -> wgpu::VertexBufferLayout<'static> {
+        const ATTRIBUTES: [wgpu::VertexAttribute; 10] = wgpu::vertex_attr_array![
+            1 => Float32x4,
+            2 => Float32x4,
+            3 => Float32x4,
+            4 => Float32x4,
+            5 => Float32x4,
+            6 => Float32x4,
+            7 => Float32,
+            8 => Uint32,
+            9 => Float32x4,
+            10 => Float32
+        ];
+
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<SdfInstance>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &ATTRIBUTES
+        }
+    }
+}
+
+// size of the surface in physical pixels, shared by every shader that works in pixels
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+pub struct ViewportUniform {
+    pub size: [f32; 2],
+    _padding: [f32; 2]
+}
+
+impl ViewportUniform {
+    pub fn new(width: u32, height: u32) -> Self {
+        ViewportUniform {
+            size: [width as f32, height as f32],
+            _padding: [0.0; 2]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_rounded_rects_pack_no_border_or_shadow() {
+        let instance = SdfInstance::rounded_rect(&RoundedRect::new(Rect::new(1.0, 2.0, 3.0, 4.0), 5.0, Color::WHITE));
+        assert_eq!(instance.rect, [1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(instance.radii, [5.0; 4]);
+        assert_eq!(instance.border_width, 0.0);
+        assert_eq!((instance.border_color, instance.shadow_color), ([0.0; 4], [0.0; 4]));
+        assert_eq!(instance.shadow, [0.0; 4]);
+    }
+
+    #[test]
+    fn negative_radii_widths_and_blur_are_clamped() {
+        let mut rect = RoundedRect::new(Rect::new(0.0, 0.0, 10.0, 10.0), 2.0, Color::WHITE);
+        rect.radii = [-1.0, 2.0, -3.0, 4.0];
+        rect.border = Some(Border::new(-2.0, Color::WHITE));
+        // a negative spread shrinks the shadow like in css, so it is kept
+        rect.shadow = Some(BoxShadow::new([1.0, -1.0], -4.0, -2.0, Color::WHITE));
+
+        let instance = SdfInstance::rounded_rect(&rect);
+        assert_eq!(instance.radii, [0.0, 2.0, 0.0, 4.0]);
+        assert_eq!(instance.border_width, 0.0);
+        assert_eq!(instance.shadow, [1.0, -1.0, 0.0, -2.0]);
+        assert_eq!(instance.shadow_color, [1.0; 4]);
+    }
+
+    #[test]
+    fn rounded_corners_are_left_out_of_the_shape() {
+        let rect = SdfInstance::rounded_rect(&RoundedRect::new(Rect::new(0.0, 0.0, 100.0, 50.0), 20.0, Color::WHITE));
+        assert!(rect.contains(50.0, 25.0));
+        assert!(rect.contains(1.0, 25.0));
+        assert!(!rect.contains(2.0, 2.0));
+        assert!(!rect.contains(101.0, 25.0));
+        assert_eq!(rect.distance(50.0, -5.0), 5.0);
+    }
+
+    #[test]
+    fn ellipses_contain_points_inside_their_outline() {
+        let ellipse = SdfInstance::ellipse(Rect::new(0.0, 0.0, 100.0, 50.0), &ShapeStyle::fill(Color::WHITE));
+        assert!(ellipse.contains(50.0, 25.0));
+        assert!(ellipse.contains(98.0, 25.0));
+        assert!(ellipse.contains(50.0, 1.0));
+        assert!(!ellipse.contains(5.0, 5.0));
+        assert!(!ellipse.contains(50.0, 51.0));
+    }
+
+    #[test]
+    fn arcs_contain_their_stroke_within_the_sweep() {
+        use std::f32::consts::PI;
+        // the lower half of a circle around 50, 50, y points down
+        let arc = SdfInstance::arc(&ArcSegment::new([50.0, 50.0], 40.0, 0.0, PI, 10.0, Color::WHITE));
+        assert_eq!(arc.rect, [5.0, 5.0, 90.0, 90.0]);
+        assert!(arc.contains(50.0, 90.0));
+        assert!(arc.contains(50.0, 94.0));
+        assert!(!arc.contains(50.0, 50.0));
+        assert!(!arc.contains(50.0, 10.0));
+        // the ends are round
+        assert!(arc.contains(88.0, 48.0));
+        assert!(!arc.contains(90.0, 44.0));
+    }
+}
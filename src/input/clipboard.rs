@@ -0,0 +1,35 @@
+use std::sync::{Arc, Mutex};
+
+// platform clipboards plug in through this trait, widgets only ever talk to it
+pub trait Clipboard: Send {
+    fn read(&mut self) -> Option<String>;
+
+    fn write(&mut self, text: String);
+}
+
+pub type SharedClipboard = Arc<Mutex<dyn Clipboard>>;
+
+#[derive(Clone, Debug, Default)]
+pub struct InMemoryClipboard {
+    contents: Option<String>
+}
+
+impl InMemoryClipboard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn shared() -> SharedClipboard {
+        Arc::new(Mutex::new(Self::new()))
+    }
+}
+
+impl Clipboard for InMemoryClipboard {
+    fn read(&mut self) -> Option<String> {
+        self.contents.clone()
+    }
+
+    fn write(&mut self, text: String) {
+        self.contents = Some(text);
+    }
+}
@@ -0,0 +1,386 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use winit::event::{ElementState, Ime, KeyEvent};
+use winit::keyboard::{Key, ModifiersState, NamedKey};
+use crate::scene::{NodeId, Rect};
+
+pub type KeyHandler = Box<dyn FnMut(&KeyEvent, ModifiersState) -> bool + Send>;
+pub type FocusHandler = Box<dyn FnMut(bool) + Send>;
+pub type ImeHandler = Box<dyn FnMut(&Ime) -> bool + Send>;
+
+struct FocusEntry {
+    id: NodeId,
+    bounds: Rect,
+    // follows the html semantics: negative values are only focusable programmatically,
+    // positive values come first in ascending order and zero follows in registration order
+    tab_index: i32,
+    enabled: bool,
+    // shortcut scope whose bindings take precedence while this node is focused
+    scope: Option<String>,
+    key_handler: Option<KeyHandler>,
+    ime_handler: Option<ImeHandler>,
+    focus_handler: Option<FocusHandler>
+}
+
+#[derive(Default)]
+struct FocusState {
+    entries: Vec<FocusEntry>,
+    focused: Option<NodeId>,
+    // focus changes waiting to be delivered to their handlers, in the order they happened
+    notifications: VecDeque<(NodeId, bool)>,
+    modifiers: ModifiersState,
+    next_id: u32
+}
+
+impl FocusState {
+    fn entry(&mut self, id: NodeId) -> Option<&mut FocusEntry> {
+        self.entries.iter_mut().find(|e| e.id == id)
+    }
+
+    fn tab_order(&self) -> Vec<NodeId> {
+        let mut order: Vec<(usize, &FocusEntry)> = self.entries
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| e.enabled && e.tab_index >= 0)
+            .collect();
+
+        order.sort_by_key(|(i, e)| {
+            let index = if e.tab_index == 0 { i32::MAX } else { e.tab_index };
+            (index, *i)
+        });
+
+        order.into_iter().map(|(_, e)| e.id).collect()
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct FocusManager {
+    state: Arc<Mutex<FocusState>>
+}
+
+impl FocusManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&self, bounds: Rect, tab_index: i32) -> NodeHandle {
+        let mut state = self.state.lock().unwrap();
+        let id = NodeId(state.next_id);
+        state.next_id += 1;
+
+        state.entries.push(FocusEntry {
+            id,
+            bounds,
+            tab_index,
+            enabled: true,
+            scope: None,
+            key_handler: None,
+            ime_handler: None,
+            focus_handler: None
+        });
+
+        NodeHandle {
+            registration: Arc::new(Registration { id, manager: self.clone() })
+        }
+    }
+
+    pub fn unregister(&self, id: NodeId) {
+        let mut state = self.state.lock().unwrap();
+        state.entries.retain(|e| e.id != id);
+        if state.focused == Some(id) {
+            state.focused = None;
+        }
+    }
+
+    pub fn focused(&self) -> Option<NodeId> {
+        self.state.lock().unwrap().focused
+    }
+
+    pub fn focused_bounds(&self) -> Option<Rect> {
+        let mut state = self.state.lock().unwrap();
+        let focused = state.focused?;
+        state.entry(focused).map(|e| e.bounds)
+    }
+
+    pub fn focused_scope(&self) -> Option<String> {
+        let mut state = self.state.lock().unwrap();
+        let focused = state.focused?;
+        state.entry(focused).and_then(|e| e.scope.clone())
+    }
+
+    // whether the focused node takes text from an input method
+    pub fn accepts_ime(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let Some(focused) = state.focused else {
+            return false;
+        };
+        state.entry(focused).is_some_and(|e| e.ime_handler.is_some())
+    }
+
+    pub fn modifiers(&self) -> ModifiersState {
+        self.state.lock().unwrap().modifiers
+    }
+
+    pub fn set_modifiers(&self, modifiers: ModifiersState) {
+        self.state.lock().unwrap().modifiers = modifiers;
+    }
+
+    // focus changes take effect at once, but their handlers only run from deliver_notifications,
+    // so a widget can take focus from its own event handler while it is locked
+    pub fn focus(&self, id: NodeId) -> bool {
+        let mut state = self.state.lock().unwrap();
+        match state.entry(id) {
+            Some(entry) if entry.enabled => {}
+            _ => return false
+        }
+
+        if state.focused == Some(id) {
+            return true;
+        }
+        if let Some(previous) = state.focused.replace(id) {
+            state.notifications.push_back((previous, false));
+        }
+        state.notifications.push_back((id, true));
+        true
+    }
+
+    pub fn blur(&self, id: NodeId) {
+        let mut state = self.state.lock().unwrap();
+        if state.focused != Some(id) {
+            return;
+        }
+        state.focused = None;
+        state.notifications.push_back((id, false));
+    }
+
+    // runs the focus handlers of every change made since the last call, the event loop calls it
+    // once an event is handled; changes the handlers make are delivered in the same call
+    pub fn deliver_notifications(&self) {
+        loop {
+            let Some((id, focused)) = self.state.lock().unwrap().notifications.pop_front() else {
+                return;
+            };
+            self.notify(id, focused);
+        }
+    }
+
+    pub fn focus_next(&self) -> Option<NodeId> {
+        self.move_focus(true)
+    }
+
+    pub fn focus_previous(&self) -> Option<NodeId> {
+        self.move_focus(false)
+    }
+
+    fn move_focus(&self, forward: bool) -> Option<NodeId> {
+        let target = {
+            let state = self.state.lock().unwrap();
+            let order = state.tab_order();
+            if order.is_empty() {
+                return None;
+            }
+
+            let current = state.focused.and_then(|f| order.iter().position(|id| *id == f));
+            let index = match (current, forward) {
+                (Some(i), true) => (i + 1) % order.len(),
+                (Some(i), false) => (i + order.len() - 1) % order.len(),
+                (None, true) => 0,
+                (None, false) => order.len() - 1
+            };
+            order[index]
+        };
+
+        self.focus(target);
+        Some(target)
+    }
+
+    // routes a key event to the focused node, falling back to tab traversal when it is not consumed
+    pub fn handle_keyboard_input(&self, event: &KeyEvent) -> bool {
+        let (focused, modifiers) = {
+            let state = self.state.lock().unwrap();
+            (state.focused, state.modifiers)
+        };
+
+        if let Some(id) = focused {
+            // the handler is taken out of the entry so it can freely call back into the manager
+            let handler = self.state.lock().unwrap().entry(id).and_then(|e| e.key_handler.take());
+            if let Some(mut handler) = handler {
+                let consumed = handler(event, modifiers);
+
+                if let Some(entry) = self.state.lock().unwrap().entry(id) {
+                    entry.key_handler.get_or_insert(handler);
+                }
+                if consumed {
+                    return true;
+                }
+            }
+        }
+
+        if event.state == ElementState::Pressed && event.logical_key == Key::Named(NamedKey::Tab) {
+            if modifiers.shift_key() {
+                self.focus_previous();
+            } else {
+                self.focus_next();
+            }
+            return true;
+        }
+
+        false
+    }
+
+    pub fn handle_ime(&self, ime: &Ime) -> bool {
+        let Some(id) = self.focused() else {
+            return false;
+        };
+
+        let handler = self.state.lock().unwrap().entry(id).and_then(|e| e.ime_handler.take());
+        let Some(mut handler) = handler else {
+            return false;
+        };
+        let consumed = handler(ime);
+
+        if let Some(entry) = self.state.lock().unwrap().entry(id) {
+            entry.ime_handler.get_or_insert(handler);
+        }
+        consumed
+    }
+
+    fn notify(&self, id: NodeId, focused: bool) {
+        let handler = self.state.lock().unwrap().entry(id).and_then(|e| e.focus_handler.take());
+        if let Some(mut handler) = handler {
+            handler(focused);
+
+            if let Some(entry) = self.state.lock().unwrap().entry(id) {
+                entry.focus_handler.get_or_insert(handler);
+            }
+        }
+    }
+
+    fn update(&self, id: NodeId, f: impl FnOnce(&mut FocusEntry)) {
+        if let Some(entry) = self.state.lock().unwrap().entry(id) {
+            f(entry);
+        }
+    }
+}
+
+struct Registration {
+    id: NodeId,
+    manager: FocusManager
+}
+
+// the node leaves the focus manager once the last handle to it is dropped
+impl Drop for Registration {
+    fn drop(&mut self) {
+        self.manager.unregister(self.id);
+    }
+}
+
+#[derive(Clone)]
+pub struct NodeHandle {
+    registration: Arc<Registration>
+}
+
+impl NodeHandle {
+    pub fn id(&self) -> NodeId {
+        self.registration.id
+    }
+
+    fn manager(&self) -> &FocusManager {
+        &self.registration.manager
+    }
+
+    pub fn focus(&self) -> bool {
+        self.manager().focus(self.id())
+    }
+
+    pub fn blur(&self) {
+        self.manager().blur(self.id())
+    }
+
+    pub fn is_focused(&self) -> bool {
+        self.manager().focused() == Some(self.id())
+    }
+
+    pub fn set_bounds(&self, bounds: Rect) {
+        self.manager().update(self.id(), |e| e.bounds = bounds);
+    }
+
+    pub fn set_tab_index(&self, tab_index: i32) {
+        self.manager().update(self.id(), |e| e.tab_index = tab_index);
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.manager().update(self.id(), |e| e.enabled = enabled);
+        if !enabled {
+            self.blur();
+        }
+    }
+
+    pub fn set_scope(&self, scope: Option<&str>) {
+        self.manager().update(self.id(), |e| e.scope = scope.map(String::from));
+    }
+
+    pub fn on_key(&self, handler: impl FnMut(&KeyEvent, ModifiersState) -> bool + Send + 'static) {
+        self.manager().update(self.id(), |e| e.key_handler = Some(Box::new(handler)));
+    }
+
+    pub fn on_ime(&self, handler: impl FnMut(&Ime) -> bool + Send + 'static) {
+        self.manager().update(self.id(), |e| e.ime_handler = Some(Box::new(handler)));
+    }
+
+    pub fn on_focus_changed(&self, handler: impl FnMut(bool) + Send + 'static) {
+        self.manager().update(self.id(), |e| e.focus_handler = Some(Box::new(handler)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dropping_the_last_handle_unregisters_the_node() {
+        let focus = FocusManager::new();
+        let handle = focus.register(Rect::default(), 0);
+        let copy = handle.clone();
+        assert!(handle.focus());
+
+        drop(handle);
+        assert!(copy.is_focused());
+        drop(copy);
+        assert_eq!(focus.focused(), None);
+        assert_eq!(focus.focus_next(), None);
+    }
+
+    #[test]
+    fn tab_order_follows_the_tab_index() {
+        let focus = FocusManager::new();
+        let last = focus.register(Rect::default(), 0);
+        let first = focus.register(Rect::default(), 1);
+        let _hidden = focus.register(Rect::default(), -1);
+
+        assert_eq!(focus.focus_next(), Some(first.id()));
+        assert_eq!(focus.focus_next(), Some(last.id()));
+        assert_eq!(focus.focus_next(), Some(first.id()));
+        assert_eq!(focus.focus_previous(), Some(last.id()));
+    }
+
+    #[test]
+    fn focus_handlers_run_once_notifications_are_delivered() {
+        let focus = FocusManager::new();
+        let first = focus.register(Rect::default(), 0);
+        let second = focus.register(Rect::default(), 0);
+        let changes = Arc::new(Mutex::new(vec![]));
+        for handle in [&first, &second] {
+            let (changes, id) = (Arc::clone(&changes), handle.id());
+            handle.on_focus_changed(move |focused| changes.lock().unwrap().push((id, focused)));
+        }
+
+        assert!(first.focus());
+        assert!(second.focus());
+        assert_eq!(focus.focused(), Some(second.id()));
+        assert!(changes.lock().unwrap().is_empty());
+
+        focus.deliver_notifications();
+        assert_eq!(*changes.lock().unwrap(), vec![(first.id(), true), (first.id(), false), (second.id(), true)]);
+    }
+}
@@ -0,0 +1,386 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use winit::event::{ElementState, KeyEvent};
+use winit::keyboard::{Key, ModifiersState, NamedKey, SmolStr};
+#[cfg(not(any(target_os = "android", target_os = "ios", target_family = "wasm")))]
+use winit::platform::modifier_supplement::KeyEventExtModifierSupplement;
+use crate::config::KeyBindingOptions;
+
+pub type CommandHandler = Box<dyn FnMut() + Send>;
+
+const NAMED_KEYS: &[(&str, NamedKey)] = &[
+    ("enter", NamedKey::Enter),
+    ("return", NamedKey::Enter),
+    ("tab", NamedKey::Tab),
+    ("space", NamedKey::Space),
+    ("backspace", NamedKey::Backspace),
+    ("delete", NamedKey::Delete),
+    ("del", NamedKey::Delete),
+    ("insert", NamedKey::Insert),
+    ("escape", NamedKey::Escape),
+    ("esc", NamedKey::Escape),
+    ("up", NamedKey::ArrowUp),
+    ("down", NamedKey::ArrowDown),
+    ("left", NamedKey::ArrowLeft),
+    ("right", NamedKey::ArrowRight),
+    ("home", NamedKey::Home),
+    ("end", NamedKey::End),
+    ("pageup", NamedKey::PageUp),
+    ("pagedown", NamedKey::PageDown),
+    ("f1", NamedKey::F1),
+    ("f2", NamedKey::F2),
+    ("f3", NamedKey::F3),
+    ("f4", NamedKey::F4),
+    ("f5", NamedKey::F5),
+    ("f6", NamedKey::F6),
+    ("f7", NamedKey::F7),
+    ("f8", NamedKey::F8),
+    ("f9", NamedKey::F9),
+    ("f10", NamedKey::F10),
+    ("f11", NamedKey::F11),
+    ("f12", NamedKey::F12)
+];
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct KeyStroke {
+    pub modifiers: ModifiersState,
+    pub key: Key
+}
+
+impl KeyStroke {
+    pub fn new(modifiers: ModifiersState, key: Key) -> Self {
+        KeyStroke {
+            modifiers,
+            key: normalize_key(key)
+        }
+    }
+
+    // parses strokes such as "Ctrl+Shift+P", "Alt+F4", "Ctrl++" or "+"
+    pub fn parse(value: &str) -> Result<Self, ShortcutError> {
+        let invalid = || ShortcutError::InvalidKey(value.to_string());
+
+        let (modifier_part, key_part) = match value.strip_suffix("++") {
+            Some(rest) => (rest, "+"),
+            None if value.trim() == "+" => ("", "+"),
+            None => value.rsplit_once('+').unwrap_or(("", value))
+        };
+
+        let mut modifiers = ModifiersState::empty();
+        for modifier in modifier_part.split('+').filter(|m| !m.is_empty()) {
+            modifiers |= match modifier.trim().to_lowercase().as_str() {
+                "ctrl" | "control" => ModifiersState::CONTROL,
+                "shift" => ModifiersState::SHIFT,
+                "alt" | "option" => ModifiersState::ALT,
+                "super" | "cmd" | "meta" | "win" => ModifiersState::SUPER,
+                _ => return Err(invalid())
+            };
+        }
+
+        let key_part = key_part.trim();
+        let lowercase = key_part.to_lowercase();
+        let key = match NAMED_KEYS.iter().find(|(name, _)| *name == lowercase) {
+            Some((_, named)) => Key::Named(*named),
+            None if key_part.chars().count() == 1 => Key::Character(SmolStr::new(key_part)),
+            None => return Err(invalid())
+        };
+
+        Ok(KeyStroke::new(modifiers, key))
+    }
+
+    fn is_modifier(&self) -> bool {
+        matches!(
+            self.key,
+            Key::Named(NamedKey::Control | NamedKey::Shift | NamedKey::Alt | NamedKey::Super | NamedKey::Meta)
+        )
+    }
+}
+
+impl Display for KeyStroke {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.modifiers.control_key() {
+            write!(f, "Ctrl+")?;
+        }
+        if self.modifiers.alt_key() {
+            write!(f, "Alt+")?;
+        }
+        if self.modifiers.shift_key() {
+            write!(f, "Shift+")?;
+        }
+        if self.modifiers.super_key() {
+            write!(f, "Super+")?;
+        }
+
+        match &self.key {
+            Key::Character(c) => write!(f, "{}", c.to_uppercase()),
+            Key::Named(named) => write!(f, "{:?}", named),
+            other => write!(f, "{:?}", other)
+        }
+    }
+}
+
+fn normalize_key(key: Key) -> Key {
+    match key {
+        Key::Character(c) => Key::Character(SmolStr::new(c.to_lowercase())),
+        other => other
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct KeyChord(pub Vec<KeyStroke>);
+
+impl KeyChord {
+    // chords are whitespace separated strokes, e.g. "Ctrl+K Ctrl+C"
+    pub fn parse(value: &str) -> Result<Self, ShortcutError> {
+        let strokes = value
+            .split_whitespace()
+            .map(KeyStroke::parse)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if strokes.is_empty() {
+            return Err(ShortcutError::InvalidKey(value.to_string()));
+        }
+        Ok(KeyChord(strokes))
+    }
+
+    fn starts_with(&self, other: &[KeyStroke]) -> bool {
+        self.0.len() >= other.len() && self.0[..other.len()] == *other
+    }
+}
+
+impl Display for KeyChord {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let strokes: Vec<String> = self.0.iter().map(|s| s.to_string()).collect();
+        write!(f, "{}", strokes.join(" "))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct KeyBinding {
+    pub chord: KeyChord,
+    pub command: String,
+    // bindings without a scope are global, scoped ones only apply while a node of that scope is focused
+    pub scope: Option<String>
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ShortcutError {
+    InvalidKey(String),
+    Conflict {
+        chord: String,
+        scope: Option<String>,
+        existing: String,
+        command: String
+    }
+}
+
+impl Display for ShortcutError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShortcutError::InvalidKey(key) => write!(f, "invalid key binding \"{}\"", key),
+            ShortcutError::Conflict { chord, scope, existing, command } => write!(
+                f,
+                "\"{}\" for \"{}\" conflicts with \"{}\" in scope {}",
+                chord,
+                command,
+                existing,
+                scope.as_deref().unwrap_or("global")
+            )
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ShortcutResult {
+    Executed(String),
+    Pending,
+    Cancelled,
+    Unhandled
+}
+
+#[derive(Default)]
+pub struct ShortcutRegistry {
+    bindings: Vec<KeyBinding>,
+    commands: HashMap<String, CommandHandler>,
+    pending: Vec<KeyStroke>
+}
+
+impl ShortcutRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register_command(&mut self, name: &str, handler: impl FnMut() + Send + 'static) {
+        self.commands.insert(name.to_string(), Box::new(handler));
+    }
+
+    pub fn bindings(&self) -> &[KeyBinding] {
+        &self.bindings
+    }
+
+    pub fn bind(&mut self, keys: &str, command: &str, scope: Option<&str>) -> Result<(), ShortcutError> {
+        let binding = KeyBinding {
+            chord: KeyChord::parse(keys)?,
+            command: command.to_string(),
+            scope: scope.map(String::from)
+        };
+
+        if let Some(existing) = self.find_conflict(&binding) {
+            return Err(ShortcutError::Conflict {
+                chord: binding.chord.to_string(),
+                scope: binding.scope,
+                existing: existing.command.clone(),
+                command: binding.command
+            });
+        }
+
+        self.bindings.push(binding);
+        Ok(())
+    }
+
+    pub fn unbind_command(&mut self, command: &str, scope: Option<&str>) {
+        self.bindings.retain(|b| b.command != command || b.scope.as_deref() != scope);
+    }
+
+    // a chord conflicts when it is equal to, or a prefix of, another chord in the same scope,
+    // because the shorter one would always fire before the longer one can complete
+    fn find_conflict(&self, binding: &KeyBinding) -> Option<&KeyBinding> {
+        self.bindings.iter().find(|b| {
+            b.scope == binding.scope
+                && b.command != binding.command
+                && (b.chord.starts_with(&binding.chord.0) || binding.chord.starts_with(&b.chord.0))
+        })
+    }
+
+    // replaces the bindings of every command mentioned in the options, returning the ones that were rejected
+    pub fn apply_options(&mut self, options: &[KeyBindingOptions]) -> Vec<ShortcutError> {
+        for option in options {
+            self.unbind_command(&option.command, option.scope.as_deref());
+        }
+
+        options
+            .iter()
+            .filter_map(|o| self.bind(&o.keys, &o.command, o.scope.as_deref()).err())
+            .collect()
+    }
+
+    pub fn handle_keyboard_input(
+        &mut self,
+        event: &KeyEvent,
+        modifiers: ModifiersState,
+        scope: Option<&str>
+    ) -> ShortcutResult {
+        if event.state != ElementState::Pressed {
+            return ShortcutResult::Unhandled;
+        }
+
+        // with shift held the logical key is the shifted character, "Ctrl+Shift+1" arrives as "!",
+        // so characters are matched as the key without modifiers
+        let key = match &event.logical_key {
+            Key::Character(_) if modifiers.shift_key() => unshifted_key(event),
+            key => key.clone()
+        };
+        let stroke = KeyStroke::new(modifiers, key);
+        if stroke.is_modifier() {
+            return if self.pending.is_empty() { ShortcutResult::Unhandled } else { ShortcutResult::Pending };
+        }
+
+        self.handle_stroke(stroke, scope)
+    }
+
+    pub fn handle_stroke(&mut self, stroke: KeyStroke, scope: Option<&str>) -> ShortcutResult {
+        let was_pending = !self.pending.is_empty();
+        self.pending.push(stroke);
+
+        // scoped bindings shadow global ones
+        let mut scopes = vec![None];
+        if scope.is_some() {
+            scopes.insert(0, scope);
+        }
+
+        for scope in scopes {
+            let candidates: Vec<&KeyBinding> = self.bindings
+                .iter()
+                .filter(|b| b.scope.as_deref() == scope && b.chord.starts_with(&self.pending))
+                .collect();
+
+            if let Some(binding) = candidates.iter().find(|b| b.chord.0.len() == self.pending.len()) {
+                let command = binding.command.clone();
+                self.pending.clear();
+                self.execute(&command);
+                return ShortcutResult::Executed(command);
+            }
+
+            if !candidates.is_empty() {
+                return ShortcutResult::Pending;
+            }
+        }
+
+        self.pending.clear();
+        if was_pending {
+            // an unfinished chord swallows the stroke that broke it
+            ShortcutResult::Cancelled
+        } else {
+            ShortcutResult::Unhandled
+        }
+    }
+
+    pub fn execute(&mut self, command: &str) -> bool {
+        match self.commands.get_mut(command) {
+            Some(handler) => {
+                handler();
+                true
+            }
+            None => false
+        }
+    }
+}
+
+// only desktop backends can report the key without modifiers, elsewhere shifted characters are
+// matched as they arrive
+#[cfg(not(any(target_os = "android", target_os = "ios", target_family = "wasm")))]
+fn unshifted_key(event: &KeyEvent) -> Key {
+    event.key_without_modifiers()
+}
+
+#[cfg(any(target_os = "android", target_os = "ios", target_family = "wasm"))]
+fn unshifted_key(event: &KeyEvent) -> Key {
+    event.logical_key.clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_modifiers_and_keys() {
+        assert_eq!(
+            KeyStroke::parse("Ctrl+Shift+P"),
+            Ok(KeyStroke::new(ModifiersState::CONTROL | ModifiersState::SHIFT, Key::Character("p".into())))
+        );
+        assert_eq!(KeyStroke::parse("alt+f4"), Ok(KeyStroke::new(ModifiersState::ALT, Key::Named(NamedKey::F4))));
+        assert_eq!(KeyStroke::parse("Esc"), Ok(KeyStroke::new(ModifiersState::empty(), Key::Named(NamedKey::Escape))));
+        assert_eq!(KeyStroke::parse("Cmd + K"), Ok(KeyStroke::new(ModifiersState::SUPER, Key::Character("k".into()))));
+    }
+
+    #[test]
+    fn plus_can_be_the_key() {
+        assert_eq!(KeyStroke::parse("Ctrl++"), Ok(KeyStroke::new(ModifiersState::CONTROL, Key::Character("+".into()))));
+        assert_eq!(KeyStroke::parse("+"), Ok(KeyStroke::new(ModifiersState::empty(), Key::Character("+".into()))));
+    }
+
+    #[test]
+    fn rejects_unknown_names() {
+        assert_eq!(KeyStroke::parse("Hyper+A"), Err(ShortcutError::InvalidKey("Hyper+A".to_string())));
+        assert_eq!(KeyStroke::parse("Ctrl+Foo"), Err(ShortcutError::InvalidKey("Ctrl+Foo".to_string())));
+        assert_eq!(KeyStroke::parse("Ctrl+"), Err(ShortcutError::InvalidKey("Ctrl+".to_string())));
+        assert!(KeyChord::parse("  ").is_err());
+    }
+
+    #[test]
+    fn chords_display_as_they_parse() {
+        let chord = KeyChord::parse("ctrl+k shift+alt+Enter").unwrap();
+        assert_eq!(chord.0.len(), 2);
+        assert_eq!(chord.to_string(), "Ctrl+K Alt+Shift+Enter");
+        assert_eq!(KeyChord::parse(&chord.to_string()), Ok(chord));
+    }
+}
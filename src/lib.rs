@@ -0,0 +1,9 @@
+pub mod render;
+pub mod graphics;
+pub mod config;
+pub mod designer;
+pub mod input;
+pub mod scene;
+pub mod text;
+pub mod widgets;
+pub mod animation;
@@ -1,45 +1,115 @@
-mod render;
-mod graphics;
-mod config;
-mod designer;
-
-use crate::config::ShuiqiOptions;
-use crate::designer::Designer;
-use crate::render::wgpu::WgpuRenderer;
-use crate::render::Renderer;
-use futures::FutureExt;
+use shuiqi::animation::scheduler::FrameScheduler;
+use shuiqi::animation::transition::TransitionManager;
+use shuiqi::config::ShuiqiOptions;
+use shuiqi::designer::Designer;
+use shuiqi::graphics::color::Color;
+use shuiqi::render::wgpu::WgpuRenderer;
+use shuiqi::render::Renderer;
 use rand::Rng;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use winit::application::ApplicationHandler;
-use winit::dpi::PhysicalSize;
-use winit::event::WindowEvent;
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::event::{ElementState, MouseButton, WindowEvent};
 use winit::event_loop::{ActiveEventLoop, EventLoop};
 use winit::window::{Window, WindowId};
-use crate::designer::point::{Measurement, Point};
+use shuiqi::designer::point::{Measurement, Point};
+use shuiqi::input::focus::FocusManager;
+use shuiqi::input::shortcuts::{ShortcutRegistry, ShortcutResult};
+use shuiqi::scene::Rect;
+use shuiqi::widgets::popup::PopupLayer;
+use shuiqi::widgets::slider::Slider;
+use shuiqi::widgets::theme::Theme;
+use shuiqi::widgets::Widget;
+
+const CONFIG_FILE: &str = "shuiqi.toml";
 
 #[derive(Default)]
 pub struct ShuqiApp {
     config: ShuiqiOptions
 }
 
-#[derive(Default)]
 struct ShuqiIntermediateApp {
     pub app: ShuqiApp,
     pub window: Option<Window>,
     pub renderer: Option<Arc<Mutex<WgpuRenderer<'static>>>>,
-    pub allow_resize: bool,
-    pub resize_task: Option<tokio::task::JoinHandle<()>>
+    pub resize_task: Option<tokio::task::JoinHandle<()>>,
+    pub scheduler: FrameScheduler,
+    pub frame_task: Option<tokio::task::JoinHandle<()>>,
+    pub transitions: TransitionManager,
+    pub focus: FocusManager,
+    pub shortcuts: ShortcutRegistry,
+    pub ime_allowed: bool,
+    // open popups get pointer and key events before the widget tree below them
+    pub popups: PopupLayer,
+    pub root: Option<Arc<std::sync::Mutex<dyn Widget>>>,
+    // the last cursor position, winit only reports it with CursorMoved
+    pub cursor: [f32; 2]
 }
 
 impl ShuqiIntermediateApp {
     fn new(app: ShuqiApp) -> Self {
+        let mut shortcuts = ShortcutRegistry::new();
+        for error in shortcuts.apply_options(&app.config.key_bindings) {
+            println!("Ignoring key binding: {}", error);
+        }
+
+        let scheduler = FrameScheduler::new(app.config.frame_rate);
+        let transitions = TransitionManager::new(scheduler.clone());
+
         ShuqiIntermediateApp {
             app,
             window: None,
             renderer: None,
             resize_task: None,
-            allow_resize: true
+            scheduler,
+            frame_task: None,
+            transitions,
+            focus: FocusManager::new(),
+            shortcuts,
+            ime_allowed: false,
+            popups: PopupLayer::new(),
+            root: None,
+            cursor: [0.0, 0.0]
+        }
+    }
+
+    // the widget tree that gets pointer events the popup layer leaves alone, drawn below the popups
+    pub fn set_root(&mut self, root: impl Widget + 'static) {
+        self.root = Some(Arc::new(std::sync::Mutex::new(root)));
+        self.request_redraw();
+    }
+
+    // the popup layer sees the event first, the widget tree only gets what it leaves alone
+    fn route(&self, popups: impl FnOnce(&PopupLayer) -> bool, root: impl FnOnce(&mut dyn Widget) -> bool) -> bool {
+        if popups(&self.popups) {
+            return true;
+        }
+        self.root.as_ref().is_some_and(|widget| root(&mut *widget.lock().unwrap()))
+    }
+
+    fn request_redraw(&self) {
+        if let Some(window) = &self.window {
+            window.request_redraw();
+        }
+    }
+
+    // the platform only composes text while a node that edits text is focused
+    fn update_ime(&mut self) {
+        let Some(window) = &self.window else {
+            return;
+        };
+
+        let allowed = self.focus.accepts_ime();
+        if allowed != self.ime_allowed {
+            window.set_ime_allowed(allowed);
+            self.ime_allowed = allowed;
+        }
+        if let (true, Some(bounds)) = (allowed, self.focus.focused_bounds()) {
+            window.set_ime_cursor_area(
+                PhysicalPosition::new(bounds.x, bounds.y),
+                PhysicalSize::new(bounds.width, bounds.height)
+            );
         }
     }
 
@@ -60,9 +130,10 @@ impl ShuqiIntermediateApp {
 
         let delay = self.app.config.resize_interval;
         let clone = Arc::clone(self.renderer.as_ref().unwrap());
+        let transitions = self.transitions.clone();
 
         self.resize_task = Some(tokio::spawn(async move {
-            tokio::time::sleep(tokio::time::Duration::from_millis(delay as u64)).await;
+            tokio::time::sleep(tokio::time::Duration::from_millis(delay)).await;
             let mut renderer = clone.lock().await;
 
             let designer = Designer::new();
@@ -71,10 +142,14 @@ impl ShuqiIntermediateApp {
                 Point::new(Measurement::Percentage(0.0), Measurement::Percentage(0.0)),
                 Measurement::Percentage(10.0),
                 Measurement::Percentage(100.0),
-                [rand::thread_rng().gen_range(0.0..1.0), rand::thread_rng().gen_range(0.0..1.0), rand::thread_rng().gen_range(0.0..1.0)]
+                Color::oklch(0.7, 0.15, rand::thread_rng().gen_range(0.0..360.0))
             );
 
             renderer.resize(size);
+            // anchored nodes move to their new layout positions through their transitions
+            if let Err(e) = transitions.relayout(size) {
+                println!("Could not move anchored nodes: {}", e);
+            }
             renderer.render();
         }));
     }
@@ -88,16 +163,20 @@ impl ApplicationHandler for ShuqiIntermediateApp {
             let static_window = unsafe {
                 std::mem::transmute::<&Window, &'static Window>(&window)
             };
-            let renderer = WgpuRenderer::init(static_window).await;
+            let mut renderer = WgpuRenderer::init(static_window, &self.app.config).await;
+            renderer.set_focus_manager(self.focus.clone());
 
 
-            self.renderer = Some(Arc::new(Mutex::new(renderer)));
+            self.popups.set_window_size(window.inner_size());
+            let renderer = Arc::new(Mutex::new(renderer));
+            self.frame_task = Some(self.scheduler.run(Arc::clone(&renderer)));
+            self.renderer = Some(renderer);
         });
         self.window = Some(window);
     }
 
 
-    fn window_event(&mut self, event_loop: &ActiveEventLoop, window_id: WindowId, event: WindowEvent) {
+    fn window_event(&mut self, event_loop: &ActiveEventLoop, _window_id: WindowId, event: WindowEvent) {
         match event {
             WindowEvent::CloseRequested => {
                 println!("Closing app");
@@ -110,23 +189,99 @@ impl ApplicationHandler for ShuqiIntermediateApp {
 
                 if let Some(renderer) = &self.renderer {
                     let clone = Arc::clone(renderer);
+                    let popups = self.popups.clone();
+                    let root = self.root.clone();
                     tokio::spawn(async move {
-                        let renderer = clone.lock().await;
+                        let mut renderer = clone.lock().await;
+                        let designer = Designer::new();
+                        if let Some(root) = root {
+                            let mut root = root.lock().unwrap();
+                            if root.is_dirty() {
+                                if let Err(e) = root.draw(&designer, &mut renderer) {
+                                    println!("Could not draw widgets: {}", e);
+                                }
+                            }
+                        }
+                        if let Err(e) = popups.draw(&designer, &mut renderer) {
+                            println!("Could not draw popups: {}", e);
+                        }
                         renderer.render();
                     });
                 }
             }
             WindowEvent::Resized(size) => {
+                self.popups.set_window_size(size);
                 self.schedule_resize(size);
             }
+            WindowEvent::CursorMoved { position, .. } => {
+                self.cursor = [position.x as f32, position.y as f32];
+                let [x, y] = self.cursor;
+                if self.route(|popups| popups.handle_mouse_move(x, y), |root| root.handle_mouse_move(x, y)) {
+                    self.request_redraw();
+                }
+            }
+            WindowEvent::MouseInput { state, button: MouseButton::Left, .. } => {
+                let [x, y] = self.cursor;
+                let handled = match state {
+                    ElementState::Pressed => self.route(|popups| popups.handle_mouse_down(x, y), |root| root.handle_mouse_down(x, y)),
+                    ElementState::Released => self.route(|popups| popups.handle_mouse_up(x, y), |root| root.handle_mouse_up(x, y))
+                };
+                if handled {
+                    self.request_redraw();
+                }
+                self.update_ime();
+            }
+            WindowEvent::MouseWheel { delta, phase, .. } => {
+                let [x, y] = self.cursor;
+                let handled = self.route(
+                    |popups| popups.handle_scroll(&delta, phase, x, y),
+                    |root| root.handle_scroll(&delta, phase, x, y)
+                );
+                if handled {
+                    self.request_redraw();
+                }
+            }
+            WindowEvent::ModifiersChanged(modifiers) => {
+                self.focus.set_modifiers(modifiers.state());
+            }
+            WindowEvent::KeyboardInput { event, .. } => {
+                let modifiers = self.focus.modifiers();
+                let handled = self.popups.handle_key(&event, modifiers) || {
+                    let scope = self.focus.focused_scope();
+                    match self.shortcuts.handle_keyboard_input(&event, modifiers, scope.as_deref()) {
+                        ShortcutResult::Unhandled => self.focus.handle_keyboard_input(&event),
+                        _ => true
+                    }
+                };
+                if handled {
+                    self.request_redraw();
+                }
+                self.update_ime();
+            }
+            WindowEvent::Ime(ime) => {
+                if let (true, Some(window)) = (self.focus.handle_ime(&ime), &self.window) {
+                    window.request_redraw();
+                }
+            }
             _ => {}
         }
+
+        // widgets that took focus while handling the event are told once they are no longer locked
+        self.focus.deliver_notifications();
     }
 }
 
 #[tokio::main]
 async fn main() {
     let mut app = ShuqiApp::default();
+    if std::path::Path::new(CONFIG_FILE).exists() {
+        match ShuiqiOptions::from_file(CONFIG_FILE) {
+            Ok(config) => app.config = config,
+            Err(e) => println!("{}, using default options", e)
+        }
+    }
+
     let mut intermediate = ShuqiIntermediateApp::new(app);
+    intermediate.set_root(Slider::new(0.0, 1.0, 0.5, Rect::new(24.0, 24.0, 240.0, 32.0), Theme::default()));
     intermediate.start();
 }
\ No newline at end of file
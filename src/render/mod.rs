@@ -1,14 +1,15 @@
 use async_trait::async_trait;
 use winit::dpi::PhysicalSize;
 use winit::window::Window;
+use crate::config::ShuiqiOptions;
 
 pub mod wgpu;
 
 #[async_trait(?Send)]
 pub trait Renderer<'window> {
-    async fn init(window: &'window Window) -> Self;
+    async fn init(window: &'window Window, options: &ShuiqiOptions) -> Self;
 
-    fn render(&self);
+    fn render(&mut self);
 
     fn resize(&mut self, size: PhysicalSize<u32>);
 }
\ No newline at end of file
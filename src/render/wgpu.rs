@@ -1,10 +1,37 @@
 use async_trait::async_trait;
-use wgpu::{Buffer, Device, DeviceDescriptor, IndexFormat, Instance, InstanceDescriptor, Queue, RenderPipeline, Surface, SurfaceConfiguration, TextureViewDescriptor};
+use bytemuck::Zeroable;
+use std::ops::Range;
+use wgpu::{Adapter, BindGroup, BindGroupLayout, Buffer, Device, DeviceDescriptor, IndexFormat, Instance, InstanceDescriptor, Queue, RenderPass, RenderPipeline, Surface, SurfaceConfiguration, TextureView, TextureViewDescriptor};
 use wgpu::util::{BufferInitDescriptor, DeviceExt};
 use winit::dpi::PhysicalSize;
 use winit::window::Window;
-use crate::graphics::instance::{InstanceData, ObjectInstance, Shape, ShapeData};
+use crate::graphics::instance::{InstanceData, InstanceId, ObjectInstance, Shape, ShapeData};
+use crate::graphics::atlas::{AtlasError, AtlasHandle, AtlasInstance, AtlasRegion, TextureAtlas, QUAD_CORNERS, QUAD_INDICES};
+use crate::graphics::clip::{Clip, ClipId};
+use crate::graphics::color::Color;
+use crate::graphics::layer::{BlurUniform, CompositeUniform, DrawScope, LayerId, LayerOptions, RenderTarget};
+use crate::graphics::paint::{Paint, PaintUniform};
+use crate::graphics::path::{PathError, Tessellator};
+use crate::graphics::pipeline::STENCIL_FORMAT;
+use crate::graphics::sdf::{SdfInstance, ViewportUniform};
+use crate::graphics::image::{GpuTexture, Image, ImageError, Sampling, TextureHandle};
+use crate::graphics::{ImageVertex, TextVertex};
+use crate::config::ShuiqiOptions;
+use crate::input::focus::FocusManager;
 use crate::render::Renderer;
+use crate::scene::Rect;
+use crate::text::atlas::{GlyphAtlas, GlyphKey};
+use crate::text::font::Font;
+use crate::text::PositionedGlyph;
+
+const FOCUS_RING_COLOR: Color = Color::new(1.0, 0.6, 0.0, 1.0);
+const FOCUS_RING_WIDTH: f32 = 2.0;
+const CLEAR_COLOR: wgpu::Color = wgpu::Color {
+    r: 0.0117647059,
+    g: 0.7890625,
+    b: 0.984375,
+    a: 1.0,
+};
 
 pub struct WgpuRenderer<'window> {
     device: Device,
@@ -14,34 +41,1336 @@ pub struct WgpuRenderer<'window> {
     config: SurfaceConfiguration,
     window: &'window Window,
     render_pipeline: RenderPipeline,
-    instances: Vec<ObjectInstance>,
-    instance_buffer: Buffer
+    // removed instances leave an empty slot behind so the ids of the others stay valid
+    instances: Vec<Option<ObjectInstance>>,
+    free_instances: Vec<u32>,
+    instance_buffer: Buffer,
+    focus: Option<FocusManager>,
+    focus_ring: Option<FocusRing>,
+    // the ring is drawn in place, so its single instance never changes
+    focus_ring_instance: Buffer,
+    text_pipeline: RenderPipeline,
+    glyph_atlas: GlyphAtlas,
+    texts: Vec<Option<TextDraw>>,
+    texture_layout: BindGroupLayout,
+    image_pipeline: RenderPipeline,
+    images: Vec<Option<ImageDraw>>,
+    atlas: TextureAtlas,
+    atlas_pipeline: RenderPipeline,
+    atlas_quads: Vec<Option<AtlasQuad>>,
+    atlas_instance_buffer: Buffer,
+    quad: ShapeData,
+    viewport_buffer: Buffer,
+    viewport_bind_group: BindGroup,
+    sdf_pipeline: RenderPipeline,
+    sdf_shapes: Vec<Option<SdfShape>>,
+    sdf_instance_buffer: Buffer,
+    uniform_layout: BindGroupLayout,
+    paint_pipeline: RenderPipeline,
+    painted_shapes: Vec<Option<PaintedShape>>,
+    // clips stay alive until removed so content added under them keeps its clip
+    clips: Vec<Option<ClipNode>>,
+    clip_stack: Vec<ClipId>,
+    clip_pipeline: RenderPipeline,
+    stencil_reset_pipeline: RenderPipeline,
+    stencil_view: TextureView,
+    sample_count: u32,
+    // content is rendered here and resolved into the scene or layer texture when msaa is on
+    multisample_view: Option<TextureView>,
+    screen_quad: ShapeData,
+    layers: Vec<Option<LayerNode>>,
+    layer_stack: Vec<LayerId>,
+    // layers composited above every other layer, bottom to top, for popups and dialogs
+    overlays: Vec<LayerId>,
+    // the clips set aside while drawing into an overlay, restored when leaving it
+    saved_clips: Vec<Vec<ClipId>>,
+    targets: RenderTargets,
+    // multisampled content cannot be drawn on top of an already resolved target, so the focus ring and the content
+    // added after a child layer are drawn into this transparent layer and composited instead
+    top_surface: LayerSurface,
+    blit_layout: BindGroupLayout,
+    blit_pipeline: RenderPipeline,
+    // reads the scene target, made again when the targets are
+    blit_bind_group: BindGroup,
+    blur_layout: BindGroupLayout,
+    blur_pipeline: RenderPipeline,
+    composite_layout: BindGroupLayout,
+    composite_pipeline: RenderPipeline,
+    next_order: u64
+}
+
+// everything is rendered offscreen first so layers can read what is behind them
+struct RenderTargets {
+    scene: RenderTarget,
+    backdrop: RenderTarget,
+    blurred: RenderTarget,
+    scratch: RenderTarget
+}
+
+impl RenderTargets {
+    fn new(device: &Device, size: PhysicalSize<u32>) -> Self {
+        RenderTargets {
+            scene: RenderTarget::new(device, size, "Scene Texture"),
+            backdrop: RenderTarget::new(device, size, "Backdrop Texture"),
+            blurred: RenderTarget::new(device, size, "Blurred Backdrop Texture"),
+            scratch: RenderTarget::new(device, size, "Scratch Texture")
+        }
+    }
+}
+
+// the two passes of a separable gaussian from a source through the scratch target, made with the layer
+// so a frame only records them; the sigma is written again when the layer options change
+struct BlurPasses {
+    uniforms: [Buffer; 2],
+    bind_groups: [BindGroup; 2]
+}
+
+const BLUR_DIRECTIONS: [[f32; 2]; 2] = [[1.0, 0.0], [0.0, 1.0]];
+
+impl BlurPasses {
+    fn new(device: &Device, layout: &BindGroupLayout, source: &TextureView, scratch: &TextureView, sigma: f32) -> Self {
+        let uniforms = BLUR_DIRECTIONS.map(|direction| device.create_buffer_init(
+            &BufferInitDescriptor {
+                label: Some("Blur Buffer"),
+                contents: bytemuck::cast_slice(&[BlurUniform::new(direction, sigma)]),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            },
+        ));
+        let bind_groups = [
+            crate::graphics::pipeline::create_post_bind_group(device, layout, &[source], Some(&uniforms[0])),
+            crate::graphics::pipeline::create_post_bind_group(device, layout, &[scratch], Some(&uniforms[1]))
+        ];
+
+        BlurPasses { uniforms, bind_groups }
+    }
+
+    fn set_sigma(&self, queue: &Queue, sigma: f32) {
+        for (uniform, direction) in self.uniforms.iter().zip(BLUR_DIRECTIONS) {
+            queue.write_buffer(uniform, 0, bytemuck::cast_slice(&[BlurUniform::new(direction, sigma)]));
+        }
+    }
+}
+
+// the texture a layer is rendered into and what compositing it needs, rebuilt when the window is resized
+struct LayerSurface {
+    target: RenderTarget,
+    uniform: Buffer,
+    bind_group: BindGroup,
+    blur: BlurPasses,
+    backdrop_blur: BlurPasses
+}
+
+impl LayerSurface {
+    fn new(
+        device: &Device,
+        layout: &BindGroupLayout,
+        blur_layout: &BindGroupLayout,
+        targets: &RenderTargets,
+        size: PhysicalSize<u32>,
+        options: &LayerOptions
+    ) -> Self {
+        let target = RenderTarget::new(device, size, "Layer Texture");
+        let uniform = device.create_buffer_init(
+            &BufferInitDescriptor {
+                label: Some("Composite Buffer"),
+                contents: bytemuck::cast_slice(&[CompositeUniform::new(options)]),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            },
+        );
+        let bind_group = crate::graphics::pipeline::create_post_bind_group(
+            device,
+            layout,
+            &[&target.view, &targets.backdrop.view, &targets.blurred.view],
+            Some(&uniform)
+        );
+        let blur = BlurPasses::new(device, blur_layout, &target.view, &targets.scratch.view, options.blur);
+        let backdrop_blur = BlurPasses::new(device, blur_layout, &targets.backdrop.view, &targets.scratch.view, options.backdrop_blur);
+
+        LayerSurface { target, uniform, bind_group, blur, backdrop_blur }
+    }
+}
+
+// the outline around the focused node, made again only when the focused bounds or the window size change
+struct FocusRing {
+    bounds: Rect,
+    size: PhysicalSize<u32>,
+    shape: ShapeData
+}
+
+struct LayerNode {
+    options: LayerOptions,
+    // layers pushed inside another layer are composited into it instead of the scene
+    parent: Option<LayerId>,
+    // the layer is composited between the content of its parent added before and after it was pushed
+    order: u64,
+    surface: LayerSurface
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TextId(pub u32);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ImageId(pub u32);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct AtlasQuadId(pub u32);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SdfId(pub u32);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PaintId(pub u32);
+
+struct PaintedShape {
+    shape: ShapeData,
+    uniform: Buffer,
+    bind_group: BindGroup,
+    scope: DrawScope,
+    order: u64
+}
+
+struct AtlasQuad {
+    // keeps the atlas entry alive for as long as the quad is drawn
+    _handle: AtlasHandle,
+    instance: AtlasInstance,
+    scope: DrawScope,
+    order: u64
+}
+
+struct ImageDraw {
+    texture: TextureHandle,
+    sampling: Sampling,
+    shape: ShapeData,
+    scope: DrawScope,
+    order: u64
+}
+
+struct TextDraw {
+    shape: ShapeData,
+    // released in the glyph atlas when the text is removed, so their room can be reused
+    glyphs: Vec<GlyphKey>,
+    scope: DrawScope,
+    order: u64
+}
+
+struct SdfShape {
+    instance: SdfInstance,
+    scope: DrawScope,
+    order: u64
+}
+
+struct ClipNode {
+    parent: Option<ClipId>,
+    // kept to rebuild the scissor and stencil on resize and to hit test the outline
+    clip: Clip,
+    // the clip's bounds intersected with those of every outer clip, in physical pixels
+    scissor: Rect,
+    // outline written into the stencil buffer for clips that are not rectangles
+    stencil: Option<ShapeData>
+}
+
+fn quad_instance(quads: &[Option<AtlasQuad>], index: u32) -> AtlasInstance {
+    quads[index as usize].as_ref().map(|q| q.instance).unwrap_or(AtlasInstance::zeroed())
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum DrawKind {
+    Instance,
+    Sdf,
+    Paint,
+    Image,
+    AtlasQuad,
+    Text
+}
+
+impl DrawKind {
+    // instanced kinds draw neighbouring slots with a single call
+    fn is_instanced(self) -> bool {
+        matches!(self, DrawKind::Sdf | DrawKind::AtlasQuad)
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+struct DrawItem {
+    kind: DrawKind,
+    slot: u32,
+    clip: Option<ClipId>,
+    order: u64
+}
+
+fn collect_items<T>(
+    items: &mut Vec<DrawItem>,
+    slots: &[Option<T>],
+    kind: DrawKind,
+    layer: Option<LayerId>,
+    scope_of: impl Fn(&T) -> (DrawScope, u64)
+) {
+    for (slot, value) in slots.iter().enumerate() {
+        let Some((scope, order)) = value.as_ref().map(&scope_of) else {
+            continue;
+        };
+        if scope.layer == layer {
+            items.push(DrawItem { kind, slot: slot as u32, clip: scope.clip, order });
+        }
+    }
+}
+
+// splits a sorted draw list before each of the sorted orders, so there is one more segment than orders
+fn split_draw_list<'a>(items: &'a [DrawItem], orders: &[u64]) -> Vec<&'a [DrawItem]> {
+    let mut segments = Vec::with_capacity(orders.len() + 1);
+    let mut rest = items;
+    for &order in orders {
+        let (segment, after) = rest.split_at(rest.partition_point(|item| item.order < order));
+        segments.push(segment);
+        rest = after;
+    }
+    segments.push(rest);
+    segments
+}
+
+// where content ends up in the composited frame, compared as a list: overlays in the order they were added above
+// the scene, then the order of every layer on the way down to the content and the content's own order last,
+// none when a layer on the way is gone or hidden
+fn stacking_key(
+    layer: Option<LayerId>,
+    order: u64,
+    overlays: &[LayerId],
+    layer_of: impl Fn(LayerId) -> Option<(Option<LayerId>, u64)>
+) -> Option<Vec<u64>> {
+    let mut key = vec![order];
+    let mut current = layer;
+
+    while let Some(id) = current {
+        let (parent, layer_order) = layer_of(id)?;
+        match overlays.iter().position(|overlay| *overlay == id) {
+            Some(index) => {
+                key.push(index as u64 + 1);
+                key.reverse();
+                return Some(key);
+            }
+            None => key.push(layer_order)
+        }
+        current = parent;
+    }
+
+    key.push(0);
+    key.reverse();
+    Some(key)
+}
+
+// only 1 and 4 samples can be used without the adapter specific format features enabled on the device
+fn sample_count_candidates(features: wgpu::Features) -> &'static [u32] {
+    if features.contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES) {
+        &[8, 4, 2]
+    } else {
+        &[4]
+    }
+}
+
+// picks the highest supported sample count that does not exceed the requested one
+fn supported_sample_count(adapter: &Adapter, features: wgpu::Features, requested: u32) -> u32 {
+    let color = adapter.get_texture_format_features(wgpu::TextureFormat::Bgra8UnormSrgb).flags;
+    let stencil = adapter.get_texture_format_features(STENCIL_FORMAT).flags;
+
+    sample_count_candidates(features)
+        .iter()
+        .copied()
+        .filter(|count| *count <= requested)
+        .find(|count| color.sample_count_supported(*count) && stencil.sample_count_supported(*count))
+        .unwrap_or(1)
+}
+
+fn create_multisample_view(device: &Device, size: PhysicalSize<u32>, sample_count: u32) -> Option<TextureView> {
+    if sample_count <= 1 {
+        return None;
+    }
+
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Multisample Texture"),
+        size: wgpu::Extent3d {
+            width: size.width.max(1),
+            height: size.height.max(1),
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Bgra8UnormSrgb,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    });
+
+    Some(texture.create_view(&TextureViewDescriptor::default()))
+}
+
+fn create_stencil_view(device: &Device, size: PhysicalSize<u32>, sample_count: u32) -> TextureView {
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Stencil Texture"),
+        size: wgpu::Extent3d {
+            width: size.width.max(1),
+            height: size.height.max(1),
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count,
+        dimension: wgpu::TextureDimension::D2,
+        format: STENCIL_FORMAT,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    });
+
+    texture.create_view(&TextureViewDescriptor::default())
+}
+
+// the clip's bounds intersected with the scissor of the clip it is pushed onto, or with the screen
+fn clip_scissor(clips: &[Option<ClipNode>], parent: Option<ClipId>, clip: &Clip, screen: Rect) -> Rect {
+    let outer = parent
+        .and_then(|id| clips.get(id.0 as usize)?.as_ref())
+        .map(|node| node.scissor)
+        .unwrap_or(screen);
+    clip.bounds().map(|bounds| bounds.intersect(&outer)).unwrap_or(outer)
+}
+
+// clip nodes from the outermost to the given one, None if any of them was removed
+fn clip_chain(clips: &[Option<ClipNode>], id: ClipId) -> Option<Vec<&ClipNode>> {
+    let mut chain = vec![];
+    let mut current = Some(id);
+
+    while let Some(id) = current {
+        let node = clips.get(id.0 as usize)?.as_ref()?;
+        chain.push(node);
+        current = node.parent;
+    }
+
+    chain.reverse();
+    Some(chain)
+}
+
+// buffers at least double when they grow, so adding shapes one at a time only rewrites everything
+// a logarithmic number of times and scrolling through reused slots writes just the changed entries
+fn grow_buffer(device: &Device, label: &str, current: u64, required: u64) -> Buffer {
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some(label),
+        size: required.max(current * 2),
+        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
+// stores a value in the first free slot so ids handed out earlier stay valid
+fn insert_slot<T>(slots: &mut Vec<Option<T>>, value: T) -> u32 {
+    match slots.iter().position(|s| s.is_none()) {
+        Some(index) => {
+            slots[index] = Some(value);
+            index as u32
+        }
+        None => {
+            slots.push(Some(value));
+            slots.len() as u32 - 1
+        }
+    }
 }
 
 impl<'window> WgpuRenderer<'window> {
-    pub fn add_instance(&mut self, shape: ShapeData, position: [f32; 2], scale: [f32; 2]) {
-        let instance_data = InstanceData::new(position, scale);
-        self.instances.push(ObjectInstance::new(shape, instance_data));
-        self.update_instance_buffer();
-        println!("Total instances: {}", self.instances.len());
+    pub fn window(&self) -> &'window Window {
+        self.window
+    }
+
+    pub fn add_instance(&mut self, shape: ShapeData, position: [f32; 2], scale: [f32; 2]) -> InstanceId {
+        let mut instance = ObjectInstance::new(shape, InstanceData::new(position, scale));
+        instance.scope = self.current_scope();
+        instance.order = self.next_order();
+
+        let id = match self.free_instances.pop() {
+            Some(slot) => {
+                self.instances[slot as usize] = Some(instance);
+                InstanceId(slot)
+            }
+            None => {
+                self.instances.push(Some(instance));
+                InstanceId(self.instances.len() as u32 - 1)
+            }
+        };
+
+        let required = self.instances.len() as u64 * std::mem::size_of::<InstanceData>() as u64;
+        if self.instance_buffer.size() < required {
+            self.update_instance_buffer();
+        } else {
+            self.write_instance(id);
+        }
+        id
+    }
+
+    // the shape is given in physical pixels and projected to clip space again whenever the window is resized
+    pub fn add_pixel_shape(&mut self, shape: Shape) -> InstanceId {
+        let data = self.create_shape(shape.to_clip(self.size));
+        let id = self.add_instance(data, [0.0, 0.0], [1.0, 1.0]);
+        if let Some(Some(instance)) = self.instances.get_mut(id.0 as usize) {
+            instance.pixels = Some(shape);
+        }
+        id
+    }
+
+    pub fn remove_instance(&mut self, id: InstanceId) {
+        if let Some(slot) = self.instances.get_mut(id.0 as usize) {
+            if slot.take().is_some() {
+                self.free_instances.push(id.0);
+            }
+        }
+    }
+
+    pub fn instance_data(&self, id: InstanceId) -> Option<InstanceData> {
+        self.instances.get(id.0 as usize)?.as_ref().map(|i| i.data)
+    }
+
+    // only the entry of the changed instance is written to the gpu
+    pub fn update_instance(&mut self, id: InstanceId, data: InstanceData) {
+        if let Some(Some(instance)) = self.instances.get_mut(id.0 as usize) {
+            instance.data = data;
+            self.write_instance(id);
+        }
+    }
+
+    fn write_instance(&self, id: InstanceId) {
+        if let Some(Some(instance)) = self.instances.get(id.0 as usize) {
+            let offset = id.0 as u64 * std::mem::size_of::<InstanceData>() as u64;
+            self.queue.write_buffer(&self.instance_buffer, offset, bytemuck::cast_slice(&[instance.data]));
+        }
     }
 
     pub fn update_instance_buffer(&mut self) {
-        let instance_data: Vec<_> = self.instances.iter().map(|i| i.data).collect();
+        let instance_data: Vec<_> = self.instances
+            .iter()
+            .map(|i| i.as_ref().map(|i| i.data).unwrap_or(InstanceData::new([0.0, 0.0], [0.0, 0.0])))
+            .collect();
         let buffer_size = instance_data.len() as u64 * std::mem::size_of::<InstanceData>() as u64;
 
-        println!("Updating instance buffer with {} instances", instance_data.len());
         if self.instance_buffer.size() < buffer_size {
-            self.instance_buffer = self.device.create_buffer_init(
+            self.instance_buffer = grow_buffer(&self.device, "Instance Buffer", self.instance_buffer.size(), buffer_size);
+        }
+        self.queue.write_buffer(&self.instance_buffer, 0, bytemuck::cast_slice(&instance_data));
+    }
+
+    // glyph positions are relative to origin, which is given in physical pixels;
+    // vertices stay in pixels and the shader maps them with the viewport like sdf shapes
+    pub fn add_text(&mut self, font: &Font, glyphs: &[PositionedGlyph], origin: [f32; 2], size: f32, color: [f32; 3]) -> Option<TextId> {
+        let mut vertices = Vec::with_capacity(glyphs.len() * 4);
+        let mut indices: Vec<u32> = Vec::with_capacity(glyphs.len() * 6);
+        let mut keys = Vec::with_capacity(glyphs.len());
+
+        for glyph in glyphs {
+            let Some((key, entry)) = self.glyph_atlas.glyph(&self.queue, font, glyph.glyph, size) else {
+                continue;
+            };
+            keys.push(key);
+
+            let left = origin[0] + glyph.position[0] + entry.offset[0];
+            let top = origin[1] + glyph.position[1] + entry.offset[1];
+            let [right, bottom] = [left + entry.size[0], top + entry.size[1]];
+            let [u0, v0, u1, v1] = entry.uv;
+
+            let base = vertices.len() as u32;
+            vertices.push(TextVertex::new([left, bottom], [u0, v1], color));
+            vertices.push(TextVertex::new([right, bottom], [u1, v1], color));
+            vertices.push(TextVertex::new([right, top], [u1, v0], color));
+            vertices.push(TextVertex::new([left, top], [u0, v0], color));
+            indices.extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+        }
+
+        if indices.is_empty() {
+            return None;
+        }
+
+        let vertex_buffer = self.device.create_buffer_init(
+            &BufferInitDescriptor {
+                label: Some("Text Vertex Buffer"),
+                contents: bytemuck::cast_slice(&vertices),
+                usage: wgpu::BufferUsages::VERTEX
+            }
+        );
+        let index_buffer = self.device.create_buffer_init(
+            &BufferInitDescriptor {
+                label: Some("Text Index Buffer"),
+                contents: bytemuck::cast_slice(&indices),
+                usage: wgpu::BufferUsages::INDEX
+            }
+        );
+
+        let text = TextDraw {
+            shape: ShapeData {
+                vertex_buffer,
+                index_buffer,
+                indices_count: indices.len() as u32
+            },
+            glyphs: keys,
+            scope: self.current_scope(),
+            order: self.next_order()
+        };
+        Some(TextId(insert_slot(&mut self.texts, text)))
+    }
+
+    // the msaa sample count in use, lower than the configured one when the adapter does not support it
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    // glyphs left out of texts because the glyph atlas ran out of room
+    pub fn missing_glyphs(&self) -> usize {
+        self.glyph_atlas.missing_glyphs()
+    }
+
+    pub fn remove_text(&mut self, id: TextId) {
+        let Some(text) = self.texts.get_mut(id.0 as usize).and_then(Option::take) else {
+            return;
+        };
+        for key in text.glyphs {
+            self.glyph_atlas.release(key);
+        }
+    }
+
+    pub fn create_texture(&self, image: &Image) -> Result<TextureHandle, ImageError> {
+        GpuTexture::new(&self.device, &self.queue, &self.texture_layout, image).map(TextureHandle::new)
+    }
+
+    // dest is given in physical pixels, uv as the [u0, v0, u1, v1] part of the texture to show
+    pub fn add_image(
+        &mut self,
+        texture: &TextureHandle,
+        dest: Rect,
+        uv: [f32; 4],
+        sampling: Sampling,
+        opacity: f32
+    ) -> ImageId {
+        let [left, top, right, bottom] = [dest.x, dest.y, dest.right(), dest.bottom()];
+        let [u0, v0, u1, v1] = uv;
+
+        let vertices = [
+            ImageVertex::new([left, bottom], [u0, v1], opacity),
+            ImageVertex::new([right, bottom], [u1, v1], opacity),
+            ImageVertex::new([right, top], [u1, v0], opacity),
+            ImageVertex::new([left, top], [u0, v0], opacity)
+        ];
+        let indices: [u16; 6] = [0, 1, 2, 0, 2, 3];
+
+        let shape = ShapeData {
+            vertex_buffer: self.device.create_buffer_init(
                 &BufferInitDescriptor {
-                    label: Some("Instance Buffer"),
-                    contents: bytemuck::cast_slice(&instance_data),
-                    usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
-                },
-            );
+                    label: Some("Image Vertex Buffer"),
+                    contents: bytemuck::cast_slice(&vertices),
+                    usage: wgpu::BufferUsages::VERTEX
+                }
+            ),
+            index_buffer: self.device.create_buffer_init(
+                &BufferInitDescriptor {
+                    label: Some("Image Index Buffer"),
+                    contents: bytemuck::cast_slice(&indices),
+                    usage: wgpu::BufferUsages::INDEX
+                }
+            ),
+            indices_count: indices.len() as u32
+        };
+
+        let draw = ImageDraw {
+            texture: TextureHandle::clone(texture),
+            sampling,
+            shape,
+            scope: self.current_scope(),
+            order: self.next_order()
+        };
+        ImageId(insert_slot(&mut self.images, draw))
+    }
+
+    pub fn remove_image(&mut self, id: ImageId) {
+        if let Some(slot) = self.images.get_mut(id.0 as usize) {
+            *slot = None;
+        }
+    }
+
+    pub fn insert_atlas_image(&mut self, image: &Image) -> Result<AtlasHandle, AtlasError> {
+        self.atlas.insert(&self.queue, image)
+    }
+
+    pub fn atlas_region(&self, handle: &AtlasHandle) -> Option<AtlasRegion> {
+        self.atlas.get(handle)
+    }
+
+    // frees atlas space used by images whose handles have all been dropped
+    pub fn trim_atlas(&mut self) -> usize {
+        self.atlas.evict_unused(&self.queue)
+    }
+
+    pub fn add_atlas_quad(&mut self, handle: &AtlasHandle, dest: Rect, uv: [f32; 4], opacity: f32) -> Option<AtlasQuadId> {
+        let region = self.atlas.get(handle)?;
+        let quad = AtlasQuad {
+            _handle: handle.clone(),
+            instance: AtlasInstance::new([dest.x, dest.y, dest.right(), dest.bottom()], region, uv, opacity),
+            scope: self.current_scope(),
+            order: self.next_order()
+        };
+        let id = insert_slot(&mut self.atlas_quads, quad);
+
+        let required = self.atlas_quads.len() as u64 * std::mem::size_of::<AtlasInstance>() as u64;
+        if self.atlas_instance_buffer.size() < required {
+            let instances: Vec<AtlasInstance> = self.atlas_quads
+                .iter()
+                .map(|q| q.as_ref().map(|q| q.instance).unwrap_or(AtlasInstance::zeroed()))
+                .collect();
+
+            self.atlas_instance_buffer = grow_buffer(&self.device, "Atlas Instance Buffer", self.atlas_instance_buffer.size(), required);
+            self.queue.write_buffer(&self.atlas_instance_buffer, 0, bytemuck::cast_slice(&instances));
         } else {
-            self.queue.write_buffer(&self.instance_buffer, 0, bytemuck::cast_slice(&instance_data));
+            self.write_atlas_instance(id, quad_instance(&self.atlas_quads, id));
+        }
+
+        Some(AtlasQuadId(id))
+    }
+
+    pub fn remove_atlas_quad(&mut self, id: AtlasQuadId) {
+        if let Some(slot) = self.atlas_quads.get_mut(id.0 as usize) {
+            if slot.take().is_some() {
+                // the emptied slot is still part of the single instanced draw, so it is collapsed to nothing
+                self.write_atlas_instance(id.0, AtlasInstance::zeroed());
+            }
+        }
+    }
+
+    fn write_atlas_instance(&self, index: u32, instance: AtlasInstance) {
+        let offset = index as u64 * std::mem::size_of::<AtlasInstance>() as u64;
+        self.queue.write_buffer(&self.atlas_instance_buffer, offset, bytemuck::cast_slice(&[instance]));
+    }
+
+    // shapes are kept in pixels and converted by the shader, so they survive a resize untouched
+    pub fn add_sdf(&mut self, instance: SdfInstance) -> SdfId {
+        let shape = SdfShape {
+            instance,
+            scope: self.current_scope(),
+            order: self.next_order()
+        };
+        let id = insert_slot(&mut self.sdf_shapes, shape);
+
+        let required = self.sdf_shapes.len() as u64 * std::mem::size_of::<SdfInstance>() as u64;
+        if self.sdf_instance_buffer.size() < required {
+            let instances: Vec<SdfInstance> = self.sdf_shapes
+                .iter()
+                .map(|s| s.as_ref().map(|s| s.instance).unwrap_or(SdfInstance::zeroed()))
+                .collect();
+
+            self.sdf_instance_buffer = grow_buffer(&self.device, "SDF Instance Buffer", self.sdf_instance_buffer.size(), required);
+            self.queue.write_buffer(&self.sdf_instance_buffer, 0, bytemuck::cast_slice(&instances));
+        } else {
+            self.write_sdf_instance(id, instance);
+        }
+
+        SdfId(id)
+    }
+
+    pub fn sdf(&self, id: SdfId) -> Option<SdfInstance> {
+        self.sdf_shapes.get(id.0 as usize)?.as_ref().map(|s| s.instance)
+    }
+
+    pub fn update_sdf(&mut self, id: SdfId, instance: SdfInstance) {
+        if let Some(Some(shape)) = self.sdf_shapes.get_mut(id.0 as usize) {
+            shape.instance = instance;
+            self.write_sdf_instance(id.0, instance);
+        }
+    }
+
+    pub fn remove_sdf(&mut self, id: SdfId) {
+        if let Some(slot) = self.sdf_shapes.get_mut(id.0 as usize) {
+            if slot.take().is_some() {
+                self.write_sdf_instance(id.0, SdfInstance::zeroed());
+            }
+        }
+    }
+
+    // returns the topmost shape under the point, given in physical pixels, skipping shapes that are clipped
+    // away there or inside a removed or fully transparent layer
+    pub fn hit_test_sdf(&self, x: f32, y: f32) -> Option<SdfId> {
+        let layer_of = |id: LayerId| {
+            self.layers
+                .get(id.0 as usize)?
+                .as_ref()
+                .filter(|layer| layer.options.opacity > 0.0)
+                .map(|layer| (layer.parent, layer.order))
+        };
+
+        self.sdf_shapes
+            .iter()
+            .enumerate()
+            .filter_map(|(index, shape)| {
+                let shape = shape.as_ref()?;
+                if !shape.instance.contains(x, y) || !self.clip_contains(shape.scope.clip, x, y) {
+                    return None;
+                }
+                let key = stacking_key(shape.scope.layer, shape.order, &self.overlays, layer_of)?;
+                Some((key, SdfId(index as u32)))
+            })
+            .max_by(|(a, _), (b, _)| a.cmp(b))
+            .map(|(_, id)| id)
+    }
+
+    fn write_sdf_instance(&self, index: u32, instance: SdfInstance) {
+        let offset = index as u64 * std::mem::size_of::<SdfInstance>() as u64;
+        self.queue.write_buffer(&self.sdf_instance_buffer, offset, bytemuck::cast_slice(&[instance]));
+    }
+
+    // the shape is given in physical pixels, its vertex colors are ignored and every pixel is colored by the paint
+    pub fn add_painted_shape(&mut self, shape: Shape, paint: &Paint) -> PaintId {
+        let uniform = self.device.create_buffer_init(
+            &BufferInitDescriptor {
+                label: Some("Paint Buffer"),
+                contents: bytemuck::cast_slice(&[PaintUniform::new(paint)]),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            },
+        );
+        let bind_group = crate::graphics::pipeline::create_uniform_bind_group(&self.device, &self.uniform_layout, &uniform);
+
+        let painted = PaintedShape {
+            shape: self.create_shape(shape),
+            uniform,
+            bind_group,
+            scope: self.current_scope(),
+            order: self.next_order()
+        };
+        PaintId(insert_slot(&mut self.painted_shapes, painted))
+    }
+
+    pub fn update_paint(&mut self, id: PaintId, paint: &Paint) {
+        if let Some(Some(painted)) = self.painted_shapes.get(id.0 as usize) {
+            self.queue.write_buffer(&painted.uniform, 0, bytemuck::cast_slice(&[PaintUniform::new(paint)]));
+        }
+    }
+
+    pub fn remove_painted_shape(&mut self, id: PaintId) {
+        if let Some(slot) = self.painted_shapes.get_mut(id.0 as usize) {
+            *slot = None;
+        }
+    }
+
+    // everything added until the clip is popped is clipped by it and by every clip below it on the stack,
+    // nothing is pushed when the outline cannot be tessellated
+    pub fn push_clip(&mut self, clip: &Clip) -> Result<ClipId, PathError> {
+        let parent = self.current_clip();
+        let stencil = self.clip_stencil(clip)?;
+        let scissor = clip_scissor(&self.clips, parent, clip, self.screen_rect());
+
+        let node = ClipNode { parent, clip: clip.clone(), scissor, stencil };
+        let id = ClipId(insert_slot(&mut self.clips, node));
+        self.clip_stack.push(id);
+        Ok(id)
+    }
+
+    // rectangles only set a scissor rect, so pushing them cannot fail
+    pub fn push_rect_clip(&mut self, rect: Rect) -> ClipId {
+        let parent = self.current_clip();
+        let scissor = clip_scissor(&self.clips, parent, &Clip::Rect(rect), self.screen_rect());
+
+        let node = ClipNode { parent, clip: Clip::Rect(rect), scissor, stencil: None };
+        let id = ClipId(insert_slot(&mut self.clips, node));
+        self.clip_stack.push(id);
+        id
+    }
+
+    // stencil geometry is in clip space, so it is projected again whenever the window size changes
+    fn clip_stencil(&self, clip: &Clip) -> Result<Option<ShapeData>, PathError> {
+        let Some((path, rule)) = clip.outline() else {
+            return Ok(None);
+        };
+        let shape = Tessellator::new().fill(&path, rule, [0.0; 3])?;
+        Ok(Some(self.create_shape(shape.to_clip(self.size))))
+    }
+
+    fn reproject_instances(&mut self) {
+        let shapes: Vec<(usize, ShapeData)> = self.instances
+            .iter()
+            .enumerate()
+            .filter_map(|(index, instance)| {
+                let pixels = instance.as_ref()?.pixels.as_ref()?;
+                Some((index, self.create_shape(pixels.to_clip(self.size))))
+            })
+            .collect();
+
+        for (index, shape) in shapes {
+            if let Some(Some(instance)) = self.instances.get_mut(index) {
+                instance.shape = shape;
+            }
+        }
+    }
+
+    // parents can sit in later slots than their children, so every scissor is derived from its whole chain
+    fn rebuild_clips(&mut self) {
+        for index in 0..self.clips.len() {
+            let Some(chain) = clip_chain(&self.clips, ClipId(index as u32)) else {
+                continue;
+            };
+            let scissor = chain
+                .iter()
+                .filter_map(|node| node.clip.bounds())
+                .fold(self.screen_rect(), |outer, bounds| bounds.intersect(&outer));
+            // the outline already tessellated once when the clip was pushed
+            let stencil = chain.last().and_then(|node| self.clip_stencil(&node.clip).ok().flatten());
+
+            if let Some(Some(node)) = self.clips.get_mut(index) {
+                node.scissor = scissor;
+                node.stencil = stencil;
+            }
+        }
+    }
+
+    pub fn pop_clip(&mut self) -> Option<ClipId> {
+        self.clip_stack.pop()
+    }
+
+    // makes a clip pushed earlier current again, so content added later is clipped by it and drawn with
+    // the content already under it, returns false when the clip was removed
+    pub fn reenter_clip(&mut self, id: ClipId) -> bool {
+        if !self.clips.get(id.0 as usize).is_some_and(|node| node.is_some()) {
+            return false;
+        }
+        self.clip_stack.push(id);
+        true
+    }
+
+    pub fn current_clip(&self) -> Option<ClipId> {
+        self.clip_stack.last().copied()
+    }
+
+    // content that was added under a removed clip is no longer drawn
+    pub fn remove_clip(&mut self, id: ClipId) {
+        if let Some(slot) = self.clips.get_mut(id.0 as usize) {
+            *slot = None;
+        }
+        self.clip_stack.retain(|c| *c != id);
+    }
+
+    // the point has to pass the scissor and the outline of the clip and of every clip around it
+    fn clip_contains(&self, clip: Option<ClipId>, x: f32, y: f32) -> bool {
+        let Some(id) = clip else {
+            return true;
+        };
+        clip_chain(&self.clips, id).is_some_and(|chain| {
+            chain.iter().all(|node| node.scissor.contains(x, y) && node.clip.contains(x, y))
+        })
+    }
+
+    fn screen_rect(&self) -> Rect {
+        Rect::new(0.0, 0.0, self.size.width as f32, self.size.height as f32)
+    }
+
+    fn set_scissor(&self, render_pass: &mut RenderPass, rect: Rect) -> bool {
+        let rect = rect.intersect(&self.screen_rect());
+        let left = rect.x.floor() as u32;
+        let top = rect.y.floor() as u32;
+        let right = (rect.right().ceil() as u32).min(self.size.width);
+        let bottom = (rect.bottom().ceil() as u32).min(self.size.height);
+
+        if right <= left || bottom <= top {
+            return false;
+        }
+        render_pass.set_scissor_rect(left, top, right - left, bottom - top);
+        true
+    }
+
+    fn reset_stencil(&self, render_pass: &mut RenderPass) {
+        self.set_scissor(render_pass, self.screen_rect());
+        render_pass.set_pipeline(&self.stencil_reset_pipeline);
+        render_pass.set_stencil_reference(0);
+        render_pass.set_vertex_buffer(0, self.screen_quad.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(self.screen_quad.index_buffer.slice(..), IndexFormat::Uint16);
+        render_pass.draw_indexed(0..self.screen_quad.indices_count, 0, 0..1);
+    }
+
+    // sets up the scissor rect and stencil for the clip, returns false when nothing under it can be visible
+    fn apply_clip(&self, render_pass: &mut RenderPass, clip: Option<ClipId>, stencil_dirty: &mut bool) -> bool {
+        let chain = match clip {
+            None => vec![],
+            Some(id) => match clip_chain(&self.clips, id) {
+                Some(chain) => chain,
+                None => return false
+            }
+        };
+        let scissor = chain.last().map(|node| node.scissor).unwrap_or(self.screen_rect());
+        let outlines: Vec<&ShapeData> = chain.iter().filter_map(|node| node.stencil.as_ref()).collect();
+
+        if *stencil_dirty || !outlines.is_empty() {
+            self.reset_stencil(render_pass);
+            *stencil_dirty = false;
+        }
+
+        if !self.set_scissor(render_pass, scissor) {
+            return false;
+        }
+
+        // every outline only increments pixels that passed all the outlines before it
+        render_pass.set_pipeline(&self.clip_pipeline);
+        for (depth, outline) in outlines.iter().enumerate() {
+            render_pass.set_stencil_reference(depth as u32);
+            render_pass.set_vertex_buffer(0, outline.vertex_buffer.slice(..));
+            render_pass.set_index_buffer(outline.index_buffer.slice(..), IndexFormat::Uint16);
+            render_pass.draw_indexed(0..outline.indices_count, 0, 0..1);
+            *stencil_dirty = true;
+        }
+        render_pass.set_stencil_reference(outlines.len() as u32);
+        true
+    }
+
+    // everything added until the layer is popped is rendered offscreen and composited as a whole
+    pub fn push_layer(&mut self, options: LayerOptions) -> LayerId {
+        let order = self.next_order();
+        let node = self.create_layer(options, self.current_layer(), order);
+        let id = LayerId(insert_slot(&mut self.layers, node));
+        self.layer_stack.push(id);
+        id
+    }
+
+    fn create_layer(&self, options: LayerOptions, parent: Option<LayerId>, order: u64) -> LayerNode {
+        let surface = LayerSurface::new(&self.device, &self.composite_layout, &self.blur_layout, &self.targets, self.size, &options);
+        LayerNode { options, parent, order, surface }
+    }
+
+    pub fn pop_layer(&mut self) -> Option<LayerId> {
+        self.layer_stack.pop()
+    }
+
+    pub fn current_layer(&self) -> Option<LayerId> {
+        self.layer_stack.last().copied()
+    }
+
+    pub fn layer_options(&self, id: LayerId) -> Option<LayerOptions> {
+        self.layers.get(id.0 as usize)?.as_ref().map(|layer| layer.options)
+    }
+
+    pub fn set_layer_options(&mut self, id: LayerId, options: LayerOptions) {
+        if let Some(Some(layer)) = self.layers.get_mut(id.0 as usize) {
+            layer.options = options;
+            self.queue.write_buffer(&layer.surface.uniform, 0, bytemuck::cast_slice(&[CompositeUniform::new(&options)]));
+            layer.surface.blur.set_sigma(&self.queue, options.blur);
+            layer.surface.backdrop_blur.set_sigma(&self.queue, options.backdrop_blur);
+        }
+    }
+
+    // content that was added to a removed layer or to any layer inside it is no longer drawn
+    pub fn remove_layer(&mut self, id: LayerId) {
+        if let Some(slot) = self.layers.get_mut(id.0 as usize) {
+            *slot = None;
+        }
+        self.layer_stack.retain(|l| *l != id);
+        self.overlays.retain(|l| *l != id);
+
+        let children: Vec<LayerId> = self.layers
+            .iter()
+            .enumerate()
+            .filter(|(_, layer)| layer.as_ref().is_some_and(|layer| layer.parent == Some(id)))
+            .map(|(index, _)| LayerId(index as u32))
+            .collect();
+        for child in children {
+            self.remove_layer(child);
+        }
+    }
+
+    // creates a layer above all other layers and overlays, it is drawn into between enter_overlay and exit_overlay
+    pub fn add_overlay(&mut self, options: LayerOptions) -> LayerId {
+        let node = self.create_layer(options, None, 0);
+        let id = LayerId(insert_slot(&mut self.layers, node));
+        self.overlays.push(id);
+        id
+    }
+
+    // content added until exit_overlay goes into the overlay, outside of any clip pushed before
+    pub fn enter_overlay(&mut self, id: LayerId) -> bool {
+        if !self.overlays.contains(&id) {
+            return false;
+        }
+        self.saved_clips.push(std::mem::take(&mut self.clip_stack));
+        self.layer_stack.push(id);
+        true
+    }
+
+    pub fn exit_overlay(&mut self) -> Option<LayerId> {
+        let id = self.layer_stack.pop();
+        self.clip_stack = self.saved_clips.pop().unwrap_or_default();
+        id
+    }
+
+    pub fn is_overlay(&self, id: LayerId) -> bool {
+        self.overlays.contains(&id)
+    }
+
+    fn next_order(&mut self) -> u64 {
+        self.next_order += 1;
+        self.next_order
+    }
+
+    pub fn current_scope(&self) -> DrawScope {
+        DrawScope {
+            layer: self.current_layer(),
+            clip: self.current_clip()
+        }
+    }
+
+    fn begin_scene_pass<'pass>(
+        &'pass self,
+        encoder: &'pass mut wgpu::CommandEncoder,
+        target: &'pass TextureView,
+        clear: wgpu::Color
+    ) -> RenderPass<'pass> {
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: self.multisample_view.as_ref().unwrap_or(target),
+                resolve_target: self.multisample_view.as_ref().map(|_| target),
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(clear),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &self.stencil_view,
+                depth_ops: None,
+                stencil_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(0),
+                    store: wgpu::StoreOp::Discard,
+                }),
+            }),
+            occlusion_query_set: None,
+            timestamp_writes: None,
+        })
+    }
+
+    // draws the items in the order they were added, whatever kind of content they are
+    fn render_content(&self, encoder: &mut wgpu::CommandEncoder, target: &TextureView, items: &[DrawItem], clear: wgpu::Color) {
+        let mut render_pass = self.begin_scene_pass(encoder, target, clear);
+
+        let mut stencil_dirty = false;
+        let mut clip = None;
+        let mut visible = true;
+        let mut bound = None;
+        let mut batch: Option<(DrawKind, Range<u32>)> = None;
+
+        for item in items {
+            if item.clip != clip {
+                if let Some((kind, run)) = batch.take() {
+                    self.draw_run(&mut render_pass, kind, run, &mut bound);
+                }
+                clip = item.clip;
+                visible = self.apply_clip(&mut render_pass, clip, &mut stencil_dirty);
+                bound = None;
+            }
+            if !visible {
+                continue;
+            }
+
+            match &mut batch {
+                Some((kind, run)) if *kind == item.kind && kind.is_instanced() && run.end == item.slot => run.end += 1,
+                _ => {
+                    if let Some((kind, run)) = batch.replace((item.kind, item.slot..item.slot + 1)) {
+                        self.draw_run(&mut render_pass, kind, run, &mut bound);
+                    }
+                }
+            }
+        }
+        if let Some((kind, run)) = batch {
+            self.draw_run(&mut render_pass, kind, run, &mut bound);
+        }
+    }
+
+    fn draw_list(&self, layer: Option<LayerId>) -> Vec<DrawItem> {
+        let mut items = vec![];
+        collect_items(&mut items, &self.instances, DrawKind::Instance, layer, |i| (i.scope, i.order));
+        collect_items(&mut items, &self.sdf_shapes, DrawKind::Sdf, layer, |s| (s.scope, s.order));
+        collect_items(&mut items, &self.painted_shapes, DrawKind::Paint, layer, |p| (p.scope, p.order));
+        collect_items(&mut items, &self.images, DrawKind::Image, layer, |i| (i.scope, i.order));
+        collect_items(&mut items, &self.atlas_quads, DrawKind::AtlasQuad, layer, |q| (q.scope, q.order));
+        collect_items(&mut items, &self.texts, DrawKind::Text, layer, |t| (t.scope, t.order));
+        items.sort_unstable_by_key(|item| item.order);
+        items
+    }
+
+    // draws consecutive slots of one kind, the pipeline is only set up again when the kind changes
+    fn draw_run(&self, render_pass: &mut RenderPass, kind: DrawKind, run: Range<u32>, bound: &mut Option<DrawKind>) {
+        if *bound != Some(kind) {
+            self.bind_kind(render_pass, kind);
+            *bound = Some(kind);
+        }
+
+        match kind {
+            DrawKind::Sdf | DrawKind::AtlasQuad => render_pass.draw_indexed(0..self.quad.indices_count, 0, run),
+            DrawKind::Instance => {
+                for index in run {
+                    let Some(instance) = &self.instances[index as usize] else {
+                        continue;
+                    };
+                    render_pass.set_vertex_buffer(0, instance.shape.vertex_buffer.slice(..));
+                    render_pass.set_index_buffer(instance.shape.index_buffer.slice(..), IndexFormat::Uint16);
+                    render_pass.draw_indexed(0..instance.shape.indices_count, 0, index..index + 1);
+                }
+            }
+            DrawKind::Paint => {
+                for painted in run.filter_map(|index| self.painted_shapes[index as usize].as_ref()) {
+                    render_pass.set_bind_group(0, &painted.bind_group, &[]);
+                    render_pass.set_vertex_buffer(0, painted.shape.vertex_buffer.slice(..));
+                    render_pass.set_index_buffer(painted.shape.index_buffer.slice(..), IndexFormat::Uint16);
+                    render_pass.draw_indexed(0..painted.shape.indices_count, 0, 0..1);
+                }
+            }
+            DrawKind::Image => {
+                for image in run.filter_map(|index| self.images[index as usize].as_ref()) {
+                    render_pass.set_bind_group(0, image.texture.bind_group(image.sampling), &[]);
+                    render_pass.set_vertex_buffer(0, image.shape.vertex_buffer.slice(..));
+                    render_pass.set_index_buffer(image.shape.index_buffer.slice(..), IndexFormat::Uint16);
+                    render_pass.draw_indexed(0..image.shape.indices_count, 0, 0..1);
+                }
+            }
+            // long texts have more than u16::MAX vertices, so their indices are 32 bit
+            DrawKind::Text => {
+                for text in run.filter_map(|index| self.texts[index as usize].as_ref()) {
+                    render_pass.set_vertex_buffer(0, text.shape.vertex_buffer.slice(..));
+                    render_pass.set_index_buffer(text.shape.index_buffer.slice(..), IndexFormat::Uint32);
+                    render_pass.draw_indexed(0..text.shape.indices_count, 0, 0..1);
+                }
+            }
+        }
+    }
+
+    fn bind_kind(&self, render_pass: &mut RenderPass, kind: DrawKind) {
+        match kind {
+            DrawKind::Instance => {
+                render_pass.set_pipeline(&self.render_pipeline);
+                render_pass.set_vertex_buffer(1, self.instance_buffer.slice(..));
+            }
+            DrawKind::Sdf => {
+                render_pass.set_pipeline(&self.sdf_pipeline);
+                render_pass.set_bind_group(0, &self.viewport_bind_group, &[]);
+                render_pass.set_vertex_buffer(0, self.quad.vertex_buffer.slice(..));
+                render_pass.set_vertex_buffer(1, self.sdf_instance_buffer.slice(..));
+                render_pass.set_index_buffer(self.quad.index_buffer.slice(..), IndexFormat::Uint16);
+            }
+            DrawKind::Paint => {
+                render_pass.set_pipeline(&self.paint_pipeline);
+                render_pass.set_bind_group(1, &self.viewport_bind_group, &[]);
+            }
+            DrawKind::Image => {
+                render_pass.set_pipeline(&self.image_pipeline);
+                render_pass.set_bind_group(1, &self.viewport_bind_group, &[]);
+            }
+            DrawKind::AtlasQuad => {
+                render_pass.set_pipeline(&self.atlas_pipeline);
+                render_pass.set_bind_group(0, self.atlas.bind_group(), &[]);
+                render_pass.set_bind_group(1, &self.viewport_bind_group, &[]);
+                render_pass.set_vertex_buffer(0, self.quad.vertex_buffer.slice(..));
+                render_pass.set_vertex_buffer(1, self.atlas_instance_buffer.slice(..));
+                render_pass.set_index_buffer(self.quad.index_buffer.slice(..), IndexFormat::Uint16);
+            }
+            DrawKind::Text => {
+                render_pass.set_pipeline(&self.text_pipeline);
+                render_pass.set_bind_group(0, self.glyph_atlas.bind_group(), &[]);
+                render_pass.set_bind_group(1, &self.viewport_bind_group, &[]);
+            }
+        }
+    }
+
+    // renders the content of a layer and composites the layers pushed inside it in between, where they were pushed
+    fn render_layer(&self, encoder: &mut wgpu::CommandEncoder, target: &RenderTarget, layer: Option<LayerId>, clear: wgpu::Color) {
+        let mut children: Vec<(LayerId, &LayerNode)> = self.layers
+            .iter()
+            .enumerate()
+            .filter_map(|(index, child)| child.as_ref().map(|child| (LayerId(index as u32), child)))
+            .filter(|(id, child)| child.parent == layer && !self.overlays.contains(id))
+            .collect();
+        children.sort_unstable_by_key(|(_, child)| child.order);
+
+        let items = self.draw_list(layer);
+        let orders: Vec<u64> = children.iter().map(|(_, child)| child.order).collect();
+        let mut segments = split_draw_list(&items, &orders).into_iter();
+
+        self.render_content(encoder, &target.view, segments.next().unwrap_or_default(), clear);
+        for ((id, child), segment) in children.into_iter().zip(segments) {
+            self.render_layer(encoder, &child.surface.target, Some(id), wgpu::Color::TRANSPARENT);
+            self.composite_layer(encoder, &child.surface, &child.options, target);
+
+            if !segment.is_empty() {
+                self.render_content(encoder, &self.top_surface.target.view, segment, wgpu::Color::TRANSPARENT);
+                self.composite_layer(encoder, &self.top_surface, &LayerOptions::new(1.0), target);
+            }
+        }
+    }
+
+    // blends the layer texture onto the target below it
+    fn composite_layer(&self, encoder: &mut wgpu::CommandEncoder, surface: &LayerSurface, options: &LayerOptions, target: &RenderTarget) {
+        let targets = &self.targets;
+
+        // blending needs to read what is behind the layer, so the target is copied before it is replaced
+        encoder.copy_texture_to_texture(
+            target.texture.as_image_copy(),
+            targets.backdrop.texture.as_image_copy(),
+            target.texture.size()
+        );
+        if options.backdrop_blur > 0.0 {
+            self.blur(encoder, &surface.backdrop_blur, &targets.blurred);
         }
+
+        if options.blur > 0.0 {
+            self.blur(encoder, &surface.blur, &surface.target);
+        }
+
+        self.post_pass(encoder, &target.view, &self.composite_pipeline, &surface.bind_group, "Composite Pass");
+    }
+
+    // the target may be the source of the passes, the scratch target sits between them
+    fn blur(&self, encoder: &mut wgpu::CommandEncoder, passes: &BlurPasses, target: &RenderTarget) {
+        self.post_pass(encoder, &self.targets.scratch.view, &self.blur_pipeline, &passes.bind_groups[0], "Blur Pass");
+        self.post_pass(encoder, &target.view, &self.blur_pipeline, &passes.bind_groups[1], "Blur Pass");
+    }
+
+    fn post_pass(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &TextureView,
+        pipeline: &RenderPipeline,
+        bind_group: &BindGroup,
+        label: &str
+    ) {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some(label),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            occlusion_query_set: None,
+            timestamp_writes: None,
+        });
+
+        render_pass.set_pipeline(pipeline);
+        render_pass.set_bind_group(0, bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+
+    pub fn set_focus_manager(&mut self, focus: FocusManager) {
+        self.focus = Some(focus);
+    }
+
+    fn update_focus_ring(&mut self) {
+        let Some(bounds) = self.focus.as_ref().and_then(FocusManager::focused_bounds) else {
+            self.focus_ring = None;
+            return;
+        };
+        if self.focus_ring.as_ref().is_some_and(|ring| ring.bounds == bounds && ring.size == self.size) {
+            return;
+        }
+
+        let thickness = [
+            FOCUS_RING_WIDTH * 2.0 / self.size.width as f32,
+            FOCUS_RING_WIDTH * 2.0 / self.size.height as f32
+        ];
+        let shape = self.create_shape(
+            Shape::outline(bounds.to_clip(self.size), thickness, FOCUS_RING_COLOR.to_linear_rgb())
+        );
+        self.focus_ring = Some(FocusRing { bounds, size: self.size, shape });
     }
 
     pub fn create_shape(&self, shape: Shape) -> ShapeData {
@@ -71,7 +1400,7 @@ impl<'window> WgpuRenderer<'window> {
 
 #[async_trait(?Send)]
 impl<'window> Renderer<'window> for WgpuRenderer<'window> {
-    async fn init(window: &'window Window) -> WgpuRenderer<'window> {
+    async fn init(window: &'window Window, options: &ShuiqiOptions) -> WgpuRenderer<'window> {
         println!("Initializing WGPU renderer");
         let size = window.inner_size();
 
@@ -84,11 +1413,19 @@ impl<'window> Renderer<'window> for WgpuRenderer<'window> {
                 compatible_surface: Some(&surface),
             }
         ).await.unwrap();
+        // sample counts other than 1 and 4 depend on the adapter and have to be enabled explicitly
+        let required_features = adapter.features() & wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES;
         let (device, queue) = adapter.request_device(
-            &DeviceDescriptor::default(),
+            &DeviceDescriptor {
+                required_features,
+                ..Default::default()
+            },
             None
         ).await.unwrap();
 
+        // the requested count is lowered when the adapter does not support it, see sample_count
+        let sample_count = supported_sample_count(&adapter, device.features(), options.msaa_samples);
+
         let surface_caps = surface.get_capabilities(&adapter);
         let surface_format = surface_caps.formats.iter()
             .find(|format| format.is_srgb())
@@ -109,7 +1446,8 @@ impl<'window> Renderer<'window> for WgpuRenderer<'window> {
         surface.configure(&device, &config);
 
         let pipeline = crate::graphics::pipeline::create_instance_pipeline(
-            &device
+            &device,
+            sample_count
         );
 
         let instance_buffer = device.create_buffer_init(
@@ -120,6 +1458,102 @@ impl<'window> Renderer<'window> for WgpuRenderer<'window> {
             },
         );
 
+        let uniform_layout = crate::graphics::pipeline::create_uniform_bind_group_layout(&device);
+        let texture_layout = crate::graphics::pipeline::create_texture_bind_group_layout(&device);
+        let glyph_atlas = GlyphAtlas::new(&device, &texture_layout);
+        let image_pipeline = crate::graphics::pipeline::create_image_pipeline(&device, &texture_layout, &uniform_layout, sample_count);
+
+        let atlas_layout = crate::graphics::pipeline::create_texture_array_bind_group_layout(&device);
+        let atlas = TextureAtlas::new(&device, &atlas_layout);
+        let atlas_pipeline = crate::graphics::pipeline::create_atlas_pipeline(&device, &atlas_layout, &uniform_layout, sample_count);
+        let atlas_instance_buffer = device.create_buffer_init(
+            &BufferInitDescriptor {
+                label: Some("Atlas Instance Buffer"),
+                contents: &[],
+                usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            },
+        );
+        let quad = ShapeData {
+            vertex_buffer: device.create_buffer_init(
+                &BufferInitDescriptor {
+                    label: Some("Quad Vertex Buffer"),
+                    contents: bytemuck::cast_slice(&QUAD_CORNERS),
+                    usage: wgpu::BufferUsages::VERTEX
+                }
+            ),
+            index_buffer: device.create_buffer_init(
+                &BufferInitDescriptor {
+                    label: Some("Quad Index Buffer"),
+                    contents: bytemuck::cast_slice(&QUAD_INDICES),
+                    usage: wgpu::BufferUsages::INDEX
+                }
+            ),
+            indices_count: QUAD_INDICES.len() as u32
+        };
+
+        let viewport_buffer = device.create_buffer_init(
+            &BufferInitDescriptor {
+                label: Some("Viewport Buffer"),
+                contents: bytemuck::cast_slice(&[ViewportUniform::new(size.width, size.height)]),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            },
+        );
+        let viewport_bind_group = crate::graphics::pipeline::create_uniform_bind_group(
+            &device,
+            &uniform_layout,
+            &viewport_buffer
+        );
+        let text_pipeline = crate::graphics::pipeline::create_text_pipeline(&device, &texture_layout, &uniform_layout, sample_count);
+        let sdf_pipeline = crate::graphics::pipeline::create_sdf_pipeline(&device, &uniform_layout, sample_count);
+        let paint_pipeline = crate::graphics::pipeline::create_paint_pipeline(&device, &uniform_layout, &uniform_layout, sample_count);
+        let sdf_instance_buffer = device.create_buffer_init(
+            &BufferInitDescriptor {
+                label: Some("SDF Instance Buffer"),
+                contents: &[],
+                usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            },
+        );
+
+        let clip_pipeline = crate::graphics::pipeline::create_clip_pipeline(&device, sample_count);
+        let stencil_reset_pipeline = crate::graphics::pipeline::create_stencil_reset_pipeline(&device, sample_count);
+        let stencil_view = create_stencil_view(&device, size, sample_count);
+        let multisample_view = create_multisample_view(&device, size, sample_count);
+        let screen_quad = Shape::rect([-1.0, 1.0, 1.0, -1.0], [0.0; 3]);
+        let screen_quad = ShapeData {
+            vertex_buffer: device.create_buffer_init(
+                &BufferInitDescriptor {
+                    label: Some("Screen Quad Vertex Buffer"),
+                    contents: bytemuck::cast_slice(&screen_quad.vertices),
+                    usage: wgpu::BufferUsages::VERTEX
+                }
+            ),
+            index_buffer: device.create_buffer_init(
+                &BufferInitDescriptor {
+                    label: Some("Screen Quad Index Buffer"),
+                    contents: bytemuck::cast_slice(&screen_quad.indices),
+                    usage: wgpu::BufferUsages::INDEX
+                }
+            ),
+            indices_count: screen_quad.indices.len() as u32
+        };
+
+        let targets = RenderTargets::new(&device, size);
+        let blit_layout = crate::graphics::pipeline::create_post_bind_group_layout(&device, 1, false);
+        let blit_pipeline = crate::graphics::pipeline::create_blit_pipeline(&device, &blit_layout);
+        let blit_bind_group = crate::graphics::pipeline::create_post_bind_group(&device, &blit_layout, &[&targets.scene.view], None);
+        let blur_layout = crate::graphics::pipeline::create_post_bind_group_layout(&device, 1, true);
+        let blur_pipeline = crate::graphics::pipeline::create_blur_pipeline(&device, &blur_layout);
+        let composite_layout = crate::graphics::pipeline::create_post_bind_group_layout(&device, 3, true);
+        let composite_pipeline = crate::graphics::pipeline::create_composite_pipeline(&device, &composite_layout);
+        let top_surface = LayerSurface::new(&device, &composite_layout, &blur_layout, &targets, size, &LayerOptions::new(1.0));
+        let focus_ring_instance = device.create_buffer_init(
+            &BufferInitDescriptor {
+                label: Some("Focus Ring Instance Buffer"),
+                contents: bytemuck::cast_slice(&[InstanceData::new([0.0, 0.0], [1.0, 1.0])]),
+                usage: wgpu::BufferUsages::VERTEX,
+            },
+        );
+
         WgpuRenderer {
             device,
             queue,
@@ -129,54 +1563,94 @@ impl<'window> Renderer<'window> for WgpuRenderer<'window> {
             size,
             render_pipeline: pipeline,
             instances: vec![],
-            instance_buffer
+            free_instances: vec![],
+            instance_buffer,
+            focus: None,
+            focus_ring: None,
+            focus_ring_instance,
+            text_pipeline,
+            glyph_atlas,
+            texts: vec![],
+            texture_layout,
+            image_pipeline,
+            images: vec![],
+            atlas,
+            atlas_pipeline,
+            atlas_quads: vec![],
+            atlas_instance_buffer,
+            quad,
+            viewport_buffer,
+            viewport_bind_group,
+            sdf_pipeline,
+            sdf_shapes: vec![],
+            sdf_instance_buffer,
+            paint_pipeline,
+            uniform_layout,
+            painted_shapes: vec![],
+            clips: vec![],
+            clip_stack: vec![],
+            clip_pipeline,
+            stencil_reset_pipeline,
+            stencil_view,
+            sample_count,
+            multisample_view,
+            screen_quad,
+            layers: vec![],
+            layer_stack: vec![],
+            overlays: vec![],
+            saved_clips: vec![],
+            targets,
+            top_surface,
+            blit_layout,
+            blit_pipeline,
+            blit_bind_group,
+            blur_layout,
+            blur_pipeline,
+            composite_layout,
+            composite_pipeline,
+            next_order: 0
         }
     }
 
-    fn render(&self) {
-        println!("Rendering with WGPU");
+    fn render(&mut self) {
         let output = self.surface.get_current_texture().unwrap();
         let view = output.texture.create_view(
             &TextureViewDescriptor::default()
         );
 
+        self.update_focus_ring();
+
         let mut encoder = self.device.create_command_encoder(
             &wgpu::CommandEncoderDescriptor::default()
         );
-        {
-            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Render Pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 0.0117647059,
-                            g: 0.7890625,
-                            b: 0.984375,
-                            a: 1.0,
-                        }),
-                        store: wgpu::StoreOp::Store,
-                    },
-                })],
-                depth_stencil_attachment: None,
-                occlusion_query_set: None,
-                timestamp_writes: None,
-            });
-
-            render_pass.set_pipeline(&self.render_pipeline);
-
-            // Set the instance buffer for all instances at once
-            render_pass.set_vertex_buffer(1, self.instance_buffer.slice(..));
-
-            // Draw all instances in a single call
-            if let Some(first_instance) = self.instances.first() {
-                render_pass.set_vertex_buffer(0, first_instance.shape.vertex_buffer.slice(..));
-                render_pass.set_index_buffer(first_instance.shape.index_buffer.slice(..), IndexFormat::Uint16);
-                render_pass.draw_indexed(0..first_instance.shape.indices_count, 0, 0..self.instances.len() as u32);
+
+        let scene = &self.targets.scene;
+        self.render_layer(&mut encoder, scene, None, CLEAR_COLOR);
+
+        // overlays are composited onto the scene after every other layer
+        for &id in &self.overlays {
+            if let Some(Some(overlay)) = self.layers.get(id.0 as usize) {
+                self.render_layer(&mut encoder, &overlay.surface.target, Some(id), wgpu::Color::TRANSPARENT);
+                self.composite_layer(&mut encoder, &overlay.surface, &overlay.options, scene);
             }
         }
 
+        // the focus ring is drawn last so it stays on top of the focused node
+        if let Some(FocusRing { shape, .. }) = &self.focus_ring {
+            {
+                let mut render_pass = self.begin_scene_pass(&mut encoder, &self.top_surface.target.view, wgpu::Color::TRANSPARENT);
+                render_pass.set_pipeline(&self.render_pipeline);
+                render_pass.set_stencil_reference(0);
+                render_pass.set_vertex_buffer(0, shape.vertex_buffer.slice(..));
+                render_pass.set_vertex_buffer(1, self.focus_ring_instance.slice(..));
+                render_pass.set_index_buffer(shape.index_buffer.slice(..), IndexFormat::Uint16);
+                render_pass.draw_indexed(0..shape.indices_count, 0, 0..1);
+            }
+            self.composite_layer(&mut encoder, &self.top_surface, &LayerOptions::new(1.0), scene);
+        }
+
+        self.post_pass(&mut encoder, &view, &self.blit_pipeline, &self.blit_bind_group, "Blit Pass");
+
         self.queue.submit(std::iter::once(encoder.finish()));
         output.present();
     }
@@ -191,5 +1665,135 @@ impl<'window> Renderer<'window> for WgpuRenderer<'window> {
         self.config.width = size.width;
         self.config.height = size.height;
         self.surface.configure(&self.device, &self.config);
+        self.stencil_view = create_stencil_view(&self.device, size, self.sample_count);
+        self.multisample_view = create_multisample_view(&self.device, size, self.sample_count);
+        self.targets = RenderTargets::new(&self.device, size);
+        self.blit_bind_group = crate::graphics::pipeline::create_post_bind_group(&self.device, &self.blit_layout, &[&self.targets.scene.view], None);
+        self.top_surface = LayerSurface::new(&self.device, &self.composite_layout, &self.blur_layout, &self.targets, size, &LayerOptions::new(1.0));
+        for layer in self.layers.iter_mut().flatten() {
+            layer.surface = LayerSurface::new(&self.device, &self.composite_layout, &self.blur_layout, &self.targets, size, &layer.options);
+        }
+        self.rebuild_clips();
+        self.reproject_instances();
+        self.queue.write_buffer(
+            &self.viewport_buffer,
+            0,
+            bytemuck::cast_slice(&[ViewportUniform::new(size.width, size.height)])
+        );
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphics::path::{FillRule, Path};
+
+    // pushes the clip onto the top of the stack like the renderer does, without a stencil outline
+    fn push(clips: &mut Vec<Option<ClipNode>>, stack: &mut Vec<ClipId>, clip: &Clip) -> ClipId {
+        let parent = stack.last().copied();
+        let scissor = clip_scissor(clips, parent, clip, Rect::new(0.0, 0.0, 100.0, 100.0));
+        let id = ClipId(insert_slot(clips, ClipNode { parent, clip: clip.clone(), scissor, stencil: None }));
+        stack.push(id);
+        id
+    }
+
+    #[test]
+    fn nested_clips_are_cut_to_their_parent() {
+        let (mut clips, mut stack) = (vec![], vec![]);
+        let outer = push(&mut clips, &mut stack, &Clip::Rect(Rect::new(-10.0, 10.0, 60.0, 60.0)));
+        let inner = push(&mut clips, &mut stack, &Clip::Rect(Rect::new(40.0, 40.0, 100.0, 100.0)));
+        let path = push(&mut clips, &mut stack, &Clip::Path(Path::new(), FillRule::NonZero));
+
+        let scissor = |id: ClipId| clips[id.0 as usize].as_ref().unwrap().scissor;
+        assert_eq!(scissor(outer), Rect::new(0.0, 10.0, 50.0, 60.0));
+        assert_eq!(scissor(inner), Rect::new(40.0, 40.0, 10.0, 30.0));
+        // outlines without known bounds keep the scissor of the clip below them
+        assert_eq!(scissor(path), scissor(inner));
+    }
+
+    #[test]
+    fn popped_clips_no_longer_parent_new_ones() {
+        let (mut clips, mut stack) = (vec![], vec![]);
+        let first = push(&mut clips, &mut stack, &Clip::Rect(Rect::new(0.0, 0.0, 10.0, 10.0)));
+        stack.pop();
+        let second = push(&mut clips, &mut stack, &Clip::Rect(Rect::new(50.0, 50.0, 10.0, 10.0)));
+
+        assert_eq!(clips[second.0 as usize].as_ref().unwrap().parent, None);
+        assert_eq!(clip_chain(&clips, second).unwrap().len(), 1);
+        assert!(clip_chain(&clips, first).is_some());
+    }
+
+    #[test]
+    fn chains_run_outermost_first_and_break_when_a_clip_is_removed() {
+        let (mut clips, mut stack) = (vec![], vec![]);
+        let outer = push(&mut clips, &mut stack, &Clip::Rect(Rect::new(0.0, 0.0, 50.0, 50.0)));
+        let inner = push(&mut clips, &mut stack, &Clip::Rect(Rect::new(10.0, 10.0, 10.0, 10.0)));
+
+        let chain = clip_chain(&clips, inner).unwrap();
+        assert_eq!(chain.iter().map(|node| node.scissor).collect::<Vec<_>>(), vec![
+            Rect::new(0.0, 0.0, 50.0, 50.0),
+            Rect::new(10.0, 10.0, 10.0, 10.0)
+        ]);
+
+        clips[outer.0 as usize] = None;
+        assert!(clip_chain(&clips, inner).is_none());
+    }
+
+    #[test]
+    fn adapter_specific_sample_counts_need_the_device_feature() {
+        assert_eq!(sample_count_candidates(wgpu::Features::empty()), &[4]);
+        assert_eq!(sample_count_candidates(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES), &[8, 4, 2]);
+    }
+
+    #[test]
+    fn items_are_collected_for_their_layer_only() {
+        let clipped = (DrawScope { layer: None, clip: Some(ClipId(0)) }, 3);
+        let layered = (DrawScope { layer: Some(LayerId(0)), clip: None }, 1);
+        let slots = vec![Some(clipped), None, Some(layered), Some(clipped)];
+
+        let mut items = vec![];
+        collect_items(&mut items, &slots, DrawKind::Sdf, None, |s| *s);
+        assert_eq!(items.iter().map(|item| (item.slot, item.clip, item.order)).collect::<Vec<_>>(), vec![
+            (0, Some(ClipId(0)), 3),
+            (3, Some(ClipId(0)), 3)
+        ]);
+
+        items.clear();
+        collect_items(&mut items, &slots, DrawKind::Sdf, Some(LayerId(0)), |s| *s);
+        assert_eq!(items.iter().map(|item| item.slot).collect::<Vec<_>>(), vec![2]);
+    }
+
+    #[test]
+    fn draw_lists_are_split_where_child_layers_were_pushed() {
+        let items: Vec<DrawItem> = [1, 2, 4, 7]
+            .map(|order| DrawItem { kind: DrawKind::Sdf, slot: order as u32, clip: None, order })
+            .to_vec();
+        let orders = |segments: Vec<&[DrawItem]>| segments
+            .iter()
+            .map(|segment| segment.iter().map(|item| item.order).collect())
+            .collect::<Vec<Vec<u64>>>();
+
+        assert_eq!(orders(split_draw_list(&items, &[3, 5, 6])), vec![vec![1, 2], vec![4], vec![], vec![7]]);
+        assert_eq!(orders(split_draw_list(&items, &[])), vec![vec![1, 2, 4, 7]]);
+        assert_eq!(orders(split_draw_list(&[], &[3])), vec![vec![], vec![]]);
+    }
+
+    #[test]
+    fn stacking_keys_follow_composite_order() {
+        // layer 0 pushed at order 5 in the scene, layer 1 at order 2 inside it, layer 2 is an overlay, layer 3 is gone
+        let layer_of = |id: LayerId| match id.0 {
+            0 => Some((None, 5)),
+            1 => Some((Some(LayerId(0)), 2)),
+            2 => Some((None, 0)),
+            _ => None
+        };
+        let key = |layer: Option<u32>, order| stacking_key(layer.map(LayerId), order, &[LayerId(2)], layer_of);
+
+        assert!(key(None, 4) < key(Some(0), 1));
+        assert!(key(Some(0), 9) < key(None, 6));
+        assert!(key(Some(0), 1) < key(Some(1), 3));
+        assert!(key(Some(1), 3) < key(Some(0), 4));
+        assert!(key(None, 100) < key(Some(2), 1));
+        assert_eq!(key(Some(3), 1), None);
+    }
+}
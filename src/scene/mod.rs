@@ -0,0 +1,67 @@
+use winit::dpi::PhysicalSize;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(pub u32);
+
+// rectangles are stored in physical pixels with the origin at the top left corner of the window
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Rect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32
+}
+
+impl Rect {
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Rect { x, y, width, height }
+    }
+
+    pub fn right(&self) -> f32 {
+        self.x + self.width
+    }
+
+    pub fn bottom(&self) -> f32 {
+        self.y + self.height
+    }
+
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        x >= self.x && x < self.right() && y >= self.y && y < self.bottom()
+    }
+
+    pub fn inflate(&self, amount: f32) -> Rect {
+        Rect::new(
+            self.x - amount,
+            self.y - amount,
+            self.width + amount * 2.0,
+            self.height + amount * 2.0
+        )
+    }
+
+    // the overlapping part of both rectangles, empty rectangles have no size
+    pub fn intersect(&self, other: &Rect) -> Rect {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let right = self.right().min(other.right());
+        let bottom = self.bottom().min(other.bottom());
+
+        Rect::new(x, y, (right - x).max(0.0), (bottom - y).max(0.0))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width <= 0.0 || self.height <= 0.0
+    }
+
+    // converts the rectangle to clip space as [left, top, right, bottom]
+    pub fn to_clip(self, screen_size: PhysicalSize<u32>) -> [f32; 4] {
+        let width = screen_size.width as f32;
+        let height = screen_size.height as f32;
+
+        [
+            self.x / width * 2.0 - 1.0,
+            1.0 - self.y / height * 2.0,
+            self.right() / width * 2.0 - 1.0,
+            1.0 - self.bottom() / height * 2.0
+        ]
+    }
+}
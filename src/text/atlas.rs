@@ -0,0 +1,251 @@
+use std::collections::HashMap;
+use ab_glyph::{point, Font as _, GlyphId, PxScale};
+use wgpu::{BindGroup, BindGroupLayout, Device, Queue, Texture};
+use crate::graphics::packer::ShelfPacker;
+use crate::text::font::Font;
+
+const ATLAS_SIZE: u32 = 2048;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct GlyphKey {
+    font: u32,
+    glyph: u16,
+    // sizes are quantized to a quarter pixel so close sizes share the same bitmap
+    size: u32
+}
+
+struct AtlasFull;
+
+#[derive(Copy, Clone, Debug)]
+pub struct GlyphEntry {
+    // [u0, v0, u1, v1] in normalized texture coordinates
+    pub uv: [f32; 4],
+    pub size: [f32; 2],
+    // offset of the bitmap's top left corner from the pen position on the baseline
+    pub offset: [f32; 2]
+}
+
+struct CachedGlyph {
+    // none for glyphs without any coverage, they take no room in the atlas
+    entry: Option<GlyphEntry>,
+    position: [u32; 2],
+    // texts drawing the glyph, only glyphs no text uses can be evicted
+    users: usize,
+    last_used: u64
+}
+
+pub struct GlyphAtlas {
+    texture: Texture,
+    bind_group: BindGroup,
+    packer: ShelfPacker,
+    glyphs: HashMap<GlyphKey, CachedGlyph>,
+    // counts glyph lookups, so the least recently used glyph is the one looked up the longest ago
+    clock: u64,
+    missing: usize
+}
+
+impl GlyphAtlas {
+    pub fn new(device: &Device, layout: &BindGroupLayout) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Glyph Atlas"),
+            size: wgpu::Extent3d {
+                width: ATLAS_SIZE,
+                height: ATLAS_SIZE,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::R8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Glyph Atlas Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let bind_group = crate::graphics::pipeline::create_texture_bind_group(
+            device,
+            layout,
+            &view,
+            &sampler
+        );
+
+        GlyphAtlas {
+            texture,
+            bind_group,
+            packer: ShelfPacker::new(ATLAS_SIZE, ATLAS_SIZE, 1),
+            glyphs: HashMap::new(),
+            clock: 0,
+            missing: 0
+        }
+    }
+
+    pub fn bind_group(&self) -> &BindGroup {
+        &self.bind_group
+    }
+
+    // how many glyphs could not be drawn because the atlas had no room left for them
+    pub fn missing_glyphs(&self) -> usize {
+        self.missing
+    }
+
+    // returns the atlas entry of a glyph, rasterizing and uploading it on first use, and counts the caller
+    // as a user until it releases the key; glyphs without any coverage (like spaces) and glyphs that do not
+    // fit even after evicting every unused glyph return none
+    pub fn glyph(&mut self, queue: &Queue, font: &Font, glyph: GlyphId, size: f32) -> Option<(GlyphKey, GlyphEntry)> {
+        let key = GlyphKey {
+            font: font.id(),
+            glyph: glyph.0,
+            size: (size * 4.0).round() as u32
+        };
+        self.clock += 1;
+
+        if !self.glyphs.contains_key(&key) {
+            // glyphs that did not fit are not cached, so they show up once space is available again
+            let Ok(cached) = self.rasterize(queue, font, glyph, key.size as f32 / 4.0) else {
+                self.missing += 1;
+                return None;
+            };
+            self.glyphs.insert(key, cached);
+        }
+
+        let cached = self.glyphs.get_mut(&key)?;
+        cached.last_used = self.clock;
+        let entry = cached.entry?;
+        cached.users += 1;
+        Some((key, entry))
+    }
+
+    // gives back a glyph returned by glyph once the text drawing it is removed
+    pub fn release(&mut self, key: GlyphKey) {
+        if let Some(cached) = self.glyphs.get_mut(&key) {
+            cached.users = cached.users.saturating_sub(1);
+        }
+    }
+
+    // frees the room of the least recently used glyph no text draws, false when there is none
+    fn evict(&mut self) -> bool {
+        let Some(key) = least_recently_used(&self.glyphs) else {
+            return false;
+        };
+        if let Some(CachedGlyph { entry: Some(entry), position, .. }) = self.glyphs.remove(&key) {
+            self.packer.deallocate(position, entry.size[0] as u32);
+        }
+        true
+    }
+
+    fn allocate(&mut self, width: u32, height: u32) -> Result<[u32; 2], AtlasFull> {
+        if !self.packer.fits(width, height) {
+            return Err(AtlasFull);
+        }
+        loop {
+            if let Some(position) = self.packer.allocate(width, height) {
+                return Ok(position);
+            }
+            if !self.evict() {
+                return Err(AtlasFull);
+            }
+        }
+    }
+
+    fn rasterize(&mut self, queue: &Queue, font: &Font, glyph: GlyphId, size: f32) -> Result<CachedGlyph, AtlasFull> {
+        let empty = CachedGlyph { entry: None, position: [0, 0], users: 0, last_used: 0 };
+        let Some(outlined) = font.inner().outline_glyph(
+            glyph.with_scale_and_position(PxScale::from(size), point(0.0, 0.0))
+        ) else {
+            return Ok(empty);
+        };
+
+        let bounds = outlined.px_bounds();
+        let width = bounds.width() as u32;
+        let height = bounds.height() as u32;
+        if width == 0 || height == 0 {
+            return Ok(empty);
+        }
+
+        let mut coverage = vec![0u8; (width * height) as usize];
+        outlined.draw(|x, y, c| {
+            coverage[(y * width + x) as usize] = (c.clamp(0.0, 1.0) * 255.0) as u8;
+        });
+
+        let [x, y] = self.allocate(width, height)?;
+
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &self.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d { x, y, z: 0 },
+                aspect: wgpu::TextureAspect::All,
+            },
+            &coverage,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(width),
+                rows_per_image: Some(height),
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+
+        let atlas_size = ATLAS_SIZE as f32;
+        let entry = GlyphEntry {
+            uv: [
+                x as f32 / atlas_size,
+                y as f32 / atlas_size,
+                (x + width) as f32 / atlas_size,
+                (y + height) as f32 / atlas_size
+            ],
+            size: [width as f32, height as f32],
+            offset: [bounds.min.x, bounds.min.y]
+        };
+        Ok(CachedGlyph { entry: Some(entry), position: [x, y], users: 0, last_used: 0 })
+    }
+}
+
+// glyphs without coverage are kept, they are cheap and take no room
+fn least_recently_used(glyphs: &HashMap<GlyphKey, CachedGlyph>) -> Option<GlyphKey> {
+    glyphs
+        .iter()
+        .filter(|(_, cached)| cached.users == 0 && cached.entry.is_some())
+        .min_by_key(|(_, cached)| cached.last_used)
+        .map(|(key, _)| *key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cached(users: usize, last_used: u64) -> CachedGlyph {
+        let entry = GlyphEntry { uv: [0.0; 4], size: [8.0, 8.0], offset: [0.0; 2] };
+        CachedGlyph { entry: Some(entry), position: [0, 0], users, last_used }
+    }
+
+    fn key(glyph: u16) -> GlyphKey {
+        GlyphKey { font: 0, glyph, size: 64 }
+    }
+
+    #[test]
+    fn only_unused_glyphs_are_evicted_oldest_first() {
+        let mut glyphs = HashMap::new();
+        glyphs.insert(key(1), cached(1, 1));
+        glyphs.insert(key(2), cached(0, 5));
+        glyphs.insert(key(3), cached(0, 3));
+        glyphs.insert(key(4), CachedGlyph { entry: None, ..cached(0, 0) });
+        assert_eq!(least_recently_used(&glyphs), Some(key(3)));
+
+        glyphs.remove(&key(3));
+        assert_eq!(least_recently_used(&glyphs), Some(key(2)));
+
+        glyphs.remove(&key(2));
+        assert_eq!(least_recently_used(&glyphs), None);
+    }
+}
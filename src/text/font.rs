@@ -0,0 +1,127 @@
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use std::sync::atomic::{AtomicU32, Ordering};
+use ab_glyph::{Font as _, FontArc, GlyphId, PxScale, ScaleFont};
+
+static NEXT_FONT_ID: AtomicU32 = AtomicU32::new(0);
+
+#[derive(Debug)]
+pub enum FontError {
+    Io(std::io::Error),
+    InvalidFont
+}
+
+impl Display for FontError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FontError::Io(e) => write!(f, "could not read font file: {}", e),
+            FontError::InvalidFont => write!(f, "the data is not a valid TTF/OTF font")
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct LineMetrics {
+    pub ascent: f32,
+    pub descent: f32,
+    pub line_gap: f32
+}
+
+impl LineMetrics {
+    pub fn height(&self) -> f32 {
+        self.ascent - self.descent + self.line_gap
+    }
+}
+
+// cheap to clone, the font data is shared between clones
+#[derive(Clone, Debug)]
+pub struct Font {
+    id: u32,
+    inner: FontArc
+}
+
+impl Font {
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self, FontError> {
+        let inner = FontArc::try_from_vec(data).map_err(|_| FontError::InvalidFont)?;
+
+        Ok(Font {
+            id: NEXT_FONT_ID.fetch_add(1, Ordering::Relaxed),
+            inner
+        })
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, FontError> {
+        let data = std::fs::read(path).map_err(FontError::Io)?;
+        Self::from_bytes(data)
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn data(&self) -> &[u8] {
+        self.inner.font_data()
+    }
+
+    pub fn glyph_id(&self, c: char) -> GlyphId {
+        self.inner.glyph_id(c)
+    }
+
+    pub fn advance(&self, glyph: GlyphId, size: f32) -> f32 {
+        self.inner.as_scaled(PxScale::from(size)).h_advance(glyph)
+    }
+
+    pub fn kerning(&self, first: GlyphId, second: GlyphId, size: f32) -> f32 {
+        self.inner.as_scaled(PxScale::from(size)).kern(first, second)
+    }
+
+    pub fn line_metrics(&self, size: f32) -> LineMetrics {
+        let scaled = self.inner.as_scaled(PxScale::from(size));
+
+        LineMetrics {
+            ascent: scaled.ascent(),
+            descent: scaled.descent(),
+            line_gap: scaled.line_gap()
+        }
+    }
+
+    pub(crate) fn inner(&self) -> &FontArc {
+        &self.inner
+    }
+}
+
+// a font that ships with the repository, so tests do not depend on the fonts installed on the machine
+#[cfg(test)]
+pub(crate) fn test_font() -> Font {
+    Font::from_bytes(include_bytes!("../../tests/fixtures/DejaVuSans.ttf").to_vec()).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_data_is_rejected() {
+        assert!(matches!(Font::from_bytes(vec![0; 16]), Err(FontError::InvalidFont)));
+    }
+
+    #[test]
+    fn clones_share_the_font_id() {
+        let font = test_font();
+        let other = test_font();
+        assert_eq!(font.clone().id(), font.id());
+        assert_ne!(font.id(), other.id());
+    }
+
+    #[test]
+    fn metrics_scale_with_the_size() {
+        let font = test_font();
+        let small = font.line_metrics(10.0);
+        let large = font.line_metrics(20.0);
+        assert!(small.ascent > 0.0 && small.descent < 0.0);
+        assert!((large.height() - small.height() * 2.0).abs() < 0.01);
+
+        let glyph = font.glyph_id('m');
+        assert!((font.advance(glyph, 20.0) - font.advance(glyph, 10.0) * 2.0).abs() < 0.01);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,491 @@
+use std::ops::Range;
+use ab_glyph::GlyphId;
+use rustybuzz::{Direction, Face, UnicodeBuffer};
+use unicode_bidi::{BidiInfo, ParagraphInfo};
+use unicode_linebreak::{linebreaks, BreakOpportunity};
+use winit::dpi::PhysicalSize;
+use crate::designer::point::{get_measurement_pixels, Measurement};
+use crate::text::font::Font;
+use crate::text::PositionedGlyph;
+
+const ELLIPSIS: &str = "\u{2026}";
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TextAlign {
+    // start and end follow the base direction of each paragraph
+    Start,
+    End,
+    Left,
+    Center,
+    Right
+}
+
+#[derive(Clone, Debug)]
+pub struct TextStyle {
+    pub size: f32,
+    // multiplier of the font size, the line metrics of the font are used when unset
+    pub line_height: Option<f32>,
+    pub align: TextAlign,
+    pub max_width: Option<Measurement>,
+    pub wrap: bool,
+    pub max_lines: Option<usize>,
+    pub ellipsis: bool
+}
+
+impl TextStyle {
+    pub fn new(size: f32) -> Self {
+        TextStyle {
+            size,
+            line_height: None,
+            align: TextAlign::Start,
+            max_width: None,
+            wrap: true,
+            max_lines: None,
+            ellipsis: false
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TextLine {
+    // byte range of the source text covered by this line, including trailing whitespace
+    pub text: Range<usize>,
+    pub glyphs: Range<usize>,
+    pub x: f32,
+    pub width: f32,
+    pub baseline: f32,
+    pub top: f32,
+    pub height: f32,
+    pub rtl: bool
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct TextLayout {
+    pub glyphs: Vec<PositionedGlyph>,
+    pub lines: Vec<TextLine>,
+    pub width: f32,
+    pub height: f32
+}
+
+impl TextLayout {
+    pub fn size(&self) -> [f32; 2] {
+        [self.width, self.height]
+    }
+
+    // a wrapped line boundary belongs to the line it starts
+    pub fn line_at_index(&self, index: usize) -> usize {
+        self.lines
+            .iter()
+            .rposition(|l| l.text.start <= index && index <= l.text.end)
+            .unwrap_or(self.lines.len().saturating_sub(1))
+    }
+
+    pub fn line_at_y(&self, y: f32) -> usize {
+        self.lines
+            .iter()
+            .position(|l| y < l.top + l.height)
+            .unwrap_or(self.lines.len().saturating_sub(1))
+    }
+
+    // returns the caret position for a byte index of the source text as [x, line top]
+    pub fn caret_position(&self, index: usize) -> [f32; 2] {
+        let Some(line) = self.lines.get(self.line_at_index(index)) else {
+            return [0.0, 0.0];
+        };
+
+        let glyphs = &self.glyphs[line.glyphs.clone()];
+        let containing = glyphs
+            .iter()
+            .filter(|g| g.cluster <= index)
+            .max_by_key(|g| g.cluster);
+
+        let x = match containing {
+            // the leading edge of a glyph is its left side in ltr runs and its right side in rtl runs
+            Some(glyph) if glyph.cluster == index && glyph.rtl => glyph.position[0] + glyph.advance,
+            Some(glyph) if glyph.cluster == index => glyph.position[0],
+            _ if index >= line.text.end || glyphs.is_empty() => {
+                if line.rtl { line.x } else { line.x + line.width }
+            }
+            Some(glyph) if glyph.rtl => glyph.position[0],
+            Some(glyph) => glyph.position[0] + glyph.advance,
+            None => line.x
+        };
+
+        [x, line.top]
+    }
+
+    // maps a point relative to the top left corner of the text to the closest byte index
+    pub fn hit_test(&self, text: &str, x: f32, y: f32) -> usize {
+        let Some(line) = self.lines.get(self.line_at_y(y)) else {
+            return 0;
+        };
+
+        let visible_end = trim_end(text, line.text.clone()).end;
+        let line_start = if line.rtl { visible_end } else { line.text.start };
+        let line_end = if line.rtl { line.text.start } else { visible_end };
+
+        if x <= line.x {
+            return line_start;
+        }
+
+        for glyph in &self.glyphs[line.glyphs.clone()] {
+            let left = glyph.position[0];
+            if x >= left + glyph.advance {
+                continue;
+            }
+
+            let cluster = glyph.cluster.min(visible_end);
+            let after = next_char_boundary(text, cluster).min(visible_end);
+            let right_half = x >= left + glyph.advance / 2.0;
+
+            return match (right_half, glyph.rtl) {
+                (false, false) | (true, true) => cluster,
+                _ => after
+            };
+        }
+
+        line_end
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+struct ShapedGlyph {
+    glyph: GlyphId,
+    cluster: usize,
+    advance: f32,
+    offset: [f32; 2]
+}
+
+struct Shaper<'a> {
+    face: Face<'a>,
+    scale: f32
+}
+
+impl<'a> Shaper<'a> {
+    fn new(font: &'a Font, size: f32) -> Option<Self> {
+        let face = Face::from_slice(font.data(), 0)?;
+        let scale = size / face.units_per_em() as f32;
+        Some(Shaper { face, scale })
+    }
+
+    // clusters of the returned glyphs are byte offsets into the given text
+    fn shape(&self, text: &str, rtl: bool) -> Vec<ShapedGlyph> {
+        let mut buffer = UnicodeBuffer::new();
+        buffer.push_str(text);
+        buffer.guess_segment_properties();
+        buffer.set_direction(if rtl { Direction::RightToLeft } else { Direction::LeftToRight });
+
+        let output = rustybuzz::shape(&self.face, &[], buffer);
+        output.glyph_infos()
+            .iter()
+            .zip(output.glyph_positions())
+            .map(|(info, position)| ShapedGlyph {
+                glyph: GlyphId(info.glyph_id as u16),
+                cluster: info.cluster as usize,
+                advance: position.x_advance as f32 * self.scale,
+                offset: [position.x_offset as f32 * self.scale, -position.y_offset as f32 * self.scale]
+            })
+            .collect()
+    }
+
+    fn width(&self, text: &str, rtl: bool) -> f32 {
+        self.shape(text, rtl).iter().map(|g| g.advance).sum()
+    }
+}
+
+// the width of any part of a range from a single shaping pass, every glyph's advance counts
+// at the byte its cluster starts on so measuring a candidate line is a subtraction
+struct Advances {
+    start: usize,
+    // prefix[i] is the advance of every cluster starting before byte start + i
+    prefix: Vec<f32>
+}
+
+impl Advances {
+    fn new(shaper: &Shaper, text: &str, range: Range<usize>, rtl: bool) -> Self {
+        let mut prefix = vec![0.0; range.len() + 1];
+        for glyph in shaper.shape(&text[range.clone()], rtl) {
+            prefix[glyph.cluster + 1] += glyph.advance;
+        }
+        for i in 1..prefix.len() {
+            prefix[i] += prefix[i - 1];
+        }
+
+        Advances { start: range.start, prefix }
+    }
+
+    fn width(&self, range: Range<usize>) -> f32 {
+        self.prefix[range.end - self.start] - self.prefix[range.start - self.start]
+    }
+}
+
+fn trim_end(text: &str, range: Range<usize>) -> Range<usize> {
+    let trimmed = text[range.clone()].trim_end();
+    range.start..range.start + trimmed.len()
+}
+
+fn previous_char_boundary(text: &str, index: usize) -> usize {
+    text[..index].char_indices().next_back().map(|(i, _)| i).unwrap_or(0)
+}
+
+fn next_char_boundary(text: &str, index: usize) -> usize {
+    text[index..].chars().next().map(|c| index + c.len_utf8()).unwrap_or(index)
+}
+
+// splits a paragraph into lines using the unicode line breaking opportunities,
+// falling back to breaking between characters when a single word is wider than the line
+fn break_lines(
+    shaper: &Shaper,
+    text: &str,
+    paragraph: Range<usize>,
+    rtl: bool,
+    max_width: Option<f32>
+) -> Vec<Range<usize>> {
+    let advances = max_width.map(|_| Advances::new(shaper, text, paragraph.clone(), rtl));
+    let fits = |range: Range<usize>| match (max_width, &advances) {
+        (Some(max_width), Some(advances)) => advances.width(trim_end(text, range)) <= max_width,
+        _ => true
+    };
+
+    let mut lines = vec![];
+    let mut line_start = paragraph.start;
+    let mut last_break = paragraph.start;
+
+    for (offset, opportunity) in linebreaks(&text[paragraph.clone()]) {
+        let position = paragraph.start + offset;
+
+        if !fits(line_start..position) {
+            if last_break > line_start {
+                lines.push(line_start..last_break);
+                line_start = last_break;
+            }
+
+            while !fits(line_start..position) {
+                let mut end = next_char_boundary(text, line_start);
+                while end < position && fits(line_start..next_char_boundary(text, end)) {
+                    end = next_char_boundary(text, end);
+                }
+
+                lines.push(line_start..end);
+                line_start = end;
+            }
+        }
+
+        if opportunity == BreakOpportunity::Mandatory && position < paragraph.end {
+            lines.push(line_start..position);
+            line_start = position;
+        }
+        last_break = position;
+    }
+
+    if line_start < paragraph.end || lines.is_empty() {
+        lines.push(line_start..paragraph.end);
+    }
+    lines
+}
+
+struct PendingLine<'a> {
+    text: Range<usize>,
+    paragraph: &'a ParagraphInfo,
+    ellipsis: bool
+}
+
+pub fn layout_text(font: &Font, text: &str, style: &TextStyle, screen_size: PhysicalSize<u32>) -> TextLayout {
+    let Some(shaper) = Shaper::new(font, style.size) else {
+        return TextLayout::default();
+    };
+
+    let max_width = style.max_width.as_ref().map(|m| get_measurement_pixels(m, screen_size.width));
+    let metrics = font.line_metrics(style.size);
+    let line_height = style.line_height.map(|h| h * style.size).unwrap_or(metrics.height());
+    // extra leading is split evenly above and below the glyphs, like css does
+    let half_leading = (line_height - (metrics.ascent - metrics.descent)) / 2.0;
+
+    if text.is_empty() {
+        return TextLayout {
+            height: line_height,
+            ..TextLayout::default()
+        };
+    }
+
+    let bidi = BidiInfo::new(text, None);
+    let mut pending = vec![];
+    for paragraph in &bidi.paragraphs {
+        let content = paragraph.range.start..paragraph.range.start + text[paragraph.range.clone()]
+            .trim_end_matches(['\n', '\r', '\u{2029}'])
+            .len();
+
+        let wrap_width = if style.wrap { max_width } else { None };
+        for line in break_lines(&shaper, text, content, paragraph.level.is_rtl(), wrap_width) {
+            pending.push(PendingLine { text: line, paragraph, ellipsis: false });
+        }
+    }
+
+    // a trailing paragraph separator starts one more, empty line
+    if let (Some(last), true) = (bidi.paragraphs.last(), text.ends_with(['\n', '\r', '\u{2029}'])) {
+        pending.push(PendingLine { text: text.len()..text.len(), paragraph: last, ellipsis: false });
+    }
+
+    if let Some(max_lines) = style.max_lines {
+        if pending.len() > max_lines {
+            pending.truncate(max_lines.max(1));
+            if let Some(last) = pending.last_mut() {
+                last.ellipsis = style.ellipsis;
+            }
+        }
+    }
+
+    if style.ellipsis {
+        if let Some(max_width) = max_width {
+            let ellipsis_width = [shaper.width(ELLIPSIS, false), shaper.width(ELLIPSIS, true)];
+            for line in pending.iter_mut() {
+                let rtl = line.paragraph.level.is_rtl();
+                let ellipsis_width = ellipsis_width[rtl as usize];
+                let visible = trim_end(text, line.text.clone());
+                let advances = Advances::new(&shaper, text, visible.clone(), rtl);
+
+                if !line.ellipsis && advances.width(visible.clone()) <= max_width {
+                    continue;
+                }
+
+                let mut end = visible.end;
+                while end > visible.start && advances.width(visible.start..end) + ellipsis_width > max_width {
+                    end = previous_char_boundary(text, end);
+                }
+                line.text = line.text.start..end;
+                line.ellipsis = true;
+            }
+        }
+    }
+
+    let mut layout = TextLayout::default();
+    for (index, line) in pending.iter().enumerate() {
+        let rtl = line.paragraph.level.is_rtl();
+        let top = index as f32 * line_height;
+        let baseline = top + half_leading + metrics.ascent;
+        let first_glyph = layout.glyphs.len();
+        let mut x = 0.0;
+
+        let place = |layout: &mut TextLayout, glyphs: Vec<ShapedGlyph>, base: usize, rtl: bool, x: &mut f32| {
+            for glyph in glyphs {
+                layout.glyphs.push(PositionedGlyph {
+                    glyph: glyph.glyph,
+                    position: [*x + glyph.offset[0], baseline + glyph.offset[1]],
+                    advance: glyph.advance,
+                    cluster: base + glyph.cluster,
+                    rtl
+                });
+                *x += glyph.advance;
+            }
+        };
+
+        // ellipses go on the visual end of the line, which is the left side of rtl paragraphs
+        if line.ellipsis && rtl {
+            place(&mut layout, shaper.shape(ELLIPSIS, true), line.text.end, true, &mut x);
+        }
+
+        let visible = trim_end(text, line.text.clone());
+        if !visible.is_empty() {
+            let (levels, runs) = bidi.visual_runs(line.paragraph, visible);
+            for run in runs {
+                let run_rtl = levels[run.start].is_rtl();
+                place(&mut layout, shaper.shape(&text[run.clone()], run_rtl), run.start, run_rtl, &mut x);
+            }
+        }
+
+        if line.ellipsis && !rtl {
+            place(&mut layout, shaper.shape(ELLIPSIS, false), line.text.end, false, &mut x);
+        }
+
+        layout.lines.push(TextLine {
+            text: line.text.clone(),
+            glyphs: first_glyph..layout.glyphs.len(),
+            x: 0.0,
+            width: x,
+            baseline,
+            top,
+            height: line_height,
+            rtl
+        });
+        layout.width = layout.width.max(x);
+    }
+
+    let container_width = max_width.unwrap_or(layout.width);
+    for line in layout.lines.iter_mut() {
+        let factor = match (style.align, line.rtl) {
+            (TextAlign::Left, _) | (TextAlign::Start, false) | (TextAlign::End, true) => 0.0,
+            (TextAlign::Right, _) | (TextAlign::Start, true) | (TextAlign::End, false) => 1.0,
+            (TextAlign::Center, _) => 0.5
+        };
+
+        line.x = (container_width - line.width) * factor;
+        for glyph in &mut layout.glyphs[line.glyphs.clone()] {
+            glyph.position[0] += line.x;
+        }
+    }
+
+    layout.height = layout.lines.len() as f32 * line_height;
+    layout
+}
+
+pub fn measure_text(font: &Font, text: &str, style: &TextStyle, screen_size: PhysicalSize<u32>) -> [f32; 2] {
+    layout_text(font, text, style, screen_size).size()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::text::font::test_font as font;
+
+    fn lines(text: &str, style: &TextStyle) -> Vec<String> {
+        let layout = layout_text(&font(), text, style, PhysicalSize::new(1000, 1000));
+        layout.lines.iter().map(|l| text[l.text.clone()].to_string()).collect()
+    }
+
+    fn wrapped(width: f32) -> TextStyle {
+        TextStyle { max_width: Some(Measurement::Pixels(width)), ..TextStyle::new(16.0) }
+    }
+
+    #[test]
+    fn words_wrap_at_spaces() {
+        let width = measure_text(&font(), "hello world", &TextStyle::new(16.0), PhysicalSize::new(1000, 1000))[0];
+        assert_eq!(lines("hello world hello world", &wrapped(width + 1.0)), vec!["hello world ", "hello world"]);
+    }
+
+    #[test]
+    fn newlines_always_break() {
+        assert_eq!(lines("a\nb\n", &TextStyle::new(16.0)), vec!["a", "b", ""]);
+    }
+
+    #[test]
+    fn long_words_break_between_characters() {
+        let text = "x".repeat(50);
+        let lines = lines(&text, &wrapped(40.0));
+        assert!(lines.len() > 1);
+        assert_eq!(lines.concat(), text);
+
+        let font = font();
+        for line in &lines {
+            assert!(measure_text(&font, line, &TextStyle::new(16.0), PhysicalSize::new(1000, 1000))[0] <= 40.0);
+        }
+    }
+
+    #[test]
+    fn ellipsis_fits_the_width() {
+        let style = TextStyle { wrap: false, ellipsis: true, ..wrapped(60.0) };
+        let layout = layout_text(&font(), "a long line that does not fit", &style, PhysicalSize::new(1000, 1000));
+        assert_eq!(layout.lines.len(), 1);
+        assert!(layout.width <= 60.0);
+        assert!(layout.lines[0].text.end < 10);
+    }
+
+    #[test]
+    fn advances_measure_any_part_of_a_run() {
+        let font = font();
+        let shaper = Shaper::new(&font, 16.0).unwrap();
+        let text = "hello world";
+        let advances = Advances::new(&shaper, text, 0..text.len(), false);
+        assert!((advances.width(0..text.len()) - shaper.width(text, false)).abs() < 0.01);
+        assert!((advances.width(6..11) - shaper.width("world", false)).abs() < 0.5);
+    }
+}
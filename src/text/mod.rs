@@ -0,0 +1,16 @@
+pub mod atlas;
+pub mod font;
+pub mod layout;
+
+use ab_glyph::GlyphId;
+
+#[derive(Copy, Clone, Debug)]
+pub struct PositionedGlyph {
+    pub glyph: GlyphId,
+    // pen position on the baseline, relative to the top left corner of the text
+    pub position: [f32; 2],
+    pub advance: f32,
+    // byte offset of the source text the glyph was shaped from
+    pub cluster: usize,
+    pub rtl: bool
+}
@@ -0,0 +1,113 @@
+use winit::event::KeyEvent;
+use winit::keyboard::ModifiersState;
+use crate::designer::Designer;
+use crate::graphics::path::PathError;
+use crate::graphics::sdf::Border;
+use crate::render::wgpu::WgpuRenderer;
+use crate::scene::Rect;
+use crate::text::font::Font;
+use crate::text::layout::measure_text;
+use crate::widgets::theme::{Theme, WidgetKind};
+use crate::widgets::{is_activation_key, ClickHandler, Drawn, Widget, WidgetBase};
+
+pub struct Button {
+    base: WidgetBase,
+    font: Font,
+    label: String,
+    on_click: Option<ClickHandler>
+}
+
+impl Button {
+    pub fn new(font: Font, label: &str, bounds: Rect, theme: Theme) -> Self {
+        Button {
+            base: WidgetBase::new(bounds, theme),
+            font,
+            label: label.to_string(),
+            on_click: None
+        }
+    }
+
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    pub fn set_label(&mut self, label: &str) {
+        self.label = label.to_string();
+        self.base.mark_dirty();
+    }
+
+    pub fn on_click(&mut self, handler: impl FnMut() + Send + 'static) {
+        self.on_click = Some(Box::new(handler));
+    }
+
+    pub fn click(&mut self) {
+        if self.base.state.disabled {
+            return;
+        }
+        if let Some(handler) = &mut self.on_click {
+            handler();
+        }
+    }
+}
+
+impl Widget for Button {
+    fn base(&self) -> &WidgetBase {
+        &self.base
+    }
+
+    fn base_mut(&mut self) -> &mut WidgetBase {
+        &mut self.base
+    }
+
+    fn handle_key(&mut self, event: &KeyEvent, _modifiers: ModifiersState) -> bool {
+        if self.base.state.disabled || !is_activation_key(event) {
+            return false;
+        }
+        self.click();
+        true
+    }
+
+    fn handle_mouse_down(&mut self, x: f32, y: f32) -> bool {
+        if self.base.state.disabled || !self.base.bounds.contains(x, y) {
+            return false;
+        }
+        self.base.set_pressed(true);
+        self.base.take_focus();
+        true
+    }
+
+    fn handle_mouse_move(&mut self, x: f32, y: f32) -> bool {
+        self.base.set_hovered(self.base.bounds.contains(x, y))
+    }
+
+    // a press only clicks when it is released over the button
+    fn handle_mouse_up(&mut self, x: f32, y: f32) -> bool {
+        if !self.base.state.pressed {
+            return false;
+        }
+        self.base.set_pressed(false);
+        if self.base.bounds.contains(x, y) {
+            self.click();
+        }
+        true
+    }
+
+    fn draw_content(&mut self, designer: &Designer, renderer: &mut WgpuRenderer, drawn: &mut Drawn) -> Result<(), PathError> {
+        let style = self.base.style(WidgetKind::Button);
+        let bounds = self.base.bounds;
+        drawn.rounded_rect(
+            designer,
+            renderer,
+            bounds,
+            style.corner_radius,
+            style.background,
+            Some(Border::new(style.border_width, style.border))
+        );
+
+        let text_style = self.base.theme().text.clone();
+        let [width, _] = measure_text(&self.font, &self.label, &text_style, renderer.size);
+        let x = bounds.x + (bounds.width - width).max(0.0) / 2.0;
+        drawn.text(renderer, &self.font, &self.label, &text_style, Rect::new(x, bounds.y, bounds.right() - x, bounds.height), style.foreground);
+        Ok(())
+    }
+}
@@ -0,0 +1,141 @@
+use winit::event::KeyEvent;
+use winit::keyboard::ModifiersState;
+use crate::designer::Designer;
+use crate::graphics::path::{LineCap, LineJoin, Path, PathError, StrokeStyle};
+use crate::graphics::sdf::Border;
+use crate::render::wgpu::WgpuRenderer;
+use crate::scene::Rect;
+use crate::text::font::Font;
+use crate::widgets::theme::{Theme, WidgetKind};
+use crate::widgets::{is_activation_key, Drawn, ToggleHandler, Widget, WidgetBase};
+
+pub struct Checkbox {
+    base: WidgetBase,
+    font: Font,
+    label: String,
+    checked: bool,
+    on_toggle: Option<ToggleHandler>
+}
+
+impl Checkbox {
+    pub fn new(font: Font, label: &str, bounds: Rect, theme: Theme) -> Self {
+        Checkbox {
+            base: WidgetBase::new(bounds, theme),
+            font,
+            label: label.to_string(),
+            checked: false,
+            on_toggle: None
+        }
+    }
+
+    pub fn is_checked(&self) -> bool {
+        self.checked
+    }
+
+    // changes the state without calling the handler
+    pub fn set_checked(&mut self, checked: bool) {
+        if self.checked != checked {
+            self.checked = checked;
+            self.base.mark_dirty();
+        }
+    }
+
+    pub fn on_toggle(&mut self, handler: impl FnMut(bool) + Send + 'static) {
+        self.on_toggle = Some(Box::new(handler));
+    }
+
+    pub fn toggle(&mut self) {
+        if self.base.state.disabled {
+            return;
+        }
+        self.set_checked(!self.checked);
+        if let Some(handler) = &mut self.on_toggle {
+            handler(self.checked);
+        }
+    }
+
+    // the box is as tall as the text and sits at the left of the bounds
+    fn box_bounds(&self) -> Rect {
+        let bounds = self.base.bounds;
+        let size = (self.base.theme().text.size * 1.1).min(bounds.height);
+        Rect::new(bounds.x, bounds.y + (bounds.height - size) / 2.0, size, size)
+    }
+}
+
+impl Widget for Checkbox {
+    fn base(&self) -> &WidgetBase {
+        &self.base
+    }
+
+    fn base_mut(&mut self) -> &mut WidgetBase {
+        &mut self.base
+    }
+
+    fn handle_key(&mut self, event: &KeyEvent, _modifiers: ModifiersState) -> bool {
+        if self.base.state.disabled || !is_activation_key(event) {
+            return false;
+        }
+        self.toggle();
+        true
+    }
+
+    fn handle_mouse_down(&mut self, x: f32, y: f32) -> bool {
+        if self.base.state.disabled || !self.base.bounds.contains(x, y) {
+            return false;
+        }
+        self.base.set_pressed(true);
+        self.base.take_focus();
+        true
+    }
+
+    fn handle_mouse_move(&mut self, x: f32, y: f32) -> bool {
+        self.base.set_hovered(self.base.bounds.contains(x, y))
+    }
+
+    fn handle_mouse_up(&mut self, x: f32, y: f32) -> bool {
+        if !self.base.state.pressed {
+            return false;
+        }
+        self.base.set_pressed(false);
+        if self.base.bounds.contains(x, y) {
+            self.toggle();
+        }
+        true
+    }
+
+    fn draw_content(&mut self, designer: &Designer, renderer: &mut WgpuRenderer, drawn: &mut Drawn) -> Result<(), PathError> {
+        let style = self.base.style(WidgetKind::Checkbox);
+        let bounds = self.base.bounds;
+        let check_box = self.box_bounds();
+        let radius = style.corner_radius.min(check_box.width / 4.0);
+
+        if self.checked {
+            drawn.rounded_rect(designer, renderer, check_box, radius, style.accent, None);
+
+            // the check mark runs through points placed relative to the box
+            let point = |x: f32, y: f32| [check_box.x + check_box.width * x, check_box.y + check_box.height * y];
+            let [start, middle, end] = [point(0.24, 0.52), point(0.42, 0.7), point(0.76, 0.32)];
+            let path = Path::new()
+                .move_to(start[0], start[1])
+                .line_to(middle[0], middle[1])
+                .line_to(end[0], end[1]);
+            let mut stroke = StrokeStyle::new((check_box.width * 0.12).max(1.5));
+            stroke.cap = LineCap::Round;
+            stroke.join = LineJoin::Round;
+            drawn.instances.push(designer.create_path_stroke(renderer, &path, &stroke, style.on_accent)?);
+        } else {
+            drawn.rounded_rect(
+                designer,
+                renderer,
+                check_box,
+                radius,
+                style.background,
+                Some(Border::new(style.border_width.max(1.0), style.border))
+            );
+        }
+
+        let x = check_box.right() + self.base.theme().padding;
+        drawn.text(renderer, &self.font, &self.label, &self.base.theme().text, Rect::new(x, bounds.y, bounds.right() - x, bounds.height), style.foreground);
+        Ok(())
+    }
+}
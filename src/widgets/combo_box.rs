@@ -0,0 +1,398 @@
+use winit::event::{ElementState, Ime, KeyEvent, MouseScrollDelta};
+use winit::keyboard::{Key, ModifiersState, NamedKey};
+use crate::designer::Designer;
+use crate::graphics::path::PathError;
+use crate::graphics::sdf::Border;
+use crate::input::clipboard::SharedClipboard;
+use crate::render::wgpu::WgpuRenderer;
+use crate::scene::Rect;
+use crate::text::font::Font;
+use crate::widgets::popup::{draw_chevron, place_below, PanelRow, PanelView, Popup, PopupHandle};
+use crate::widgets::radio::SelectHandler;
+use crate::widgets::text_input::{ChangeHandler, TextInput, TextInputStyle};
+use crate::widgets::theme::{Theme, WidgetKind};
+use crate::widgets::{Drawn, Widget, WidgetBase};
+
+// a text field whose list shows the options containing the typed text, any text can be entered;
+// register it with a popup layer so the list can open
+pub struct ComboBox {
+    base: WidgetBase,
+    font: Font,
+    input: TextInput,
+    options: Vec<String>,
+    // the options shown in the list, in the order they are given
+    matches: Vec<usize>,
+    panel: PanelView,
+    popup: Option<PopupHandle>,
+    on_change: Option<ChangeHandler>,
+    on_select: Option<SelectHandler>
+}
+
+impl ComboBox {
+    pub fn new(font: Font, options: Vec<String>, bounds: Rect, theme: Theme, clipboard: SharedClipboard) -> Self {
+        let input = TextInput::new(font.clone(), input_style(&theme, bounds), input_bounds(bounds, &theme), false, clipboard);
+        ComboBox {
+            base: WidgetBase::new(bounds, theme),
+            font,
+            input,
+            options,
+            matches: vec![],
+            panel: PanelView::new(),
+            popup: None,
+            on_change: None,
+            on_select: None
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        self.input.text()
+    }
+
+    // replaces the text without calling the handlers
+    pub fn set_text(&mut self, text: &str) {
+        self.input.set_text(text);
+        self.close();
+    }
+
+    pub fn options(&self) -> &[String] {
+        &self.options
+    }
+
+    pub fn set_options(&mut self, options: Vec<String>) {
+        self.close();
+        self.options = options;
+    }
+
+    // indices of the options shown in the list
+    pub fn matches(&self) -> &[usize] {
+        &self.matches
+    }
+
+    // called whenever the text changes, by typing or by choosing an option
+    pub fn on_change(&mut self, handler: impl FnMut(&str) + Send + 'static) {
+        self.on_change = Some(Box::new(handler));
+    }
+
+    // called with the index of an option chosen from the list
+    pub fn on_select(&mut self, handler: impl FnMut(usize) + Send + 'static) {
+        self.on_select = Some(Box::new(handler));
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.popup.as_ref().is_some_and(|popup| popup.is_open())
+    }
+
+    // shows every option, not only the ones matching the text, false when there is nothing to
+    // choose or the combo box was never registered with a popup layer
+    pub fn open(&mut self) -> bool {
+        self.matches = (0..self.options.len()).collect();
+        self.show_matches(false)
+    }
+
+    pub fn close(&mut self) {
+        if let Some(popup) = &self.popup {
+            popup.close();
+        }
+    }
+
+    // case insensitive, an option matches when it contains the text anywhere
+    fn filter(&mut self) {
+        let query = self.input.text().to_lowercase();
+        self.matches = (0..self.options.len())
+            .filter(|&index| self.options[index].to_lowercase().contains(&query))
+            .collect();
+    }
+
+    fn show_matches(&mut self, highlight_first: bool) -> bool {
+        if self.base.state.disabled || self.matches.is_empty() {
+            self.close();
+            return false;
+        }
+        let Some(popup) = &self.popup else {
+            return false;
+        };
+
+        let theme = self.base.theme().clone();
+        self.panel.set_rows(self.matches.iter().map(|&index| PanelRow::new(&self.options[index])).collect());
+        let [width, height] = self.panel.measure(&self.font, &theme, popup.window_size());
+        self.panel.bounds = place_below(self.base.bounds, [width.max(self.base.bounds.width), height], popup.window());
+        if highlight_first {
+            self.panel.highlight_edge(false, &theme);
+        }
+        popup.open();
+        true
+    }
+
+    fn text_edited(&mut self, before: &str) {
+        if self.input.text() == before {
+            return;
+        }
+        if self.input.text().is_empty() {
+            self.close();
+        } else {
+            self.filter();
+            self.show_matches(true);
+        }
+        self.changed();
+    }
+
+    fn changed(&mut self) {
+        self.base.mark_dirty();
+        if let Some(handler) = &mut self.on_change {
+            handler(self.input.text());
+        }
+    }
+
+    // row is a row of the list, which only shows the matches
+    fn choose(&mut self, row: usize) {
+        let Some(&index) = self.matches.get(row) else {
+            return;
+        };
+        self.close();
+        let changed = self.input.text() != self.options[index];
+        if changed {
+            self.input.set_text(&self.options[index]);
+        }
+        if let Some(handler) = &mut self.on_select {
+            handler(index);
+        }
+        if changed {
+            self.changed();
+        }
+    }
+
+    fn chevron_bounds(&self) -> Rect {
+        let bounds = self.base.bounds;
+        Rect::new(bounds.right() - bounds.height, bounds.y, bounds.height, bounds.height)
+    }
+}
+
+// the text sits between the rounded corners and the chevron, vertically centered by its padding
+fn input_bounds(bounds: Rect, theme: &Theme) -> Rect {
+    let inset = theme.corner_radius.max(theme.border_width);
+    Rect::new(
+        bounds.x + inset,
+        bounds.y + theme.border_width,
+        (bounds.width - bounds.height - inset).max(0.0),
+        (bounds.height - theme.border_width * 2.0).max(0.0)
+    )
+}
+
+fn input_style(theme: &Theme, bounds: Rect) -> TextInputStyle {
+    let line_height = theme.text.size * 1.25;
+    TextInputStyle {
+        text: theme.text.clone(),
+        text_color: theme.foreground,
+        background_color: theme.background,
+        selection_color: theme.accent.lerp(theme.background, 0.7),
+        caret_color: theme.foreground,
+        caret_width: 1.0,
+        padding: ((bounds.height - theme.border_width * 2.0 - line_height) / 2.0).max(2.0)
+    }
+}
+
+impl Widget for ComboBox {
+    fn base(&self) -> &WidgetBase {
+        &self.base
+    }
+
+    fn base_mut(&mut self) -> &mut WidgetBase {
+        &mut self.base
+    }
+
+    // the popup layer gets navigation keys first while the list is open, the rest edits the text
+    fn handle_key(&mut self, event: &KeyEvent, modifiers: ModifiersState) -> bool {
+        if self.base.state.disabled {
+            return false;
+        }
+        if event.state == ElementState::Pressed && event.logical_key == Key::Named(NamedKey::ArrowDown) && !self.is_open() {
+            self.filter();
+            if self.input.text().is_empty() || self.matches.is_empty() {
+                self.open();
+            } else {
+                self.show_matches(true);
+            }
+            return true;
+        }
+
+        let before = self.input.text().to_string();
+        let handled = self.input.handle_key(event, modifiers);
+        self.text_edited(&before);
+        handled
+    }
+
+    fn accepts_ime(&self) -> bool {
+        true
+    }
+
+    fn handle_ime(&mut self, ime: &Ime) -> bool {
+        let before = self.input.text().to_string();
+        let handled = self.input.handle_ime(ime);
+        self.text_edited(&before);
+        handled
+    }
+
+    fn handle_mouse_down(&mut self, x: f32, y: f32) -> bool {
+        if self.base.state.disabled || !self.base.bounds.contains(x, y) {
+            return false;
+        }
+        self.base.take_focus();
+        if self.chevron_bounds().contains(x, y) {
+            self.base.set_pressed(true);
+            self.open();
+        } else {
+            self.input.select_at(x, y, false);
+        }
+        true
+    }
+
+    fn handle_mouse_move(&mut self, x: f32, y: f32) -> bool {
+        let hovered = self.base.set_hovered(self.base.bounds.contains(x, y));
+        hovered | self.input.handle_mouse_move(x, y)
+    }
+
+    fn handle_mouse_up(&mut self, x: f32, y: f32) -> bool {
+        self.base.set_pressed(false);
+        self.input.handle_mouse_up(x, y)
+    }
+
+    fn draw_content(&mut self, designer: &Designer, renderer: &mut WgpuRenderer, drawn: &mut Drawn) -> Result<(), PathError> {
+        let style = self.base.style(WidgetKind::Button);
+        let theme = self.base.theme().clone();
+        let bounds = self.base.bounds;
+        drawn.rounded_rect(designer, renderer, bounds, style.corner_radius, theme.background, Some(Border::new(style.border_width, style.border)));
+
+        let chevron = self.chevron_bounds();
+        if self.base.state.pressed || self.base.state.hovered {
+            let area = Rect::new(chevron.x, chevron.y + theme.border_width, chevron.width - theme.border_width, chevron.height - theme.border_width * 2.0);
+            drawn.rounded_rect(designer, renderer, area, (style.corner_radius - theme.border_width).max(0.0), style.background, None);
+        }
+        draw_chevron(designer, renderer, drawn, chevron, style.foreground)?;
+
+        self.input.draw(designer, renderer)?;
+        Ok(())
+    }
+
+    fn clear(&mut self, renderer: &mut WgpuRenderer) {
+        self.base.drawn.clear(renderer);
+        self.input.clear(renderer);
+        self.base.dirty = true;
+    }
+
+    fn set_bounds(&mut self, bounds: Rect) {
+        self.base.bounds = bounds;
+        self.base.dirty = true;
+        if let Some(node) = &self.base.node {
+            node.set_bounds(bounds);
+        }
+        self.input.set_bounds(input_bounds(bounds, self.base.theme()));
+        self.close();
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.base.dirty || self.input.is_dirty()
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.base.state.focused = focused;
+        self.base.dirty = true;
+        self.input.set_focused(focused);
+        if !focused {
+            self.base.state.pressed = false;
+            self.close();
+        }
+    }
+
+    fn set_enabled(&mut self, enabled: bool) {
+        if !enabled {
+            self.close();
+            self.base.state.hovered = false;
+            self.base.state.pressed = false;
+        }
+        self.base.state.disabled = !enabled;
+        if let Some(node) = &self.base.node {
+            node.set_enabled(enabled);
+        }
+        self.base.mark_dirty();
+    }
+}
+
+impl Popup for ComboBox {
+    fn popup_contains(&self, x: f32, y: f32) -> bool {
+        self.panel.bounds.contains(x, y)
+    }
+
+    fn popup_mouse_down(&mut self, x: f32, y: f32) -> bool {
+        self.panel.bounds.contains(x, y)
+    }
+
+    fn popup_mouse_move(&mut self, x: f32, y: f32) -> bool {
+        let row = self.panel.row_at(x, y, self.base.theme());
+        if row.is_none() {
+            return false;
+        }
+        self.panel.highlight(row, self.base.theme())
+    }
+
+    fn popup_mouse_up(&mut self, x: f32, y: f32) -> bool {
+        match self.panel.row_at(x, y, self.base.theme()) {
+            Some(row) => {
+                self.choose(row);
+                true
+            }
+            None => false
+        }
+    }
+
+    // enter without a highlighted option and every other key go on to the text
+    fn popup_key(&mut self, event: &KeyEvent, _modifiers: ModifiersState) -> bool {
+        if event.state != ElementState::Pressed {
+            return false;
+        }
+        let theme = self.base.theme().clone();
+        match event.logical_key {
+            Key::Named(NamedKey::ArrowDown) => {
+                self.panel.move_highlight(true, &theme);
+            }
+            Key::Named(NamedKey::ArrowUp) => {
+                self.panel.move_highlight(false, &theme);
+            }
+            Key::Named(NamedKey::Enter) => match self.panel.highlighted {
+                Some(row) => self.choose(row),
+                None => return false
+            },
+            Key::Named(NamedKey::Escape) => self.close(),
+            Key::Named(NamedKey::Tab) => {
+                self.close();
+                return false;
+            }
+            _ => return false
+        }
+        true
+    }
+
+    fn popup_scroll(&mut self, delta: &MouseScrollDelta, _x: f32, _y: f32) -> bool {
+        self.panel.scroll(delta, self.base.theme())
+    }
+
+    fn draw_popup(&mut self, designer: &Designer, renderer: &mut WgpuRenderer) -> Result<(), PathError> {
+        let theme = self.base.theme().clone();
+        self.panel.draw(designer, renderer, &self.font, &theme)
+    }
+
+    fn clear_popup(&mut self, renderer: &mut WgpuRenderer) {
+        self.panel.clear(renderer);
+    }
+
+    fn is_popup_dirty(&self) -> bool {
+        self.panel.dirty
+    }
+
+    fn dismiss(&mut self) {
+        self.close();
+    }
+
+    fn set_popup_handle(&mut self, handle: PopupHandle) {
+        self.popup = Some(handle);
+    }
+}
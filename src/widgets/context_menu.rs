@@ -0,0 +1,360 @@
+use winit::event::{ElementState, KeyEvent, MouseScrollDelta};
+use winit::keyboard::{Key, ModifiersState, NamedKey};
+use crate::designer::Designer;
+use crate::graphics::path::PathError;
+use crate::render::wgpu::WgpuRenderer;
+use crate::scene::Rect;
+use crate::text::font::Font;
+use crate::widgets::popup::{place_below, place_beside, PanelRow, PanelView, Popup, PopupHandle};
+use crate::widgets::theme::Theme;
+
+// called with the index of the chosen item in each menu level, the root first
+pub type ActivateHandler = Box<dyn FnMut(&[usize]) + Send>;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MenuItem {
+    pub label: String,
+    // shown at the right, the menu does not bind it
+    pub shortcut: Option<String>,
+    pub enabled: bool,
+    // items of the submenu the item opens
+    pub children: Vec<MenuItem>,
+    pub separator: bool
+}
+
+impl MenuItem {
+    pub fn new(label: &str) -> Self {
+        MenuItem {
+            label: label.to_string(),
+            enabled: true,
+            ..MenuItem::default()
+        }
+    }
+
+    pub fn submenu(label: &str, children: Vec<MenuItem>) -> Self {
+        MenuItem {
+            children,
+            ..MenuItem::new(label)
+        }
+    }
+
+    pub fn separator() -> Self {
+        MenuItem {
+            separator: true,
+            ..MenuItem::default()
+        }
+    }
+
+    pub fn with_shortcut(mut self, shortcut: &str) -> Self {
+        self.shortcut = Some(shortcut.to_string());
+        self
+    }
+
+    pub fn disabled(mut self) -> Self {
+        self.enabled = false;
+        self
+    }
+
+    fn row(&self) -> PanelRow {
+        PanelRow {
+            label: self.label.clone(),
+            detail: self.shortcut.clone(),
+            enabled: self.enabled,
+            submenu: !self.children.is_empty(),
+            separator: self.separator
+        }
+    }
+}
+
+// an open level of the menu, the root first and then one per open submenu
+struct MenuLevel {
+    panel: PanelView,
+    // the items chosen to reach this level
+    path: Vec<usize>
+}
+
+// a menu opened at the pointer, usually on a right click, with submenus opening next to their item;
+// register it with a popup layer so it can open
+pub struct ContextMenu {
+    font: Font,
+    theme: Theme,
+    items: Vec<MenuItem>,
+    levels: Vec<MenuLevel>,
+    // closed levels, what they drew is removed on the next draw
+    closed: Vec<PanelView>,
+    popup: Option<PopupHandle>,
+    on_activate: Option<ActivateHandler>
+}
+
+impl ContextMenu {
+    pub fn new(font: Font, items: Vec<MenuItem>, theme: Theme) -> Self {
+        ContextMenu {
+            font,
+            theme,
+            items,
+            levels: vec![],
+            closed: vec![],
+            popup: None,
+            on_activate: None
+        }
+    }
+
+    pub fn items(&self) -> &[MenuItem] {
+        &self.items
+    }
+
+    pub fn set_items(&mut self, items: Vec<MenuItem>) {
+        self.close();
+        self.items = items;
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+        for level in &mut self.levels {
+            level.panel.dirty = true;
+        }
+    }
+
+    pub fn on_activate(&mut self, handler: impl FnMut(&[usize]) + Send + 'static) {
+        self.on_activate = Some(Box::new(handler));
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.popup.as_ref().is_some_and(|popup| popup.is_open())
+    }
+
+    // opens below and to the right of the point, flipped where that would leave the window,
+    // false when the menu has no items or was never registered with a popup layer
+    pub fn open_at(&mut self, x: f32, y: f32) -> bool {
+        if self.items.is_empty() {
+            return false;
+        }
+        let Some(popup) = &self.popup else {
+            return false;
+        };
+
+        self.closed.extend(self.levels.drain(..).map(|level| level.panel));
+        let mut panel = PanelView::new();
+        panel.set_rows(self.items.iter().map(MenuItem::row).collect());
+        let size = panel.measure(&self.font, &self.theme, popup.window_size());
+        panel.bounds = place_below(Rect::new(x, y, 0.0, 0.0), size, popup.window());
+        self.levels.push(MenuLevel { panel, path: vec![] });
+        popup.open();
+        true
+    }
+
+    pub fn close(&mut self) {
+        self.closed.extend(self.levels.drain(..).map(|level| level.panel));
+        if let Some(popup) = &self.popup {
+            popup.close();
+        }
+    }
+
+    fn items_at(&self, path: &[usize]) -> &[MenuItem] {
+        path.iter().fold(&self.items, |items, &index| &items[index].children)
+    }
+
+    fn item(&self, level: usize, row: usize) -> &MenuItem {
+        &self.items_at(&self.levels[level].path)[row]
+    }
+
+    // closes the submenus opened from the level
+    fn close_after(&mut self, level: usize) -> bool {
+        if self.levels.len() <= level + 1 {
+            return false;
+        }
+        self.closed.extend(self.levels.drain(level + 1..).map(|level| level.panel));
+        true
+    }
+
+    fn open_submenu(&mut self, level: usize, row: usize) -> bool {
+        let open = self.levels.get(level + 1).is_some_and(|next| next.path.last() == Some(&row));
+        if open || self.item(level, row).children.is_empty() || !self.item(level, row).enabled {
+            return false;
+        }
+        self.close_after(level);
+
+        let mut path = self.levels[level].path.clone();
+        path.push(row);
+        let mut panel = PanelView::new();
+        panel.set_rows(self.items_at(&path).iter().map(MenuItem::row).collect());
+
+        let parent = &self.levels[level].panel;
+        let Some(row_rect) = parent.row_rect(row, &self.theme) else {
+            return false;
+        };
+        let (size, window) = match &self.popup {
+            Some(popup) => (panel.measure(&self.font, &self.theme, popup.window_size()), popup.window()),
+            None => return false
+        };
+        let anchor = Rect::new(parent.bounds.x, row_rect.y, parent.bounds.width, row_rect.height);
+        panel.bounds = place_beside(anchor, size, window);
+        self.levels.push(MenuLevel { panel, path });
+        true
+    }
+
+    // chooses the row, or opens its submenu with the first item highlighted like keyboard navigation does
+    fn activate(&mut self, level: usize, row: usize) {
+        let item = self.item(level, row);
+        if !item.enabled || item.separator {
+            return;
+        }
+        if !item.children.is_empty() {
+            self.open_submenu(level, row);
+            let theme = self.theme.clone();
+            if let Some(submenu) = self.levels.get_mut(level + 1).filter(|submenu| submenu.panel.highlighted.is_none()) {
+                submenu.panel.highlight_edge(false, &theme);
+            }
+            return;
+        }
+
+        let mut path = self.levels[level].path.clone();
+        path.push(row);
+        self.close();
+        if let Some(handler) = &mut self.on_activate {
+            handler(&path);
+        }
+    }
+
+    // the deepest open level under the point
+    fn level_at(&self, x: f32, y: f32) -> Option<usize> {
+        self.levels.iter().rposition(|level| level.panel.bounds.contains(x, y))
+    }
+}
+
+impl Popup for ContextMenu {
+    fn popup_contains(&self, x: f32, y: f32) -> bool {
+        self.level_at(x, y).is_some()
+    }
+
+    fn popup_mouse_down(&mut self, x: f32, y: f32) -> bool {
+        self.level_at(x, y).is_some()
+    }
+
+    // hovering an item with a submenu opens it, hovering any other item closes deeper submenus
+    fn popup_mouse_move(&mut self, x: f32, y: f32) -> bool {
+        let theme = self.theme.clone();
+        let Some(level) = self.level_at(x, y) else {
+            // items whose submenu is open stay highlighted, only the deepest level has none open
+            return self.levels.last_mut().is_some_and(|last| last.panel.highlight(None, &theme));
+        };
+
+        let Some(row) = self.levels[level].panel.row_at(x, y, &theme) else {
+            return false;
+        };
+        let mut changed = self.levels[level].panel.highlight(Some(row), &theme);
+        if self.item(level, row).children.is_empty() {
+            changed |= self.close_after(level);
+        } else {
+            changed |= self.open_submenu(level, row);
+        }
+        changed
+    }
+
+    fn popup_mouse_up(&mut self, x: f32, y: f32) -> bool {
+        let Some(level) = self.level_at(x, y) else {
+            return false;
+        };
+        match self.levels[level].panel.row_at(x, y, &self.theme) {
+            Some(row) if !self.item(level, row).children.is_empty() => self.open_submenu(level, row),
+            Some(row) => {
+                self.activate(level, row);
+                true
+            }
+            None => false
+        }
+    }
+
+    // menus take every key while open, arrows move through the deepest open level
+    fn popup_key(&mut self, event: &KeyEvent, _modifiers: ModifiersState) -> bool {
+        if event.state != ElementState::Pressed || self.levels.is_empty() {
+            return true;
+        }
+        let theme = self.theme.clone();
+        let deepest = self.levels.len() - 1;
+
+        match &event.logical_key {
+            Key::Named(NamedKey::ArrowDown) => {
+                self.levels[deepest].panel.move_highlight(true, &theme);
+            }
+            Key::Named(NamedKey::ArrowUp) => {
+                self.levels[deepest].panel.move_highlight(false, &theme);
+            }
+            Key::Named(NamedKey::Home) => {
+                self.levels[deepest].panel.highlight_edge(false, &theme);
+            }
+            Key::Named(NamedKey::End) => {
+                self.levels[deepest].panel.highlight_edge(true, &theme);
+            }
+            Key::Named(NamedKey::ArrowRight) => {
+                let highlighted = self.levels[deepest].panel.highlighted;
+                if let Some(row) = highlighted.filter(|&row| !self.item(deepest, row).children.is_empty()) {
+                    self.activate(deepest, row);
+                }
+            }
+            Key::Named(NamedKey::ArrowLeft) if deepest > 0 => {
+                self.close_after(deepest - 1);
+            }
+            Key::Named(NamedKey::Escape) if deepest > 0 => {
+                self.close_after(deepest - 1);
+            }
+            Key::Named(NamedKey::Escape) => self.close(),
+            Key::Named(NamedKey::Enter | NamedKey::Space) => {
+                if let Some(row) = self.levels[deepest].panel.highlighted {
+                    self.activate(deepest, row);
+                }
+            }
+            _ => {}
+        }
+        true
+    }
+
+    fn popup_scroll(&mut self, delta: &MouseScrollDelta, x: f32, y: f32) -> bool {
+        let theme = self.theme.clone();
+        match self.level_at(x, y) {
+            Some(level) => {
+                let scrolled = self.levels[level].panel.scroll(delta, &theme);
+                if scrolled {
+                    self.close_after(level);
+                }
+                scrolled
+            }
+            None => false
+        }
+    }
+
+    // levels are drawn root first, so every submenu gets an overlay above the one it opened from
+    fn draw_popup(&mut self, designer: &Designer, renderer: &mut WgpuRenderer) -> Result<(), PathError> {
+        for mut panel in self.closed.drain(..) {
+            panel.clear(renderer);
+        }
+        let mut result = Ok(());
+        for level in &mut self.levels {
+            if level.panel.dirty {
+                result = result.and(level.panel.draw(designer, renderer, &self.font, &self.theme));
+            }
+        }
+        result
+    }
+
+    fn clear_popup(&mut self, renderer: &mut WgpuRenderer) {
+        for mut panel in self.closed.drain(..) {
+            panel.clear(renderer);
+        }
+        for level in &mut self.levels {
+            level.panel.clear(renderer);
+        }
+    }
+
+    fn is_popup_dirty(&self) -> bool {
+        !self.closed.is_empty() || self.levels.iter().any(|level| level.panel.dirty)
+    }
+
+    fn dismiss(&mut self) {
+        self.close();
+    }
+
+    fn set_popup_handle(&mut self, handle: PopupHandle) {
+        self.popup = Some(handle);
+    }
+}
@@ -0,0 +1,526 @@
+use winit::event::{ElementState, KeyEvent, MouseScrollDelta, TouchPhase};
+use winit::keyboard::{Key, ModifiersState, NamedKey};
+use crate::designer::Designer;
+use crate::graphics::clip::ClipId;
+use crate::graphics::color::Color;
+use crate::graphics::layer::{LayerId, LayerOptions};
+use crate::graphics::path::PathError;
+use crate::graphics::sdf::{Border, BoxShadow, RoundedRect};
+use crate::input::focus::FocusManager;
+use crate::render::wgpu::{SdfId, WgpuRenderer};
+use crate::scene::{NodeId, Rect};
+use crate::text::font::Font;
+use crate::widgets::popup::{Popup, PopupHandle};
+use crate::widgets::theme::Theme;
+use crate::widgets::{ClickHandler, Drawn, Widget};
+
+// covers the content below an open dialog
+const SCRIM_COLOR: Color = Color::new(0.0, 0.0, 0.0, 0.4);
+
+struct DialogChild {
+    widget: Box<dyn Widget>,
+    // relative to the top left of the content area
+    bounds: Rect,
+    focusable: bool
+}
+
+// overlays and clip the dialog draws under, made again when the window resizes
+struct DialogLayers {
+    window: Rect,
+    scrim: LayerId,
+    scrim_rect: SdfId,
+    panel: LayerId,
+    content: ClipId
+}
+
+// a window centered above everything else while open; the content below gets no input, the children
+// keep focus to themselves and escape closes it. register it with a popup layer so it can open
+pub struct Dialog {
+    font: Font,
+    theme: Theme,
+    title: String,
+    size: [f32; 2],
+    bounds: Rect,
+    children: Vec<DialogChild>,
+    focused: Option<usize>,
+    pressed: Option<usize>,
+    close_on_escape: bool,
+    layers: Option<DialogLayers>,
+    drawn: Drawn,
+    dirty: bool,
+    // the node focused below when the dialog opened, focused again when it closes
+    focus: Option<FocusManager>,
+    previous_focus: Option<NodeId>,
+    popup: Option<PopupHandle>,
+    on_close: Option<ClickHandler>
+}
+
+impl Dialog {
+    pub fn new(font: Font, title: &str, size: [f32; 2], theme: Theme) -> Self {
+        Dialog {
+            font,
+            theme,
+            title: title.to_string(),
+            size,
+            bounds: Rect::default(),
+            children: vec![],
+            focused: None,
+            pressed: None,
+            close_on_escape: true,
+            layers: None,
+            drawn: Drawn::default(),
+            dirty: true,
+            focus: None,
+            previous_focus: None,
+            popup: None,
+            on_close: None
+        }
+    }
+
+    // bounds are relative to the content area below the title, returns the index of the child
+    pub fn add(&mut self, widget: impl Widget + 'static, bounds: Rect, focusable: bool) -> usize {
+        let mut child = DialogChild { widget: Box::new(widget), bounds, focusable };
+        place(&mut child, self.content_bounds());
+        self.children.push(child);
+        self.children.len() - 1
+    }
+
+    pub fn child(&self, index: usize) -> Option<&dyn Widget> {
+        self.children.get(index).map(|child| child.widget.as_ref())
+    }
+
+    pub fn child_mut(&mut self, index: usize) -> Option<&mut (dyn Widget + 'static)> {
+        self.children.get_mut(index).map(|child| child.widget.as_mut())
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn set_title(&mut self, title: &str) {
+        self.title = title.to_string();
+        self.dirty = true;
+    }
+
+    pub fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    pub fn set_close_on_escape(&mut self, close_on_escape: bool) {
+        self.close_on_escape = close_on_escape;
+    }
+
+    // lets the dialog take keyboard focus away from the content below while it is open
+    pub fn set_focus_manager(&mut self, focus: FocusManager) {
+        self.focus = Some(focus);
+    }
+
+    pub fn on_close(&mut self, handler: impl FnMut() + Send + 'static) {
+        self.on_close = Some(Box::new(handler));
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.popup.as_ref().is_some_and(|popup| popup.is_open())
+    }
+
+    // false when the dialog was never registered with a popup layer and cannot be shown
+    pub fn open(&mut self) -> bool {
+        let Some(popup) = &self.popup else {
+            return false;
+        };
+        if popup.is_open() {
+            return true;
+        }
+        popup.open();
+
+        if let Some(focus) = &self.focus {
+            self.previous_focus = focus.focused();
+            if let Some(id) = self.previous_focus {
+                focus.blur(id);
+            }
+        }
+        self.layout();
+        let first = self.focus_order().first().copied();
+        self.focus_child(first);
+        true
+    }
+
+    pub fn close(&mut self) {
+        let Some(popup) = &self.popup else {
+            return;
+        };
+        if !popup.is_open() {
+            return;
+        }
+        popup.close();
+
+        self.focus_child(None);
+        if let (Some(focus), Some(id)) = (&self.focus, self.previous_focus.take()) {
+            focus.focus(id);
+        }
+        if let Some(handler) = &mut self.on_close {
+            handler();
+        }
+    }
+
+    pub fn focused_child(&self) -> Option<usize> {
+        self.focused
+    }
+
+    pub fn focus_child(&mut self, index: Option<usize>) {
+        if self.focused == index {
+            return;
+        }
+        if let Some(child) = self.focused.and_then(|focused| self.children.get_mut(focused)) {
+            child.widget.set_focused(false);
+        }
+        self.focused = index.filter(|&index| index < self.children.len());
+        if let Some(child) = self.focused.and_then(|focused| self.children.get_mut(focused)) {
+            child.widget.set_focused(true);
+        }
+    }
+
+    fn focus_order(&self) -> Vec<usize> {
+        (0..self.children.len())
+            .filter(|&index| self.children[index].focusable && !self.children[index].widget.state().disabled)
+            .collect()
+    }
+
+    // tab wraps around inside the dialog instead of leaving it
+    fn move_focus(&mut self, forward: bool) {
+        let order = self.focus_order();
+        if order.is_empty() {
+            return;
+        }
+        let current = self.focused.and_then(|focused| order.iter().position(|&index| index == focused));
+        let next = match (current, forward) {
+            (Some(i), true) => (i + 1) % order.len(),
+            (Some(i), false) => (i + order.len() - 1) % order.len(),
+            (None, true) => 0,
+            (None, false) => order.len() - 1
+        };
+        self.focus_child(Some(order[next]));
+    }
+
+    fn title_height(&self) -> f32 {
+        self.theme.text.size * 2.5
+    }
+
+    fn content_bounds(&self) -> Rect {
+        let title = self.title_height();
+        let padding = self.theme.padding * 2.0;
+        Rect::new(
+            self.bounds.x + padding,
+            self.bounds.y + title,
+            (self.bounds.width - padding * 2.0).max(0.0),
+            (self.bounds.height - title - padding).max(0.0)
+        )
+    }
+
+    // centers the dialog in the window and places the children inside it
+    fn layout(&mut self) {
+        let Some(window) = self.popup.as_ref().map(PopupHandle::window) else {
+            return;
+        };
+        let [width, height] = [self.size[0].min(window.width), self.size[1].min(window.height)];
+        let bounds = Rect::new(window.x + (window.width - width) / 2.0, window.y + (window.height - height) / 2.0, width, height);
+        if bounds == self.bounds {
+            return;
+        }
+        self.bounds = bounds;
+        self.dirty = true;
+
+        let content = self.content_bounds();
+        for child in &mut self.children {
+            place(child, content);
+        }
+    }
+
+    fn child_at(&self, x: f32, y: f32) -> Option<usize> {
+        if !self.content_bounds().contains(x, y) {
+            return None;
+        }
+        self.children.iter().rposition(|child| child.widget.bounds().contains(x, y))
+    }
+
+    fn window_changed(&self) -> bool {
+        match (&self.layers, &self.popup) {
+            (Some(layers), Some(popup)) => layers.window != popup.window(),
+            _ => false
+        }
+    }
+
+    fn remove_layers(&mut self, renderer: &mut WgpuRenderer) {
+        if let Some(layers) = self.layers.take() {
+            renderer.remove_sdf(layers.scrim_rect);
+            renderer.remove_layer(layers.scrim);
+            renderer.remove_layer(layers.panel);
+            renderer.remove_clip(layers.content);
+        }
+    }
+}
+
+fn place(child: &mut DialogChild, content: Rect) {
+    let bounds = child.bounds;
+    child.widget.set_bounds(Rect::new(content.x + bounds.x, content.y + bounds.y, bounds.width, bounds.height));
+}
+
+impl Popup for Dialog {
+    // the whole window, so nothing below an open dialog gets the pointer
+    fn popup_contains(&self, _x: f32, _y: f32) -> bool {
+        true
+    }
+
+    fn popup_mouse_down(&mut self, x: f32, y: f32) -> bool {
+        let Some(index) = self.child_at(x, y) else {
+            return true;
+        };
+        if self.children[index].focusable {
+            self.focus_child(Some(index));
+        }
+        if self.children[index].widget.handle_mouse_down(x, y) {
+            self.pressed = Some(index);
+        }
+        true
+    }
+
+    fn popup_mouse_move(&mut self, x: f32, y: f32) -> bool {
+        let mut changed = false;
+        for child in &mut self.children {
+            changed |= child.widget.handle_mouse_move(x, y);
+        }
+        changed
+    }
+
+    fn popup_mouse_up(&mut self, x: f32, y: f32) -> bool {
+        if let Some(child) = self.pressed.take().and_then(|index| self.children.get_mut(index)) {
+            child.widget.handle_mouse_up(x, y);
+        }
+        true
+    }
+
+    // the focused child gets keys first, nothing reaches the content below
+    fn popup_key(&mut self, event: &KeyEvent, modifiers: ModifiersState) -> bool {
+        if let Some(child) = self.focused.and_then(|focused| self.children.get_mut(focused)) {
+            if child.widget.handle_key(event, modifiers) {
+                return true;
+            }
+        }
+        if event.state != ElementState::Pressed {
+            return true;
+        }
+        match event.logical_key {
+            Key::Named(NamedKey::Tab) => self.move_focus(!modifiers.shift_key()),
+            Key::Named(NamedKey::Escape) if self.close_on_escape => self.close(),
+            _ => {}
+        }
+        true
+    }
+
+    fn popup_scroll(&mut self, delta: &MouseScrollDelta, x: f32, y: f32) -> bool {
+        if let Some(index) = self.child_at(x, y) {
+            self.children[index].widget.handle_scroll(delta, TouchPhase::Moved, x, y);
+        }
+        true
+    }
+
+    fn draw_popup(&mut self, designer: &Designer, renderer: &mut WgpuRenderer) -> Result<(), PathError> {
+        if self.window_changed() {
+            self.clear_popup(renderer);
+        }
+        self.layout();
+
+        let layers = match self.layers.take() {
+            Some(layers) => layers,
+            None => {
+                let window = self.popup.as_ref().map(PopupHandle::window).unwrap_or_default();
+                let scrim = renderer.add_overlay(LayerOptions::new(1.0));
+                let panel = renderer.add_overlay(LayerOptions::new(1.0));
+                renderer.enter_overlay(panel);
+                // children are drawn under the clip so they stay above the panel whatever slots they get
+                let content = renderer.push_rect_clip(self.content_bounds());
+                renderer.pop_clip();
+                renderer.exit_overlay();
+
+                renderer.enter_overlay(scrim);
+                let scrim_rect = designer.create_rounded_rect(renderer, &RoundedRect::new(window, 0.0, SCRIM_COLOR));
+                renderer.exit_overlay();
+                DialogLayers { window, scrim, scrim_rect, panel, content }
+            }
+        };
+
+        renderer.enter_overlay(layers.panel);
+        if self.dirty {
+            self.drawn.clear(renderer);
+            let theme = &self.theme;
+            let mut panel = RoundedRect::new(self.bounds, theme.corner_radius * 2.0, theme.background);
+            panel.border = Some(Border::new(theme.border_width, theme.border));
+            panel.shadow = Some(BoxShadow::new([0.0, 8.0], 24.0, 0.0, Color::rgba(0, 0, 0, 0.3)));
+            self.drawn.sdf.push(designer.create_rounded_rect(renderer, &panel));
+
+            let title = Rect::new(self.bounds.x + theme.padding * 2.0, self.bounds.y, self.bounds.width - theme.padding * 4.0, self.title_height());
+            let mut style = theme.text.clone();
+            style.size *= 1.125;
+            self.drawn.text(renderer, &self.font, &self.title, &style, title, theme.foreground);
+            self.dirty = false;
+        }
+
+        renderer.reenter_clip(layers.content);
+        let mut result = Ok(());
+        for child in &mut self.children {
+            if child.widget.is_dirty() {
+                result = result.and(child.widget.draw(designer, renderer));
+            }
+        }
+        renderer.pop_clip();
+        renderer.exit_overlay();
+        self.layers = Some(layers);
+        result
+    }
+
+    fn clear_popup(&mut self, renderer: &mut WgpuRenderer) {
+        self.drawn.clear(renderer);
+        for child in &mut self.children {
+            child.widget.clear(renderer);
+        }
+        self.remove_layers(renderer);
+        self.dirty = true;
+    }
+
+    fn is_popup_dirty(&self) -> bool {
+        self.dirty || self.layers.is_none() || self.window_changed() || self.children.iter().any(|child| child.widget.is_dirty())
+    }
+
+    // presses and scrolls never land outside a dialog, so only dismiss_all on the layer gets here
+    fn dismiss(&mut self) {
+        self.close();
+    }
+
+    fn set_popup_handle(&mut self, handle: PopupHandle) {
+        self.popup = Some(handle);
+        self.layout();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use winit::dpi::PhysicalSize;
+    use crate::text::font::test_font;
+    use crate::widgets::popup::PopupLayer;
+    use crate::widgets::WidgetBase;
+
+    struct Blank {
+        base: WidgetBase
+    }
+
+    impl Widget for Blank {
+        fn base(&self) -> &WidgetBase {
+            &self.base
+        }
+
+        fn base_mut(&mut self) -> &mut WidgetBase {
+            &mut self.base
+        }
+
+        fn handle_key(&mut self, _event: &KeyEvent, _modifiers: ModifiersState) -> bool {
+            false
+        }
+
+        fn handle_mouse_down(&mut self, _x: f32, _y: f32) -> bool {
+            false
+        }
+
+        fn handle_mouse_move(&mut self, _x: f32, _y: f32) -> bool {
+            false
+        }
+
+        fn handle_mouse_up(&mut self, _x: f32, _y: f32) -> bool {
+            false
+        }
+
+        fn draw_content(&mut self, _designer: &Designer, _renderer: &mut WgpuRenderer, _drawn: &mut Drawn) -> Result<(), PathError> {
+            Ok(())
+        }
+    }
+
+    fn blank() -> Blank {
+        Blank { base: WidgetBase::new(Rect::default(), Theme::default()) }
+    }
+
+    // a 200 by 100 dialog with a label and two focusable children in a 400 by 300 window
+    fn dialog() -> (PopupLayer, Arc<Mutex<Dialog>>) {
+        let layer = PopupLayer::new();
+        layer.set_window_size(PhysicalSize::new(400, 300));
+        let mut dialog = Dialog::new(test_font(), "Title", [200.0, 100.0], Theme::default());
+        dialog.add(blank(), Rect::new(0.0, 0.0, 40.0, 20.0), false);
+        dialog.add(blank(), Rect::new(50.0, 0.0, 40.0, 20.0), true);
+        dialog.add(blank(), Rect::new(100.0, 0.0, 40.0, 20.0), true);
+        let dialog = Arc::new(Mutex::new(dialog));
+        layer.register(&dialog);
+        (layer, dialog)
+    }
+
+    #[test]
+    fn opens_centered_with_the_first_focusable_child_focused() {
+        let (layer, dialog) = dialog();
+        let mut dialog = dialog.lock().unwrap();
+        assert!(dialog.open());
+
+        assert!(dialog.is_open() && layer.has_open());
+        assert_eq!(dialog.bounds(), Rect::new(100.0, 100.0, 200.0, 100.0));
+        assert_eq!(dialog.focused_child(), Some(1));
+        assert!(dialog.child(1).unwrap().state().focused);
+
+        let content = dialog.content_bounds();
+        assert_eq!(dialog.child(2).unwrap().bounds(), Rect::new(content.x + 100.0, content.y, 40.0, 20.0));
+    }
+
+    #[test]
+    fn presses_focus_the_child_below_and_never_reach_the_content() {
+        let (layer, dialog) = dialog();
+        dialog.lock().unwrap().open();
+        let child = dialog.lock().unwrap().child(2).unwrap().bounds();
+
+        assert!(layer.handle_mouse_down(child.x + 1.0, child.y + 1.0));
+        assert!(layer.handle_mouse_up(child.x + 1.0, child.y + 1.0));
+        assert_eq!(dialog.lock().unwrap().focused_child(), Some(2));
+
+        // outside the panel the press is taken but the dialog stays open
+        assert!(layer.handle_mouse_down(5.0, 5.0));
+        assert!(dialog.lock().unwrap().is_open());
+    }
+
+    #[test]
+    fn closing_unfocuses_the_children_and_restores_the_focus_below() {
+        let (_layer, dialog) = dialog();
+        let focus = FocusManager::new();
+        let below = focus.register(Rect::new(0.0, 0.0, 10.0, 10.0), 0);
+        below.focus();
+
+        let closed = Arc::new(Mutex::new(false));
+        let mut dialog = dialog.lock().unwrap();
+        dialog.set_focus_manager(focus.clone());
+        dialog.on_close({
+            let closed = Arc::clone(&closed);
+            move || *closed.lock().unwrap() = true
+        });
+
+        dialog.open();
+        assert_eq!(focus.focused(), None);
+
+        dialog.close();
+        assert_eq!(focus.focused(), Some(below.id()));
+        assert_eq!(dialog.focused_child(), None);
+        assert!(!dialog.child(1).unwrap().state().focused);
+        assert!(*closed.lock().unwrap());
+    }
+
+    #[test]
+    fn unregistered_dialogs_do_not_open() {
+        let mut dialog = Dialog::new(test_font(), "title", [200.0, 100.0], Theme::default());
+        assert!(!dialog.open());
+        assert!(!dialog.is_open());
+    }
+}
\ No newline at end of file
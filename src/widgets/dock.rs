@@ -0,0 +1,1164 @@
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use winit::event::{ElementState, KeyEvent, MouseScrollDelta, TouchPhase};
+use winit::keyboard::{Key, ModifiersState, NamedKey};
+use crate::designer::Designer;
+use crate::graphics::clip::ClipId;
+use crate::graphics::layer::{LayerId, LayerOptions};
+use crate::graphics::path::PathError;
+use crate::graphics::sdf::Border;
+use crate::render::wgpu::WgpuRenderer;
+use crate::scene::Rect;
+use crate::text::font::Font;
+use crate::text::layout::{measure_text, TextStyle};
+use crate::widgets::scroll_view::Axis;
+use crate::widgets::split_pane::{available_length, drag_divider, draw_divider, slice, split_lengths, split_rects, DIVIDER_SIZE};
+use crate::widgets::tabs::{draw_tab_strip, layout_tabs, strip_height, TabRect, TabStrip};
+use crate::widgets::theme::Theme;
+use crate::widgets::{ClickHandler, Drawn, Widget, WidgetBase};
+
+// smallest content area a group of tabs is squeezed to while there is room
+const MIN_PANEL_SIZE: f32 = 64.0;
+// how far a tab has to be dragged before it comes off its strip
+const DRAG_THRESHOLD: f32 = 6.0;
+// dropping this close to the edge of the dock puts the panel along the whole edge
+const ROOT_EDGE: f32 = 16.0;
+// the part of a group near each edge that splits it instead of adding a tab
+const EDGE_FRACTION: f32 = 0.25;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DockSide {
+    Center,
+    Left,
+    Right,
+    Top,
+    Bottom
+}
+
+impl DockSide {
+    fn axis(self) -> Axis {
+        match self {
+            DockSide::Left | DockSide::Right | DockSide::Center => Axis::Horizontal,
+            DockSide::Top | DockSide::Bottom => Axis::Vertical
+        }
+    }
+
+    fn is_before(self) -> bool {
+        matches!(self, DockSide::Left | DockSide::Top)
+    }
+
+    // the part of bounds a panel docked on this side would take
+    fn preview(self, bounds: Rect, fraction: f32) -> Rect {
+        let axis = self.axis();
+        let length = axis.length(&bounds);
+        match self {
+            DockSide::Center => bounds,
+            _ if self.is_before() => slice(axis, bounds, 0.0, length * fraction),
+            _ => slice(axis, bounds, length * (1.0 - fraction), length * fraction)
+        }
+    }
+}
+
+// how the panels are arranged, panels are named by the ids they were added with;
+// saved as toml, for example
+//
+// [root]
+// kind = "split"
+// axis = "Horizontal"
+// fractions = [0.25, 0.75]
+//
+// [[root.children]]
+// kind = "tabs"
+// panels = ["files"]
+// selected = 0
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum DockNode {
+    Split {
+        axis: Axis,
+        // one per child, adding up to one
+        fractions: Vec<f32>,
+        children: Vec<DockNode>
+    },
+    Tabs {
+        panels: Vec<String>,
+        #[serde(default)]
+        selected: usize
+    }
+}
+
+impl DockNode {
+    pub fn tabs(panels: &[&str]) -> Self {
+        DockNode::Tabs {
+            panels: panels.iter().map(|id| id.to_string()).collect(),
+            selected: 0
+        }
+    }
+
+    // the children share the space evenly
+    pub fn split(axis: Axis, children: Vec<DockNode>) -> Self {
+        DockNode::Split {
+            axis,
+            fractions: vec![1.0 / children.len().max(1) as f32; children.len()],
+            children
+        }
+    }
+
+    // every panel id in the arrangement, groups in order from the left and top
+    pub fn panels(&self) -> Vec<&str> {
+        match self {
+            DockNode::Split { children, .. } => children.iter().flat_map(DockNode::panels).collect(),
+            DockNode::Tabs { panels, .. } => panels.iter().map(String::as_str).collect()
+        }
+    }
+
+    fn at_mut(&mut self, path: &[usize]) -> Option<&mut DockNode> {
+        match path.split_first() {
+            None => Some(self),
+            Some((&index, rest)) => match self {
+                DockNode::Split { children, .. } => children.get_mut(index)?.at_mut(rest),
+                DockNode::Tabs { .. } => None
+            }
+        }
+    }
+
+    // the path to the group holding the panel
+    fn find(&self, id: &str) -> Option<Vec<usize>> {
+        match self {
+            DockNode::Tabs { panels, .. } => panels.iter().any(|panel| panel == id).then(Vec::new),
+            DockNode::Split { children, .. } => children.iter().enumerate().find_map(|(index, child)| {
+                let mut path = child.find(id)?;
+                path.insert(0, index);
+                Some(path)
+            })
+        }
+    }
+
+    // keeps the panels the filter accepts, the selected tab stays selected where it can
+    fn retain(&mut self, keep: &mut impl FnMut(&str) -> bool) {
+        match self {
+            DockNode::Split { children, .. } => children.iter_mut().for_each(|child| child.retain(keep)),
+            DockNode::Tabs { panels, selected } => {
+                let selected_id = panels.get(*selected).cloned();
+                panels.retain(|panel| keep(panel));
+                *selected = selected_id.and_then(|id| panels.iter().position(|panel| *panel == id)).unwrap_or(0);
+            }
+        }
+    }
+
+    // the smallest size along the axis the node can be laid out in
+    fn min_length(&self, axis: Axis, theme: &Theme) -> f32 {
+        match self {
+            DockNode::Tabs { .. } if axis == Axis::Vertical => strip_height(theme) + MIN_PANEL_SIZE,
+            DockNode::Tabs { .. } => MIN_PANEL_SIZE,
+            DockNode::Split { axis: split, children, .. } if *split == axis => {
+                let dividers = DIVIDER_SIZE * children.len().saturating_sub(1) as f32;
+                children.iter().map(|child| child.min_length(axis, theme)).sum::<f32>() + dividers
+            }
+            DockNode::Split { children, .. } => children.iter().map(|child| child.min_length(axis, theme)).fold(0.0, f32::max)
+        }
+    }
+
+    // puts the node next to this one on the side, taking fraction of the space
+    fn wrap(&mut self, side: DockSide, node: DockNode, fraction: f32) {
+        let old = std::mem::replace(self, DockNode::tabs(&[]));
+        let (children, fractions) = if side.is_before() {
+            (vec![node, old], vec![fraction, 1.0 - fraction])
+        } else {
+            (vec![old, node], vec![1.0 - fraction, fraction])
+        };
+        *self = DockNode::Split { axis: side.axis(), fractions, children };
+    }
+}
+
+// removes empty groups, lifts the only child of a split into its place and merges splits into
+// parents along the same axis, the space of removed nodes goes to their siblings
+fn normalize(node: DockNode) -> Option<DockNode> {
+    match node {
+        DockNode::Tabs { panels, selected } if !panels.is_empty() => {
+            let selected = selected.min(panels.len() - 1);
+            Some(DockNode::Tabs { panels, selected })
+        }
+        DockNode::Tabs { .. } => None,
+        DockNode::Split { axis, fractions, children } => {
+            let sum: f32 = fractions.iter().map(|fraction| fraction.max(0.0)).sum();
+            let fractions = if fractions.len() == children.len() && sum > 0.0 {
+                fractions.iter().map(|fraction| fraction.max(0.0) / sum).collect()
+            } else {
+                vec![1.0 / children.len().max(1) as f32; children.len()]
+            };
+
+            let mut kept = vec![];
+            let mut kept_fractions = vec![];
+            for (child, fraction) in children.into_iter().zip(fractions) {
+                match normalize(child) {
+                    Some(DockNode::Split { axis: inner, fractions: inner_fractions, children: grandchildren }) if inner == axis => {
+                        for (grandchild, inner_fraction) in grandchildren.into_iter().zip(inner_fractions) {
+                            kept.push(grandchild);
+                            kept_fractions.push(fraction * inner_fraction);
+                        }
+                    }
+                    Some(child) => {
+                        kept.push(child);
+                        kept_fractions.push(fraction);
+                    }
+                    None => {}
+                }
+            }
+
+            match kept.len() {
+                0 => None,
+                1 => kept.pop(),
+                _ => {
+                    let sum = kept_fractions.iter().sum::<f32>().max(f32::EPSILON);
+                    let fractions = kept_fractions.iter().map(|fraction| fraction / sum).collect();
+                    Some(DockNode::Split { axis, fractions, children: kept })
+                }
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct DockFile {
+    root: Option<DockNode>
+}
+
+#[derive(Debug)]
+pub enum DockError {
+    Io(std::io::Error),
+    Parse(toml::de::Error),
+    Serialize(toml::ser::Error)
+}
+
+impl Display for DockError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DockError::Io(e) => write!(f, "could not access dock layout file: {}", e),
+            DockError::Parse(e) => write!(f, "could not parse dock layout: {}", e),
+            DockError::Serialize(e) => write!(f, "could not save dock layout: {}", e)
+        }
+    }
+}
+
+struct DockPanel {
+    id: String,
+    title: String,
+    widget: Box<dyn Widget>
+}
+
+// where a group of tabs was laid out
+struct GroupLayout {
+    path: Vec<usize>,
+    bounds: Rect,
+    strip: Rect,
+    content: Rect,
+    tabs: Vec<TabRect>,
+    panels: Vec<String>,
+    selected: usize
+}
+
+// a divider between two children of the split at path
+#[derive(Clone)]
+struct DividerLayout {
+    path: Vec<usize>,
+    index: usize,
+    axis: Axis,
+    rect: Rect,
+    lengths: Vec<f32>,
+    mins: Vec<f32>
+}
+
+#[derive(Default)]
+struct Arrangement {
+    groups: Vec<GroupLayout>,
+    dividers: Vec<DividerLayout>
+}
+
+// where a dragged tab would go, a group of None is the edge of the whole dock; index is where
+// the tab goes in the strip it was dropped on
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct DropTarget {
+    group: Option<usize>,
+    side: DockSide,
+    index: Option<usize>,
+    preview: Rect
+}
+
+enum DockPress {
+    Divider { divider: DividerLayout, grab: f32 },
+    Tab { id: String, group: usize, start: [f32; 2], pointer: [f32; 2], dragging: bool },
+    Panel(String)
+}
+
+// the preview of a tab drop, drawn in an overlay above the panels
+#[derive(Default)]
+struct DropPreview {
+    overlay: Option<LayerId>,
+    drawn: Drawn,
+    dirty: bool
+}
+
+// panels arranged in groups of tabs separated by draggable dividers, like the tool windows of an
+// ide; tabs can be dragged onto another group or its edges to re-dock them, and the arrangement
+// can be saved and restored
+pub struct DockManager {
+    base: WidgetBase,
+    font: Font,
+    panels: Vec<DockPanel>,
+    root: Option<DockNode>,
+    arrangement: Arrangement,
+    // the clip of every group and the content bounds it clips to
+    clips: Vec<(Rect, ClipId)>,
+    // the panels drawn last and the clips they were drawn under
+    shown: Vec<(String, ClipId)>,
+    // removed panels, what they drew is removed on the next draw
+    removed: Vec<Box<dyn Widget>>,
+    hovered: Option<(usize, usize)>,
+    hovered_divider: Option<usize>,
+    press: Option<DockPress>,
+    drop: Option<DropTarget>,
+    preview: DropPreview,
+    // the last panel pressed gets the keys
+    active: Option<String>,
+    on_layout_change: Option<ClickHandler>
+}
+
+impl DockManager {
+    pub fn new(font: Font, bounds: Rect, theme: Theme) -> Self {
+        DockManager {
+            base: WidgetBase::new(bounds, theme),
+            font,
+            panels: vec![],
+            root: None,
+            arrangement: Arrangement::default(),
+            clips: vec![],
+            shown: vec![],
+            removed: vec![],
+            hovered: None,
+            hovered_divider: None,
+            press: None,
+            drop: None,
+            preview: DropPreview::default(),
+            active: None,
+            on_layout_change: None
+        }
+    }
+
+    // the panel joins the group of the active panel, or the first group, as its selected tab,
+    // false when the dock already has a panel with this id
+    pub fn add_panel(&mut self, id: &str, title: &str, widget: impl Widget + 'static) -> bool {
+        let target = self.active.clone().or_else(|| self.root.as_ref()?.panels().first().map(|id| id.to_string()));
+        if !self.insert_panel(id, title, Box::new(widget)) {
+            return false;
+        }
+        match target {
+            Some(target) => self.place(id, &target, DockSide::Center, None),
+            None => self.root = Some(DockNode::tabs(&[id]))
+        }
+        self.arrange();
+        true
+    }
+
+    // the panel is put on a side of the group holding target, or joins it as a tab,
+    // false when there is no panel target or already a panel with this id
+    pub fn add_panel_beside(&mut self, id: &str, title: &str, widget: impl Widget + 'static, target: &str, side: DockSide) -> bool {
+        if !self.panels.iter().any(|panel| panel.id == target) {
+            return false;
+        }
+        if !self.insert_panel(id, title, Box::new(widget)) {
+            return false;
+        }
+        self.place(id, target, side, None);
+        self.arrange();
+        true
+    }
+
+    pub fn remove_panel(&mut self, id: &str) -> bool {
+        let Some(index) = self.panels.iter().position(|panel| panel.id == id) else {
+            return false;
+        };
+        let panel = self.panels.remove(index);
+        self.removed.push(panel.widget);
+        self.take(id);
+        if self.active.as_deref() == Some(id) {
+            self.active = None;
+        }
+        self.arrange();
+        true
+    }
+
+    pub fn panel(&self, id: &str) -> Option<&dyn Widget> {
+        self.panels.iter().find(|panel| panel.id == id).map(|panel| panel.widget.as_ref())
+    }
+
+    pub fn panel_mut(&mut self, id: &str) -> Option<&mut (dyn Widget + 'static)> {
+        self.panels.iter_mut().find(|panel| panel.id == id).map(|panel| panel.widget.as_mut())
+    }
+
+    pub fn set_title(&mut self, id: &str, title: &str) {
+        if let Some(panel) = self.panels.iter_mut().find(|panel| panel.id == id) {
+            panel.title = title.to_string();
+            self.arrange();
+        }
+    }
+
+    // selects the tab of the panel and sends it the keys
+    pub fn activate(&mut self, id: &str) {
+        let Some(path) = self.root.as_ref().and_then(|root| root.find(id)) else {
+            return;
+        };
+        if let Some(DockNode::Tabs { panels, selected }) = self.root.as_mut().and_then(|root| root.at_mut(&path)) {
+            *selected = panels.iter().position(|panel| panel == id).unwrap_or(0);
+        }
+        self.active = Some(id.to_string());
+        self.arrange();
+    }
+
+    // moves a panel next to the group holding target, or into it as a tab
+    pub fn dock(&mut self, id: &str, target: &str, side: DockSide) -> bool {
+        let known = |id: &str| self.panels.iter().any(|panel| panel.id == id);
+        if id == target || !known(id) || !known(target) {
+            return false;
+        }
+        self.take(id);
+        self.place(id, target, side, None);
+        self.arrange();
+        true
+    }
+
+    // moves a panel along a whole edge of the dock
+    pub fn dock_to_edge(&mut self, id: &str, side: DockSide) -> bool {
+        if side == DockSide::Center || !self.panels.iter().any(|panel| panel.id == id) {
+            return false;
+        }
+        self.take(id);
+        match &mut self.root {
+            Some(root) => root.wrap(side, DockNode::tabs(&[id]), EDGE_FRACTION),
+            None => self.root = Some(DockNode::tabs(&[id]))
+        }
+        self.root = self.root.take().and_then(normalize);
+        self.arrange();
+        true
+    }
+
+    pub fn layout(&self) -> Option<&DockNode> {
+        self.root.as_ref()
+    }
+
+    // unknown and repeated panel ids are dropped, panels the layout leaves out join the first group
+    pub fn set_layout(&mut self, root: Option<DockNode>) {
+        let known: HashSet<String> = self.panels.iter().map(|panel| panel.id.clone()).collect();
+        let mut seen = HashSet::new();
+        let mut root = root;
+        if let Some(root) = &mut root {
+            root.retain(&mut |id| known.contains(id) && seen.insert(id.to_string()));
+        }
+        self.root = root.and_then(normalize);
+
+        let missing: Vec<String> = self.panels.iter().map(|panel| panel.id.clone()).filter(|id| !seen.contains(id)).collect();
+        for id in missing {
+            let path = self.root.as_ref().and_then(|root| root.find(root.panels().first()?));
+            match path.and_then(|path| self.root.as_mut()?.at_mut(&path)) {
+                Some(DockNode::Tabs { panels, .. }) => panels.push(id),
+                _ => self.root = Some(DockNode::tabs(&[&id]))
+            }
+        }
+        if self.active.as_ref().is_some_and(|id| !known.contains(id)) {
+            self.active = None;
+        }
+        self.arrange();
+    }
+
+    pub fn save(&self) -> Result<String, DockError> {
+        toml::to_string(&DockFile { root: self.root.clone() }).map_err(DockError::Serialize)
+    }
+
+    pub fn restore(&mut self, contents: &str) -> Result<(), DockError> {
+        let file: DockFile = toml::from_str(contents).map_err(DockError::Parse)?;
+        self.set_layout(file.root);
+        Ok(())
+    }
+
+    pub fn save_to_file(&self, path: impl AsRef<Path>) -> Result<(), DockError> {
+        std::fs::write(path, self.save()?).map_err(DockError::Io)
+    }
+
+    pub fn restore_from_file(&mut self, path: impl AsRef<Path>) -> Result<(), DockError> {
+        let contents = std::fs::read_to_string(path).map_err(DockError::Io)?;
+        self.restore(&contents)
+    }
+
+    // called when the user drags a divider or re-docks a panel, not for changes made in code
+    pub fn on_layout_change(&mut self, handler: impl FnMut() + Send + 'static) {
+        self.on_layout_change = Some(Box::new(handler));
+    }
+
+    fn insert_panel(&mut self, id: &str, title: &str, widget: Box<dyn Widget>) -> bool {
+        if self.panels.iter().any(|panel| panel.id == id) {
+            return false;
+        }
+        self.panels.push(DockPanel { id: id.to_string(), title: title.to_string(), widget });
+        true
+    }
+
+    // removes the panel from the arrangement, its widget stays
+    fn take(&mut self, id: &str) {
+        if let Some(root) = &mut self.root {
+            root.retain(&mut |panel| panel != id);
+        }
+        self.root = self.root.take().and_then(normalize);
+    }
+
+    // puts a panel that is not in the arrangement next to or into the group holding target,
+    // at index in its strip or at the end
+    fn place(&mut self, id: &str, target: &str, side: DockSide, index: Option<usize>) {
+        let Some(path) = self.root.as_ref().and_then(|root| root.find(target)) else {
+            self.root = Some(DockNode::tabs(&[id]));
+            return;
+        };
+        let Some(node) = self.root.as_mut().and_then(|root| root.at_mut(&path)) else {
+            return;
+        };
+        match node {
+            DockNode::Tabs { panels, selected } if side == DockSide::Center => {
+                let index = index.unwrap_or(panels.len()).min(panels.len());
+                panels.insert(index, id.to_string());
+                *selected = index;
+            }
+            _ => node.wrap(side, DockNode::tabs(&[id]), 0.5)
+        }
+        self.root = self.root.take().and_then(normalize);
+    }
+
+    // lays out the groups and dividers and gives every panel the content bounds of its group
+    fn arrange(&mut self) {
+        let mut arrangement = Arrangement::default();
+        if let Some(root) = &self.root {
+            arrange_node(root, vec![], self.base.bounds, &self.font, self.base.theme(), &self.panels, &mut arrangement);
+        }
+        for group in &arrangement.groups {
+            for id in &group.panels {
+                if let Some(panel) = self.panels.iter_mut().find(|panel| panel.id == *id) {
+                    if panel.widget.bounds() != group.content {
+                        panel.widget.set_bounds(group.content);
+                    }
+                }
+            }
+        }
+        self.arrangement = arrangement;
+        self.base.dirty = true;
+    }
+
+    fn layout_changed(&mut self) {
+        if let Some(handler) = &mut self.on_layout_change {
+            handler();
+        }
+    }
+
+    fn selected_panel(&self, group: usize) -> Option<&str> {
+        let group = self.arrangement.groups.get(group)?;
+        group.panels.get(group.selected).map(String::as_str)
+    }
+
+    fn panel_widget(&mut self, id: &str) -> Option<&mut Box<dyn Widget>> {
+        self.panels.iter_mut().find(|panel| panel.id == id).map(|panel| &mut panel.widget)
+    }
+
+    fn group_at(&self, x: f32, y: f32) -> Option<usize> {
+        self.arrangement.groups.iter().position(|group| group.bounds.contains(x, y))
+    }
+
+    fn tab_at(&self, x: f32, y: f32) -> Option<(usize, usize)> {
+        self.arrangement.groups.iter().enumerate().find_map(|(index, group)| {
+            Some((index, group.tabs.iter().position(|rect| rect.tab.contains(x, y))?))
+        })
+    }
+
+    fn divider_at(&self, x: f32, y: f32) -> Option<usize> {
+        self.arrangement.dividers.iter().position(|divider| divider.rect.contains(x, y))
+    }
+
+    // the visible panel of the group under the point
+    fn panel_at(&self, x: f32, y: f32) -> Option<String> {
+        let group = self.arrangement.groups.iter().position(|group| group.content.contains(x, y))?;
+        self.selected_panel(group).map(str::to_string)
+    }
+
+    fn select_tab(&mut self, group: usize, tab: usize) {
+        let Some(layout) = self.arrangement.groups.get(group) else {
+            return;
+        };
+        let id = layout.panels[tab].clone();
+        self.activate(&id);
+    }
+
+    // ctrl+tab moves through the tabs of the group holding the active panel
+    fn step(&mut self, forward: bool) -> bool {
+        let Some(group) = self.active.as_ref().and_then(|id| self.arrangement.groups.iter().position(|group| group.panels.contains(id))) else {
+            return false;
+        };
+        let layout = &self.arrangement.groups[group];
+        let count = layout.panels.len();
+        if count < 2 {
+            return false;
+        }
+        let next = if forward { (layout.selected + 1) % count } else { (layout.selected + count - 1) % count };
+        self.select_tab(group, next);
+        true
+    }
+
+    fn drop_target(&self, from: usize, x: f32, y: f32) -> Option<DropTarget> {
+        let bounds = self.base.bounds;
+        if !bounds.contains(x, y) {
+            return None;
+        }
+
+        // the edges of the dock, unless the panel is all there is; tab strips reach to the edge
+        let in_strip = self.arrangement.groups.iter().any(|group| group.strip.contains(x, y));
+        let edge = [
+            (x - bounds.x, DockSide::Left),
+            (bounds.right() - x, DockSide::Right),
+            (y - bounds.y, DockSide::Top),
+            (bounds.bottom() - y, DockSide::Bottom)
+        ].into_iter().find(|(distance, _)| *distance < ROOT_EDGE).map(|(_, side)| side);
+        if let Some(side) = edge.filter(|_| self.panels.len() > 1 && !in_strip) {
+            return Some(DropTarget { group: None, side, index: None, preview: side.preview(bounds, EDGE_FRACTION) });
+        }
+
+        let index = self.group_at(x, y)?;
+        let group = &self.arrangement.groups[index];
+        let alone = index == from && group.panels.len() == 1;
+
+        if group.strip.contains(x, y) {
+            let position = group.tabs.iter().position(|rect| x < rect.tab.x + rect.tab.width / 2.0).unwrap_or(group.tabs.len());
+            let marker = match group.tabs.get(position) {
+                Some(rect) => rect.tab.x,
+                None => group.tabs.last().map_or(group.strip.x, |rect| rect.tab.right())
+            };
+            let preview = Rect::new(marker - 1.0, group.strip.y, 2.0, group.strip.height);
+            return Some(DropTarget { group: Some(index), side: DockSide::Center, index: Some(position), preview });
+        }
+
+        let content = group.content;
+        let across = (x - content.x) / content.width.max(1.0);
+        let down = (y - content.y) / content.height.max(1.0);
+        let side = [
+            (across, DockSide::Left),
+            (1.0 - across, DockSide::Right),
+            (down, DockSide::Top),
+            (1.0 - down, DockSide::Bottom)
+        ].into_iter()
+            .filter(|(distance, _)| *distance < EDGE_FRACTION)
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .map_or(DockSide::Center, |(_, side)| side);
+
+        // a panel dropped back on its own group, or next to itself, stays where it is
+        if (index == from && side == DockSide::Center) || (alone && side != DockSide::Center) {
+            return None;
+        }
+        Some(DropTarget { group: Some(index), side, index: None, preview: side.preview(content, 0.5) })
+    }
+
+    fn apply_drop(&mut self, id: &str, from: usize, target: DropTarget) {
+        let Some(group) = target.group else {
+            self.dock_to_edge(id, target.side);
+            return;
+        };
+        let Some(layout) = self.arrangement.groups.get(group) else {
+            return;
+        };
+
+        // reordering in its own strip
+        if group == from && target.side == DockSide::Center {
+            let path = layout.path.clone();
+            if let Some(DockNode::Tabs { panels, selected }) = self.root.as_mut().and_then(|root| root.at_mut(&path)) {
+                let Some(current) = panels.iter().position(|panel| panel == id) else {
+                    return;
+                };
+                let index = target.index.unwrap_or(panels.len());
+                let index = if index > current { index - 1 } else { index };
+                let panel = panels.remove(current);
+                panels.insert(index.min(panels.len()), panel);
+                *selected = index.min(panels.len() - 1);
+            }
+            self.arrange();
+            return;
+        }
+
+        let Some(anchor) = layout.panels.iter().find(|panel| *panel != id).cloned() else {
+            return;
+        };
+        let index = target.index.map(|index| {
+            // the tab leaving the strip moves the ones after it
+            let leaving = layout.panels.iter().position(|panel| panel == id).filter(|&position| position < index);
+            index - leaving.map_or(0, |_| 1)
+        });
+        self.take(id);
+        self.place(id, &anchor, target.side, index);
+        self.active = Some(id.to_string());
+        self.arrange();
+    }
+
+    fn draw_preview(&mut self, designer: &Designer, renderer: &mut WgpuRenderer) {
+        self.preview.dirty = false;
+        self.preview.drawn.clear(renderer);
+        if let Some(overlay) = self.preview.overlay.take() {
+            renderer.remove_layer(overlay);
+        }
+        let Some(DockPress::Tab { id, pointer, dragging: true, .. }) = &self.press else {
+            return;
+        };
+
+        let theme = self.base.theme().clone();
+        let overlay = renderer.add_overlay(LayerOptions::new(1.0));
+        self.preview.overlay = Some(overlay);
+        renderer.enter_overlay(overlay);
+        if let Some(target) = self.drop {
+            let border = Border::new(theme.border_width.max(1.0), theme.accent);
+            self.preview.drawn.rounded_rect(designer, renderer, target.preview, theme.corner_radius.min(4.0), theme.accent.with_alpha(0.2), Some(border));
+        }
+
+        // the title of the dragged tab follows the pointer
+        let title = self.panels.iter().find(|panel| panel.id == *id).map(|panel| panel.title.clone()).unwrap_or_default();
+        let style = TextStyle { max_width: None, ..theme.text.clone() };
+        let [width, _] = measure_text(&self.font, &title, &style, renderer.size);
+        let height = strip_height(&theme);
+        let ghost = Rect::new(pointer[0] + 12.0, pointer[1] + 12.0, width + theme.padding * 2.0, height);
+        let border = Border::new(theme.border_width, theme.border);
+        self.preview.drawn.rounded_rect(designer, renderer, ghost, theme.corner_radius, theme.background.with_alpha(0.9), Some(border));
+        let area = Rect::new(ghost.x + theme.padding, ghost.y, width, height);
+        self.preview.drawn.text(renderer, &self.font, &title, &style, area, theme.foreground);
+        renderer.exit_overlay();
+    }
+
+    fn clear_preview(&mut self, renderer: &mut WgpuRenderer) {
+        self.preview.drawn.clear(renderer);
+        if let Some(overlay) = self.preview.overlay.take() {
+            renderer.remove_layer(overlay);
+        }
+    }
+}
+
+fn arrange_node(node: &DockNode, path: Vec<usize>, bounds: Rect, font: &Font, theme: &Theme, panels: &[DockPanel], out: &mut Arrangement) {
+    match node {
+        DockNode::Tabs { panels: ids, selected } => {
+            let height = strip_height(theme).min(bounds.height);
+            let strip = Rect::new(bounds.x, bounds.y, bounds.width, height);
+            let content = Rect::new(bounds.x, bounds.y + height, bounds.width, bounds.height - height);
+            let titles: Vec<(&str, bool)> = ids.iter().map(|id| {
+                let title = panels.iter().find(|panel| panel.id == *id).map_or(id.as_str(), |panel| panel.title.as_str());
+                (title, false)
+            }).collect();
+            let tabs = layout_tabs(font, theme, &titles, strip);
+            out.groups.push(GroupLayout { path, bounds, strip, content, tabs, panels: ids.clone(), selected: *selected });
+        }
+        DockNode::Split { axis, fractions, children } => {
+            let mins: Vec<f32> = children.iter().map(|child| child.min_length(*axis, theme)).collect();
+            let lengths = split_lengths(available_length(*axis, &bounds, children.len()), fractions, &mins);
+            let (rects, dividers) = split_rects(*axis, bounds, &lengths);
+            for (index, rect) in dividers.into_iter().enumerate() {
+                out.dividers.push(DividerLayout { path: path.clone(), index, axis: *axis, rect, lengths: lengths.clone(), mins: mins.clone() });
+            }
+            for (index, (child, rect)) in children.iter().zip(rects).enumerate() {
+                let mut child_path = path.clone();
+                child_path.push(index);
+                arrange_node(child, child_path, rect, font, theme, panels, out);
+            }
+        }
+    }
+}
+
+impl Widget for DockManager {
+    fn base(&self) -> &WidgetBase {
+        &self.base
+    }
+
+    fn base_mut(&mut self) -> &mut WidgetBase {
+        &mut self.base
+    }
+
+    // escape drops a dragged tab where it came from
+    fn handle_key(&mut self, event: &KeyEvent, modifiers: ModifiersState) -> bool {
+        if self.base.state.disabled {
+            return false;
+        }
+        if event.state == ElementState::Pressed {
+            if matches!(self.press, Some(DockPress::Tab { dragging: true, .. })) && event.logical_key == Key::Named(NamedKey::Escape) {
+                self.press = None;
+                self.drop = None;
+                self.preview.dirty = true;
+                return true;
+            }
+            if modifiers.control_key() {
+                match event.logical_key {
+                    Key::Named(NamedKey::Tab) => return self.step(!modifiers.shift_key()),
+                    Key::Named(NamedKey::PageDown) => return self.step(true),
+                    Key::Named(NamedKey::PageUp) => return self.step(false),
+                    _ => {}
+                }
+            }
+        }
+        let Some(id) = self.active.clone() else {
+            return false;
+        };
+        self.panel_widget(&id).is_some_and(|widget| widget.handle_key(event, modifiers))
+    }
+
+    // tabs are selected on press and come off their strip once dragged a few pixels
+    fn handle_mouse_down(&mut self, x: f32, y: f32) -> bool {
+        if self.base.state.disabled || !self.base.bounds.contains(x, y) {
+            return false;
+        }
+        if let Some(index) = self.divider_at(x, y) {
+            let divider = self.arrangement.dividers[index].clone();
+            self.press = Some(DockPress::Divider { grab: divider.axis.pick(x, y), divider });
+            self.base.dirty = true;
+            return true;
+        }
+        if let Some((group, tab)) = self.tab_at(x, y) {
+            let id = self.arrangement.groups[group].panels[tab].clone();
+            self.select_tab(group, tab);
+            self.base.take_focus();
+            self.press = Some(DockPress::Tab { id, group, start: [x, y], pointer: [x, y], dragging: false });
+            return true;
+        }
+
+        let Some(id) = self.panel_at(x, y) else {
+            return false;
+        };
+        self.active = Some(id.clone());
+        self.press = Some(DockPress::Panel(id.clone()));
+        self.panel_widget(&id).is_some_and(|widget| widget.handle_mouse_down(x, y))
+    }
+
+    fn handle_mouse_move(&mut self, x: f32, y: f32) -> bool {
+        match &mut self.press {
+            Some(DockPress::Divider { divider, grab }) => {
+                let mut lengths = divider.lengths.clone();
+                drag_divider(&mut lengths, divider.index, divider.axis.pick(x, y) - *grab, &divider.mins);
+                let total = lengths.iter().sum::<f32>().max(f32::EPSILON);
+                let path = divider.path.clone();
+                if let Some(DockNode::Split { fractions, .. }) = self.root.as_mut().and_then(|root| root.at_mut(&path)) {
+                    *fractions = lengths.iter().map(|length| length / total).collect();
+                }
+                self.arrange();
+                return true;
+            }
+            Some(DockPress::Tab { group, start, pointer, dragging, .. }) => {
+                *pointer = [x, y];
+                if !*dragging && (x - start[0]).hypot(y - start[1]) < DRAG_THRESHOLD {
+                    return false;
+                }
+                *dragging = true;
+                let group = *group;
+                self.drop = self.drop_target(group, x, y);
+                self.preview.dirty = true;
+                return true;
+            }
+            _ => {}
+        }
+
+        let (hovered, hovered_divider) = if self.base.state.disabled {
+            (None, None)
+        } else {
+            (self.tab_at(x, y), self.divider_at(x, y))
+        };
+        let mut changed = (hovered, hovered_divider) != (self.hovered, self.hovered_divider);
+        if changed {
+            self.hovered = hovered;
+            self.hovered_divider = hovered_divider;
+            self.base.dirty = true;
+        }
+        let visible: Vec<String> = (0..self.arrangement.groups.len()).filter_map(|group| self.selected_panel(group).map(str::to_string)).collect();
+        for id in visible {
+            if let Some(widget) = self.panel_widget(&id) {
+                changed |= widget.handle_mouse_move(x, y);
+            }
+        }
+        changed
+    }
+
+    fn handle_mouse_up(&mut self, x: f32, y: f32) -> bool {
+        match self.press.take() {
+            Some(DockPress::Divider { .. }) => {
+                self.hovered_divider = self.divider_at(x, y);
+                self.base.dirty = true;
+                self.layout_changed();
+                true
+            }
+            Some(DockPress::Tab { id, group, dragging: true, .. }) => {
+                self.preview.dirty = true;
+                if let Some(target) = self.drop.take() {
+                    self.apply_drop(&id, group, target);
+                    self.layout_changed();
+                }
+                true
+            }
+            Some(DockPress::Tab { .. }) => true,
+            Some(DockPress::Panel(id)) => self.panel_widget(&id).is_some_and(|widget| widget.handle_mouse_up(x, y)),
+            None => false
+        }
+    }
+
+    fn handle_scroll(&mut self, delta: &MouseScrollDelta, phase: TouchPhase, x: f32, y: f32) -> bool {
+        if self.base.state.disabled {
+            return false;
+        }
+        let Some(id) = self.panel_at(x, y) else {
+            return false;
+        };
+        self.panel_widget(&id).is_some_and(|widget| widget.handle_scroll(delta, phase, x, y))
+    }
+
+    fn tick(&mut self, elapsed: f32) -> bool {
+        let visible: Vec<String> = (0..self.arrangement.groups.len()).filter_map(|group| self.selected_panel(group).map(str::to_string)).collect();
+        let mut moving = false;
+        for id in visible {
+            if let Some(widget) = self.panel_widget(&id) {
+                moving |= widget.tick(elapsed);
+            }
+        }
+        moving
+    }
+
+    fn draw_content(&mut self, designer: &Designer, renderer: &mut WgpuRenderer, drawn: &mut Drawn) -> Result<(), PathError> {
+        for mut widget in self.removed.drain(..) {
+            widget.clear(renderer);
+        }
+
+        // panels drawn under the old clips have to be drawn again under the new ones
+        let contents: Vec<Rect> = self.arrangement.groups.iter().map(|group| group.content).collect();
+        if self.clips.iter().map(|(rect, _)| *rect).ne(contents.iter().copied()) {
+            for (_, clip) in self.clips.drain(..) {
+                renderer.remove_clip(clip);
+            }
+            for (id, _) in std::mem::take(&mut self.shown) {
+                if let Some(panel) = self.panels.iter_mut().find(|panel| panel.id == id) {
+                    panel.widget.clear(renderer);
+                }
+            }
+            self.clips = contents.into_iter().map(|rect| {
+                let clip = renderer.push_rect_clip(rect);
+                renderer.pop_clip();
+                (rect, clip)
+            }).collect();
+        }
+
+        let theme = self.base.theme().clone();
+        let dragged = match &self.press {
+            Some(DockPress::Divider { divider, .. }) => Some((divider.path.clone(), divider.index)),
+            _ => None
+        };
+        for (index, divider) in self.arrangement.dividers.iter().enumerate() {
+            let active = self.hovered_divider == Some(index) || dragged.as_ref().is_some_and(|(path, at)| *path == divider.path && *at == divider.index);
+            draw_divider(designer, renderer, drawn, divider.axis, divider.rect, active, &theme);
+        }
+
+        let mut result = Ok(());
+        let mut visible = vec![];
+        for (index, group) in self.arrangement.groups.iter().enumerate() {
+            let titles: Vec<&str> = group.panels.iter().map(|id| {
+                self.panels.iter().find(|panel| panel.id == *id).map_or(id.as_str(), |panel| panel.title.as_str())
+            }).collect();
+            let strip = TabStrip {
+                bounds: group.strip,
+                tabs: &group.tabs,
+                titles: &titles,
+                selected: Some(group.selected),
+                hovered: self.hovered.filter(|(at, _)| *at == index).map(|(_, tab)| tab),
+                hovered_close: None,
+                focused: self.base.state.focused && self.active.as_ref().is_some_and(|id| group.panels.contains(id))
+            };
+            result = result.and(draw_tab_strip(designer, renderer, drawn, &self.font, &theme, &strip));
+            if let Some(id) = group.panels.get(group.selected) {
+                visible.push((id.clone(), self.clips[index].1));
+            }
+        }
+
+        // panels no longer visible, or moved to another group, are cleared
+        for (id, clip) in std::mem::take(&mut self.shown) {
+            if !visible.contains(&(id.clone(), clip)) {
+                if let Some(widget) = self.panel_widget(&id) {
+                    widget.clear(renderer);
+                }
+            }
+        }
+        for (id, clip) in &visible {
+            if let Some(panel) = self.panels.iter_mut().find(|panel| panel.id == *id) {
+                if panel.widget.is_dirty() {
+                    renderer.reenter_clip(*clip);
+                    result = result.and(panel.widget.draw(designer, renderer));
+                    renderer.pop_clip();
+                }
+            }
+        }
+        self.shown = visible;
+
+        if self.preview.dirty {
+            self.draw_preview(designer, renderer);
+        }
+        result
+    }
+
+    fn clear(&mut self, renderer: &mut WgpuRenderer) {
+        for mut widget in self.removed.drain(..) {
+            widget.clear(renderer);
+        }
+        for panel in &mut self.panels {
+            panel.widget.clear(renderer);
+        }
+        for (_, clip) in self.clips.drain(..) {
+            renderer.remove_clip(clip);
+        }
+        self.shown.clear();
+        self.clear_preview(renderer);
+        self.base.drawn.clear(renderer);
+        self.base.dirty = true;
+    }
+
+    fn set_bounds(&mut self, bounds: Rect) {
+        self.base.bounds = bounds;
+        if let Some(node) = &self.base.node {
+            node.set_bounds(bounds);
+        }
+        self.arrange();
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.base.dirty
+            || self.preview.dirty
+            || !self.removed.is_empty()
+            || self.shown.iter().any(|(id, _)| self.panels.iter().any(|panel| panel.id == *id && panel.widget.is_dirty()))
+    }
+
+    fn set_enabled(&mut self, enabled: bool) {
+        if !enabled {
+            self.hovered = None;
+            self.hovered_divider = None;
+            self.press = None;
+            self.drop = None;
+            self.preview.dirty = true;
+        }
+        self.base.state.disabled = !enabled;
+        if let Some(node) = &self.base.node {
+            node.set_enabled(enabled);
+        }
+        for panel in &mut self.panels {
+            panel.widget.set_enabled(enabled);
+        }
+        self.base.mark_dirty();
+    }
+
+    fn set_theme(&mut self, theme: Theme) {
+        self.base.theme = theme;
+        self.arrange();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::text::font::test_font;
+    use crate::widgets::button::Button;
+
+    fn button(font: &Font) -> Button {
+        Button::new(font.clone(), "ok", Rect::default(), Theme::default())
+    }
+
+    // files on the left, editor and output stacked on the right
+    fn dock() -> DockManager {
+        let font = test_font();
+        let mut dock = DockManager::new(font.clone(), Rect::new(0.0, 0.0, 800.0, 600.0), Theme::default());
+        dock.add_panel("editor", "Editor", button(&font));
+        dock.add_panel_beside("files", "Files", button(&font), "editor", DockSide::Left);
+        dock.add_panel_beside("output", "Output", button(&font), "editor", DockSide::Bottom);
+        dock
+    }
+
+    #[test]
+    fn normalizing_drops_empty_groups_and_merges_splits_along_the_same_axis() {
+        let node = DockNode::Split {
+            axis: Axis::Horizontal,
+            fractions: vec![0.5, 0.5],
+            children: vec![
+                DockNode::tabs(&["a"]),
+                DockNode::Split {
+                    axis: Axis::Horizontal,
+                    fractions: vec![0.25, 0.5, 0.25],
+                    children: vec![DockNode::tabs(&["b"]), DockNode::tabs(&[]), DockNode::tabs(&["c"])]
+                }
+            ]
+        };
+        let Some(DockNode::Split { fractions, children, .. }) = normalize(node) else {
+            panic!("expected a split");
+        };
+        assert_eq!(children, vec![DockNode::tabs(&["a"]), DockNode::tabs(&["b"]), DockNode::tabs(&["c"])]);
+        // the share of the empty group goes to its siblings
+        assert_eq!(fractions, vec![0.5, 0.25, 0.25]);
+
+        // a split left with one child is replaced by it
+        let single = DockNode::split(Axis::Vertical, vec![DockNode::tabs(&[]), DockNode::tabs(&["a"])]);
+        assert_eq!(normalize(single), Some(DockNode::tabs(&["a"])));
+    }
+
+    #[test]
+    fn panels_dock_beside_their_target() {
+        let mut dock = dock();
+        assert_eq!(dock.layout().unwrap().panels(), vec!["files", "editor", "output"]);
+
+        assert!(dock.dock("output", "files", DockSide::Center));
+        assert_eq!(dock.layout(), Some(&DockNode::Split {
+            axis: Axis::Horizontal,
+            fractions: vec![0.5, 0.5],
+            children: vec![DockNode::Tabs { panels: vec!["files".into(), "output".into()], selected: 1 }, DockNode::tabs(&["editor"])]
+        }));
+        assert!(!dock.dock("editor", "editor", DockSide::Left));
+
+        assert!(dock.remove_panel("files"));
+        assert!(dock.remove_panel("output"));
+        assert_eq!(dock.layout(), Some(&DockNode::tabs(&["editor"])));
+    }
+
+    #[test]
+    fn layouts_round_trip_through_toml() {
+        let dock = dock();
+        let saved = dock.save().unwrap();
+
+        let font = test_font();
+        let mut restored = DockManager::new(font.clone(), Rect::new(0.0, 0.0, 800.0, 600.0), Theme::default());
+        for id in ["output", "editor", "files"] {
+            restored.add_panel(id, id, button(&font));
+        }
+        restored.restore(&saved).unwrap();
+        assert_eq!(restored.layout(), dock.layout());
+        assert!(matches!(restored.restore("root = 1"), Err(DockError::Parse(_))));
+    }
+
+    #[test]
+    fn restored_layouts_drop_unknown_panels_and_keep_missing_ones() {
+        let mut dock = dock();
+        dock.set_layout(Some(DockNode::split(Axis::Vertical, vec![
+            DockNode::tabs(&["missing", "editor"]),
+            DockNode::tabs(&["files", "editor"])
+        ])));
+        assert_eq!(dock.layout(), Some(&DockNode::split(Axis::Vertical, vec![
+            DockNode::Tabs { panels: vec!["editor".into(), "output".into()], selected: 0 },
+            DockNode::tabs(&["files"])
+        ])));
+    }
+
+    #[test]
+    fn panel_ids_are_unique() {
+        let font = test_font();
+        let mut dock = DockManager::new(font.clone(), Rect::new(0.0, 0.0, 800.0, 600.0), Theme::default());
+
+        assert!(dock.add_panel("files", "Files", button(&font)));
+        assert!(!dock.add_panel("files", "Files", button(&font)));
+        assert!(!dock.add_panel_beside("output", "Output", button(&font), "missing", DockSide::Bottom));
+        assert!(dock.add_panel_beside("output", "Output", button(&font), "files", DockSide::Bottom));
+        assert_eq!(dock.layout().unwrap().panels(), vec!["files", "output"]);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,307 @@
+use winit::event::{ElementState, KeyEvent, MouseScrollDelta};
+use winit::keyboard::{Key, ModifiersState, NamedKey};
+use crate::designer::point::Measurement;
+use crate::designer::Designer;
+use crate::graphics::path::PathError;
+use crate::graphics::sdf::Border;
+use crate::render::wgpu::WgpuRenderer;
+use crate::scene::Rect;
+use crate::text::font::Font;
+use crate::text::layout::TextStyle;
+use crate::widgets::popup::{draw_chevron, place_below, PanelRow, PanelView, Popup, PopupHandle};
+use crate::widgets::radio::SelectHandler;
+use crate::widgets::theme::{Theme, WidgetKind};
+use crate::widgets::{Drawn, Widget, WidgetBase};
+
+// a button showing the selected option that opens the list of options below it, or above it
+// near the bottom of the window; register it with a popup layer so the list can open
+pub struct Dropdown {
+    base: WidgetBase,
+    font: Font,
+    options: Vec<String>,
+    selected: Option<usize>,
+    placeholder: String,
+    panel: PanelView,
+    popup: Option<PopupHandle>,
+    on_change: Option<SelectHandler>
+}
+
+impl Dropdown {
+    pub fn new(font: Font, options: Vec<String>, bounds: Rect, theme: Theme) -> Self {
+        Dropdown {
+            base: WidgetBase::new(bounds, theme),
+            font,
+            options,
+            selected: None,
+            placeholder: String::new(),
+            panel: PanelView::new(),
+            popup: None,
+            on_change: None
+        }
+    }
+
+    pub fn options(&self) -> &[String] {
+        &self.options
+    }
+
+    pub fn set_options(&mut self, options: Vec<String>) {
+        self.close();
+        self.options = options;
+        self.selected = self.selected.filter(|&index| index < self.options.len());
+        self.base.mark_dirty();
+    }
+
+    // shown while nothing is selected
+    pub fn set_placeholder(&mut self, placeholder: &str) {
+        self.placeholder = placeholder.to_string();
+        self.base.mark_dirty();
+    }
+
+    pub fn selected(&self) -> Option<usize> {
+        self.selected
+    }
+
+    // changes the selection without calling the handler
+    pub fn set_selected(&mut self, selected: Option<usize>) {
+        let selected = selected.filter(|&index| index < self.options.len());
+        if self.selected != selected {
+            self.selected = selected;
+            self.base.mark_dirty();
+        }
+    }
+
+    pub fn on_change(&mut self, handler: impl FnMut(usize) + Send + 'static) {
+        self.on_change = Some(Box::new(handler));
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.popup.as_ref().is_some_and(|popup| popup.is_open())
+    }
+
+    // false when there is nothing to choose or the dropdown was never registered with a popup layer
+    pub fn open(&mut self) -> bool {
+        if self.base.state.disabled || self.options.is_empty() {
+            return false;
+        }
+        let Some(popup) = &self.popup else {
+            return false;
+        };
+
+        let theme = self.base.theme().clone();
+        self.panel.set_rows(self.options.iter().map(|option| PanelRow::new(option)).collect());
+        let [width, height] = self.panel.measure(&self.font, &theme, popup.window_size());
+        self.panel.bounds = place_below(self.base.bounds, [width.max(self.base.bounds.width), height], popup.window());
+        self.panel.highlight(self.selected, &theme);
+        popup.open();
+        true
+    }
+
+    pub fn close(&mut self) {
+        if let Some(popup) = &self.popup {
+            popup.close();
+        }
+    }
+
+    fn choose(&mut self, index: usize) {
+        self.close();
+        if self.selected == Some(index) {
+            return;
+        }
+        self.set_selected(Some(index));
+        if let Some(handler) = &mut self.on_change {
+            handler(index);
+        }
+    }
+
+    // jumps to the next option starting with the typed character, like native selects
+    fn jump_to(&mut self, text: &str) -> bool {
+        let Some(c) = text.chars().next().map(|c| c.to_lowercase().to_string()) else {
+            return false;
+        };
+        let start = self.panel.highlighted.map_or(0, |index| index + 1);
+        let count = self.options.len();
+        let found = (0..count)
+            .map(|step| (start + step) % count)
+            .find(|&index| self.options[index].to_lowercase().starts_with(&c));
+        match found {
+            Some(index) => self.panel.highlight(Some(index), self.base.theme()),
+            None => false
+        }
+    }
+}
+
+impl Widget for Dropdown {
+    fn base(&self) -> &WidgetBase {
+        &self.base
+    }
+
+    fn base_mut(&mut self) -> &mut WidgetBase {
+        &mut self.base
+    }
+
+    // only opens the list, the popup layer sends keys to it while it is open
+    fn handle_key(&mut self, event: &KeyEvent, _modifiers: ModifiersState) -> bool {
+        if self.base.state.disabled || event.state != ElementState::Pressed || self.is_open() {
+            return false;
+        }
+        match event.logical_key {
+            Key::Named(NamedKey::Space | NamedKey::Enter | NamedKey::ArrowDown | NamedKey::ArrowUp) => {
+                self.open();
+                true
+            }
+            _ => false
+        }
+    }
+
+    fn handle_mouse_down(&mut self, x: f32, y: f32) -> bool {
+        if self.base.state.disabled || !self.base.bounds.contains(x, y) {
+            return false;
+        }
+        self.base.set_pressed(true);
+        self.base.take_focus();
+        self.open();
+        true
+    }
+
+    fn handle_mouse_move(&mut self, x: f32, y: f32) -> bool {
+        self.base.set_hovered(self.base.bounds.contains(x, y))
+    }
+
+    fn handle_mouse_up(&mut self, _x: f32, _y: f32) -> bool {
+        let pressed = self.base.state.pressed;
+        self.base.set_pressed(false);
+        pressed
+    }
+
+    fn draw_content(&mut self, designer: &Designer, renderer: &mut WgpuRenderer, drawn: &mut Drawn) -> Result<(), PathError> {
+        let style = self.base.style(WidgetKind::Button);
+        let theme = self.base.theme().clone();
+        let bounds = self.base.bounds;
+        drawn.rounded_rect(designer, renderer, bounds, style.corner_radius, style.background, Some(Border::new(style.border_width, style.border)));
+
+        let chevron = Rect::new(bounds.right() - bounds.height, bounds.y, bounds.height, bounds.height);
+        draw_chevron(designer, renderer, drawn, chevron, style.foreground)?;
+
+        let (text, color) = match self.selected {
+            Some(index) => (self.options[index].as_str(), style.foreground),
+            None => (self.placeholder.as_str(), theme.disabled)
+        };
+        let area = Rect::new(bounds.x + theme.padding, bounds.y, chevron.x - bounds.x - theme.padding, bounds.height);
+        if area.width > 0.0 && !text.is_empty() {
+            let text_style = TextStyle {
+                max_width: Some(Measurement::Pixels(area.width)),
+                ellipsis: true,
+                ..theme.text.clone()
+            };
+            drawn.text(renderer, &self.font, text, &text_style, area, color);
+        }
+        Ok(())
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.base.state.focused = focused;
+        self.base.mark_dirty();
+        if !focused {
+            self.base.set_pressed(false);
+            self.close();
+        }
+    }
+
+    fn set_enabled(&mut self, enabled: bool) {
+        if !enabled {
+            self.close();
+            self.base.state.hovered = false;
+            self.base.state.pressed = false;
+        }
+        self.base.state.disabled = !enabled;
+        if let Some(node) = &self.base.node {
+            node.set_enabled(enabled);
+        }
+        self.base.mark_dirty();
+    }
+}
+
+impl Popup for Dropdown {
+    fn popup_contains(&self, x: f32, y: f32) -> bool {
+        self.panel.bounds.contains(x, y)
+    }
+
+    fn popup_mouse_down(&mut self, x: f32, y: f32) -> bool {
+        self.panel.bounds.contains(x, y)
+    }
+
+    fn popup_mouse_move(&mut self, x: f32, y: f32) -> bool {
+        let row = self.panel.row_at(x, y, self.base.theme());
+        if row.is_none() {
+            return false;
+        }
+        self.panel.highlight(row, self.base.theme())
+    }
+
+    fn popup_mouse_up(&mut self, x: f32, y: f32) -> bool {
+        match self.panel.row_at(x, y, self.base.theme()) {
+            Some(index) => {
+                self.choose(index);
+                true
+            }
+            None => false
+        }
+    }
+
+    fn popup_key(&mut self, event: &KeyEvent, _modifiers: ModifiersState) -> bool {
+        if event.state != ElementState::Pressed {
+            return false;
+        }
+        let theme = self.base.theme().clone();
+        match &event.logical_key {
+            Key::Named(NamedKey::ArrowDown) => self.panel.move_highlight(true, &theme),
+            Key::Named(NamedKey::ArrowUp) => self.panel.move_highlight(false, &theme),
+            Key::Named(NamedKey::Home) => self.panel.highlight_edge(false, &theme),
+            Key::Named(NamedKey::End) => self.panel.highlight_edge(true, &theme),
+            Key::Character(text) => self.jump_to(text),
+            Key::Named(NamedKey::Enter | NamedKey::Space) => {
+                match self.panel.highlighted {
+                    Some(index) => self.choose(index),
+                    None => self.close()
+                }
+                true
+            }
+            Key::Named(NamedKey::Escape) => {
+                self.close();
+                true
+            }
+            // tab closes the list and moves focus as usual
+            Key::Named(NamedKey::Tab) => {
+                self.close();
+                return false;
+            }
+            _ => return false
+        };
+        true
+    }
+
+    fn popup_scroll(&mut self, delta: &MouseScrollDelta, _x: f32, _y: f32) -> bool {
+        self.panel.scroll(delta, self.base.theme())
+    }
+
+    fn draw_popup(&mut self, designer: &Designer, renderer: &mut WgpuRenderer) -> Result<(), PathError> {
+        let theme = self.base.theme().clone();
+        self.panel.draw(designer, renderer, &self.font, &theme)
+    }
+
+    fn clear_popup(&mut self, renderer: &mut WgpuRenderer) {
+        self.panel.clear(renderer);
+    }
+
+    fn is_popup_dirty(&self) -> bool {
+        self.panel.dirty
+    }
+
+    fn dismiss(&mut self) {
+        self.close();
+    }
+
+    fn set_popup_handle(&mut self, handle: PopupHandle) {
+        self.popup = Some(handle);
+    }
+}
@@ -0,0 +1,190 @@
+use std::ops::Range;
+use winit::event::KeyEvent;
+use winit::keyboard::ModifiersState;
+use crate::designer::Designer;
+use crate::graphics::path::PathError;
+use crate::render::wgpu::WgpuRenderer;
+use crate::scene::Rect;
+use crate::widgets::list_view::{ListAdapter, RowPool, VirtualBody, VirtualView};
+use crate::widgets::theme::Theme;
+use crate::widgets::{Drawn, Widget, WidgetBase};
+
+// rows of cells bound beyond each edge of the viewport
+const OVERSCAN_ROWS: usize = 2;
+
+// cells of one size filling the width left to right and then top to bottom, the height the
+// adapter returns when binding is ignored
+pub struct GridBody<A: ListAdapter> {
+    base: WidgetBase,
+    adapter: A,
+    pub cell_size: [f32; 2],
+    cells: RowPool<A::Row>,
+    viewport: Rect
+}
+
+pub type GridView<A> = VirtualView<GridBody<A>>;
+
+impl<A: ListAdapter> GridBody<A> {
+    fn new(adapter: A, cell_size: [f32; 2], theme: Theme) -> Self {
+        GridBody {
+            base: WidgetBase::new(Rect::default(), theme),
+            adapter,
+            cell_size: [cell_size[0].max(1.0), cell_size[1].max(1.0)],
+            cells: RowPool::new(),
+            viewport: Rect::default()
+        }
+    }
+
+    pub fn adapter(&self) -> &A {
+        &self.adapter
+    }
+
+    pub fn adapter_mut(&mut self) -> &mut A {
+        &mut self.adapter
+    }
+
+    pub fn columns(&self) -> usize {
+        ((self.base.bounds.width / self.cell_size[0]) as usize).max(1)
+    }
+
+    fn cell_rect(&self, index: usize) -> Rect {
+        let columns = self.columns();
+        let [width, height] = self.cell_size;
+        Rect::new(
+            (index % columns) as f32 * width,
+            (index / columns) as f32 * height,
+            width,
+            height
+        )
+    }
+
+    // the cells of the grid rows between the two positions
+    fn cells_between(&self, top: f32, bottom: f32) -> Range<usize> {
+        let columns = self.columns();
+        let first_row = (top.max(0.0) / self.cell_size[1]) as usize;
+        let end_row = (bottom.max(0.0) / self.cell_size[1]).ceil() as usize;
+        let count = self.adapter.len();
+        (first_row * columns).min(count)..(end_row * columns).min(count)
+    }
+
+    fn layout(&mut self) {
+        let bounds = self.base.bounds;
+        if self.viewport.is_empty() {
+            self.cells.release_all();
+            self.base.mark_dirty();
+            return;
+        }
+
+        let overscan = OVERSCAN_ROWS as f32 * self.cell_size[1];
+        let top = self.viewport.y - bounds.y - overscan;
+        let range = self.cells_between(top, top + self.viewport.height + overscan * 2.0);
+        self.cells.release_outside(range.clone());
+
+        for index in range {
+            if !self.cells.is_active(index) {
+                let adapter = &mut self.adapter;
+                let cell = self.cells.acquire(index, || adapter.create_row());
+                adapter.bind_row(cell, index, self.cell_size[0]);
+                cell.base_mut().mark_dirty();
+            }
+        }
+
+        let placed: Vec<(usize, Rect)> = self.cells.active.keys().map(|&index| (index, self.cell_rect(index))).collect();
+        for (index, rect) in placed {
+            let rect = Rect::new(bounds.x + rect.x, bounds.y + rect.y, rect.width, rect.height);
+            if let Some(cell) = self.cells.active.get_mut(&index).filter(|cell| cell.bounds() != rect) {
+                cell.set_bounds(rect);
+            }
+        }
+        self.base.mark_dirty();
+    }
+}
+
+impl<A: ListAdapter> GridView<A> {
+    pub fn new(adapter: A, cell_size: [f32; 2], bounds: Rect, theme: Theme) -> Self {
+        let mut body = GridBody::new(adapter, cell_size, theme.clone());
+        // the column count is needed for the first content height
+        body.base.bounds.width = bounds.width;
+        VirtualView::wrap(body, bounds, theme)
+    }
+}
+
+impl<A: ListAdapter> VirtualBody for GridBody<A> {
+    fn total_height(&self) -> f32 {
+        let rows = self.adapter.len().div_ceil(self.columns());
+        rows as f32 * self.cell_size[1]
+    }
+
+    fn reload(&mut self) {
+        self.cells.release_all();
+        self.layout();
+    }
+
+    fn invalidate(&mut self, index: usize) {
+        self.cells.release(index);
+        self.layout();
+    }
+
+    fn refresh(&mut self) {
+        self.reload();
+    }
+
+    fn item_rect(&self, index: usize) -> Option<Rect> {
+        (index < self.adapter.len()).then(|| self.cell_rect(index))
+    }
+
+    fn visible_range(&self) -> Range<usize> {
+        let top = self.viewport.y - self.base.bounds.y;
+        self.cells_between(top, top + self.viewport.height)
+    }
+}
+
+impl<A: ListAdapter> Widget for GridBody<A> {
+    fn base(&self) -> &WidgetBase {
+        &self.base
+    }
+
+    fn base_mut(&mut self) -> &mut WidgetBase {
+        &mut self.base
+    }
+
+    fn handle_key(&mut self, _event: &KeyEvent, _modifiers: ModifiersState) -> bool {
+        false
+    }
+
+    fn handle_mouse_down(&mut self, x: f32, y: f32) -> bool {
+        self.cells.handle_mouse_down(x, y)
+    }
+
+    fn handle_mouse_move(&mut self, x: f32, y: f32) -> bool {
+        self.cells.handle_mouse_move(x, y)
+    }
+
+    fn handle_mouse_up(&mut self, x: f32, y: f32) -> bool {
+        self.cells.handle_mouse_up(x, y)
+    }
+
+    fn draw_content(&mut self, designer: &Designer, renderer: &mut WgpuRenderer, _drawn: &mut Drawn) -> Result<(), PathError> {
+        self.cells.draw(designer, renderer)
+    }
+
+    fn clear(&mut self, renderer: &mut WgpuRenderer) {
+        self.cells.clear(renderer);
+        self.base.mark_dirty();
+    }
+
+    // cells are placed when the viewport is set, right after the scroll view moves the content
+    fn set_bounds(&mut self, bounds: Rect) {
+        self.base.bounds = bounds;
+        self.base.mark_dirty();
+    }
+
+    fn set_viewport(&mut self, viewport: Rect) {
+        self.viewport = viewport;
+        self.layout();
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.base.dirty || self.cells.is_dirty()
+    }
+}
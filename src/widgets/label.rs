@@ -0,0 +1,70 @@
+use winit::event::KeyEvent;
+use winit::keyboard::ModifiersState;
+use crate::designer::Designer;
+use crate::graphics::path::PathError;
+use crate::render::wgpu::WgpuRenderer;
+use crate::scene::Rect;
+use crate::text::font::Font;
+use crate::widgets::theme::{Theme, WidgetKind};
+use crate::widgets::{Drawn, Widget, WidgetBase};
+
+// a single line of text, it takes no input but follows the theme and dims when disabled
+pub struct Label {
+    base: WidgetBase,
+    font: Font,
+    text: String
+}
+
+impl Label {
+    pub fn new(font: Font, text: &str, bounds: Rect, theme: Theme) -> Self {
+        Label {
+            base: WidgetBase::new(bounds, theme),
+            font,
+            text: text.to_string()
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn set_text(&mut self, text: &str) {
+        if self.text != text {
+            self.text = text.to_string();
+            self.base.mark_dirty();
+        }
+    }
+}
+
+impl Widget for Label {
+    fn base(&self) -> &WidgetBase {
+        &self.base
+    }
+
+    fn base_mut(&mut self) -> &mut WidgetBase {
+        &mut self.base
+    }
+
+    fn handle_key(&mut self, _event: &KeyEvent, _modifiers: ModifiersState) -> bool {
+        false
+    }
+
+    fn handle_mouse_down(&mut self, _x: f32, _y: f32) -> bool {
+        false
+    }
+
+    fn handle_mouse_move(&mut self, x: f32, y: f32) -> bool {
+        self.base.set_hovered(self.base.bounds.contains(x, y))
+    }
+
+    fn handle_mouse_up(&mut self, _x: f32, _y: f32) -> bool {
+        false
+    }
+
+    fn draw_content(&mut self, _designer: &Designer, renderer: &mut WgpuRenderer, drawn: &mut Drawn) -> Result<(), PathError> {
+        let style = self.base.style(WidgetKind::Label);
+        let bounds = self.base.bounds;
+        drawn.text(renderer, &self.font, &self.text, &self.base.theme().text, bounds, style.foreground);
+        Ok(())
+    }
+}
@@ -0,0 +1,640 @@
+use std::collections::BTreeMap;
+use std::ops::Range;
+use winit::event::{KeyEvent, MouseScrollDelta, TouchPhase};
+use winit::keyboard::ModifiersState;
+use crate::designer::Designer;
+use crate::graphics::path::PathError;
+use crate::render::wgpu::WgpuRenderer;
+use crate::scene::Rect;
+use crate::widgets::scroll_view::ScrollView;
+use crate::widgets::theme::Theme;
+use crate::widgets::{Drawn, Widget, WidgetBase};
+
+// height of rows the adapter gives no estimate for
+pub const DEFAULT_ROW_HEIGHT: f32 = 32.0;
+// rows are bound this far beyond each edge of the viewport, so short scrolls never show empty space
+const OVERSCAN: f32 = 200.0;
+// measuring rows can change the content height and the scroll offset, which can bind more rows
+const MAX_SYNC_PASSES: usize = 4;
+
+// supplies the items of a list or grid, rows are widgets that are reused for other items as the view scrolls
+pub trait ListAdapter: Send {
+    type Row: Widget + 'static;
+
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn create_row(&mut self) -> Self::Row;
+
+    // shows the item in a row that may have shown another item before, returns the height it needs
+    fn bind_row(&mut self, row: &mut Self::Row, index: usize, width: f32) -> f32;
+
+    // height of items that were not bound yet, the closer it is the less the scrollbar jumps
+    fn estimated_height(&self, _index: usize) -> f32 {
+        DEFAULT_ROW_HEIGHT
+    }
+
+    // rows wider than the view scroll sideways, zero makes them as wide as the view
+    fn content_width(&self) -> f32 {
+        0.0
+    }
+}
+
+// prefix sums of row heights in a fenwick tree, so the top of a row and the row at a position are
+// found in logarithmic time however many rows there are
+pub(crate) struct HeightIndex {
+    // sums are kept in f64 so offsets deep into a long list stay exact to the pixel
+    tree: Vec<f64>,
+    heights: Vec<f32>
+}
+
+impl HeightIndex {
+    pub(crate) fn new(heights: Vec<f32>) -> Self {
+        let count = heights.len();
+        let mut tree: Vec<f64> = heights.iter().map(|height| *height as f64).collect();
+        for i in 1..=count {
+            let parent = i + (i & i.wrapping_neg());
+            if parent <= count {
+                tree[parent - 1] += tree[i - 1];
+            }
+        }
+        HeightIndex { tree, heights }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.heights.len()
+    }
+
+    pub(crate) fn height(&self, index: usize) -> f32 {
+        self.heights.get(index).copied().unwrap_or(0.0)
+    }
+
+    pub(crate) fn set(&mut self, index: usize, height: f32) {
+        let Some(previous) = self.heights.get_mut(index) else {
+            return;
+        };
+        let delta = height as f64 - *previous as f64;
+        if delta == 0.0 {
+            return;
+        }
+        *previous = height;
+
+        let mut i = index + 1;
+        while i <= self.tree.len() {
+            self.tree[i - 1] += delta;
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    // the top of the row, which is the height of every row before it
+    pub(crate) fn offset(&self, index: usize) -> f32 {
+        let mut sum = 0.0;
+        let mut i = index.min(self.tree.len());
+        while i > 0 {
+            sum += self.tree[i - 1];
+            i -= i & i.wrapping_neg();
+        }
+        sum as f32
+    }
+
+    pub(crate) fn total(&self) -> f32 {
+        self.offset(self.len())
+    }
+
+    // the row covering the position, positions past the end give the last row
+    pub(crate) fn index_at(&self, y: f32) -> usize {
+        let count = self.tree.len();
+        if count == 0 {
+            return 0;
+        }
+
+        let mut position = 0;
+        let mut remaining = y as f64;
+        let mut step = 1 << (usize::BITS - 1 - count.leading_zeros());
+        while step > 0 {
+            let next = position + step;
+            if next <= count && self.tree[next - 1] <= remaining {
+                position = next;
+                remaining -= self.tree[next - 1];
+            }
+            step >>= 1;
+        }
+        position.min(count - 1)
+    }
+}
+
+// rows showing items and rows waiting to be reused, a new row is only created when no free one is left
+pub(crate) struct RowPool<R: Widget> {
+    pub(crate) active: BTreeMap<usize, R>,
+    free: Vec<R>,
+    // the item whose row took the last press
+    pressed: Option<usize>
+}
+
+impl<R: Widget> RowPool<R> {
+    pub(crate) fn new() -> Self {
+        RowPool {
+            active: BTreeMap::new(),
+            free: vec![],
+            pressed: None
+        }
+    }
+
+    pub(crate) fn is_active(&self, index: usize) -> bool {
+        self.active.contains_key(&index)
+    }
+
+    // a free row for the item, the caller binds it
+    pub(crate) fn acquire(&mut self, index: usize, create: impl FnOnce() -> R) -> &mut R {
+        let row = self.free.pop().unwrap_or_else(create);
+        self.active.entry(index).or_insert(row)
+    }
+
+    pub(crate) fn release(&mut self, index: usize) {
+        if let Some(row) = self.active.remove(&index) {
+            self.free.push(row);
+        }
+        if self.pressed == Some(index) {
+            self.pressed = None;
+        }
+    }
+
+    pub(crate) fn release_outside(&mut self, range: Range<usize>) {
+        let outside: Vec<usize> = self.active.keys().copied().filter(|index| !range.contains(index)).collect();
+        for index in outside {
+            self.release(index);
+        }
+    }
+
+    pub(crate) fn release_all(&mut self) {
+        self.release_outside(0..0);
+    }
+
+    pub(crate) fn is_dirty(&self) -> bool {
+        self.active.values().any(|row| row.is_dirty())
+    }
+
+    // free rows still show what they drew for their last item until they are cleared here,
+    // a row that fails to draw does not keep the others from drawing
+    pub(crate) fn draw(&mut self, designer: &Designer, renderer: &mut WgpuRenderer) -> Result<(), PathError> {
+        for row in self.free.iter_mut() {
+            row.clear(renderer);
+        }
+        let mut result = Ok(());
+        for row in self.active.values_mut() {
+            if row.is_dirty() {
+                result = result.and(row.draw(designer, renderer));
+            }
+        }
+        result
+    }
+
+    pub(crate) fn clear(&mut self, renderer: &mut WgpuRenderer) {
+        for row in self.active.values_mut().chain(self.free.iter_mut()) {
+            row.clear(renderer);
+        }
+    }
+
+    pub(crate) fn handle_mouse_down(&mut self, x: f32, y: f32) -> bool {
+        let Some((&index, row)) = self.active.iter_mut().find(|(_, row)| row.bounds().contains(x, y)) else {
+            return false;
+        };
+        let handled = row.handle_mouse_down(x, y);
+        if handled {
+            self.pressed = Some(index);
+        }
+        handled
+    }
+
+    pub(crate) fn handle_mouse_move(&mut self, x: f32, y: f32) -> bool {
+        self.active.values_mut().fold(false, |changed, row| row.handle_mouse_move(x, y) | changed)
+    }
+
+    pub(crate) fn handle_mouse_up(&mut self, x: f32, y: f32) -> bool {
+        match self.pressed.take().and_then(|index| self.active.get_mut(&index)) {
+            Some(row) => row.handle_mouse_up(x, y),
+            None => false
+        }
+    }
+}
+
+// the scrolled content of a list or grid, it binds rows for what the viewport shows
+pub trait VirtualBody: Widget {
+    fn total_height(&self) -> f32;
+
+    fn total_width(&self) -> f32 {
+        0.0
+    }
+
+    // how far the content moved under the viewport since the last call, from rows above it changing height
+    fn take_shift(&mut self) -> f32 {
+        0.0
+    }
+
+    // drops every row and measurement, for when the items changed
+    fn reload(&mut self);
+
+    // binds the item again, for when it changed
+    fn invalidate(&mut self, index: usize);
+
+    // binds every bound row again, for changes that keep the items but change how they look
+    fn refresh(&mut self);
+
+    // where the item is relative to the top left of the content
+    fn item_rect(&self, index: usize) -> Option<Rect>;
+
+    // the items at least partly inside the viewport
+    fn visible_range(&self) -> Range<usize>;
+}
+
+// a scroll view around a body that only creates rows for the visible items, so its cost and the
+// renderer buffer updates follow the size of the viewport and not the number of items
+pub struct VirtualView<B: VirtualBody> {
+    scroll: ScrollView<B>
+}
+
+pub type ListView<A> = VirtualView<ListBody<A>>;
+
+impl<B: VirtualBody> VirtualView<B> {
+    pub(crate) fn wrap(body: B, bounds: Rect, theme: Theme) -> Self {
+        let size = [body.total_width(), body.total_height()];
+        let scroll = ScrollView::new(body, size, bounds, theme);
+        let mut view = VirtualView { scroll };
+        view.sync();
+        view
+    }
+
+    pub fn body(&self) -> &B {
+        self.scroll.content()
+    }
+
+    // call reload or invalidate after changing items through the body
+    pub fn body_mut(&mut self) -> &mut B {
+        self.scroll.content_mut()
+    }
+
+    pub fn scroll_view(&self) -> &ScrollView<B> {
+        &self.scroll
+    }
+
+    pub fn scroll_view_mut(&mut self) -> &mut ScrollView<B> {
+        &mut self.scroll
+    }
+
+    pub fn reload(&mut self) {
+        self.scroll.content_mut().reload();
+        self.sync();
+    }
+
+    pub fn invalidate(&mut self, index: usize) {
+        self.scroll.content_mut().invalidate(index);
+        self.sync();
+    }
+
+    pub fn refresh(&mut self) {
+        self.scroll.content_mut().refresh();
+        self.sync();
+    }
+
+    pub fn scroll_to_index(&mut self, index: usize) {
+        if let Some(rect) = self.scroll.content().item_rect(index) {
+            self.scroll.scroll_into_view(rect);
+            self.sync();
+        }
+    }
+
+    pub fn visible_range(&self) -> Range<usize> {
+        self.scroll.content().visible_range()
+    }
+
+    // measured rows change the content height and move what is above the viewport
+    fn sync(&mut self) {
+        for _ in 0..MAX_SYNC_PASSES {
+            let size = [self.scroll.content().total_width(), self.scroll.content().total_height()];
+            let shift = self.scroll.content_mut().take_shift();
+            let resized = self.scroll.content_size() != size;
+
+            if resized {
+                self.scroll.set_content_size(size);
+            }
+            if shift != 0.0 {
+                self.scroll.shift_offset([0.0, shift]);
+            }
+            if !resized && shift == 0.0 {
+                break;
+            }
+        }
+    }
+}
+
+impl<B: VirtualBody> Widget for VirtualView<B> {
+    fn base(&self) -> &WidgetBase {
+        self.scroll.base()
+    }
+
+    fn base_mut(&mut self) -> &mut WidgetBase {
+        self.scroll.base_mut()
+    }
+
+    fn handle_key(&mut self, event: &KeyEvent, modifiers: ModifiersState) -> bool {
+        let handled = self.scroll.handle_key(event, modifiers);
+        self.sync();
+        handled
+    }
+
+    fn handle_mouse_down(&mut self, x: f32, y: f32) -> bool {
+        let handled = self.scroll.handle_mouse_down(x, y);
+        self.sync();
+        handled
+    }
+
+    fn handle_mouse_move(&mut self, x: f32, y: f32) -> bool {
+        let handled = self.scroll.handle_mouse_move(x, y);
+        self.sync();
+        handled
+    }
+
+    fn handle_mouse_up(&mut self, x: f32, y: f32) -> bool {
+        let handled = self.scroll.handle_mouse_up(x, y);
+        self.sync();
+        handled
+    }
+
+    fn draw_content(&mut self, designer: &Designer, renderer: &mut WgpuRenderer, drawn: &mut Drawn) -> Result<(), PathError> {
+        self.scroll.draw_content(designer, renderer, drawn)
+    }
+
+    fn handle_scroll(&mut self, delta: &MouseScrollDelta, phase: TouchPhase, x: f32, y: f32) -> bool {
+        let handled = self.scroll.handle_scroll(delta, phase, x, y);
+        self.sync();
+        handled
+    }
+
+    fn tick(&mut self, elapsed: f32) -> bool {
+        let moving = self.scroll.tick(elapsed);
+        self.sync();
+        moving
+    }
+
+    fn clear(&mut self, renderer: &mut WgpuRenderer) {
+        self.scroll.clear(renderer);
+    }
+
+    fn set_bounds(&mut self, bounds: Rect) {
+        self.scroll.set_bounds(bounds);
+        self.sync();
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.scroll.is_dirty()
+    }
+}
+
+// rows stacked top to bottom, each as tall as its item needs
+pub struct ListBody<A: ListAdapter> {
+    base: WidgetBase,
+    adapter: A,
+    heights: HeightIndex,
+    rows: RowPool<A::Row>,
+    viewport: Rect,
+    shift: f32
+}
+
+impl<A: ListAdapter> ListBody<A> {
+    fn new(adapter: A, theme: Theme) -> Self {
+        let mut body = ListBody {
+            base: WidgetBase::new(Rect::default(), theme),
+            adapter,
+            heights: HeightIndex::new(vec![]),
+            rows: RowPool::new(),
+            viewport: Rect::default(),
+            shift: 0.0
+        };
+        body.reset_heights();
+        body
+    }
+
+    pub fn adapter(&self) -> &A {
+        &self.adapter
+    }
+
+    pub fn adapter_mut(&mut self) -> &mut A {
+        &mut self.adapter
+    }
+
+    fn reset_heights(&mut self) {
+        let heights = (0..self.adapter.len()).map(|index| self.adapter.estimated_height(index).max(0.0)).collect();
+        self.heights = HeightIndex::new(heights);
+    }
+
+    // binds the rows from the top of the overscan down to its bottom and places every bound row
+    fn layout(&mut self) {
+        let count = self.heights.len();
+        let bounds = self.base.bounds;
+        if count == 0 || self.viewport.is_empty() {
+            self.rows.release_all();
+            self.base.mark_dirty();
+            return;
+        }
+
+        let viewport_top = self.viewport.y - bounds.y;
+        let top = (viewport_top - OVERSCAN).max(0.0);
+        let bottom = self.viewport.bottom() - bounds.y + OVERSCAN;
+
+        // the row at the top of the viewport stays in place when rows above it are measured
+        let anchor = self.heights.index_at(viewport_top.max(0.0));
+        let anchor_offset = self.heights.offset(anchor);
+
+        let first = self.heights.index_at(top);
+        self.rows.release_outside(first..count);
+
+        let mut index = first;
+        let mut y = self.heights.offset(first);
+        while index < count && y < bottom {
+            if !self.rows.is_active(index) {
+                let adapter = &mut self.adapter;
+                let row = self.rows.acquire(index, || adapter.create_row());
+                let height = adapter.bind_row(row, index, bounds.width);
+                row.base_mut().mark_dirty();
+                self.heights.set(index, height.max(0.0));
+            }
+            y += self.heights.height(index);
+            index += 1;
+        }
+        self.rows.release_outside(first..index);
+
+        for (&index, row) in self.rows.active.iter_mut() {
+            let rect = Rect::new(bounds.x, bounds.y + self.heights.offset(index), bounds.width, self.heights.height(index));
+            if row.bounds() != rect {
+                row.set_bounds(rect);
+            }
+        }
+
+        self.shift += self.heights.offset(anchor) - anchor_offset;
+        self.base.mark_dirty();
+    }
+}
+
+impl<A: ListAdapter> ListView<A> {
+    pub fn new(adapter: A, bounds: Rect, theme: Theme) -> Self {
+        VirtualView::wrap(ListBody::new(adapter, theme.clone()), bounds, theme)
+    }
+}
+
+impl<A: ListAdapter> VirtualBody for ListBody<A> {
+    fn total_height(&self) -> f32 {
+        self.heights.total()
+    }
+
+    fn total_width(&self) -> f32 {
+        self.adapter.content_width()
+    }
+
+    fn take_shift(&mut self) -> f32 {
+        std::mem::take(&mut self.shift)
+    }
+
+    fn reload(&mut self) {
+        self.rows.release_all();
+        self.reset_heights();
+        self.shift = 0.0;
+        self.layout();
+    }
+
+    fn invalidate(&mut self, index: usize) {
+        self.rows.release(index);
+        self.layout();
+    }
+
+    fn refresh(&mut self) {
+        self.rows.release_all();
+        self.layout();
+    }
+
+    fn item_rect(&self, index: usize) -> Option<Rect> {
+        (index < self.heights.len())
+            .then(|| Rect::new(0.0, self.heights.offset(index), self.base.bounds.width, self.heights.height(index)))
+    }
+
+    fn visible_range(&self) -> Range<usize> {
+        if self.heights.len() == 0 || self.viewport.is_empty() {
+            return 0..0;
+        }
+        let top = self.viewport.y - self.base.bounds.y;
+        let first = self.heights.index_at(top.max(0.0));
+        let last = self.heights.index_at((top + self.viewport.height).max(0.0) - 0.01);
+        first..last + 1
+    }
+}
+
+impl<A: ListAdapter> Widget for ListBody<A> {
+    fn base(&self) -> &WidgetBase {
+        &self.base
+    }
+
+    fn base_mut(&mut self) -> &mut WidgetBase {
+        &mut self.base
+    }
+
+    fn handle_key(&mut self, _event: &KeyEvent, _modifiers: ModifiersState) -> bool {
+        false
+    }
+
+    fn handle_mouse_down(&mut self, x: f32, y: f32) -> bool {
+        self.rows.handle_mouse_down(x, y)
+    }
+
+    fn handle_mouse_move(&mut self, x: f32, y: f32) -> bool {
+        self.rows.handle_mouse_move(x, y)
+    }
+
+    fn handle_mouse_up(&mut self, x: f32, y: f32) -> bool {
+        self.rows.handle_mouse_up(x, y)
+    }
+
+    fn draw_content(&mut self, designer: &Designer, renderer: &mut WgpuRenderer, _drawn: &mut Drawn) -> Result<(), PathError> {
+        self.rows.draw(designer, renderer)
+    }
+
+    fn clear(&mut self, renderer: &mut WgpuRenderer) {
+        self.rows.clear(renderer);
+        self.base.mark_dirty();
+    }
+
+    // rows are placed when the viewport is set, which the scroll view does right after moving the content,
+    // a new width binds every row again since their heights can depend on it
+    fn set_bounds(&mut self, bounds: Rect) {
+        if bounds.width != self.base.bounds.width {
+            self.rows.release_all();
+        }
+        self.base.bounds = bounds;
+        self.base.mark_dirty();
+    }
+
+    fn set_viewport(&mut self, viewport: Rect) {
+        self.viewport = viewport;
+        self.layout();
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.base.dirty || self.rows.is_dirty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn heights(count: usize) -> Vec<f32> {
+        (0..count).map(|i| 10.0 + (i % 7) as f32 * 3.5).collect()
+    }
+
+    #[test]
+    fn offsets_are_prefix_sums() {
+        let heights = heights(13);
+        let index = HeightIndex::new(heights.clone());
+
+        let mut top = 0.0;
+        for (i, height) in heights.iter().enumerate() {
+            assert_eq!(index.offset(i), top);
+            top += height;
+        }
+        assert_eq!(index.total(), top);
+        assert_eq!(index.offset(100), top);
+    }
+
+    #[test]
+    fn finds_the_row_at_a_position() {
+        let index = HeightIndex::new(heights(13));
+        for i in 0..13 {
+            assert_eq!(index.index_at(index.offset(i)), i);
+            assert_eq!(index.index_at(index.offset(i) + index.height(i) - 0.5), i);
+        }
+        assert_eq!(index.index_at(-5.0), 0);
+        assert_eq!(index.index_at(index.total() + 100.0), 12);
+        assert_eq!(HeightIndex::new(vec![]).index_at(10.0), 0);
+    }
+
+    #[test]
+    fn changed_heights_move_the_rows_below() {
+        let mut index = HeightIndex::new(vec![20.0; 8]);
+        index.set(2, 50.0);
+        index.set(20, 50.0);
+
+        assert_eq!(index.offset(2), 40.0);
+        assert_eq!(index.offset(3), 90.0);
+        assert_eq!(index.total(), 190.0);
+        assert_eq!(index.index_at(89.0), 2);
+        assert_eq!(index.index_at(90.0), 3);
+    }
+
+    #[test]
+    fn long_lists_stay_exact() {
+        let index = HeightIndex::new(vec![20.1; 100_000]);
+        assert_eq!(index.offset(99_999), (99_999.0 * 20.1f32 as f64) as f32);
+        assert_eq!(index.index_at(index.offset(99_999)), 99_999);
+    }
+}
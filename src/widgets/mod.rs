@@ -0,0 +1,426 @@
+pub mod text_input;
+pub mod theme;
+pub mod label;
+pub mod button;
+pub mod checkbox;
+pub mod radio;
+pub mod toggle;
+pub mod slider;
+pub mod scroll_view;
+pub mod list_view;
+pub mod grid_view;
+pub mod table;
+pub mod popup;
+pub mod dropdown;
+pub mod context_menu;
+pub mod combo_box;
+pub mod dialog;
+pub mod tooltip;
+pub mod split_pane;
+pub mod tabs;
+pub mod dock;
+
+use std::sync::{Arc, Mutex};
+use winit::event::{ElementState, Ime, KeyEvent, MouseScrollDelta, TouchPhase};
+use winit::keyboard::{Key, ModifiersState, NamedKey};
+use crate::designer::Designer;
+use crate::graphics::color::Color;
+use crate::graphics::instance::InstanceId;
+use crate::graphics::path::PathError;
+use crate::graphics::sdf::{Border, RoundedRect, ShapeStyle};
+use crate::input::focus::{FocusManager, NodeHandle};
+use crate::render::wgpu::{SdfId, TextId, WgpuRenderer};
+use crate::scene::Rect;
+use crate::text::font::Font;
+use crate::text::layout::{layout_text, TextStyle};
+use crate::widgets::theme::{StyleHook, Theme, WidgetKind, WidgetStyle};
+
+pub type ClickHandler = Box<dyn FnMut() + Send>;
+pub type ToggleHandler = Box<dyn FnMut(bool) + Send>;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct WidgetState {
+    pub hovered: bool,
+    pub pressed: bool,
+    pub disabled: bool,
+    pub focused: bool
+}
+
+// what every widget keeps: where it is, how it is styled, its interaction state and what it drew last
+pub struct WidgetBase {
+    pub bounds: Rect,
+    pub state: WidgetState,
+    theme: Theme,
+    style_hook: Option<StyleHook>,
+    node: Option<NodeHandle>,
+    drawn: Drawn,
+    dirty: bool
+}
+
+impl WidgetBase {
+    pub fn new(bounds: Rect, theme: Theme) -> Self {
+        WidgetBase {
+            bounds,
+            state: WidgetState::default(),
+            theme,
+            style_hook: None,
+            node: None,
+            drawn: Drawn::default(),
+            dirty: true
+        }
+    }
+
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    pub fn style(&self, kind: WidgetKind) -> WidgetStyle {
+        let mut style = self.theme.resolve(kind, self.state);
+        if let Some(hook) = &self.style_hook {
+            hook(kind, self.state, &mut style);
+        }
+        style
+    }
+
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    // returns whether the hover state changed
+    pub fn set_hovered(&mut self, hovered: bool) -> bool {
+        let hovered = hovered && !self.state.disabled;
+        if self.state.hovered == hovered {
+            return false;
+        }
+        self.state.hovered = hovered;
+        self.dirty = true;
+        true
+    }
+
+    // a press moves the keyboard focus to the widget, once it is attached to a focus manager
+    pub fn take_focus(&self) {
+        if let Some(node) = &self.node {
+            node.focus();
+        }
+    }
+
+    pub fn set_pressed(&mut self, pressed: bool) {
+        if self.state.pressed != pressed {
+            self.state.pressed = pressed;
+            self.dirty = true;
+        }
+    }
+}
+
+// drawables created by the last draw, removed before drawing again
+#[derive(Default)]
+pub struct Drawn {
+    pub sdf: Vec<SdfId>,
+    pub instances: Vec<InstanceId>,
+    pub texts: Vec<TextId>
+}
+
+impl Drawn {
+    pub fn clear(&mut self, renderer: &mut WgpuRenderer) {
+        for id in self.sdf.drain(..) {
+            renderer.remove_sdf(id);
+        }
+        for id in self.instances.drain(..) {
+            renderer.remove_instance(id);
+        }
+        for id in self.texts.drain(..) {
+            renderer.remove_text(id);
+        }
+    }
+
+    pub fn rounded_rect(
+        &mut self,
+        designer: &Designer,
+        renderer: &mut WgpuRenderer,
+        bounds: Rect,
+        radius: f32,
+        fill: Color,
+        border: Option<Border>
+    ) {
+        let mut rect = RoundedRect::new(bounds, radius, fill);
+        rect.border = border.filter(|b| b.width > 0.0 && b.color.a > 0.0);
+        self.sdf.push(designer.create_rounded_rect(renderer, &rect));
+    }
+
+    pub fn circle(&mut self, designer: &Designer, renderer: &mut WgpuRenderer, center: [f32; 2], radius: f32, style: &ShapeStyle) {
+        self.sdf.push(designer.create_circle(renderer, center, radius, style));
+    }
+
+    // draws a single line of text at the left of area and vertically centered in it, returns its width
+    pub fn text(&mut self, renderer: &mut WgpuRenderer, font: &Font, text: &str, style: &TextStyle, area: Rect, color: Color) -> f32 {
+        let mut style = style.clone();
+        style.wrap = false;
+        let layout = layout_text(font, text, &style, renderer.size);
+
+        let origin = [area.x, area.y + (area.height - layout.height) / 2.0];
+        if let Some(id) = renderer.add_text(font, &layout.glyphs, origin, style.size, color.to_linear_rgb()) {
+            self.texts.push(id);
+        }
+        layout.width
+    }
+}
+
+// every widget shares the same focus, theming and drawing plumbing through its base
+pub trait Widget: Send {
+    fn base(&self) -> &WidgetBase;
+    fn base_mut(&mut self) -> &mut WidgetBase;
+
+    fn handle_key(&mut self, event: &KeyEvent, modifiers: ModifiersState) -> bool;
+    fn handle_mouse_down(&mut self, x: f32, y: f32) -> bool;
+    fn handle_mouse_move(&mut self, x: f32, y: f32) -> bool;
+    fn handle_mouse_up(&mut self, x: f32, y: f32) -> bool;
+    fn draw_content(&mut self, designer: &Designer, renderer: &mut WgpuRenderer, drawn: &mut Drawn) -> Result<(), PathError>;
+
+    // mouse wheel and trackpad scrolling at the pointer, only containers that scroll handle it
+    fn handle_scroll(&mut self, _delta: &MouseScrollDelta, _phase: TouchPhase, _x: f32, _y: f32) -> bool {
+        false
+    }
+
+    // only widgets that edit text take input method events
+    fn accepts_ime(&self) -> bool {
+        false
+    }
+
+    fn handle_ime(&mut self, _ime: &Ime) -> bool {
+        false
+    }
+
+    // advances motion that runs on its own like scrolling inertia, returns whether it is still moving
+    fn tick(&mut self, _elapsed: f32) -> bool {
+        false
+    }
+
+    // the part of the widget a scroll view currently shows, in window coordinates
+    fn set_viewport(&mut self, _viewport: Rect) {}
+
+    fn draw(&mut self, designer: &Designer, renderer: &mut WgpuRenderer) -> Result<(), PathError> {
+        let mut drawn = std::mem::take(&mut self.base_mut().drawn);
+        drawn.clear(renderer);
+        let result = self.draw_content(designer, renderer, &mut drawn);
+
+        let base = self.base_mut();
+        base.drawn = drawn;
+        base.dirty = false;
+        result
+    }
+
+    // removes everything the widget drew
+    fn clear(&mut self, renderer: &mut WgpuRenderer) {
+        self.base_mut().drawn.clear(renderer);
+        self.base_mut().dirty = true;
+    }
+
+    fn bounds(&self) -> Rect {
+        self.base().bounds
+    }
+
+    fn set_bounds(&mut self, bounds: Rect) {
+        let base = self.base_mut();
+        base.bounds = bounds;
+        base.dirty = true;
+        if let Some(node) = &base.node {
+            node.set_bounds(bounds);
+        }
+    }
+
+    fn state(&self) -> WidgetState {
+        self.base().state
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.base().dirty
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        let base = self.base_mut();
+        base.state.focused = focused;
+        if !focused {
+            base.state.pressed = false;
+        }
+        base.dirty = true;
+    }
+
+    // disabled widgets ignore input and are skipped by tab traversal
+    fn set_enabled(&mut self, enabled: bool) {
+        let base = self.base_mut();
+        base.state.disabled = !enabled;
+        if !enabled {
+            base.state.hovered = false;
+            base.state.pressed = false;
+        }
+        if let Some(node) = &base.node {
+            node.set_enabled(enabled);
+        }
+        base.dirty = true;
+    }
+
+    fn set_theme(&mut self, theme: Theme) {
+        let base = self.base_mut();
+        base.theme = theme;
+        base.dirty = true;
+    }
+
+    fn on_style(&mut self, hook: impl Fn(WidgetKind, WidgetState, &mut WidgetStyle) + Send + 'static) where Self: Sized {
+        let base = self.base_mut();
+        base.style_hook = Some(Box::new(hook));
+        base.dirty = true;
+    }
+}
+
+// lets containers hold any widget, every method is passed through so overrides are kept
+impl<W: Widget + ?Sized> Widget for Box<W> {
+    fn base(&self) -> &WidgetBase {
+        (**self).base()
+    }
+
+    fn base_mut(&mut self) -> &mut WidgetBase {
+        (**self).base_mut()
+    }
+
+    fn handle_key(&mut self, event: &KeyEvent, modifiers: ModifiersState) -> bool {
+        (**self).handle_key(event, modifiers)
+    }
+
+    fn handle_mouse_down(&mut self, x: f32, y: f32) -> bool {
+        (**self).handle_mouse_down(x, y)
+    }
+
+    fn handle_mouse_move(&mut self, x: f32, y: f32) -> bool {
+        (**self).handle_mouse_move(x, y)
+    }
+
+    fn handle_mouse_up(&mut self, x: f32, y: f32) -> bool {
+        (**self).handle_mouse_up(x, y)
+    }
+
+    fn draw_content(&mut self, designer: &Designer, renderer: &mut WgpuRenderer, drawn: &mut Drawn) -> Result<(), PathError> {
+        (**self).draw_content(designer, renderer, drawn)
+    }
+
+    fn handle_scroll(&mut self, delta: &MouseScrollDelta, phase: TouchPhase, x: f32, y: f32) -> bool {
+        (**self).handle_scroll(delta, phase, x, y)
+    }
+
+    fn accepts_ime(&self) -> bool {
+        (**self).accepts_ime()
+    }
+
+    fn handle_ime(&mut self, ime: &Ime) -> bool {
+        (**self).handle_ime(ime)
+    }
+
+    fn tick(&mut self, elapsed: f32) -> bool {
+        (**self).tick(elapsed)
+    }
+
+    fn set_viewport(&mut self, viewport: Rect) {
+        (**self).set_viewport(viewport)
+    }
+
+    fn draw(&mut self, designer: &Designer, renderer: &mut WgpuRenderer) -> Result<(), PathError> {
+        (**self).draw(designer, renderer)
+    }
+
+    fn clear(&mut self, renderer: &mut WgpuRenderer) {
+        (**self).clear(renderer)
+    }
+
+    fn set_bounds(&mut self, bounds: Rect) {
+        (**self).set_bounds(bounds)
+    }
+
+    fn is_dirty(&self) -> bool {
+        (**self).is_dirty()
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        (**self).set_focused(focused)
+    }
+
+    fn set_enabled(&mut self, enabled: bool) {
+        (**self).set_enabled(enabled)
+    }
+
+    fn set_theme(&mut self, theme: Theme) {
+        (**self).set_theme(theme)
+    }
+}
+
+// registers the widget with the focus manager so it receives keyboard events while focused,
+// the node is removed again once the widget and the returned handle are both dropped
+pub fn attach<W: Widget + 'static>(widget: &Arc<Mutex<W>>, focus: &FocusManager, tab_index: i32) -> NodeHandle {
+    let handle = {
+        let mut locked = widget.lock().unwrap();
+        let handle = focus.register(locked.bounds(), tab_index);
+        handle.set_enabled(!locked.state().disabled);
+        locked.base_mut().node = Some(handle.clone());
+        handle
+    };
+
+    // the manager only holds weak references, so it never keeps a widget alive
+    let key_widget = Arc::downgrade(widget);
+    handle.on_key(move |event, modifiers| {
+        key_widget.upgrade().is_some_and(|widget| widget.lock().unwrap().handle_key(event, modifiers))
+    });
+
+    if widget.lock().unwrap().accepts_ime() {
+        let ime_widget = Arc::downgrade(widget);
+        handle.on_ime(move |ime| ime_widget.upgrade().is_some_and(|widget| widget.lock().unwrap().handle_ime(ime)));
+    }
+
+    let focus_widget = Arc::downgrade(widget);
+    handle.on_focus_changed(move |focused| {
+        if let Some(widget) = focus_widget.upgrade() {
+            widget.lock().unwrap().set_focused(focused);
+        }
+    });
+
+    handle
+}
+
+// space and enter activate buttons and toggles, like on the web
+pub fn is_activation_key(event: &KeyEvent) -> bool {
+    event.state == ElementState::Pressed
+        && !event.repeat
+        && matches!(event.logical_key, Key::Named(NamedKey::Space) | Key::Named(NamedKey::Enter))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::text::font::test_font;
+    use crate::widgets::button::Button;
+
+    #[test]
+    fn attached_widgets_are_not_kept_alive() {
+        let focus = FocusManager::new();
+        let button = Arc::new(Mutex::new(Button::new(test_font(), "ok", Rect::new(0.0, 0.0, 10.0, 10.0), Theme::default())));
+        let weak = Arc::downgrade(&button);
+
+        let handle = attach(&button, &focus, 0);
+        assert!(handle.focus());
+        drop(handle);
+        assert!(focus.focused().is_some());
+
+        drop(button);
+        assert!(weak.upgrade().is_none());
+        assert_eq!(focus.focused(), None);
+    }
+
+    #[test]
+    fn pressed_widgets_take_focus_while_locked() {
+        let focus = FocusManager::new();
+        let button = Arc::new(Mutex::new(Button::new(test_font(), "ok", Rect::new(0.0, 0.0, 10.0, 10.0), Theme::default())));
+        let handle = attach(&button, &focus, 0);
+
+        assert!(button.lock().unwrap().handle_mouse_down(5.0, 5.0));
+        assert!(handle.is_focused());
+        focus.deliver_notifications();
+        assert!(button.lock().unwrap().state().focused);
+    }
+}
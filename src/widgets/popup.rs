@@ -0,0 +1,784 @@
+use std::sync::{Arc, Mutex};
+use winit::dpi::PhysicalSize;
+use winit::event::{ElementState, KeyEvent, MouseScrollDelta, TouchPhase};
+use winit::keyboard::{Key, ModifiersState, NamedKey};
+use crate::designer::point::Measurement;
+use crate::designer::Designer;
+use crate::graphics::color::Color;
+use crate::graphics::layer::{LayerId, LayerOptions};
+use crate::graphics::path::{FillRule, Path, PathError};
+use crate::graphics::sdf::{Border, BoxShadow, RoundedRect};
+use crate::render::wgpu::WgpuRenderer;
+use crate::scene::Rect;
+use crate::text::font::Font;
+use crate::text::layout::{measure_text, TextStyle};
+use crate::widgets::theme::Theme;
+use crate::widgets::Drawn;
+
+// space between the edge of a popup list and its first and last row
+const PANEL_INSET: f32 = 4.0;
+// longer lists scroll
+const MAX_VISIBLE_ROWS: usize = 12;
+// wheel lines scroll this many rows
+const ROWS_PER_LINE: f32 = 1.0;
+
+// something drawn above all other content while open, like the list of a dropdown or a context menu,
+// the popup layer gives it every pointer and key event first
+pub trait Popup: Send {
+    // whether the point is on any part of the popup, including open submenus
+    fn popup_contains(&self, x: f32, y: f32) -> bool;
+    fn popup_mouse_down(&mut self, x: f32, y: f32) -> bool;
+    fn popup_mouse_move(&mut self, x: f32, y: f32) -> bool;
+    fn popup_mouse_up(&mut self, x: f32, y: f32) -> bool;
+    fn popup_key(&mut self, event: &KeyEvent, modifiers: ModifiersState) -> bool;
+
+    fn popup_scroll(&mut self, _delta: &MouseScrollDelta, _x: f32, _y: f32) -> bool {
+        false
+    }
+
+    // draws into overlays of its own, which keeps it above everything drawn before it opened
+    fn draw_popup(&mut self, designer: &Designer, renderer: &mut WgpuRenderer) -> Result<(), PathError>;
+    fn clear_popup(&mut self, renderer: &mut WgpuRenderer);
+    fn is_popup_dirty(&self) -> bool;
+
+    // closes the popup after a click outside of it or escape, without choosing anything
+    fn dismiss(&mut self);
+
+    fn set_popup_handle(&mut self, handle: PopupHandle);
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PopupId(pub u32);
+
+struct PopupEntry {
+    id: PopupId,
+    popup: Arc<Mutex<dyn Popup>>
+}
+
+#[derive(Default)]
+struct PopupState {
+    entries: Vec<PopupEntry>,
+    // open popups, bottom to top
+    open: Vec<PopupId>,
+    // closed since the last draw, what they drew is removed on the next one
+    closed: Vec<PopupId>,
+    // the popup that received the press gets the release even outside of it
+    pressed: Option<PopupId>,
+    // a press outside every popup closes them, its release is swallowed too
+    swallow_release: bool,
+    window: PhysicalSize<u32>,
+    next_id: u32
+}
+
+impl PopupState {
+    fn popup(&self, id: PopupId) -> Option<Arc<Mutex<dyn Popup>>> {
+        self.entries.iter().find(|e| e.id == id).map(|e| Arc::clone(&e.popup))
+    }
+}
+
+// routes input to open popups before the content below them and draws them in the order they opened
+#[derive(Clone, Default)]
+pub struct PopupLayer {
+    state: Arc<Mutex<PopupState>>
+}
+
+impl PopupLayer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register<P: Popup + 'static>(&self, popup: &Arc<Mutex<P>>) -> PopupHandle {
+        let id = {
+            let mut state = self.state.lock().unwrap();
+            let id = PopupId(state.next_id);
+            state.next_id += 1;
+            state.entries.push(PopupEntry { id, popup: Arc::clone(popup) as Arc<Mutex<dyn Popup>> });
+            id
+        };
+
+        let handle = PopupHandle { id, layer: self.clone() };
+        popup.lock().unwrap().set_popup_handle(handle.clone());
+        handle
+    }
+
+    // the popup should be cleared before, it is not drawn again
+    pub fn unregister(&self, id: PopupId) {
+        let mut state = self.state.lock().unwrap();
+        state.entries.retain(|e| e.id != id);
+        state.open.retain(|open| *open != id);
+        state.closed.retain(|closed| *closed != id);
+    }
+
+    pub fn window_size(&self) -> PhysicalSize<u32> {
+        self.state.lock().unwrap().window
+    }
+
+    // popups are kept inside the window, call it whenever the window resizes
+    pub fn set_window_size(&self, size: PhysicalSize<u32>) {
+        self.state.lock().unwrap().window = size;
+    }
+
+    pub fn has_open(&self) -> bool {
+        !self.state.lock().unwrap().open.is_empty()
+    }
+
+    pub fn is_open(&self, id: PopupId) -> bool {
+        self.state.lock().unwrap().open.contains(&id)
+    }
+
+    // opening an open popup moves it to the top
+    pub fn open(&self, id: PopupId) {
+        let mut state = self.state.lock().unwrap();
+        if state.entries.iter().any(|e| e.id == id) {
+            state.open.retain(|open| *open != id);
+            state.open.push(id);
+        }
+    }
+
+    pub fn close(&self, id: PopupId) {
+        let mut state = self.state.lock().unwrap();
+        if state.open.contains(&id) {
+            state.open.retain(|open| *open != id);
+            state.closed.push(id);
+        }
+        if state.pressed == Some(id) {
+            state.pressed = None;
+        }
+    }
+
+    // top to bottom
+    fn open_popups(&self) -> Vec<(PopupId, Arc<Mutex<dyn Popup>>)> {
+        let state = self.state.lock().unwrap();
+        state.open.iter().rev().filter_map(|id| Some((*id, state.popup(*id)?))).collect()
+    }
+
+    fn popup_at(&self, x: f32, y: f32) -> Option<(PopupId, Arc<Mutex<dyn Popup>>)> {
+        self.open_popups().into_iter().find(|(_, popup)| popup.lock().unwrap().popup_contains(x, y))
+    }
+
+    fn dismiss(&self, id: PopupId, popup: &Arc<Mutex<dyn Popup>>) {
+        popup.lock().unwrap().dismiss();
+        self.close(id);
+    }
+
+    pub fn dismiss_all(&self) {
+        for (id, popup) in self.open_popups() {
+            self.dismiss(id, &popup);
+        }
+    }
+
+    // the state lock is never held while a popup handles an event, so popups can open and close others
+    pub fn handle_mouse_down(&self, x: f32, y: f32) -> bool {
+        if !self.has_open() {
+            return false;
+        }
+
+        match self.popup_at(x, y) {
+            Some((id, popup)) => {
+                self.state.lock().unwrap().pressed = Some(id);
+                popup.lock().unwrap().popup_mouse_down(x, y);
+            }
+            None => {
+                self.state.lock().unwrap().swallow_release = true;
+                self.dismiss_all();
+            }
+        }
+        true
+    }
+
+    // every open popup sees the pointer so hover states clear when it leaves, returns whether any
+    // popup is under the pointer or changed
+    pub fn handle_mouse_move(&self, x: f32, y: f32) -> bool {
+        let mut handled = false;
+        for (_, popup) in self.open_popups() {
+            let mut popup = popup.lock().unwrap();
+            handled |= popup.popup_mouse_move(x, y) | popup.popup_contains(x, y);
+        }
+        handled
+    }
+
+    pub fn handle_mouse_up(&self, x: f32, y: f32) -> bool {
+        let (pressed, swallowed) = {
+            let mut state = self.state.lock().unwrap();
+            let pressed = state.pressed.take().and_then(|id| state.popup(id));
+            (pressed, std::mem::take(&mut state.swallow_release))
+        };
+
+        match pressed {
+            Some(popup) => {
+                popup.lock().unwrap().popup_mouse_up(x, y);
+                true
+            }
+            None => swallowed
+        }
+    }
+
+    // the topmost popup gets the key first, escape closes it when it does not handle escape itself;
+    // keys it leaves alone go on to the focused widget
+    pub fn handle_key(&self, event: &KeyEvent, modifiers: ModifiersState) -> bool {
+        let Some((id, popup)) = self.open_popups().into_iter().next() else {
+            return false;
+        };
+
+        if popup.lock().unwrap().popup_key(event, modifiers) {
+            return true;
+        }
+        if event.state == ElementState::Pressed && event.logical_key == Key::Named(NamedKey::Escape) {
+            self.dismiss(id, &popup);
+            return true;
+        }
+        false
+    }
+
+    // scrolling outside of the popups closes them and still scrolls the content below
+    pub fn handle_scroll(&self, delta: &MouseScrollDelta, _phase: TouchPhase, x: f32, y: f32) -> bool {
+        if !self.has_open() {
+            return false;
+        }
+
+        match self.popup_at(x, y) {
+            Some((_, popup)) => {
+                popup.lock().unwrap().popup_scroll(delta, x, y);
+                true
+            }
+            None => {
+                self.dismiss_all();
+                false
+            }
+        }
+    }
+
+    // popups may ask the layer about the window while checking, so the state lock is released first
+    pub fn is_dirty(&self) -> bool {
+        if !self.state.lock().unwrap().closed.is_empty() {
+            return true;
+        }
+        self.open_popups().into_iter().any(|(_, popup)| popup.lock().unwrap().is_popup_dirty())
+    }
+
+    // draw after all other content, closed popups are cleared and open ones drawn bottom to top
+    pub fn draw(&self, designer: &Designer, renderer: &mut WgpuRenderer) -> Result<(), PathError> {
+        let closed: Vec<Arc<Mutex<dyn Popup>>> = {
+            let mut state = self.state.lock().unwrap();
+            let closed = std::mem::take(&mut state.closed);
+            closed.into_iter().filter_map(|id| state.popup(id)).collect()
+        };
+        for popup in closed {
+            popup.lock().unwrap().clear_popup(renderer);
+        }
+
+        let mut result = Ok(());
+        for (_, popup) in self.open_popups().into_iter().rev() {
+            let mut popup = popup.lock().unwrap();
+            if popup.is_popup_dirty() {
+                result = result.and(popup.draw_popup(designer, renderer));
+            }
+        }
+        result
+    }
+}
+
+// what a popup keeps to open and close itself
+#[derive(Clone)]
+pub struct PopupHandle {
+    id: PopupId,
+    layer: PopupLayer
+}
+
+impl PopupHandle {
+    pub fn id(&self) -> PopupId {
+        self.id
+    }
+
+    pub fn open(&self) {
+        self.layer.open(self.id);
+    }
+
+    pub fn close(&self) {
+        self.layer.close(self.id);
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.layer.is_open(self.id)
+    }
+
+    pub fn window_size(&self) -> PhysicalSize<u32> {
+        self.layer.window_size()
+    }
+
+    pub fn window(&self) -> Rect {
+        let size = self.window_size();
+        Rect::new(0.0, 0.0, size.width as f32, size.height as f32)
+    }
+}
+
+// keeps a span of the given length inside min..max, preferring start
+fn fit(start: f32, length: f32, min: f32, max: f32) -> f32 {
+    start.min(max - length).max(min)
+}
+
+// below the anchor, or above it when it does not fit below and there is more room above; the right
+// edges are aligned when it does not fit to the right, and the height shrinks to the room there is
+pub fn place_below(anchor: Rect, size: [f32; 2], window: Rect) -> Rect {
+    let [width, height] = size;
+    let below = window.bottom() - anchor.bottom();
+    let above = anchor.y - window.y;
+
+    let (y, height) = if height <= below || below >= above {
+        (anchor.bottom(), height.min(below.max(0.0)))
+    } else {
+        let height = height.min(above.max(0.0));
+        (anchor.y - height, height)
+    };
+
+    let x = if anchor.x + width > window.right() && anchor.right() - width >= window.x {
+        anchor.right() - width
+    } else {
+        fit(anchor.x, width, window.x, window.right())
+    };
+    Rect::new(x, y, width.min(window.width), height)
+}
+
+// to the right of the anchor, or to its left when it does not fit and there is more room there,
+// for submenus next to the row that opened them
+pub fn place_beside(anchor: Rect, size: [f32; 2], window: Rect) -> Rect {
+    let [width, height] = size;
+    let right = window.right() - anchor.right();
+    let left = anchor.x - window.x;
+
+    let x = if width <= right || right >= left {
+        anchor.right()
+    } else {
+        anchor.x - width
+    };
+    let height = height.min(window.height);
+    Rect::new(fit(x, width, window.x, window.right()), fit(anchor.y, height, window.y, window.bottom()), width, height)
+}
+
+// one row of a popup list
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct PanelRow {
+    pub label: String,
+    // shown at the right, like a keyboard shortcut
+    pub detail: Option<String>,
+    pub enabled: bool,
+    pub submenu: bool,
+    pub separator: bool
+}
+
+impl PanelRow {
+    pub fn new(label: &str) -> Self {
+        PanelRow {
+            label: label.to_string(),
+            enabled: true,
+            ..PanelRow::default()
+        }
+    }
+
+    fn selectable(&self) -> bool {
+        self.enabled && !self.separator
+    }
+}
+
+// a list of rows drawn into an overlay of its own, shared by the dropdown, combo box and context menu
+pub(crate) struct PanelView {
+    pub rows: Vec<PanelRow>,
+    pub bounds: Rect,
+    pub highlighted: Option<usize>,
+    // first row shown when there are more than fit
+    first: usize,
+    overlay: Option<LayerId>,
+    drawn: Drawn,
+    pub dirty: bool
+}
+
+impl PanelView {
+    pub fn new() -> Self {
+        PanelView {
+            rows: vec![],
+            bounds: Rect::default(),
+            highlighted: None,
+            first: 0,
+            overlay: None,
+            drawn: Drawn::default(),
+            dirty: true
+        }
+    }
+
+    pub fn set_rows(&mut self, rows: Vec<PanelRow>) {
+        self.rows = rows;
+        self.first = 0;
+        self.highlighted = None;
+        self.dirty = true;
+    }
+
+    fn row_height(&self, index: usize, theme: &Theme) -> f32 {
+        if self.rows[index].separator {
+            theme.padding + 1.0
+        } else {
+            theme.text.size * 2.0
+        }
+    }
+
+    // the size showing every row up to the limit, wide enough for the longest
+    pub fn measure(&self, font: &Font, theme: &Theme, window: PhysicalSize<u32>) -> [f32; 2] {
+        let mut width: f32 = 0.0;
+        for row in self.rows.iter().filter(|row| !row.separator) {
+            let mut row_width = measure_text(font, &row.label, &theme.text, window)[0];
+            if let Some(detail) = &row.detail {
+                row_width += theme.padding * 3.0 + measure_text(font, detail, &theme.text, window)[0];
+            }
+            if row.submenu {
+                row_width += theme.padding + theme.text.size * 0.5;
+            }
+            width = width.max(row_width);
+        }
+
+        let height: f32 = (0..self.rows.len()).take(MAX_VISIBLE_ROWS).map(|index| self.row_height(index, theme)).sum();
+        [width + theme.padding * 2.0 + PANEL_INSET * 2.0, height + PANEL_INSET * 2.0]
+    }
+
+    // the rows that fit from the first shown one, with their rectangles
+    pub fn visible_rows(&self, theme: &Theme) -> Vec<(usize, Rect)> {
+        let inner = Rect::new(self.bounds.x + PANEL_INSET, self.bounds.y + PANEL_INSET, self.bounds.width - PANEL_INSET * 2.0, self.bounds.height - PANEL_INSET * 2.0);
+        let mut y = inner.y;
+        let mut rows = vec![];
+        for index in self.first..self.rows.len() {
+            let height = self.row_height(index, theme);
+            if y + height > inner.bottom() + 0.5 {
+                break;
+            }
+            rows.push((index, Rect::new(inner.x, y, inner.width, height)));
+            y += height;
+        }
+        rows
+    }
+
+    pub fn row_rect(&self, index: usize, theme: &Theme) -> Option<Rect> {
+        self.visible_rows(theme).into_iter().find(|(row, _)| *row == index).map(|(_, rect)| rect)
+    }
+
+    // the selectable row under the point
+    pub fn row_at(&self, x: f32, y: f32, theme: &Theme) -> Option<usize> {
+        self.visible_rows(theme)
+            .into_iter()
+            .find(|(_, rect)| rect.contains(x, y))
+            .map(|(index, _)| index)
+            .filter(|index| self.rows[*index].selectable())
+    }
+
+    pub fn highlight(&mut self, index: Option<usize>, theme: &Theme) -> bool {
+        if self.highlighted == index {
+            return false;
+        }
+        self.highlighted = index;
+        if let Some(index) = index {
+            self.scroll_to(index, theme);
+        }
+        self.dirty = true;
+        true
+    }
+
+    // moves the highlight to the next selectable row, wrapping around at the ends
+    pub fn move_highlight(&mut self, forward: bool, theme: &Theme) -> bool {
+        let count = self.rows.len();
+        if count == 0 {
+            return false;
+        }
+        let start = match (self.highlighted, forward) {
+            (Some(index), _) => index,
+            (None, true) => count - 1,
+            (None, false) => 0
+        };
+        let next = (1..=count)
+            .map(|step| if forward { (start + step) % count } else { (start + count - step) % count })
+            .find(|index| self.rows[*index].selectable());
+        self.highlight(next, theme)
+    }
+
+    pub fn highlight_edge(&mut self, last: bool, theme: &Theme) -> bool {
+        let mut selectable = (0..self.rows.len()).filter(|index| self.rows[*index].selectable());
+        let index = if last { selectable.next_back() } else { selectable.next() };
+        self.highlight(index, theme)
+    }
+
+    // scrolls so the row is shown
+    pub fn scroll_to(&mut self, index: usize, theme: &Theme) {
+        if index < self.first {
+            self.first = index;
+            self.dirty = true;
+            return;
+        }
+        while self.first < index && !self.visible_rows(theme).iter().any(|(row, _)| *row == index) {
+            self.first += 1;
+            self.dirty = true;
+        }
+    }
+
+    pub fn scroll(&mut self, delta: &MouseScrollDelta, theme: &Theme) -> bool {
+        let rows = match delta {
+            MouseScrollDelta::LineDelta(_, y) => -y * ROWS_PER_LINE,
+            MouseScrollDelta::PixelDelta(position) => -position.y as f32 / (theme.text.size * 2.0)
+        };
+        let fitting = self.visible_rows(theme).len();
+        let last_first = self.rows.len().saturating_sub(fitting.max(1));
+        let first = (self.first as f32 + rows.round()).clamp(0.0, last_first as f32) as usize;
+        if first == self.first {
+            return false;
+        }
+        self.first = first;
+        self.dirty = true;
+        true
+    }
+
+    // a submenu arrow that fails to draw does not keep the rest of the panel from drawing
+    pub fn draw(&mut self, designer: &Designer, renderer: &mut WgpuRenderer, font: &Font, theme: &Theme) -> Result<(), PathError> {
+        let overlay = match self.overlay {
+            Some(id) if renderer.is_overlay(id) => id,
+            _ => renderer.add_overlay(LayerOptions::new(1.0))
+        };
+        self.overlay = Some(overlay);
+        renderer.enter_overlay(overlay);
+        self.drawn.clear(renderer);
+
+        let mut panel = RoundedRect::new(self.bounds, theme.corner_radius, theme.background);
+        panel.border = Some(Border::new(theme.border_width, theme.border));
+        panel.shadow = Some(BoxShadow::new([0.0, 4.0], 12.0, 0.0, Color::rgba(0, 0, 0, 0.2)));
+        self.drawn.sdf.push(designer.create_rounded_rect(renderer, &panel));
+
+        let mut result = Ok(());
+        for (index, rect) in self.visible_rows(theme) {
+            let row = &self.rows[index];
+            if row.separator {
+                let line = Rect::new(rect.x + theme.padding, rect.y + rect.height / 2.0, rect.width - theme.padding * 2.0, 1.0);
+                self.drawn.rounded_rect(designer, renderer, line, 0.0, theme.border, None);
+                continue;
+            }
+
+            let highlighted = self.highlighted == Some(index);
+            if highlighted {
+                self.drawn.rounded_rect(designer, renderer, rect, (theme.corner_radius - 2.0).max(0.0), theme.accent, None);
+            }
+            let color = if !row.enabled {
+                theme.disabled
+            } else if highlighted {
+                theme.on_accent
+            } else {
+                theme.foreground
+            };
+
+            let mut right = rect.right() - theme.padding;
+            if row.submenu {
+                let size = theme.text.size * 0.5;
+                let x = right - size * 0.6;
+                let y = rect.y + (rect.height - size) / 2.0;
+                let arrow = Path::new().move_to(x, y).line_to(x + size * 0.6, y + size / 2.0).line_to(x, y + size).close();
+                match designer.create_path_fill(renderer, &arrow, FillRule::NonZero, color) {
+                    Ok(id) => self.drawn.instances.push(id),
+                    Err(e) => result = Err(e)
+                }
+                right -= size + theme.padding;
+            }
+            if let Some(detail) = &row.detail {
+                let width = measure_text(font, detail, &theme.text, renderer.size)[0];
+                let area = Rect::new(right - width, rect.y, width, rect.height);
+                self.drawn.text(renderer, font, detail, &theme.text, area, color.lerp(theme.background, 0.4));
+                right -= width + theme.padding * 3.0;
+            }
+
+            let label = Rect::new(rect.x + theme.padding, rect.y, right - rect.x - theme.padding, rect.height);
+            if label.width > 0.0 {
+                let style = TextStyle {
+                    max_width: Some(Measurement::Pixels(label.width)),
+                    ellipsis: true,
+                    ..theme.text.clone()
+                };
+                self.drawn.text(renderer, font, &row.label, &style, label, color);
+            }
+        }
+
+        renderer.exit_overlay();
+        self.dirty = false;
+        result
+    }
+
+    pub fn clear(&mut self, renderer: &mut WgpuRenderer) {
+        self.drawn.clear(renderer);
+        if let Some(overlay) = self.overlay.take() {
+            renderer.remove_layer(overlay);
+        }
+        self.dirty = true;
+    }
+}
+
+// the downward arrow of dropdowns and combo boxes, centered in the area
+pub(crate) fn draw_chevron(designer: &Designer, renderer: &mut WgpuRenderer, drawn: &mut Drawn, area: Rect, color: Color) -> Result<(), PathError> {
+    let size = (area.width.min(area.height) * 0.3).max(4.0);
+    let x = area.x + (area.width - size) / 2.0;
+    let y = area.y + (area.height - size * 0.6) / 2.0;
+    let arrow = Path::new().move_to(x, y).line_to(x + size / 2.0, y + size * 0.6).line_to(x + size, y).close();
+    drawn.instances.push(designer.create_path_fill(renderer, &arrow, FillRule::NonZero, color)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOW: Rect = Rect { x: 0.0, y: 0.0, width: 400.0, height: 300.0 };
+
+    #[derive(Default)]
+    struct FakePopup {
+        bounds: Rect,
+        downs: usize,
+        ups: usize,
+        dismissed: usize,
+        dirty: bool,
+        handle: Option<PopupHandle>
+    }
+
+    impl Popup for FakePopup {
+        fn popup_contains(&self, x: f32, y: f32) -> bool {
+            self.bounds.contains(x, y)
+        }
+
+        fn popup_mouse_down(&mut self, _x: f32, _y: f32) -> bool {
+            self.downs += 1;
+            true
+        }
+
+        fn popup_mouse_move(&mut self, _x: f32, _y: f32) -> bool {
+            false
+        }
+
+        fn popup_mouse_up(&mut self, _x: f32, _y: f32) -> bool {
+            self.ups += 1;
+            true
+        }
+
+        fn popup_key(&mut self, _event: &KeyEvent, _modifiers: ModifiersState) -> bool {
+            false
+        }
+
+        fn draw_popup(&mut self, _designer: &Designer, _renderer: &mut WgpuRenderer) -> Result<(), PathError> {
+            Ok(())
+        }
+
+        fn clear_popup(&mut self, _renderer: &mut WgpuRenderer) {}
+
+        fn is_popup_dirty(&self) -> bool {
+            self.dirty
+        }
+
+        fn dismiss(&mut self) {
+            self.dismissed += 1;
+        }
+
+        fn set_popup_handle(&mut self, handle: PopupHandle) {
+            self.handle = Some(handle);
+        }
+    }
+
+    fn popup(layer: &PopupLayer, bounds: Rect) -> (Arc<Mutex<FakePopup>>, PopupHandle) {
+        let popup = Arc::new(Mutex::new(FakePopup { bounds, ..FakePopup::default() }));
+        let handle = layer.register(&popup);
+        (popup, handle)
+    }
+
+    #[test]
+    fn places_below_and_flips_above_when_there_is_more_room() {
+        let anchor = Rect::new(10.0, 50.0, 100.0, 20.0);
+        assert_eq!(place_below(anchor, [80.0, 100.0], WINDOW), Rect::new(10.0, 70.0, 80.0, 100.0));
+
+        let low = Rect::new(10.0, 250.0, 100.0, 20.0);
+        assert_eq!(place_below(low, [80.0, 100.0], WINDOW), Rect::new(10.0, 150.0, 80.0, 100.0));
+
+        // too tall for either side, so it takes the larger one and shrinks
+        let middle = Rect::new(10.0, 100.0, 100.0, 20.0);
+        assert_eq!(place_below(middle, [80.0, 500.0], WINDOW), Rect::new(10.0, 120.0, 80.0, 180.0));
+    }
+
+    #[test]
+    fn places_below_aligned_to_the_right_edge_when_it_does_not_fit() {
+        let anchor = Rect::new(300.0, 50.0, 80.0, 20.0);
+        assert_eq!(place_below(anchor, [150.0, 50.0], WINDOW).x, 230.0);
+
+        // wider than the room on both sides, so it is pushed back into the window
+        let narrow = Rect::new(20.0, 50.0, 10.0, 20.0);
+        assert_eq!(place_below(narrow, [390.0, 50.0], WINDOW).x, 10.0);
+    }
+
+    #[test]
+    fn places_beside_and_flips_left_near_the_edge() {
+        let row = Rect::new(100.0, 40.0, 100.0, 20.0);
+        assert_eq!(place_beside(row, [120.0, 80.0], WINDOW), Rect::new(200.0, 40.0, 120.0, 80.0));
+
+        let edge = Rect::new(250.0, 280.0, 100.0, 20.0);
+        assert_eq!(place_beside(edge, [120.0, 80.0], WINDOW), Rect::new(130.0, 220.0, 120.0, 80.0));
+    }
+
+    #[test]
+    fn a_press_outside_dismisses_every_popup_and_swallows_its_release() {
+        let layer = PopupLayer::new();
+        let (first, first_handle) = popup(&layer, Rect::new(0.0, 0.0, 50.0, 50.0));
+        let (second, second_handle) = popup(&layer, Rect::new(100.0, 0.0, 50.0, 50.0));
+        first_handle.open();
+        second_handle.open();
+
+        assert!(layer.handle_mouse_down(300.0, 200.0));
+        assert!(!layer.has_open());
+        assert_eq!((first.lock().unwrap().dismissed, second.lock().unwrap().dismissed), (1, 1));
+        assert!(layer.is_dirty());
+
+        assert!(layer.handle_mouse_up(300.0, 200.0));
+        assert!(!layer.handle_mouse_up(300.0, 200.0));
+        assert!(!layer.handle_mouse_down(300.0, 200.0));
+    }
+
+    #[test]
+    fn the_topmost_popup_under_the_pointer_gets_the_press_and_its_release() {
+        let layer = PopupLayer::new();
+        let (below, below_handle) = popup(&layer, Rect::new(0.0, 0.0, 100.0, 100.0));
+        let (above, above_handle) = popup(&layer, Rect::new(50.0, 50.0, 100.0, 100.0));
+        below_handle.open();
+        above_handle.open();
+
+        assert!(layer.handle_mouse_down(75.0, 75.0));
+        assert!(layer.handle_mouse_up(300.0, 200.0));
+        let above_state = above.lock().unwrap();
+        assert_eq!((above_state.downs, above_state.ups), (1, 1));
+        drop(above_state);
+
+        // opening again brings it to the top
+        below_handle.open();
+        layer.handle_mouse_down(75.0, 75.0);
+        assert_eq!(below.lock().unwrap().downs, 1);
+        assert_eq!(below.lock().unwrap().dismissed + above.lock().unwrap().dismissed, 0);
+    }
+
+    #[test]
+    fn scrolling_outside_dismisses_without_taking_the_scroll() {
+        let layer = PopupLayer::new();
+        let (popup, handle) = popup(&layer, Rect::new(0.0, 0.0, 50.0, 50.0));
+        handle.open();
+
+        let delta = MouseScrollDelta::LineDelta(0.0, -1.0);
+        assert!(layer.handle_scroll(&delta, TouchPhase::Moved, 10.0, 10.0));
+        assert!(handle.is_open());
+        assert!(!layer.handle_scroll(&delta, TouchPhase::Moved, 200.0, 200.0));
+        assert!(!handle.is_open());
+        assert_eq!(popup.lock().unwrap().dismissed, 1);
+    }
+
+    #[test]
+    fn unregistered_popups_cannot_be_opened() {
+        let layer = PopupLayer::new();
+        let (popup, handle) = popup(&layer, Rect::new(0.0, 0.0, 50.0, 50.0));
+        assert_eq!(popup.lock().unwrap().handle.as_ref().map(|h| h.id()), Some(handle.id()));
+
+        layer.unregister(handle.id());
+        handle.open();
+        assert!(!layer.has_open());
+        assert!(!layer.is_dirty());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,184 @@
+use winit::event::{ElementState, KeyEvent};
+use winit::keyboard::{Key, ModifiersState, NamedKey};
+use crate::designer::Designer;
+use crate::graphics::path::PathError;
+use crate::graphics::sdf::{Border, ShapeStyle};
+use crate::render::wgpu::WgpuRenderer;
+use crate::scene::Rect;
+use crate::text::font::Font;
+use crate::widgets::theme::{Theme, WidgetKind};
+use crate::widgets::{is_activation_key, Drawn, Widget, WidgetBase};
+
+pub type SelectHandler = Box<dyn FnMut(usize) + Send>;
+
+// options stacked top to bottom, one of them selected, the group takes focus as a whole
+// and arrow keys move the selection like native radio buttons
+pub struct RadioGroup {
+    base: WidgetBase,
+    font: Font,
+    options: Vec<String>,
+    selected: Option<usize>,
+    // the row under a press
+    pressed_row: Option<usize>,
+    on_change: Option<SelectHandler>
+}
+
+impl RadioGroup {
+    pub fn new(font: Font, options: Vec<String>, bounds: Rect, theme: Theme) -> Self {
+        RadioGroup {
+            base: WidgetBase::new(bounds, theme),
+            font,
+            options,
+            selected: None,
+            pressed_row: None,
+            on_change: None
+        }
+    }
+
+    pub fn options(&self) -> &[String] {
+        &self.options
+    }
+
+    pub fn selected(&self) -> Option<usize> {
+        self.selected
+    }
+
+    // changes the selection without calling the handler
+    pub fn set_selected(&mut self, selected: Option<usize>) {
+        let selected = selected.filter(|&index| index < self.options.len());
+        if self.selected != selected {
+            self.selected = selected;
+            self.base.mark_dirty();
+        }
+    }
+
+    pub fn on_change(&mut self, handler: impl FnMut(usize) + Send + 'static) {
+        self.on_change = Some(Box::new(handler));
+    }
+
+    pub fn select(&mut self, index: usize) {
+        if self.base.state.disabled || index >= self.options.len() || self.selected == Some(index) {
+            return;
+        }
+        self.set_selected(Some(index));
+        if let Some(handler) = &mut self.on_change {
+            handler(index);
+        }
+    }
+
+    pub fn row_height(&self) -> f32 {
+        self.base.theme().text.size * 2.0
+    }
+
+    fn row_bounds(&self, index: usize) -> Rect {
+        let bounds = self.base.bounds;
+        let height = self.row_height();
+        Rect::new(bounds.x, bounds.y + index as f32 * height, bounds.width, height)
+    }
+
+    fn row_at(&self, x: f32, y: f32) -> Option<usize> {
+        if !self.base.bounds.contains(x, y) {
+            return None;
+        }
+        let index = ((y - self.base.bounds.y) / self.row_height()) as usize;
+        (index < self.options.len()).then_some(index)
+    }
+
+    // moves the selection by offset, wrapping around at the ends
+    fn step(&mut self, offset: isize) {
+        let count = self.options.len() as isize;
+        if count == 0 {
+            return;
+        }
+        let index = match self.selected {
+            Some(index) => (index as isize + offset).rem_euclid(count),
+            None if offset < 0 => count - 1,
+            None => 0
+        };
+        self.select(index as usize);
+    }
+}
+
+impl Widget for RadioGroup {
+    fn base(&self) -> &WidgetBase {
+        &self.base
+    }
+
+    fn base_mut(&mut self) -> &mut WidgetBase {
+        &mut self.base
+    }
+
+    fn handle_key(&mut self, event: &KeyEvent, _modifiers: ModifiersState) -> bool {
+        if self.base.state.disabled || event.state != ElementState::Pressed {
+            return false;
+        }
+        match &event.logical_key {
+            Key::Named(NamedKey::ArrowDown) | Key::Named(NamedKey::ArrowRight) => self.step(1),
+            Key::Named(NamedKey::ArrowUp) | Key::Named(NamedKey::ArrowLeft) => self.step(-1),
+            _ if is_activation_key(event) => {
+                if self.selected.is_none() {
+                    self.select(0);
+                }
+            }
+            _ => return false
+        }
+        true
+    }
+
+    fn handle_mouse_down(&mut self, x: f32, y: f32) -> bool {
+        if self.base.state.disabled {
+            return false;
+        }
+        self.pressed_row = self.row_at(x, y);
+        if self.pressed_row.is_none() {
+            return false;
+        }
+        self.base.set_pressed(true);
+        self.base.take_focus();
+        true
+    }
+
+    fn handle_mouse_move(&mut self, x: f32, y: f32) -> bool {
+        self.base.set_hovered(self.row_at(x, y).is_some())
+    }
+
+    fn handle_mouse_up(&mut self, x: f32, y: f32) -> bool {
+        let Some(pressed) = self.pressed_row.take() else {
+            return false;
+        };
+        self.base.set_pressed(false);
+        if self.row_at(x, y) == Some(pressed) {
+            self.select(pressed);
+        }
+        true
+    }
+
+    fn draw_content(&mut self, designer: &Designer, renderer: &mut WgpuRenderer, drawn: &mut Drawn) -> Result<(), PathError> {
+        let style = self.base.style(WidgetKind::Radio);
+        let text_style = self.base.theme().text.clone();
+        let radius = text_style.size * 0.55;
+
+        for index in 0..self.options.len() {
+            let row = self.row_bounds(index);
+            let center = [row.x + radius, row.y + row.height / 2.0];
+            let selected = self.selected == Some(index);
+
+            let ring = ShapeStyle {
+                fill: style.background,
+                stroke: Some(Border::new(
+                    style.border_width.max(1.0),
+                    if selected { style.accent } else { style.border }
+                )),
+                shadow: None
+            };
+            drawn.circle(designer, renderer, center, radius, &ring);
+            if selected {
+                drawn.circle(designer, renderer, center, radius * 0.5, &ShapeStyle::fill(style.accent));
+            }
+
+            let x = row.x + radius * 2.0 + self.base.theme().padding;
+            drawn.text(renderer, &self.font, &self.options[index], &text_style, Rect::new(x, row.y, row.right() - x, row.height), style.foreground);
+        }
+        Ok(())
+    }
+}
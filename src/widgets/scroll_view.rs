@@ -0,0 +1,657 @@
+use winit::event::{ElementState, KeyEvent, MouseScrollDelta, TouchPhase};
+use winit::keyboard::{Key, ModifiersState, NamedKey};
+use serde::{Deserialize, Serialize};
+use crate::animation::physics::{FixedTimestep, ScrollPhase, Scroller, Simulation};
+use crate::designer::Designer;
+use crate::graphics::clip::ClipId;
+use crate::graphics::path::PathError;
+use crate::render::wgpu::WgpuRenderer;
+use crate::scene::Rect;
+use crate::widgets::theme::{Theme, WidgetKind};
+use crate::widgets::{Drawn, Widget, WidgetBase};
+
+// scrollbars are drawn over the content along the right and bottom edges
+const SCROLLBAR_WIDTH: f32 = 8.0;
+const MIN_THUMB_LENGTH: f32 = 24.0;
+// pixels scrolled per line of a mouse wheel notch and per arrow key press
+const LINE_HEIGHT: f32 = 40.0;
+// page keys and clicks on the track keep a bit of the previous page visible
+const PAGE_FRACTION: f32 = 0.9;
+// mouse moves outside the viewport reach the content as a pointer that is nowhere
+const OUTSIDE: f32 = f32::NEG_INFINITY;
+
+pub type ScrollHandler = Box<dyn FnMut([f32; 2]) + Send>;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Axis {
+    Horizontal,
+    Vertical
+}
+
+impl Axis {
+    pub(crate) fn index(self) -> usize {
+        match self {
+            Axis::Horizontal => 0,
+            Axis::Vertical => 1
+        }
+    }
+
+    pub(crate) fn start(self, rect: &Rect) -> f32 {
+        match self {
+            Axis::Horizontal => rect.x,
+            Axis::Vertical => rect.y
+        }
+    }
+
+    pub(crate) fn length(self, rect: &Rect) -> f32 {
+        match self {
+            Axis::Horizontal => rect.width,
+            Axis::Vertical => rect.height
+        }
+    }
+
+    pub(crate) fn pick(self, x: f32, y: f32) -> f32 {
+        match self {
+            Axis::Horizontal => x,
+            Axis::Vertical => y
+        }
+    }
+}
+
+const AXES: [Axis; 2] = [Axis::Horizontal, Axis::Vertical];
+
+struct ScrollAxis {
+    enabled: bool,
+    scroller: Scroller,
+    clock: FixedTimestep
+}
+
+impl ScrollAxis {
+    fn new(enabled: bool) -> Self {
+        ScrollAxis {
+            enabled,
+            scroller: Scroller::new(0.0, 0.0, 0.0),
+            clock: FixedTimestep::default()
+        }
+    }
+}
+
+// a scrollbar thumb held by the pointer, grab is where it was picked up from the start of the thumb
+#[derive(Copy, Clone, Debug)]
+struct ThumbDrag {
+    axis: Axis,
+    grab: f32
+}
+
+// the content and the scrollbars each get a clip that is kept between draws, so content that did not
+// change is not redrawn, the bars clip is pushed second which draws them on top
+struct ScrollClips {
+    bounds: Rect,
+    content: ClipId,
+    bars: ClipId
+}
+
+// shows part of a larger content widget, the content is drawn at its full size and clipped to the view
+pub struct ScrollView<C: Widget = Box<dyn Widget>> {
+    base: WidgetBase,
+    content: C,
+    content_size: [f32; 2],
+    axes: [ScrollAxis; 2],
+    // the offset the content was last placed at
+    offset: [f32; 2],
+    thumb_drag: Option<ThumbDrag>,
+    hovered_bar: Option<Axis>,
+    // the content took the press, so it also gets the moves and the release
+    content_pressed: bool,
+    // a trackpad gesture reports its phases, other pixel deltas are plain jumps
+    gesture: bool,
+    clips: Option<ScrollClips>,
+    // the viewport the content was last told about
+    viewport: Rect,
+    on_scroll: Option<ScrollHandler>
+}
+
+impl<C: Widget> ScrollView<C> {
+    pub fn new(content: C, content_size: [f32; 2], bounds: Rect, theme: Theme) -> Self {
+        let mut view = ScrollView {
+            base: WidgetBase::new(bounds, theme),
+            content,
+            content_size,
+            axes: [ScrollAxis::new(true), ScrollAxis::new(true)],
+            offset: [0.0; 2],
+            thumb_drag: None,
+            hovered_bar: None,
+            content_pressed: false,
+            gesture: false,
+            clips: None,
+            viewport: Rect::default(),
+            on_scroll: None
+        };
+        view.update_bounds();
+        view.layout_content();
+        view
+    }
+
+    pub fn with_axes(mut self, horizontal: bool, vertical: bool) -> Self {
+        self.axes[0].enabled = horizontal;
+        self.axes[1].enabled = vertical;
+        self.scroll_to(self.offset);
+        self
+    }
+
+    pub fn content(&self) -> &C {
+        &self.content
+    }
+
+    pub fn content_mut(&mut self) -> &mut C {
+        &mut self.content
+    }
+
+    pub fn content_size(&self) -> [f32; 2] {
+        self.content_size
+    }
+
+    pub fn set_content_size(&mut self, size: [f32; 2]) {
+        self.content_size = size;
+        self.update_bounds();
+        self.layout_content();
+        self.base.mark_dirty();
+    }
+
+    pub fn on_scroll(&mut self, handler: impl FnMut([f32; 2]) + Send + 'static) {
+        self.on_scroll = Some(Box::new(handler));
+    }
+
+    // how far the content is scrolled, it can be outside the bounds while rubber banding
+    pub fn scroll_offset(&self) -> [f32; 2] {
+        [self.axes[0].scroller.position(), self.axes[1].scroller.position()]
+    }
+
+    pub fn max_offset(&self) -> [f32; 2] {
+        [self.axes[0].scroller.max, self.axes[1].scroller.max]
+    }
+
+    // jumps to the offset, clamped to the content and stopping any motion
+    pub fn scroll_to(&mut self, offset: [f32; 2]) {
+        for (axis, offset) in self.axes.iter_mut().zip(offset) {
+            axis.scroller.scroll_to(if axis.enabled { offset } else { 0.0 });
+        }
+        self.layout_content();
+    }
+
+    pub fn scroll_by(&mut self, delta: [f32; 2]) {
+        let offset = self.scroll_offset();
+        self.scroll_to([offset[0] + delta[0], offset[1] + delta[1]]);
+    }
+
+    // moves the offset without stopping a drag or fling, for content that changed size above the viewport
+    pub fn shift_offset(&mut self, delta: [f32; 2]) {
+        for (axis, delta) in self.axes.iter_mut().zip(delta) {
+            if axis.enabled && delta != 0.0 {
+                axis.scroller.shift(delta);
+            }
+        }
+        self.layout_content();
+    }
+
+    // scrolls as little as possible to show the rectangle, which is relative to the content
+    pub fn scroll_into_view(&mut self, rect: Rect) {
+        let mut offset = self.scroll_offset();
+        for axis in AXES {
+            let start = axis.start(&rect);
+            let end = start + axis.length(&rect);
+            let viewport = axis.length(&self.base.bounds);
+            let current = &mut offset[axis.index()];
+            if start < *current {
+                *current = start;
+            } else if end > *current + viewport {
+                *current = (end - viewport).min(start);
+            }
+        }
+        self.scroll_to(offset);
+    }
+
+    fn update_bounds(&mut self) {
+        for axis in AXES {
+            let viewport = axis.length(&self.base.bounds);
+            let content = self.content_size[axis.index()];
+            self.axes[axis.index()].scroller.set_bounds(0.0, (content - viewport).max(0.0), viewport);
+        }
+    }
+
+    // moves the content to the scroll offset and lets the handler know when it changed
+    fn layout_content(&mut self) {
+        let offset = self.scroll_offset();
+        let bounds = self.base.bounds;
+        let placed = Rect::new(
+            bounds.x - offset[0],
+            bounds.y - offset[1],
+            self.content_size[0].max(bounds.width),
+            self.content_size[1].max(bounds.height)
+        );
+        let moved = self.content.bounds() != placed;
+        if moved {
+            self.content.set_bounds(placed);
+        }
+        if moved || self.viewport != bounds {
+            self.viewport = bounds;
+            self.content.set_viewport(bounds);
+        }
+
+        if self.offset != offset {
+            self.offset = offset;
+            self.base.mark_dirty();
+            if let Some(handler) = &mut self.on_scroll {
+                handler(offset);
+            }
+        }
+    }
+
+    fn can_scroll(&self, axis: Axis) -> bool {
+        let axis = &self.axes[axis.index()];
+        axis.enabled && axis.scroller.max > axis.scroller.min
+    }
+
+    // page keys scroll vertically, or horizontally when that is the only direction
+    fn primary_axis(&self) -> Axis {
+        if self.axes[1].enabled { Axis::Vertical } else { Axis::Horizontal }
+    }
+
+    fn track(&self, axis: Axis) -> Rect {
+        let bounds = self.base.bounds;
+        match axis {
+            Axis::Vertical => {
+                let corner = if self.can_scroll(Axis::Horizontal) { SCROLLBAR_WIDTH } else { 0.0 };
+                Rect::new(bounds.right() - SCROLLBAR_WIDTH, bounds.y, SCROLLBAR_WIDTH, bounds.height - corner)
+            }
+            Axis::Horizontal => {
+                let corner = if self.can_scroll(Axis::Vertical) { SCROLLBAR_WIDTH } else { 0.0 };
+                Rect::new(bounds.x, bounds.bottom() - SCROLLBAR_WIDTH, bounds.width - corner, SCROLLBAR_WIDTH)
+            }
+        }
+    }
+
+    // the thumb is as much of the track as the viewport is of the content
+    fn thumb(&self, axis: Axis) -> Option<Rect> {
+        if !self.can_scroll(axis) {
+            return None;
+        }
+        let track = self.track(axis);
+        let track_length = axis.length(&track);
+        let viewport = axis.length(&self.base.bounds);
+        let content = self.content_size[axis.index()];
+        let length = (viewport / content * track_length).clamp(MIN_THUMB_LENGTH.min(track_length), track_length);
+
+        let scroller = &self.axes[axis.index()].scroller;
+        let fraction = (scroller.position() / scroller.max).clamp(0.0, 1.0);
+        let start = axis.start(&track) + (track_length - length) * fraction;
+        Some(match axis {
+            Axis::Horizontal => Rect::new(start, track.y, length, track.height),
+            Axis::Vertical => Rect::new(track.x, start, track.width, length)
+        })
+    }
+
+    fn bar_at(&self, x: f32, y: f32) -> Option<Axis> {
+        AXES.into_iter().find(|&axis| self.can_scroll(axis) && self.track(axis).contains(x, y))
+    }
+
+    fn drag_thumb(&mut self, drag: ThumbDrag, x: f32, y: f32) {
+        let Some(thumb) = self.thumb(drag.axis) else {
+            return;
+        };
+        let track = self.track(drag.axis);
+        let travel = drag.axis.length(&track) - drag.axis.length(&thumb);
+        let position = drag.axis.pick(x, y) - drag.axis.start(&track) - drag.grab;
+        let fraction = if travel > 0.0 { (position / travel).clamp(0.0, 1.0) } else { 0.0 };
+
+        let mut offset = self.scroll_offset();
+        offset[drag.axis.index()] = fraction * self.axes[drag.axis.index()].scroller.max;
+        self.scroll_to(offset);
+    }
+
+    fn scroll_axis_by(&mut self, axis: Axis, delta: f32) {
+        let mut offset = [0.0; 2];
+        offset[axis.index()] = delta;
+        self.scroll_by(offset);
+    }
+}
+
+impl<C: Widget> Widget for ScrollView<C> {
+    fn base(&self) -> &WidgetBase {
+        &self.base
+    }
+
+    fn base_mut(&mut self) -> &mut WidgetBase {
+        &mut self.base
+    }
+
+    fn handle_key(&mut self, event: &KeyEvent, modifiers: ModifiersState) -> bool {
+        if self.base.state.disabled || event.state != ElementState::Pressed {
+            return false;
+        }
+        let axis = self.primary_axis();
+        let page = axis.length(&self.base.bounds) * PAGE_FRACTION;
+        match &event.logical_key {
+            Key::Named(NamedKey::ArrowDown) => self.scroll_axis_by(Axis::Vertical, LINE_HEIGHT),
+            Key::Named(NamedKey::ArrowUp) => self.scroll_axis_by(Axis::Vertical, -LINE_HEIGHT),
+            Key::Named(NamedKey::ArrowRight) => self.scroll_axis_by(Axis::Horizontal, LINE_HEIGHT),
+            Key::Named(NamedKey::ArrowLeft) => self.scroll_axis_by(Axis::Horizontal, -LINE_HEIGHT),
+            Key::Named(NamedKey::PageDown) => self.scroll_axis_by(axis, page),
+            Key::Named(NamedKey::PageUp) => self.scroll_axis_by(axis, -page),
+            Key::Named(NamedKey::Space) if modifiers.shift_key() => self.scroll_axis_by(axis, -page),
+            Key::Named(NamedKey::Space) => self.scroll_axis_by(axis, page),
+            Key::Named(NamedKey::Home) => self.scroll_axis_by(axis, f32::NEG_INFINITY),
+            Key::Named(NamedKey::End) => self.scroll_axis_by(axis, f32::INFINITY),
+            _ => return false
+        }
+        true
+    }
+
+    fn handle_mouse_down(&mut self, x: f32, y: f32) -> bool {
+        if self.base.state.disabled || !self.base.bounds.contains(x, y) {
+            return false;
+        }
+
+        // a press catches a fling
+        for axis in self.axes.iter_mut() {
+            if axis.scroller.phase() == ScrollPhase::Coasting {
+                let position = axis.scroller.position();
+                axis.scroller.scroll_to(position);
+            }
+        }
+
+        if let Some(axis) = self.bar_at(x, y) {
+            let thumb = self.thumb(axis).unwrap_or_default();
+            if thumb.contains(x, y) {
+                self.thumb_drag = Some(ThumbDrag { axis, grab: axis.pick(x, y) - axis.start(&thumb) });
+                self.base.set_pressed(true);
+            } else {
+                // clicks on the track page towards the pointer
+                let page = axis.length(&self.base.bounds) * PAGE_FRACTION;
+                let direction = if axis.pick(x, y) < axis.start(&thumb) { -1.0 } else { 1.0 };
+                self.scroll_axis_by(axis, page * direction);
+            }
+            return true;
+        }
+
+        self.content_pressed = self.content.handle_mouse_down(x, y);
+        self.content_pressed
+    }
+
+    fn handle_mouse_move(&mut self, x: f32, y: f32) -> bool {
+        if let Some(drag) = self.thumb_drag {
+            self.drag_thumb(drag, x, y);
+            return true;
+        }
+
+        let hovered_bar = if self.base.state.disabled { None } else { self.bar_at(x, y) };
+        let mut changed = self.hovered_bar != hovered_bar;
+        self.hovered_bar = hovered_bar;
+        if changed {
+            self.base.mark_dirty();
+        }
+        changed |= self.base.set_hovered(self.base.bounds.contains(x, y));
+
+        let inside = self.base.bounds.contains(x, y) && hovered_bar.is_none();
+        changed |= if inside || self.content_pressed {
+            self.content.handle_mouse_move(x, y)
+        } else {
+            self.content.handle_mouse_move(OUTSIDE, OUTSIDE)
+        };
+        changed
+    }
+
+    fn handle_mouse_up(&mut self, x: f32, y: f32) -> bool {
+        if self.thumb_drag.take().is_some() {
+            self.base.set_pressed(false);
+            return true;
+        }
+        if self.content_pressed {
+            self.content_pressed = false;
+            return self.content.handle_mouse_up(x, y);
+        }
+        false
+    }
+
+    // nested scroll views get the first chance, a view that cannot move any further passes the scroll on
+    fn handle_scroll(&mut self, delta: &MouseScrollDelta, phase: TouchPhase, x: f32, y: f32) -> bool {
+        if self.base.state.disabled || !self.base.bounds.contains(x, y) {
+            return false;
+        }
+        if self.content.handle_scroll(delta, phase, x, y) {
+            return true;
+        }
+
+        let before = self.scroll_offset();
+        match *delta {
+            MouseScrollDelta::LineDelta(dx, dy) => {
+                // a plain mouse wheel only scrolls vertically, so it moves views that only scroll sideways
+                let (dx, dy) = if !self.axes[1].enabled && dx == 0.0 { (dy, 0.0) } else { (dx, dy) };
+                self.scroll_by([-dx * LINE_HEIGHT, -dy * LINE_HEIGHT]);
+            }
+            MouseScrollDelta::PixelDelta(position) => {
+                let delta = [-position.x as f32, -position.y as f32];
+                match phase {
+                    TouchPhase::Started => self.gesture = true,
+                    TouchPhase::Ended | TouchPhase::Cancelled => self.gesture = false,
+                    TouchPhase::Moved => {}
+                }
+
+                if !self.gesture && phase == TouchPhase::Moved {
+                    self.scroll_by(delta);
+                } else {
+                    for (axis, delta) in self.axes.iter_mut().zip(delta) {
+                        if !axis.enabled {
+                            continue;
+                        }
+                        if self.gesture {
+                            axis.scroller.drag_by(delta);
+                        } else if axis.scroller.phase() == ScrollPhase::Dragging {
+                            // the platform sends its own momentum after the fingers lift, so only the bounce is ours
+                            axis.scroller.release(0.0);
+                        }
+                    }
+                    self.layout_content();
+                    return true;
+                }
+            }
+        }
+        self.layout_content();
+        self.scroll_offset() != before
+    }
+
+    fn tick(&mut self, elapsed: f32) -> bool {
+        let mut moving = self.content.tick(elapsed);
+        for axis in self.axes.iter_mut() {
+            if matches!(axis.scroller.phase(), ScrollPhase::Coasting | ScrollPhase::Bouncing) {
+                axis.clock.advance(elapsed, &mut axis.scroller);
+                moving |= !axis.scroller.is_done();
+            }
+        }
+        self.layout_content();
+        moving
+    }
+
+    fn draw_content(&mut self, designer: &Designer, renderer: &mut WgpuRenderer, drawn: &mut Drawn) -> Result<(), PathError> {
+        let style = self.base.style(WidgetKind::ScrollView);
+        let bounds = self.base.bounds;
+
+        let clips = match self.clips.take() {
+            Some(clips) if clips.bounds == bounds => clips,
+            previous => {
+                // content drawn under the old clip has to be drawn again under the new one
+                if let Some(previous) = previous {
+                    renderer.remove_clip(previous.content);
+                    renderer.remove_clip(previous.bars);
+                }
+                self.content.clear(renderer);
+                let content = renderer.push_rect_clip(bounds);
+                renderer.pop_clip();
+                let bars = renderer.push_rect_clip(bounds);
+                renderer.pop_clip();
+                ScrollClips { bounds, content, bars }
+            }
+        };
+
+        renderer.reenter_clip(clips.content);
+        let mut result = Ok(());
+        if self.content.is_dirty() {
+            result = self.content.draw(designer, renderer);
+        }
+        renderer.pop_clip();
+
+        renderer.reenter_clip(clips.bars);
+        let bars: Vec<_> = AXES.into_iter().filter_map(|axis| Some((axis, self.thumb(axis)?))).collect();
+        for (axis, thumb) in bars {
+            let active = self.hovered_bar == Some(axis) || self.thumb_drag.is_some_and(|drag| drag.axis == axis);
+            if active {
+                drawn.rounded_rect(designer, renderer, self.track(axis), 0.0, style.foreground.with_alpha(0.08), None);
+            }
+            let alpha = if active { 0.55 } else { 0.35 };
+            let thumb = thumb.inflate(-2.0);
+            let radius = thumb.width.min(thumb.height) / 2.0;
+            drawn.rounded_rect(designer, renderer, thumb, radius, style.foreground.with_alpha(alpha), None);
+        }
+        renderer.pop_clip();
+        self.clips = Some(clips);
+        result
+    }
+
+    fn clear(&mut self, renderer: &mut WgpuRenderer) {
+        self.content.clear(renderer);
+        self.base.drawn.clear(renderer);
+        if let Some(clips) = self.clips.take() {
+            renderer.remove_clip(clips.content);
+            renderer.remove_clip(clips.bars);
+        }
+        self.base.dirty = true;
+    }
+
+    fn set_bounds(&mut self, bounds: Rect) {
+        self.base.bounds = bounds;
+        self.base.dirty = true;
+        if let Some(node) = &self.base.node {
+            node.set_bounds(bounds);
+        }
+        self.update_bounds();
+        self.layout_content();
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.base.dirty || self.content.is_dirty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use winit::dpi::PhysicalPosition;
+
+    struct Blank {
+        base: WidgetBase
+    }
+
+    impl Widget for Blank {
+        fn base(&self) -> &WidgetBase {
+            &self.base
+        }
+
+        fn base_mut(&mut self) -> &mut WidgetBase {
+            &mut self.base
+        }
+
+        fn handle_key(&mut self, _event: &KeyEvent, _modifiers: ModifiersState) -> bool {
+            false
+        }
+
+        fn handle_mouse_down(&mut self, _x: f32, _y: f32) -> bool {
+            false
+        }
+
+        fn handle_mouse_move(&mut self, _x: f32, _y: f32) -> bool {
+            false
+        }
+
+        fn handle_mouse_up(&mut self, _x: f32, _y: f32) -> bool {
+            false
+        }
+
+        fn draw_content(&mut self, _designer: &Designer, _renderer: &mut WgpuRenderer, _drawn: &mut Drawn) -> Result<(), PathError> {
+            Ok(())
+        }
+    }
+
+    // a 100 by 100 view onto content of the given size
+    fn view(content_size: [f32; 2]) -> ScrollView {
+        let content = Box::new(Blank { base: WidgetBase::new(Rect::default(), Theme::default()) });
+        ScrollView::new(content, content_size, Rect::new(10.0, 20.0, 100.0, 100.0), Theme::default())
+    }
+
+    fn wheel(view: &mut ScrollView, dx: f32, dy: f32) -> bool {
+        view.handle_scroll(&MouseScrollDelta::LineDelta(dx, dy), TouchPhase::Moved, 50.0, 50.0)
+    }
+
+    #[test]
+    fn offsets_are_clamped_to_the_content() {
+        let mut view = view([300.0, 250.0]);
+        assert_eq!(view.max_offset(), [200.0, 150.0]);
+
+        view.scroll_to([500.0, -20.0]);
+        assert_eq!(view.scroll_offset(), [200.0, 0.0]);
+        view.scroll_by([-50.0, 400.0]);
+        assert_eq!(view.scroll_offset(), [150.0, 150.0]);
+
+        // the content is moved against the offset and never smaller than the view
+        assert_eq!(view.content().bounds(), Rect::new(-140.0, -130.0, 300.0, 250.0));
+        let small = self::view([40.0, 40.0]);
+        assert_eq!(small.max_offset(), [0.0, 0.0]);
+        assert_eq!(small.content().bounds(), Rect::new(10.0, 20.0, 100.0, 100.0));
+    }
+
+    #[test]
+    fn the_wheel_scrolls_by_lines_until_the_end() {
+        let mut view = view([100.0, 150.0]);
+        assert!(wheel(&mut view, 0.0, -1.0));
+        assert_eq!(view.scroll_offset(), [0.0, LINE_HEIGHT]);
+        assert!(wheel(&mut view, 0.0, -1.0));
+        assert_eq!(view.scroll_offset(), [0.0, 50.0]);
+        assert!(!wheel(&mut view, 0.0, -1.0));
+        assert!(!view.handle_scroll(&MouseScrollDelta::LineDelta(0.0, 1.0), TouchPhase::Moved, 0.0, 0.0));
+    }
+
+    #[test]
+    fn a_plain_wheel_scrolls_views_that_only_move_sideways() {
+        let mut view = view([400.0, 400.0]).with_axes(true, false);
+        wheel(&mut view, 0.0, -2.0);
+        assert_eq!(view.scroll_offset(), [LINE_HEIGHT * 2.0, 0.0]);
+    }
+
+    #[test]
+    fn scrolling_into_view_moves_as_little_as_possible() {
+        let mut view = view([100.0, 1000.0]);
+        view.scroll_into_view(Rect::new(0.0, 250.0, 10.0, 50.0));
+        assert_eq!(view.scroll_offset(), [0.0, 200.0]);
+        view.scroll_into_view(Rect::new(0.0, 220.0, 10.0, 20.0));
+        assert_eq!(view.scroll_offset(), [0.0, 200.0]);
+        view.scroll_into_view(Rect::new(0.0, 120.0, 10.0, 20.0));
+        assert_eq!(view.scroll_offset(), [0.0, 120.0]);
+    }
+
+    #[test]
+    fn trackpad_overscroll_bounces_back_to_the_edge() {
+        let mut view = view([100.0, 200.0]);
+        let delta = |y: f64| MouseScrollDelta::PixelDelta(PhysicalPosition::new(0.0, y));
+
+        view.handle_scroll(&delta(0.0), TouchPhase::Started, 50.0, 50.0);
+        view.handle_scroll(&delta(-300.0), TouchPhase::Moved, 50.0, 50.0);
+        assert!(view.scroll_offset()[1] > 100.0);
+        view.handle_scroll(&delta(0.0), TouchPhase::Ended, 50.0, 50.0);
+
+        for _ in 0..600 {
+            if !view.tick(1.0 / 60.0) {
+                break;
+            }
+        }
+        assert!((view.scroll_offset()[1] - 100.0).abs() < 0.5);
+    }
+}
\ No newline at end of file
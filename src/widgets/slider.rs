@@ -0,0 +1,189 @@
+use winit::event::{ElementState, KeyEvent};
+use winit::keyboard::{Key, ModifiersState, NamedKey};
+use crate::designer::Designer;
+use crate::graphics::path::PathError;
+use crate::graphics::sdf::{Border, ShapeStyle};
+use crate::render::wgpu::WgpuRenderer;
+use crate::scene::Rect;
+use crate::widgets::theme::{Theme, WidgetKind};
+use crate::widgets::{Drawn, Widget, WidgetBase};
+
+pub type ValueHandler = Box<dyn FnMut(f32) + Send>;
+
+// page up and page down move by this part of the range
+const PAGE_FRACTION: f32 = 0.1;
+
+// a horizontal slider, clicking the track jumps to the pointer and dragging follows it
+pub struct Slider {
+    base: WidgetBase,
+    value: f32,
+    pub min: f32,
+    pub max: f32,
+    // values snap to multiples of step from min, zero is continuous
+    pub step: f32,
+    on_change: Option<ValueHandler>
+}
+
+impl Slider {
+    pub fn new(min: f32, max: f32, value: f32, bounds: Rect, theme: Theme) -> Self {
+        let max = max.max(min);
+        Slider {
+            base: WidgetBase::new(bounds, theme),
+            value: value.clamp(min, max),
+            min,
+            max,
+            step: 0.0,
+            on_change: None
+        }
+    }
+
+    pub fn with_step(self, step: f32) -> Self {
+        let mut slider = Slider { step: step.max(0.0), ..self };
+        slider.value = slider.snap(slider.value);
+        slider
+    }
+
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+
+    // changes the value without calling the handler
+    pub fn set_value(&mut self, value: f32) {
+        let value = self.snap(value);
+        if self.value != value {
+            self.value = value;
+            self.base.mark_dirty();
+        }
+    }
+
+    pub fn on_change(&mut self, handler: impl FnMut(f32) + Send + 'static) {
+        self.on_change = Some(Box::new(handler));
+    }
+
+    fn change(&mut self, value: f32) {
+        let previous = self.value;
+        self.set_value(value);
+        if self.value != previous {
+            if let Some(handler) = &mut self.on_change {
+                handler(self.value);
+            }
+        }
+    }
+
+    fn snap(&self, value: f32) -> f32 {
+        let value = value.clamp(self.min, self.max);
+        if self.step <= 0.0 {
+            return value;
+        }
+        let snapped = self.min + ((value - self.min) / self.step).round() * self.step;
+        snapped.clamp(self.min, self.max)
+    }
+
+    // the value between 0 and 1
+    fn fraction(&self) -> f32 {
+        let range = self.max - self.min;
+        if range <= 0.0 { 0.0 } else { (self.value - self.min) / range }
+    }
+
+    fn thumb_radius(&self) -> f32 {
+        (self.base.theme().text.size * 0.6).min(self.base.bounds.height / 2.0)
+    }
+
+    // the thumb center travels between the ends of this line
+    fn travel(&self) -> (f32, f32) {
+        let bounds = self.base.bounds;
+        let radius = self.thumb_radius();
+        (bounds.x + radius, (bounds.right() - radius).max(bounds.x + radius))
+    }
+
+    fn value_at(&self, x: f32) -> f32 {
+        let (start, end) = self.travel();
+        let fraction = if end > start { ((x - start) / (end - start)).clamp(0.0, 1.0) } else { 0.0 };
+        self.min + fraction * (self.max - self.min)
+    }
+
+    // keys move by the step, or by a hundredth of the range when the slider is continuous
+    fn key_step(&self) -> f32 {
+        if self.step > 0.0 { self.step } else { (self.max - self.min) / 100.0 }
+    }
+}
+
+impl Widget for Slider {
+    fn base(&self) -> &WidgetBase {
+        &self.base
+    }
+
+    fn base_mut(&mut self) -> &mut WidgetBase {
+        &mut self.base
+    }
+
+    fn handle_key(&mut self, event: &KeyEvent, _modifiers: ModifiersState) -> bool {
+        if self.base.state.disabled || event.state != ElementState::Pressed {
+            return false;
+        }
+        let page = ((self.max - self.min) * PAGE_FRACTION).max(self.key_step());
+        let value = match &event.logical_key {
+            Key::Named(NamedKey::ArrowRight) | Key::Named(NamedKey::ArrowUp) => self.value + self.key_step(),
+            Key::Named(NamedKey::ArrowLeft) | Key::Named(NamedKey::ArrowDown) => self.value - self.key_step(),
+            Key::Named(NamedKey::PageUp) => self.value + page,
+            Key::Named(NamedKey::PageDown) => self.value - page,
+            Key::Named(NamedKey::Home) => self.min,
+            Key::Named(NamedKey::End) => self.max,
+            _ => return false
+        };
+        self.change(value);
+        true
+    }
+
+    fn handle_mouse_down(&mut self, x: f32, y: f32) -> bool {
+        if self.base.state.disabled || !self.base.bounds.contains(x, y) {
+            return false;
+        }
+        self.base.set_pressed(true);
+        self.base.take_focus();
+        self.change(self.value_at(x));
+        true
+    }
+
+    // while pressed the thumb follows the pointer even outside the bounds
+    fn handle_mouse_move(&mut self, x: f32, y: f32) -> bool {
+        if self.base.state.pressed {
+            let previous = self.value;
+            self.change(self.value_at(x));
+            return self.value != previous;
+        }
+        self.base.set_hovered(self.base.bounds.contains(x, y))
+    }
+
+    fn handle_mouse_up(&mut self, _x: f32, _y: f32) -> bool {
+        if !self.base.state.pressed {
+            return false;
+        }
+        self.base.set_pressed(false);
+        true
+    }
+
+    fn draw_content(&mut self, designer: &Designer, renderer: &mut WgpuRenderer, drawn: &mut Drawn) -> Result<(), PathError> {
+        let style = self.base.style(WidgetKind::Slider);
+        let bounds = self.base.bounds;
+        let (start, end) = self.travel();
+        let center_y = bounds.y + bounds.height / 2.0;
+        let track_height = (self.thumb_radius() * 0.4).max(2.0);
+        let thumb_x = start + (end - start) * self.fraction();
+
+        let track = Rect::new(start, center_y - track_height / 2.0, end - start, track_height);
+        drawn.rounded_rect(designer, renderer, track, track_height / 2.0, style.border, None);
+        let filled = Rect::new(start, track.y, thumb_x - start, track_height);
+        if filled.width > 0.0 {
+            drawn.rounded_rect(designer, renderer, filled, track_height / 2.0, style.accent, None);
+        }
+
+        let thumb = ShapeStyle {
+            fill: style.background,
+            stroke: Some(Border::new(style.border_width.max(1.0) * 2.0, style.accent)),
+            shadow: None
+        };
+        drawn.circle(designer, renderer, [thumb_x, center_y], self.thumb_radius(), &thumb);
+        Ok(())
+    }
+}
@@ -0,0 +1,556 @@
+use std::fmt::{Display, Formatter};
+use winit::event::{KeyEvent, MouseScrollDelta, TouchPhase};
+use winit::keyboard::ModifiersState;
+use crate::designer::Designer;
+use crate::graphics::clip::ClipId;
+use crate::graphics::path::PathError;
+use crate::render::wgpu::WgpuRenderer;
+use crate::scene::Rect;
+use crate::widgets::scroll_view::Axis;
+use crate::widgets::theme::Theme;
+use crate::widgets::{Drawn, Widget, WidgetBase};
+
+// the grab area between two panes, only a line in its middle is drawn
+pub const DIVIDER_SIZE: f32 = 6.0;
+pub const DEFAULT_MIN_SIZE: f32 = 48.0;
+
+// called with the fraction of the space each pane takes after a divider is dragged
+pub type ResizeHandler = Box<dyn FnMut(&[f32]) + Send>;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum FractionsError {
+    // every pane needs exactly one fraction
+    Count { panes: usize, fractions: usize },
+    ZeroSum
+}
+
+impl Display for FractionsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FractionsError::Count { panes, fractions } => write!(f, "split pane has {} panes, got {} fractions", panes, fractions),
+            FractionsError::ZeroSum => write!(f, "pane fractions add up to zero")
+        }
+    }
+}
+
+// lengths of panes sharing total in proportion to their fractions, panes under their minimum
+// take the difference from the others in proportion to what those have above theirs
+pub(crate) fn split_lengths(total: f32, fractions: &[f32], mins: &[f32]) -> Vec<f32> {
+    let sum = fractions.iter().sum::<f32>().max(f32::EPSILON);
+    let mut lengths: Vec<f32> = fractions.iter().map(|fraction| total.max(0.0) * fraction / sum).collect();
+
+    let deficit: f32 = lengths.iter().zip(mins).map(|(length, min)| (min - length).max(0.0)).sum();
+    let slack: f32 = lengths.iter().zip(mins).map(|(length, min)| (length - min).max(0.0)).sum();
+    if deficit > 0.0 && slack > 0.0 {
+        let take = (deficit / slack).min(1.0);
+        for (length, min) in lengths.iter_mut().zip(mins) {
+            if *length < *min {
+                *length = *min;
+            } else {
+                *length -= (*length - min) * take;
+            }
+        }
+    }
+    lengths
+}
+
+// moves the divider after pane index by delta, neither pane next to it goes under its minimum
+pub(crate) fn drag_divider(lengths: &mut [f32], index: usize, delta: f32, mins: &[f32]) {
+    if index + 1 >= lengths.len() {
+        return;
+    }
+    let shrink = (lengths[index] - mins[index]).max(0.0);
+    let grow = (lengths[index + 1] - mins[index + 1]).max(0.0);
+    let delta = delta.clamp(-shrink, grow);
+    lengths[index] += delta;
+    lengths[index + 1] -= delta;
+}
+
+// the part of bounds from start along the axis, start counted from the edge of bounds
+pub(crate) fn slice(axis: Axis, bounds: Rect, start: f32, length: f32) -> Rect {
+    match axis {
+        Axis::Horizontal => Rect::new(bounds.x + start, bounds.y, length, bounds.height),
+        Axis::Vertical => Rect::new(bounds.x, bounds.y + start, bounds.width, length)
+    }
+}
+
+// the panes and the dividers between them laid out along the axis
+pub(crate) fn split_rects(axis: Axis, bounds: Rect, lengths: &[f32]) -> (Vec<Rect>, Vec<Rect>) {
+    let mut panes = Vec::with_capacity(lengths.len());
+    let mut dividers = Vec::with_capacity(lengths.len().saturating_sub(1));
+    let mut start = 0.0;
+    for (index, &length) in lengths.iter().enumerate() {
+        if index > 0 {
+            dividers.push(slice(axis, bounds, start, DIVIDER_SIZE));
+            start += DIVIDER_SIZE;
+        }
+        panes.push(slice(axis, bounds, start, length));
+        start += length;
+    }
+    (panes, dividers)
+}
+
+// the space left for panes once the dividers between count panes are taken out
+pub(crate) fn available_length(axis: Axis, bounds: &Rect, count: usize) -> f32 {
+    (axis.length(bounds) - DIVIDER_SIZE * count.saturating_sub(1) as f32).max(0.0)
+}
+
+// the line drawn for a divider, wider while it is hovered or dragged
+pub(crate) fn draw_divider(designer: &Designer, renderer: &mut WgpuRenderer, drawn: &mut Drawn, axis: Axis, divider: Rect, active: bool, theme: &Theme) {
+    let (width, color) = if active { (2.0, theme.accent) } else { (1.0, theme.border) };
+    let line = match axis {
+        Axis::Horizontal => Rect::new(divider.x + (divider.width - width) / 2.0, divider.y, width, divider.height),
+        Axis::Vertical => Rect::new(divider.x, divider.y + (divider.height - width) / 2.0, divider.width, width)
+    };
+    drawn.rounded_rect(designer, renderer, line, 0.0, color, None);
+}
+
+struct Pane<C> {
+    content: C,
+    min_size: f32
+}
+
+// a divider held by the pointer, the lengths are the ones it was picked up with
+struct DividerDrag {
+    index: usize,
+    grab: f32,
+    lengths: Vec<f32>
+}
+
+// every pane gets a clip that is kept between draws, like the scroll view content
+struct PaneClips {
+    rects: Vec<Rect>,
+    ids: Vec<ClipId>
+}
+
+// panes side by side along an axis with draggable dividers between them, a horizontal split puts
+// them left to right; every pane keeps at least its minimum size while there is room for it
+pub struct SplitPane<C: Widget = Box<dyn Widget>> {
+    base: WidgetBase,
+    axis: Axis,
+    panes: Vec<Pane<C>>,
+    fractions: Vec<f32>,
+    drag: Option<DividerDrag>,
+    hovered_divider: Option<usize>,
+    // the last pane pressed gets the keys
+    active: Option<usize>,
+    pressed: Option<usize>,
+    clips: Option<PaneClips>,
+    on_resize: Option<ResizeHandler>
+}
+
+impl<C: Widget> SplitPane<C> {
+    pub fn new(axis: Axis, bounds: Rect, theme: Theme) -> Self {
+        SplitPane {
+            base: WidgetBase::new(bounds, theme),
+            axis,
+            panes: vec![],
+            fractions: vec![],
+            drag: None,
+            hovered_divider: None,
+            active: None,
+            pressed: None,
+            clips: None,
+            on_resize: None
+        }
+    }
+
+    pub fn axis(&self) -> Axis {
+        self.axis
+    }
+
+    // the new pane takes an equal share, the others shrink keeping their proportions
+    pub fn add(&mut self, content: C, min_size: f32) -> usize {
+        let count = self.panes.len() as f32 + 1.0;
+        for fraction in &mut self.fractions {
+            *fraction *= (count - 1.0) / count;
+        }
+        self.fractions.push(1.0 / count);
+        self.panes.push(Pane { content, min_size: min_size.max(0.0) });
+        self.layout();
+        self.panes.len() - 1
+    }
+
+    pub fn pane(&self, index: usize) -> Option<&C> {
+        self.panes.get(index).map(|pane| &pane.content)
+    }
+
+    pub fn pane_mut(&mut self, index: usize) -> Option<&mut C> {
+        self.panes.get_mut(index).map(|pane| &mut pane.content)
+    }
+
+    pub fn len(&self) -> usize {
+        self.panes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.panes.is_empty()
+    }
+
+    pub fn set_min_size(&mut self, index: usize, min_size: f32) {
+        if let Some(pane) = self.panes.get_mut(index) {
+            pane.min_size = min_size.max(0.0);
+            self.layout();
+        }
+    }
+
+    pub fn fractions(&self) -> &[f32] {
+        &self.fractions
+    }
+
+    // one fraction per pane, they are scaled to add up to one
+    pub fn set_fractions(&mut self, fractions: &[f32]) -> Result<(), FractionsError> {
+        if fractions.len() != self.panes.len() {
+            return Err(FractionsError::Count { panes: self.panes.len(), fractions: fractions.len() });
+        }
+        let fractions: Vec<f32> = fractions.iter().map(|fraction| fraction.max(0.0)).collect();
+        let sum: f32 = fractions.iter().sum();
+        if sum <= 0.0 {
+            return Err(FractionsError::ZeroSum);
+        }
+        self.fractions = fractions.iter().map(|fraction| fraction / sum).collect();
+        self.layout();
+        Ok(())
+    }
+
+    // the size of every pane along the axis, in pixels
+    pub fn lengths(&self) -> Vec<f32> {
+        let mins: Vec<f32> = self.panes.iter().map(|pane| pane.min_size).collect();
+        split_lengths(available_length(self.axis, &self.base.bounds, self.panes.len()), &self.fractions, &mins)
+    }
+
+    pub fn on_resize(&mut self, handler: impl FnMut(&[f32]) + Send + 'static) {
+        self.on_resize = Some(Box::new(handler));
+    }
+
+    fn rects(&self) -> (Vec<Rect>, Vec<Rect>) {
+        split_rects(self.axis, self.base.bounds, &self.lengths())
+    }
+
+    fn layout(&mut self) {
+        let (rects, _) = self.rects();
+        for (pane, rect) in self.panes.iter_mut().zip(rects) {
+            if pane.content.bounds() != rect {
+                pane.content.set_bounds(rect);
+            }
+        }
+        self.base.dirty = true;
+    }
+
+    fn pane_at(&self, x: f32, y: f32) -> Option<usize> {
+        self.rects().0.iter().position(|rect| rect.contains(x, y))
+    }
+
+    fn divider_at(&self, x: f32, y: f32) -> Option<usize> {
+        self.rects().1.iter().position(|rect| rect.contains(x, y))
+    }
+}
+
+impl<C: Widget> Widget for SplitPane<C> {
+    fn base(&self) -> &WidgetBase {
+        &self.base
+    }
+
+    fn base_mut(&mut self) -> &mut WidgetBase {
+        &mut self.base
+    }
+
+    fn handle_key(&mut self, event: &KeyEvent, modifiers: ModifiersState) -> bool {
+        if self.base.state.disabled {
+            return false;
+        }
+        match self.active.and_then(|index| self.panes.get_mut(index)) {
+            Some(pane) => pane.content.handle_key(event, modifiers),
+            None => false
+        }
+    }
+
+    fn handle_mouse_down(&mut self, x: f32, y: f32) -> bool {
+        if self.base.state.disabled || !self.base.bounds.contains(x, y) {
+            return false;
+        }
+        if let Some(index) = self.divider_at(x, y) {
+            self.drag = Some(DividerDrag { index, grab: self.axis.pick(x, y), lengths: self.lengths() });
+            self.base.dirty = true;
+            return true;
+        }
+
+        let Some(index) = self.pane_at(x, y) else {
+            return false;
+        };
+        self.active = Some(index);
+        self.pressed = Some(index);
+        self.panes[index].content.handle_mouse_down(x, y)
+    }
+
+    fn handle_mouse_move(&mut self, x: f32, y: f32) -> bool {
+        if let Some(drag) = &self.drag {
+            let mut lengths = drag.lengths.clone();
+            let mins: Vec<f32> = self.panes.iter().map(|pane| pane.min_size).collect();
+            drag_divider(&mut lengths, drag.index, self.axis.pick(x, y) - drag.grab, &mins);
+
+            let total = lengths.iter().sum::<f32>().max(f32::EPSILON);
+            let fractions: Vec<f32> = lengths.iter().map(|length| length / total).collect();
+            if fractions == self.fractions {
+                return false;
+            }
+            self.fractions = fractions;
+            self.layout();
+            if let Some(handler) = &mut self.on_resize {
+                handler(&self.fractions);
+            }
+            return true;
+        }
+
+        let hovered = if self.base.state.disabled { None } else { self.divider_at(x, y) };
+        let mut changed = hovered != self.hovered_divider;
+        if changed {
+            self.hovered_divider = hovered;
+            self.base.dirty = true;
+        }
+        for pane in &mut self.panes {
+            changed |= pane.content.handle_mouse_move(x, y);
+        }
+        changed
+    }
+
+    fn handle_mouse_up(&mut self, x: f32, y: f32) -> bool {
+        if self.drag.take().is_some() {
+            self.hovered_divider = self.divider_at(x, y);
+            self.base.dirty = true;
+            return true;
+        }
+        match self.pressed.take().and_then(|index| self.panes.get_mut(index)) {
+            Some(pane) => pane.content.handle_mouse_up(x, y),
+            None => false
+        }
+    }
+
+    fn handle_scroll(&mut self, delta: &MouseScrollDelta, phase: TouchPhase, x: f32, y: f32) -> bool {
+        if self.base.state.disabled {
+            return false;
+        }
+        match self.pane_at(x, y) {
+            Some(index) => self.panes[index].content.handle_scroll(delta, phase, x, y),
+            None => false
+        }
+    }
+
+    fn tick(&mut self, elapsed: f32) -> bool {
+        let mut moving = false;
+        for pane in &mut self.panes {
+            moving |= pane.content.tick(elapsed);
+        }
+        moving
+    }
+
+    fn draw_content(&mut self, designer: &Designer, renderer: &mut WgpuRenderer, drawn: &mut Drawn) -> Result<(), PathError> {
+        let theme = self.base.theme().clone();
+        let (rects, dividers) = self.rects();
+
+        let clips = match self.clips.take() {
+            Some(clips) if clips.rects == rects => clips,
+            previous => {
+                // panes drawn under the old clips have to be drawn again under the new ones
+                if let Some(previous) = previous {
+                    for id in previous.ids {
+                        renderer.remove_clip(id);
+                    }
+                }
+                let ids = rects.iter().map(|rect| {
+                    let id = renderer.push_rect_clip(*rect);
+                    renderer.pop_clip();
+                    id
+                }).collect();
+                for pane in &mut self.panes {
+                    pane.content.clear(renderer);
+                }
+                PaneClips { rects, ids }
+            }
+        };
+
+        for (index, divider) in dividers.into_iter().enumerate() {
+            let active = self.hovered_divider == Some(index) || self.drag.as_ref().is_some_and(|drag| drag.index == index);
+            draw_divider(designer, renderer, drawn, self.axis, divider, active, &theme);
+        }
+
+        let mut result = Ok(());
+        for (pane, &clip) in self.panes.iter_mut().zip(&clips.ids) {
+            if pane.content.is_dirty() {
+                renderer.reenter_clip(clip);
+                result = result.and(pane.content.draw(designer, renderer));
+                renderer.pop_clip();
+            }
+        }
+        self.clips = Some(clips);
+        result
+    }
+
+    fn clear(&mut self, renderer: &mut WgpuRenderer) {
+        for pane in &mut self.panes {
+            pane.content.clear(renderer);
+        }
+        self.base.drawn.clear(renderer);
+        if let Some(clips) = self.clips.take() {
+            for id in clips.ids {
+                renderer.remove_clip(id);
+            }
+        }
+        self.base.dirty = true;
+    }
+
+    fn set_bounds(&mut self, bounds: Rect) {
+        self.base.bounds = bounds;
+        if let Some(node) = &self.base.node {
+            node.set_bounds(bounds);
+        }
+        self.layout();
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.base.dirty || self.panes.iter().any(|pane| pane.content.is_dirty())
+    }
+
+    fn set_enabled(&mut self, enabled: bool) {
+        if !enabled {
+            self.drag = None;
+            self.hovered_divider = None;
+        }
+        self.base.state.disabled = !enabled;
+        if let Some(node) = &self.base.node {
+            node.set_enabled(enabled);
+        }
+        for pane in &mut self.panes {
+            pane.content.set_enabled(enabled);
+        }
+        self.base.mark_dirty();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    struct Blank {
+        base: WidgetBase
+    }
+
+    impl Widget for Blank {
+        fn base(&self) -> &WidgetBase {
+            &self.base
+        }
+
+        fn base_mut(&mut self) -> &mut WidgetBase {
+            &mut self.base
+        }
+
+        fn handle_key(&mut self, _event: &KeyEvent, _modifiers: ModifiersState) -> bool {
+            false
+        }
+
+        fn handle_mouse_down(&mut self, _x: f32, _y: f32) -> bool {
+            false
+        }
+
+        fn handle_mouse_move(&mut self, _x: f32, _y: f32) -> bool {
+            false
+        }
+
+        fn handle_mouse_up(&mut self, _x: f32, _y: f32) -> bool {
+            false
+        }
+
+        fn draw_content(&mut self, _designer: &Designer, _renderer: &mut WgpuRenderer, _drawn: &mut Drawn) -> Result<(), PathError> {
+            Ok(())
+        }
+    }
+
+    // two panes left to right sharing 300 pixels once the divider is taken out
+    fn split(min_size: f32) -> SplitPane<Blank> {
+        let mut split = SplitPane::new(Axis::Horizontal, Rect::new(0.0, 0.0, 300.0 + DIVIDER_SIZE, 100.0), Theme::default());
+        for _ in 0..2 {
+            split.add(Blank { base: WidgetBase::new(Rect::default(), Theme::default()) }, min_size);
+        }
+        split
+    }
+
+    #[test]
+    fn lengths_follow_the_fractions() {
+        assert_eq!(split_lengths(300.0, &[1.0, 2.0], &[0.0, 0.0]), vec![100.0, 200.0]);
+        // fractions do not have to add up to one
+        assert_eq!(split_lengths(300.0, &[2.0, 2.0, 2.0], &[0.0; 3]), vec![100.0; 3]);
+    }
+
+    #[test]
+    fn panes_under_their_minimum_take_from_the_others() {
+        assert_eq!(split_lengths(300.0, &[0.1, 0.45, 0.45], &[60.0, 0.0, 0.0]), vec![60.0, 120.0, 120.0]);
+        // with every pane under its minimum there is nothing to take from
+        assert_eq!(split_lengths(100.0, &[0.5, 0.5], &[80.0, 80.0]), vec![50.0, 50.0]);
+    }
+
+    #[test]
+    fn dragging_a_divider_stops_at_the_minimum_of_either_pane() {
+        let mut lengths = vec![100.0, 100.0, 100.0];
+        drag_divider(&mut lengths, 0, 90.0, &[20.0, 20.0, 20.0]);
+        assert_eq!(lengths, vec![180.0, 20.0, 100.0]);
+        drag_divider(&mut lengths, 1, -50.0, &[20.0, 20.0, 20.0]);
+        assert_eq!(lengths, vec![180.0, 20.0, 100.0]);
+        drag_divider(&mut lengths, 2, 10.0, &[20.0, 20.0, 20.0]);
+        assert_eq!(lengths, vec![180.0, 20.0, 100.0]);
+    }
+
+    #[test]
+    fn panes_and_dividers_are_laid_out_along_the_axis() {
+        let (panes, dividers) = split_rects(Axis::Vertical, Rect::new(10.0, 20.0, 50.0, 100.0), &[40.0, 54.0]);
+        assert_eq!(panes, vec![Rect::new(10.0, 20.0, 50.0, 40.0), Rect::new(10.0, 60.0 + DIVIDER_SIZE, 50.0, 54.0)]);
+        assert_eq!(dividers, vec![Rect::new(10.0, 60.0, 50.0, DIVIDER_SIZE)]);
+    }
+
+    #[test]
+    fn added_panes_share_the_space_equally() {
+        let mut split = split(0.0);
+        assert_eq!(split.fractions(), &[0.5, 0.5]);
+        assert_eq!(split.pane(1).unwrap().bounds(), Rect::new(150.0 + DIVIDER_SIZE, 0.0, 150.0, 100.0));
+
+        split.add(Blank { base: WidgetBase::new(Rect::default(), Theme::default()) }, 0.0);
+        let fractions = split.fractions();
+        assert!(fractions.iter().all(|fraction| (fraction - 1.0 / 3.0).abs() < 1e-6));
+    }
+
+    #[test]
+    fn fractions_are_scaled_to_add_up_to_one() {
+        let mut split = split(0.0);
+        assert_eq!(split.set_fractions(&[1.0, 3.0]), Ok(()));
+        assert_eq!(split.fractions(), &[0.25, 0.75]);
+        assert_eq!(split.lengths(), vec![75.0, 225.0]);
+
+        // one fraction per pane, anything else is rejected
+        assert_eq!(split.set_fractions(&[1.0, 1.0, 1.0]), Err(FractionsError::Count { panes: 2, fractions: 3 }));
+        assert_eq!(split.set_fractions(&[0.0, -1.0]), Err(FractionsError::ZeroSum));
+        assert_eq!(split.fractions(), &[0.25, 0.75]);
+    }
+
+    #[test]
+    fn dragging_the_divider_updates_the_fractions() {
+        let mut split = split(50.0);
+        let resized = Arc::new(Mutex::new(vec![]));
+        split.on_resize({
+            let resized = Arc::clone(&resized);
+            move |fractions| *resized.lock().unwrap() = fractions.to_vec()
+        });
+
+        let divider = 150.0 + DIVIDER_SIZE / 2.0;
+        assert!(split.handle_mouse_down(divider, 50.0));
+        assert!(split.handle_mouse_move(divider - 60.0, 50.0));
+        assert_eq!(split.lengths(), vec![90.0, 210.0]);
+        assert_eq!(*resized.lock().unwrap(), vec![0.3, 0.7]);
+
+        // the first pane stops at its minimum
+        split.handle_mouse_move(0.0, 50.0);
+        assert_eq!(split.lengths(), vec![50.0, 250.0]);
+        assert!(split.handle_mouse_up(0.0, 50.0));
+        split.handle_mouse_move(divider, 50.0);
+        assert_eq!(split.lengths(), vec![50.0, 250.0]);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,836 @@
+use std::ops::Range;
+use winit::event::{ElementState, KeyEvent, MouseScrollDelta, TouchPhase};
+use winit::keyboard::{Key, ModifiersState, NamedKey};
+use crate::designer::point::Measurement;
+use crate::designer::Designer;
+use crate::graphics::clip::ClipId;
+use crate::graphics::color::Color;
+use crate::graphics::path::{FillRule, Path, PathError};
+use crate::render::wgpu::WgpuRenderer;
+use crate::scene::Rect;
+use crate::text::font::Font;
+use crate::text::layout::TextStyle;
+use crate::widgets::list_view::{ListAdapter, ListView};
+use crate::widgets::theme::{Theme, WidgetKind};
+use crate::widgets::{Drawn, Widget, WidgetBase};
+
+pub const DEFAULT_COLUMN_WIDTH: f32 = 120.0;
+const MIN_COLUMN_WIDTH: f32 = 32.0;
+// how close to the edge between two header cells the pointer has to be to resize the column
+const RESIZE_GRAB: f32 = 4.0;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SortDirection {
+    Ascending,
+    Descending
+}
+
+impl SortDirection {
+    pub fn reversed(self) -> Self {
+        match self {
+            SortDirection::Ascending => SortDirection::Descending,
+            SortDirection::Descending => SortDirection::Ascending
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SelectionMode {
+    None,
+    Single,
+    // ctrl or cmd click toggles a row, shift click and shift with arrow keys select a range
+    Multiple
+}
+
+// the data behind a table, rows are only asked for while they are visible
+pub trait TableModel: Send {
+    fn row_count(&self) -> usize;
+    fn column_count(&self) -> usize;
+    fn column_title(&self, column: usize) -> String;
+    fn cell_text(&self, row: usize, column: usize) -> String;
+
+    fn column_width(&self, _column: usize) -> f32 {
+        DEFAULT_COLUMN_WIDTH
+    }
+
+    // clicking the header of a sortable column calls the sort handler, which sorts the model
+    fn is_sortable(&self, _column: usize) -> bool {
+        true
+    }
+}
+
+pub type SortHandler = Box<dyn FnMut(usize, SortDirection) + Send>;
+pub type SelectionHandler = Box<dyn FnMut(&Selection) + Send>;
+
+// selected rows, the anchor ranges start from and the cursor keyboard navigation moves;
+// rows are kept as sorted, disjoint and non touching ranges so selecting everything stays small
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Selection {
+    ranges: Vec<Range<usize>>,
+    anchor: Option<usize>,
+    cursor: Option<usize>
+}
+
+// a selection of one range is the common case, not a mistaken range of rows
+#[allow(clippy::single_range_in_vec_init)]
+impl Selection {
+    pub fn contains(&self, row: usize) -> bool {
+        self.position(row).is_ok()
+    }
+
+    pub fn ranges(&self) -> &[Range<usize>] {
+        &self.ranges
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = usize> + '_ {
+        self.ranges.iter().flat_map(|range| range.clone())
+    }
+
+    pub fn len(&self) -> usize {
+        self.ranges.iter().map(|range| range.len()).sum()
+    }
+
+    pub fn cursor(&self) -> Option<usize> {
+        self.cursor
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    pub fn select_only(&mut self, row: usize) {
+        self.ranges = vec![row..row + 1];
+        self.anchor = Some(row);
+        self.cursor = Some(row);
+    }
+
+    pub fn toggle(&mut self, row: usize) {
+        match self.position(row) {
+            Ok(index) => self.remove(index, row),
+            Err(index) => self.insert(index, row)
+        }
+        self.anchor = Some(row);
+        self.cursor = Some(row);
+    }
+
+    // selects from the anchor to the row and nothing else, the anchor stays for the next range
+    pub fn extend_to(&mut self, row: usize) {
+        let anchor = self.anchor.unwrap_or(row);
+        self.ranges = vec![anchor.min(row)..anchor.max(row) + 1];
+        self.anchor = Some(anchor);
+        self.cursor = Some(row);
+    }
+
+    pub fn select_all(&mut self, count: usize) {
+        self.ranges = if count == 0 { vec![] } else { vec![0..count] };
+    }
+
+    pub fn clear(&mut self) {
+        self.ranges.clear();
+        self.anchor = None;
+    }
+
+    // drops rows past the end after the model shrank
+    fn truncate(&mut self, count: usize) {
+        self.ranges.retain(|range| range.start < count);
+        if let Some(last) = self.ranges.last_mut() {
+            last.end = last.end.min(count);
+        }
+        self.anchor = self.anchor.filter(|row| *row < count);
+        self.cursor = self.cursor.filter(|row| *row < count);
+    }
+
+    // the index of the range holding the row, or where a range starting at it would go
+    fn position(&self, row: usize) -> Result<usize, usize> {
+        let index = self.ranges.partition_point(|range| range.end <= row);
+        match self.ranges.get(index) {
+            Some(range) if range.start <= row => Ok(index),
+            _ => Err(index)
+        }
+    }
+
+    fn insert(&mut self, index: usize, row: usize) {
+        let joins_previous = index > 0 && self.ranges[index - 1].end == row;
+        let joins_next = self.ranges.get(index).is_some_and(|range| range.start == row + 1);
+
+        match (joins_previous, joins_next) {
+            (true, true) => {
+                let next = self.ranges.remove(index);
+                self.ranges[index - 1].end = next.end;
+            }
+            (true, false) => self.ranges[index - 1].end = row + 1,
+            (false, true) => self.ranges[index].start = row,
+            (false, false) => self.ranges.insert(index, row..row + 1)
+        }
+    }
+
+    fn remove(&mut self, index: usize, row: usize) {
+        let range = self.ranges[index].clone();
+        match (range.start == row, range.end == row + 1) {
+            (true, true) => {
+                self.ranges.remove(index);
+            }
+            (true, false) => self.ranges[index].start = row + 1,
+            (false, true) => self.ranges[index].end = row,
+            (false, false) => {
+                self.ranges[index].end = row;
+                self.ranges.insert(index + 1, row + 1..range.end);
+            }
+        }
+    }
+}
+
+// binds the model to recycled rows
+pub struct TableRows<M: TableModel> {
+    model: M,
+    font: Font,
+    theme: Theme,
+    widths: Vec<f32>,
+    selection: Selection,
+    // the cursor is only shown while the table has focus
+    focused: bool,
+    row_height: f32
+}
+
+impl<M: TableModel> TableRows<M> {
+    fn column_widths(model: &M) -> Vec<f32> {
+        (0..model.column_count()).map(|column| model.column_width(column).max(MIN_COLUMN_WIDTH)).collect()
+    }
+}
+
+impl<M: TableModel> ListAdapter for TableRows<M> {
+    type Row = TableRow;
+
+    fn len(&self) -> usize {
+        self.model.row_count()
+    }
+
+    fn create_row(&mut self) -> TableRow {
+        TableRow {
+            base: WidgetBase::new(Rect::default(), self.theme.clone()),
+            font: self.font.clone(),
+            cells: vec![],
+            widths: vec![],
+            selected: false,
+            cursor: false
+        }
+    }
+
+    fn bind_row(&mut self, row: &mut TableRow, index: usize, _width: f32) -> f32 {
+        row.cells = (0..self.widths.len()).map(|column| self.model.cell_text(index, column)).collect();
+        row.widths = self.widths.clone();
+        row.selected = self.selection.contains(index);
+        row.cursor = self.focused && self.selection.cursor() == Some(index);
+        self.row_height
+    }
+
+    fn estimated_height(&self, _index: usize) -> f32 {
+        self.row_height
+    }
+
+    fn content_width(&self) -> f32 {
+        self.widths.iter().sum()
+    }
+}
+
+pub struct TableRow {
+    base: WidgetBase,
+    font: Font,
+    cells: Vec<String>,
+    widths: Vec<f32>,
+    selected: bool,
+    cursor: bool
+}
+
+impl Widget for TableRow {
+    fn base(&self) -> &WidgetBase {
+        &self.base
+    }
+
+    fn base_mut(&mut self) -> &mut WidgetBase {
+        &mut self.base
+    }
+
+    fn handle_key(&mut self, _event: &KeyEvent, _modifiers: ModifiersState) -> bool {
+        false
+    }
+
+    // the table selects rows itself, rows only track hover
+    fn handle_mouse_down(&mut self, _x: f32, _y: f32) -> bool {
+        false
+    }
+
+    fn handle_mouse_move(&mut self, x: f32, y: f32) -> bool {
+        self.base.set_hovered(self.base.bounds.contains(x, y))
+    }
+
+    fn handle_mouse_up(&mut self, _x: f32, _y: f32) -> bool {
+        false
+    }
+
+    fn draw_content(&mut self, designer: &Designer, renderer: &mut WgpuRenderer, drawn: &mut Drawn) -> Result<(), PathError> {
+        let style = self.base.style(WidgetKind::Table);
+        let theme = self.base.theme().clone();
+        let bounds = self.base.bounds;
+
+        let background = if self.selected {
+            Some(style.accent.lerp(theme.background, 0.75))
+        } else if self.base.state.hovered {
+            Some(style.background)
+        } else {
+            None
+        };
+        if background.is_some() || self.cursor {
+            let border = self.cursor.then(|| crate::graphics::sdf::Border::new(1.0, style.accent));
+            drawn.rounded_rect(designer, renderer, bounds, 0.0, background.unwrap_or(Color::TRANSPARENT), border);
+        }
+
+        let mut x = bounds.x;
+        for (text, width) in self.cells.iter().zip(&self.widths) {
+            let cell = Rect::new(x + theme.padding, bounds.y, width - theme.padding * 2.0, bounds.height);
+            if cell.width > 0.0 {
+                drawn.text(renderer, &self.font, text, &cell_style(&theme.text, cell.width), cell, style.foreground);
+            }
+            x += width;
+        }
+        Ok(())
+    }
+}
+
+// single line text cut with an ellipsis where the cell ends
+fn cell_style(text: &TextStyle, width: f32) -> TextStyle {
+    TextStyle {
+        max_width: Some(Measurement::Pixels(width)),
+        wrap: false,
+        ellipsis: true,
+        ..text.clone()
+    }
+}
+
+// a header cell edge held by the pointer
+#[derive(Copy, Clone, Debug)]
+struct ColumnResize {
+    column: usize,
+    start_x: f32,
+    start_width: f32
+}
+
+// a header that stays at the top while the rows scroll under it, both scroll sideways together
+pub struct Table<M: TableModel> {
+    base: WidgetBase,
+    font: Font,
+    list: ListView<TableRows<M>>,
+    mode: SelectionMode,
+    sort: Option<(usize, SortDirection)>,
+    resize: Option<ColumnResize>,
+    header_pressed: Option<usize>,
+    hovered_column: Option<usize>,
+    // mouse events carry no modifiers, the application passes them on when they change
+    modifiers: ModifiersState,
+    header_clip: Option<ClipId>,
+    on_sort: Option<SortHandler>,
+    on_selection_change: Option<SelectionHandler>
+}
+
+impl<M: TableModel> Table<M> {
+    pub fn new(model: M, font: Font, bounds: Rect, theme: Theme) -> Self {
+        let row_height = theme.text.size * 2.0;
+        let rows = TableRows {
+            widths: TableRows::column_widths(&model),
+            model,
+            font: font.clone(),
+            theme: theme.clone(),
+            selection: Selection::default(),
+            focused: false,
+            row_height
+        };
+        let list = ListView::new(rows, list_bounds(bounds, &theme), theme.clone());
+
+        Table {
+            base: WidgetBase::new(bounds, theme),
+            font,
+            list,
+            mode: SelectionMode::Single,
+            sort: None,
+            resize: None,
+            header_pressed: None,
+            hovered_column: None,
+            modifiers: ModifiersState::empty(),
+            header_clip: None,
+            on_sort: None,
+            on_selection_change: None
+        }
+    }
+
+    pub fn model(&self) -> &M {
+        &self.rows().model
+    }
+
+    // call reload after changing the rows
+    pub fn model_mut(&mut self) -> &mut M {
+        &mut self.rows_mut().model
+    }
+
+    pub fn reload(&mut self) {
+        let count = self.model().row_count();
+        let rows = self.rows_mut();
+        rows.selection.truncate(count);
+        if rows.widths.len() != rows.model.column_count() {
+            rows.widths = TableRows::column_widths(&rows.model);
+        }
+        self.list.reload();
+        self.base.mark_dirty();
+    }
+
+    pub fn selection_mode(&self) -> SelectionMode {
+        self.mode
+    }
+
+    pub fn set_selection_mode(&mut self, mode: SelectionMode) {
+        self.mode = mode;
+        self.change_selection(|selection| selection.clear());
+    }
+
+    pub fn selection(&self) -> &Selection {
+        &self.rows().selection
+    }
+
+    // replaces the selection without calling the handler, extra rows are dropped in single selection
+    pub fn set_selected_rows(&mut self, rows: &[usize]) {
+        let count = self.model().row_count();
+        let limit = if self.mode == SelectionMode::Multiple { usize::MAX } else { 1 };
+        let selection = &mut self.rows_mut().selection;
+        selection.clear();
+        for &row in rows.iter().filter(|row| **row < count).take(limit) {
+            selection.toggle(row);
+        }
+        self.list.refresh();
+    }
+
+    pub fn column_widths(&self) -> &[f32] {
+        &self.rows().widths
+    }
+
+    pub fn set_column_width(&mut self, column: usize, width: f32) {
+        if let Some(current) = self.rows_mut().widths.get_mut(column) {
+            *current = width.max(MIN_COLUMN_WIDTH);
+            self.list.refresh();
+            self.base.mark_dirty();
+        }
+    }
+
+    pub fn sort(&self) -> Option<(usize, SortDirection)> {
+        self.sort
+    }
+
+    // shows the indicator without calling the handler, for a model that starts sorted
+    pub fn set_sort(&mut self, sort: Option<(usize, SortDirection)>) {
+        self.sort = sort;
+        self.base.mark_dirty();
+    }
+
+    pub fn on_sort(&mut self, handler: impl FnMut(usize, SortDirection) + Send + 'static) {
+        self.on_sort = Some(Box::new(handler));
+    }
+
+    pub fn on_selection_change(&mut self, handler: impl FnMut(&Selection) + Send + 'static) {
+        self.on_selection_change = Some(Box::new(handler));
+    }
+
+    pub fn set_modifiers(&mut self, modifiers: ModifiersState) {
+        self.modifiers = modifiers;
+    }
+
+    pub fn scroll_to_row(&mut self, row: usize) {
+        self.list.scroll_to_index(row);
+    }
+
+    fn rows(&self) -> &TableRows<M> {
+        self.list.body().adapter()
+    }
+
+    fn rows_mut(&mut self) -> &mut TableRows<M> {
+        self.list.body_mut().adapter_mut()
+    }
+
+    fn header_bounds(&self) -> Rect {
+        let bounds = self.base.bounds;
+        Rect::new(bounds.x, bounds.y, bounds.width, header_height(self.base.theme()).min(bounds.height))
+    }
+
+    // left edge of every column in window coordinates, followed by the right edge of the last one
+    fn column_edges(&self) -> Vec<f32> {
+        let mut x = self.base.bounds.x - self.list.scroll_view().scroll_offset()[0];
+        let mut edges = vec![x];
+        for width in &self.rows().widths {
+            x += width;
+            edges.push(x);
+        }
+        edges
+    }
+
+    // the column under the pointer in the header, and whether the pointer is on its right edge
+    fn header_hit(&self, x: f32, y: f32) -> Option<(usize, bool)> {
+        if !self.header_bounds().contains(x, y) {
+            return None;
+        }
+        let edges = self.column_edges();
+        for column in 0..edges.len() - 1 {
+            let right = edges[column + 1];
+            if (x - right).abs() <= RESIZE_GRAB {
+                return Some((column, true));
+            }
+            if x >= edges[column] && x < right {
+                return Some((column, false));
+            }
+        }
+        None
+    }
+
+    fn row_at(&self, x: f32, y: f32) -> Option<usize> {
+        let list = self.list.bounds();
+        if !list.contains(x, y) {
+            return None;
+        }
+        let content_y = y - list.y + self.list.scroll_view().scroll_offset()[1];
+        let row = (content_y / self.rows().row_height).floor();
+        (row >= 0.0 && (row as usize) < self.model().row_count()).then_some(row as usize)
+    }
+
+    fn change_selection(&mut self, change: impl FnOnce(&mut Selection)) {
+        let selection = &mut self.rows_mut().selection;
+        let before = selection.clone();
+        change(selection);
+        if *selection == before {
+            return;
+        }
+
+        let cursor = selection.cursor();
+        let changed = selection.ranges != before.ranges;
+        self.list.refresh();
+        if let Some(cursor) = cursor.filter(|_| cursor != before.cursor) {
+            self.list.scroll_to_index(cursor);
+        }
+        if changed {
+            if let Some(handler) = &mut self.on_selection_change {
+                handler(&self.list.body().adapter().selection);
+            }
+        }
+    }
+
+    fn click_row(&mut self, row: usize) {
+        let command = self.modifiers.control_key() || self.modifiers.super_key();
+        let extend = self.modifiers.shift_key();
+        match self.mode {
+            SelectionMode::None => self.change_selection(|selection| selection.cursor = Some(row)),
+            SelectionMode::Single => self.change_selection(|selection| selection.select_only(row)),
+            SelectionMode::Multiple if extend => self.change_selection(|selection| selection.extend_to(row)),
+            SelectionMode::Multiple if command => self.change_selection(|selection| selection.toggle(row)),
+            SelectionMode::Multiple => self.change_selection(|selection| selection.select_only(row))
+        }
+    }
+
+    // moves the cursor, selecting the row it lands on or the range up to it
+    fn move_cursor(&mut self, row: usize, extend: bool) {
+        let mode = self.mode;
+        self.change_selection(|selection| match mode {
+            SelectionMode::None => selection.cursor = Some(row),
+            SelectionMode::Multiple if extend => selection.extend_to(row),
+            _ => selection.select_only(row)
+        });
+    }
+
+    fn click_header(&mut self, column: usize) {
+        if !self.model().is_sortable(column) {
+            return;
+        }
+        let direction = match self.sort {
+            Some((sorted, direction)) if sorted == column => direction.reversed(),
+            _ => SortDirection::Ascending
+        };
+        self.sort = Some((column, direction));
+        self.base.mark_dirty();
+        if let Some(handler) = &mut self.on_sort {
+            handler(column, direction);
+        }
+    }
+
+    fn draw_header(&mut self, designer: &Designer, renderer: &mut WgpuRenderer, drawn: &mut Drawn) -> Result<(), PathError> {
+        let style = self.base.style(WidgetKind::Table);
+        let theme = self.base.theme().clone();
+        let header = self.header_bounds();
+        let edges = self.column_edges();
+
+        drawn.rounded_rect(designer, renderer, header, 0.0, style.background, None);
+        let line = Rect::new(header.x, header.bottom() - 1.0, header.width, 1.0);
+        drawn.rounded_rect(designer, renderer, line, 0.0, theme.border, None);
+
+        for column in 0..edges.len() - 1 {
+            let cell = Rect::new(edges[column], header.y, edges[column + 1] - edges[column], header.height);
+            if cell.right() < header.x || cell.x > header.right() {
+                continue;
+            }
+            if self.hovered_column == Some(column) && self.resize.is_none() {
+                let hover = Rect::new(cell.x, cell.y, cell.width, cell.height - 1.0);
+                drawn.rounded_rect(designer, renderer, hover, 0.0, style.background.lerp(theme.foreground, 0.06), None);
+            }
+
+            let sorted = self.sort.filter(|(sorted, _)| *sorted == column).map(|(_, direction)| direction);
+            let indicator = theme.text.size * 0.6;
+            let reserved = if sorted.is_some() { indicator + theme.padding } else { 0.0 };
+            let title = Rect::new(cell.x + theme.padding, cell.y, cell.width - theme.padding * 2.0 - reserved, cell.height);
+            if title.width > 0.0 {
+                let text = self.model().column_title(column);
+                drawn.text(renderer, &self.font, &text, &cell_style(&theme.text, title.width), title, style.foreground);
+            }
+
+            if let Some(direction) = sorted {
+                let x = cell.right() - theme.padding - indicator;
+                let y = cell.y + (cell.height - indicator * 0.6) / 2.0;
+                let path = match direction {
+                    SortDirection::Ascending => Path::new()
+                        .move_to(x, y + indicator * 0.6)
+                        .line_to(x + indicator / 2.0, y)
+                        .line_to(x + indicator, y + indicator * 0.6),
+                    SortDirection::Descending => Path::new()
+                        .move_to(x, y)
+                        .line_to(x + indicator / 2.0, y + indicator * 0.6)
+                        .line_to(x + indicator, y)
+                };
+                drawn.instances.push(designer.create_path_fill(renderer, &path.close(), FillRule::NonZero, style.foreground)?);
+            }
+
+            let resizing = self.resize.is_some_and(|resize| resize.column == column);
+            let divider = Rect::new(cell.right() - 1.0, cell.y + 4.0, 1.0, (cell.height - 8.0).max(0.0));
+            drawn.rounded_rect(designer, renderer, divider, 0.0, if resizing { theme.accent } else { theme.border }, None);
+        }
+        Ok(())
+    }
+}
+
+fn header_height(theme: &Theme) -> f32 {
+    theme.text.size * 2.0
+}
+
+fn list_bounds(bounds: Rect, theme: &Theme) -> Rect {
+    let header = header_height(theme).min(bounds.height);
+    Rect::new(bounds.x, bounds.y + header, bounds.width, bounds.height - header)
+}
+
+impl<M: TableModel> Widget for Table<M> {
+    fn base(&self) -> &WidgetBase {
+        &self.base
+    }
+
+    fn base_mut(&mut self) -> &mut WidgetBase {
+        &mut self.base
+    }
+
+    fn handle_key(&mut self, event: &KeyEvent, modifiers: ModifiersState) -> bool {
+        self.modifiers = modifiers;
+        if self.base.state.disabled || event.state != ElementState::Pressed {
+            return false;
+        }
+
+        let count = self.model().row_count();
+        if count == 0 {
+            return self.list.handle_key(event, modifiers);
+        }
+        let cursor = self.selection().cursor();
+        let page = ((self.list.bounds().height / self.rows().row_height) as usize).max(1);
+        let extend = modifiers.shift_key();
+        let command = modifiers.control_key() || modifiers.super_key();
+
+        let target = match &event.logical_key {
+            Key::Named(NamedKey::ArrowDown) => cursor.map_or(0, |row| (row + 1).min(count - 1)),
+            Key::Named(NamedKey::ArrowUp) => cursor.map_or(0, |row| row.saturating_sub(1)),
+            Key::Named(NamedKey::PageDown) => cursor.map_or(0, |row| (row + page).min(count - 1)),
+            Key::Named(NamedKey::PageUp) => cursor.map_or(0, |row| row.saturating_sub(page)),
+            Key::Named(NamedKey::Home) => 0,
+            Key::Named(NamedKey::End) => count - 1,
+            Key::Named(NamedKey::Space) => {
+                let row = cursor.unwrap_or(0);
+                match self.mode {
+                    SelectionMode::Multiple => self.change_selection(|selection| selection.toggle(row)),
+                    SelectionMode::Single => self.change_selection(|selection| selection.select_only(row)),
+                    SelectionMode::None => {}
+                }
+                return true;
+            }
+            Key::Character(c) if command && c.eq_ignore_ascii_case("a") && self.mode == SelectionMode::Multiple => {
+                self.change_selection(|selection| selection.select_all(count));
+                return true;
+            }
+            _ => return self.list.handle_key(event, modifiers)
+        };
+        self.move_cursor(target, extend);
+        true
+    }
+
+    fn handle_mouse_down(&mut self, x: f32, y: f32) -> bool {
+        if self.base.state.disabled || !self.base.bounds.contains(x, y) {
+            return false;
+        }
+
+        if let Some((column, edge)) = self.header_hit(x, y) {
+            if edge {
+                let start_width = self.rows().widths[column];
+                self.resize = Some(ColumnResize { column, start_x: x, start_width });
+            } else {
+                self.header_pressed = Some(column);
+            }
+            self.base.set_pressed(true);
+            return true;
+        }
+
+        // the scrollbars come first
+        if self.list.handle_mouse_down(x, y) {
+            return true;
+        }
+        if let Some(row) = self.row_at(x, y) {
+            self.base.take_focus();
+            self.click_row(row);
+        }
+        true
+    }
+
+    fn handle_mouse_move(&mut self, x: f32, y: f32) -> bool {
+        if let Some(resize) = self.resize {
+            self.set_column_width(resize.column, resize.start_width + x - resize.start_x);
+            return true;
+        }
+
+        let hovered = self.header_hit(x, y).filter(|(_, edge)| !edge).map(|(column, _)| column);
+        let mut changed = hovered != self.hovered_column;
+        if changed {
+            self.hovered_column = hovered;
+            self.base.mark_dirty();
+        }
+        changed |= self.base.set_hovered(self.base.bounds.contains(x, y));
+        changed | self.list.handle_mouse_move(x, y)
+    }
+
+    fn handle_mouse_up(&mut self, x: f32, y: f32) -> bool {
+        self.base.set_pressed(false);
+        if self.resize.take().is_some() {
+            self.base.mark_dirty();
+            return true;
+        }
+        if let Some(pressed) = self.header_pressed.take() {
+            if self.header_hit(x, y) == Some((pressed, false)) {
+                self.click_header(pressed);
+            }
+            return true;
+        }
+        self.list.handle_mouse_up(x, y)
+    }
+
+    fn draw_content(&mut self, designer: &Designer, renderer: &mut WgpuRenderer, drawn: &mut Drawn) -> Result<(), PathError> {
+        let mut result = Ok(());
+        if self.list.is_dirty() {
+            result = self.list.draw(designer, renderer);
+        }
+
+        // the header is drawn again on every draw, so its clip is made again with it
+        if let Some(clip) = self.header_clip.take() {
+            renderer.remove_clip(clip);
+        }
+        self.header_clip = Some(renderer.push_rect_clip(self.header_bounds()));
+        let header = self.draw_header(designer, renderer, drawn);
+        renderer.pop_clip();
+        result.and(header)
+    }
+
+    fn handle_scroll(&mut self, delta: &MouseScrollDelta, phase: TouchPhase, x: f32, y: f32) -> bool {
+        // the header scrolls the rows too, so it moves with them
+        let (x, y) = if self.header_bounds().contains(x, y) { (x, self.list.bounds().y) } else { (x, y) };
+        let handled = self.list.handle_scroll(delta, phase, x, y);
+        if handled {
+            self.base.mark_dirty();
+        }
+        handled
+    }
+
+    fn tick(&mut self, elapsed: f32) -> bool {
+        let offset = self.list.scroll_view().scroll_offset()[0];
+        let moving = self.list.tick(elapsed);
+        if self.list.scroll_view().scroll_offset()[0] != offset {
+            self.base.mark_dirty();
+        }
+        moving
+    }
+
+    fn clear(&mut self, renderer: &mut WgpuRenderer) {
+        self.list.clear(renderer);
+        self.base.drawn.clear(renderer);
+        if let Some(clip) = self.header_clip.take() {
+            renderer.remove_clip(clip);
+        }
+        self.base.dirty = true;
+    }
+
+    fn set_bounds(&mut self, bounds: Rect) {
+        self.base.bounds = bounds;
+        self.base.dirty = true;
+        if let Some(node) = &self.base.node {
+            node.set_bounds(bounds);
+        }
+        let list = list_bounds(bounds, self.base.theme());
+        self.list.set_bounds(list);
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.base.state.focused = focused;
+        self.base.dirty = true;
+        self.rows_mut().focused = focused;
+        self.list.refresh();
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.base.dirty || self.list.is_dirty()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::single_range_in_vec_init)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toggling_splits_and_merges_ranges() {
+        let mut selection = Selection::default();
+        selection.select_all(10);
+        selection.toggle(4);
+        assert_eq!(selection.ranges(), &[0..4, 5..10]);
+        assert_eq!(selection.len(), 9);
+
+        selection.toggle(4);
+        assert_eq!(selection.ranges(), &[0..10]);
+        selection.toggle(0);
+        selection.toggle(9);
+        assert_eq!(selection.ranges(), &[1..9]);
+    }
+
+    #[test]
+    fn ranges_extend_from_the_anchor() {
+        let mut selection = Selection::default();
+        selection.select_only(5);
+        selection.extend_to(2);
+        assert_eq!(selection.rows().collect::<Vec<_>>(), vec![2, 3, 4, 5]);
+        selection.extend_to(7);
+        assert_eq!(selection.ranges(), &[5..8]);
+        assert_eq!(selection.cursor(), Some(7));
+    }
+
+    #[test]
+    fn selecting_everything_stays_one_range() {
+        let mut selection = Selection::default();
+        selection.select_all(usize::MAX);
+        assert_eq!(selection.ranges().len(), 1);
+        assert!(selection.contains(usize::MAX - 1));
+
+        selection.truncate(3);
+        assert_eq!(selection.ranges(), &[0..3]);
+        assert!(!selection.contains(3));
+    }
+}
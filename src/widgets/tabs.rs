@@ -0,0 +1,626 @@
+use winit::dpi::PhysicalSize;
+use winit::event::{ElementState, KeyEvent, MouseScrollDelta, TouchPhase};
+use winit::keyboard::{Key, ModifiersState, NamedKey};
+use crate::designer::point::Measurement;
+use crate::designer::Designer;
+use crate::graphics::clip::ClipId;
+use crate::graphics::color::Color;
+use crate::graphics::path::{Path, PathError, StrokeStyle};
+use crate::render::wgpu::WgpuRenderer;
+use crate::scene::Rect;
+use crate::text::font::Font;
+use crate::text::layout::{measure_text, TextStyle};
+use crate::widgets::radio::SelectHandler;
+use crate::widgets::theme::Theme;
+use crate::widgets::{Drawn, Widget, WidgetBase};
+
+const MIN_TAB_WIDTH: f32 = 64.0;
+const MAX_TAB_WIDTH: f32 = 200.0;
+const CLOSE_SIZE: f32 = 16.0;
+
+// a tab in a strip and its close button, when it has one
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct TabRect {
+    pub tab: Rect,
+    pub close: Option<Rect>
+}
+
+pub(crate) fn strip_height(theme: &Theme) -> f32 {
+    theme.text.size + theme.padding * 2.0
+}
+
+// tabs are as wide as their titles within limits, and share the strip evenly when they do not fit
+pub(crate) fn layout_tabs(font: &Font, theme: &Theme, titles: &[(&str, bool)], strip: Rect) -> Vec<TabRect> {
+    let style = TextStyle {
+        max_width: None,
+        ..theme.text.clone()
+    };
+    let mut widths: Vec<f32> = titles.iter().map(|(title, closable)| {
+        let [text, _] = measure_text(font, title, &style, PhysicalSize::new(1, 1));
+        let close = if *closable { CLOSE_SIZE + theme.padding / 2.0 } else { 0.0 };
+        (text + close + theme.padding * 2.0).clamp(MIN_TAB_WIDTH, MAX_TAB_WIDTH)
+    }).collect();
+    let total: f32 = widths.iter().sum();
+    if total > strip.width && total > 0.0 {
+        let share = strip.width / widths.len() as f32;
+        widths.iter_mut().for_each(|width| *width = share);
+    }
+
+    let mut x = strip.x;
+    widths.into_iter().zip(titles).map(|(width, (_, closable))| {
+        let tab = Rect::new(x, strip.y, width, strip.height);
+        x += width;
+        let close = closable.then(|| Rect::new(
+            tab.right() - theme.padding / 2.0 - CLOSE_SIZE,
+            tab.y + (tab.height - CLOSE_SIZE) / 2.0,
+            CLOSE_SIZE,
+            CLOSE_SIZE
+        ));
+        TabRect { tab, close }
+    }).collect()
+}
+
+// a strip of tabs as it is drawn
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct TabStrip<'a> {
+    pub bounds: Rect,
+    pub tabs: &'a [TabRect],
+    pub titles: &'a [&'a str],
+    pub selected: Option<usize>,
+    pub hovered: Option<usize>,
+    pub hovered_close: Option<usize>,
+    pub focused: bool
+}
+
+// the selected tab opens into the content below it, the line under the strip stops at its edges
+pub(crate) fn draw_tab_strip(
+    designer: &Designer,
+    renderer: &mut WgpuRenderer,
+    drawn: &mut Drawn,
+    font: &Font,
+    theme: &Theme,
+    state: &TabStrip
+) -> Result<(), PathError> {
+    let strip = state.bounds;
+    drawn.rounded_rect(designer, renderer, strip, 0.0, theme.surface, None);
+
+    let line_y = strip.bottom() - theme.border_width;
+    let gap = state.selected.and_then(|index| state.tabs.get(index)).map(|rect| rect.tab);
+    let (left, right) = match gap {
+        Some(gap) => (gap.x, gap.right()),
+        None => (strip.right(), strip.right())
+    };
+    if left > strip.x {
+        drawn.rounded_rect(designer, renderer, Rect::new(strip.x, line_y, left - strip.x, theme.border_width), 0.0, theme.border, None);
+    }
+    if strip.right() > right {
+        drawn.rounded_rect(designer, renderer, Rect::new(right, line_y, strip.right() - right, theme.border_width), 0.0, theme.border, None);
+    }
+
+    for (index, (rect, title)) in state.tabs.iter().zip(state.titles).enumerate() {
+        let selected = state.selected == Some(index);
+        let hovered = state.hovered == Some(index);
+        let tab = rect.tab;
+        if selected {
+            drawn.rounded_rect(designer, renderer, tab, 0.0, theme.background, None);
+            let bar = if state.focused { 3.0 } else { 2.0 };
+            drawn.rounded_rect(designer, renderer, Rect::new(tab.x, tab.y, tab.width, bar), 0.0, theme.accent, None);
+        } else if hovered {
+            drawn.rounded_rect(designer, renderer, tab, 0.0, theme.surface.lerp(theme.foreground, 0.06), None);
+        }
+
+        let end = rect.close.map_or(tab.right() - theme.padding, |close| close.x - theme.padding / 2.0);
+        let area = Rect::new(tab.x + theme.padding, tab.y, end - tab.x - theme.padding, tab.height);
+        if area.width > 0.0 {
+            let style = TextStyle {
+                max_width: Some(Measurement::Pixels(area.width)),
+                ellipsis: true,
+                ..theme.text.clone()
+            };
+            let color = if selected { theme.foreground } else { theme.foreground.lerp(theme.surface, 0.35) };
+            drawn.text(renderer, font, title, &style, area, color);
+        }
+
+        // close buttons only show on the selected tab and under the pointer, like most editors
+        if let Some(close) = rect.close.filter(|_| selected || hovered) {
+            if state.hovered_close == Some(index) {
+                drawn.rounded_rect(designer, renderer, close, theme.corner_radius.min(CLOSE_SIZE / 2.0), theme.foreground.with_alpha(0.1), None);
+            }
+            draw_cross(designer, renderer, drawn, close.inflate(-4.5), theme.foreground)?;
+        }
+    }
+    Ok(())
+}
+
+fn draw_cross(designer: &Designer, renderer: &mut WgpuRenderer, drawn: &mut Drawn, area: Rect, color: Color) -> Result<(), PathError> {
+    let cross = Path::new()
+        .move_to(area.x, area.y)
+        .line_to(area.right(), area.bottom())
+        .move_to(area.right(), area.y)
+        .line_to(area.x, area.bottom());
+    drawn.instances.push(designer.create_path_stroke(renderer, &cross, &StrokeStyle::new(1.5), color)?);
+    Ok(())
+}
+
+struct Page<C> {
+    title: String,
+    content: C,
+    closable: bool
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Press {
+    Tab,
+    Close(usize),
+    Page
+}
+
+// pages behind a strip of titles, only the selected page is drawn and gets input;
+// ctrl+tab and ctrl+page down move to the next tab, with shift or page up to the previous one
+pub struct Tabs<C: Widget = Box<dyn Widget>> {
+    base: WidgetBase,
+    font: Font,
+    pages: Vec<Page<C>>,
+    selected: Option<usize>,
+    // the page drawn last, cleared when another one is selected
+    shown: Option<usize>,
+    // removed pages, what they drew is removed on the next draw
+    removed: Vec<C>,
+    strip: Vec<TabRect>,
+    hovered: Option<usize>,
+    hovered_close: Option<usize>,
+    pressed: Option<Press>,
+    clip: Option<(Rect, ClipId)>,
+    on_select: Option<SelectHandler>,
+    on_close: Option<SelectHandler>
+}
+
+impl<C: Widget> Tabs<C> {
+    pub fn new(font: Font, bounds: Rect, theme: Theme) -> Self {
+        Tabs {
+            base: WidgetBase::new(bounds, theme),
+            font,
+            pages: vec![],
+            selected: None,
+            shown: None,
+            removed: vec![],
+            strip: vec![],
+            hovered: None,
+            hovered_close: None,
+            pressed: None,
+            clip: None,
+            on_select: None,
+            on_close: None
+        }
+    }
+
+    // the first page added is selected
+    pub fn add(&mut self, title: &str, content: C) -> usize {
+        self.insert(self.pages.len(), title, content, false)
+    }
+
+    pub fn add_closable(&mut self, title: &str, content: C) -> usize {
+        self.insert(self.pages.len(), title, content, true)
+    }
+
+    pub fn insert(&mut self, index: usize, title: &str, mut content: C, closable: bool) -> usize {
+        let index = index.min(self.pages.len());
+        content.set_bounds(self.content_bounds());
+        self.pages.insert(index, Page { title: title.to_string(), content, closable });
+        for position in [&mut self.selected, &mut self.shown].into_iter().flatten() {
+            if *position >= index {
+                *position += 1;
+            }
+        }
+        if self.selected.is_none() {
+            self.selected = Some(index);
+        }
+        self.layout_strip();
+        index
+    }
+
+    // the next tab is selected when the selected one is removed, or the previous one at the end
+    pub fn remove(&mut self, index: usize) {
+        if index >= self.pages.len() {
+            return;
+        }
+        let page = self.pages.remove(index);
+        self.removed.push(page.content);
+
+        self.shown = match self.shown {
+            Some(shown) if shown == index => None,
+            Some(shown) if shown > index => Some(shown - 1),
+            shown => shown
+        };
+        self.selected = match self.selected {
+            _ if self.pages.is_empty() => None,
+            Some(selected) if selected > index => Some(selected - 1),
+            Some(selected) => Some(selected.min(self.pages.len() - 1)),
+            None => None
+        };
+        self.hovered = None;
+        self.hovered_close = None;
+        self.layout_strip();
+    }
+
+    pub fn len(&self) -> usize {
+        self.pages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pages.is_empty()
+    }
+
+    pub fn title(&self, index: usize) -> Option<&str> {
+        self.pages.get(index).map(|page| page.title.as_str())
+    }
+
+    pub fn set_title(&mut self, index: usize, title: &str) {
+        if let Some(page) = self.pages.get_mut(index) {
+            page.title = title.to_string();
+            self.layout_strip();
+        }
+    }
+
+    pub fn page(&self, index: usize) -> Option<&C> {
+        self.pages.get(index).map(|page| &page.content)
+    }
+
+    pub fn page_mut(&mut self, index: usize) -> Option<&mut C> {
+        self.pages.get_mut(index).map(|page| &mut page.content)
+    }
+
+    pub fn selected(&self) -> Option<usize> {
+        self.selected
+    }
+
+    // changes the selected tab without calling the handler
+    pub fn select(&mut self, index: usize) {
+        if index < self.pages.len() && self.selected != Some(index) {
+            self.selected = Some(index);
+            self.base.mark_dirty();
+        }
+    }
+
+    pub fn on_select(&mut self, handler: impl FnMut(usize) + Send + 'static) {
+        self.on_select = Some(Box::new(handler));
+    }
+
+    // called with the index a tab had when its close button removed it
+    pub fn on_close(&mut self, handler: impl FnMut(usize) + Send + 'static) {
+        self.on_close = Some(Box::new(handler));
+    }
+
+    // where the selected page is laid out, below the strip
+    pub fn content_bounds(&self) -> Rect {
+        let bounds = self.base.bounds;
+        let strip = strip_height(self.base.theme()).min(bounds.height);
+        Rect::new(bounds.x, bounds.y + strip, bounds.width, bounds.height - strip)
+    }
+
+    fn strip_bounds(&self) -> Rect {
+        let bounds = self.base.bounds;
+        Rect::new(bounds.x, bounds.y, bounds.width, strip_height(self.base.theme()).min(bounds.height))
+    }
+
+    fn layout_strip(&mut self) {
+        let titles: Vec<(&str, bool)> = self.pages.iter().map(|page| (page.title.as_str(), page.closable)).collect();
+        self.strip = layout_tabs(&self.font, self.base.theme(), &titles, self.strip_bounds());
+        self.base.dirty = true;
+    }
+
+    fn choose(&mut self, index: usize) {
+        if self.selected == Some(index) {
+            return;
+        }
+        self.select(index);
+        if let Some(handler) = &mut self.on_select {
+            handler(index);
+        }
+    }
+
+    // steps through the tabs, wrapping around at the ends
+    fn step(&mut self, forward: bool) -> bool {
+        let count = self.pages.len();
+        let Some(selected) = self.selected.filter(|_| count > 1) else {
+            return false;
+        };
+        self.choose(if forward { (selected + 1) % count } else { (selected + count - 1) % count });
+        true
+    }
+
+    fn close(&mut self, index: usize) {
+        self.remove(index);
+        if let Some(handler) = &mut self.on_close {
+            handler(index);
+        }
+    }
+
+    fn tab_at(&self, x: f32, y: f32) -> Option<usize> {
+        self.strip.iter().position(|rect| rect.tab.contains(x, y))
+    }
+
+    fn close_at(&self, x: f32, y: f32) -> Option<usize> {
+        self.strip.iter().position(|rect| rect.close.is_some_and(|close| close.contains(x, y)))
+    }
+
+    fn selected_page(&mut self) -> Option<&mut C> {
+        let index = self.selected?;
+        self.pages.get_mut(index).map(|page| &mut page.content)
+    }
+}
+
+impl<C: Widget> Widget for Tabs<C> {
+    fn base(&self) -> &WidgetBase {
+        &self.base
+    }
+
+    fn base_mut(&mut self) -> &mut WidgetBase {
+        &mut self.base
+    }
+
+    // the shortcuts that switch tabs come first, then the selected page, then arrows on the strip
+    fn handle_key(&mut self, event: &KeyEvent, modifiers: ModifiersState) -> bool {
+        if self.base.state.disabled {
+            return false;
+        }
+        if event.state == ElementState::Pressed && modifiers.control_key() {
+            match event.logical_key {
+                Key::Named(NamedKey::Tab) => return self.step(!modifiers.shift_key()),
+                Key::Named(NamedKey::PageDown) => return self.step(true),
+                Key::Named(NamedKey::PageUp) => return self.step(false),
+                _ => {}
+            }
+        }
+        if self.selected_page().is_some_and(|page| page.handle_key(event, modifiers)) {
+            return true;
+        }
+
+        if event.state != ElementState::Pressed || !self.base.state.focused || self.pages.is_empty() {
+            return false;
+        }
+        match event.logical_key {
+            Key::Named(NamedKey::ArrowRight) => self.step(true),
+            Key::Named(NamedKey::ArrowLeft) => self.step(false),
+            Key::Named(NamedKey::Home) => {
+                self.choose(0);
+                true
+            }
+            Key::Named(NamedKey::End) => {
+                self.choose(self.pages.len() - 1);
+                true
+            }
+            _ => false
+        }
+    }
+
+    // tabs are selected on press, close buttons act on release like buttons do
+    fn handle_mouse_down(&mut self, x: f32, y: f32) -> bool {
+        if self.base.state.disabled || !self.base.bounds.contains(x, y) {
+            return false;
+        }
+        if let Some(index) = self.close_at(x, y) {
+            self.pressed = Some(Press::Close(index));
+            return true;
+        }
+        if let Some(index) = self.tab_at(x, y) {
+            self.pressed = Some(Press::Tab);
+            self.base.take_focus();
+            self.choose(index);
+            return true;
+        }
+        if !self.content_bounds().contains(x, y) {
+            return false;
+        }
+        self.pressed = Some(Press::Page);
+        self.selected_page().is_some_and(|page| page.handle_mouse_down(x, y))
+    }
+
+    fn handle_mouse_move(&mut self, x: f32, y: f32) -> bool {
+        let (hovered, hovered_close) = if self.base.state.disabled {
+            (None, None)
+        } else {
+            (self.tab_at(x, y), self.close_at(x, y))
+        };
+        let mut changed = (hovered, hovered_close) != (self.hovered, self.hovered_close);
+        if changed {
+            self.hovered = hovered;
+            self.hovered_close = hovered_close;
+            self.base.dirty = true;
+        }
+        if let Some(page) = self.selected_page() {
+            changed |= page.handle_mouse_move(x, y);
+        }
+        changed
+    }
+
+    fn handle_mouse_up(&mut self, x: f32, y: f32) -> bool {
+        match self.pressed.take() {
+            Some(Press::Close(index)) => {
+                if self.close_at(x, y) == Some(index) {
+                    self.close(index);
+                }
+                true
+            }
+            Some(Press::Tab) => true,
+            Some(Press::Page) => self.selected_page().is_some_and(|page| page.handle_mouse_up(x, y)),
+            None => false
+        }
+    }
+
+    fn handle_scroll(&mut self, delta: &MouseScrollDelta, phase: TouchPhase, x: f32, y: f32) -> bool {
+        if self.base.state.disabled || !self.content_bounds().contains(x, y) {
+            return false;
+        }
+        self.selected_page().is_some_and(|page| page.handle_scroll(delta, phase, x, y))
+    }
+
+    fn tick(&mut self, elapsed: f32) -> bool {
+        self.selected_page().is_some_and(|page| page.tick(elapsed))
+    }
+
+    fn draw_content(&mut self, designer: &Designer, renderer: &mut WgpuRenderer, drawn: &mut Drawn) -> Result<(), PathError> {
+        for mut page in self.removed.drain(..) {
+            page.clear(renderer);
+        }
+        if self.shown != self.selected {
+            if let Some(page) = self.shown.and_then(|index| self.pages.get_mut(index)) {
+                page.content.clear(renderer);
+            }
+            self.shown = self.selected;
+        }
+
+        let theme = self.base.theme().clone();
+        let titles: Vec<&str> = self.pages.iter().map(|page| page.title.as_str()).collect();
+        let state = TabStrip {
+            bounds: self.strip_bounds(),
+            tabs: &self.strip,
+            titles: &titles,
+            selected: self.selected,
+            hovered: self.hovered,
+            hovered_close: self.hovered_close,
+            focused: self.base.state.focused
+        };
+        let mut result = draw_tab_strip(designer, renderer, drawn, &self.font, &theme, &state);
+
+        let content = self.content_bounds();
+        let clip = match self.clip.take() {
+            Some((bounds, clip)) if bounds == content => clip,
+            previous => {
+                // the page drawn under the old clip has to be drawn again under the new one
+                if let Some((_, clip)) = previous {
+                    renderer.remove_clip(clip);
+                }
+                if let Some(page) = self.selected_page() {
+                    page.clear(renderer);
+                }
+                let clip = renderer.push_rect_clip(content);
+                renderer.pop_clip();
+                clip
+            }
+        };
+        if let Some(page) = self.selected_page().filter(|page| page.is_dirty()) {
+            renderer.reenter_clip(clip);
+            result = result.and(page.draw(designer, renderer));
+            renderer.pop_clip();
+        }
+        self.clip = Some((content, clip));
+        result
+    }
+
+    fn clear(&mut self, renderer: &mut WgpuRenderer) {
+        for mut page in self.removed.drain(..) {
+            page.clear(renderer);
+        }
+        for page in &mut self.pages {
+            page.content.clear(renderer);
+        }
+        self.base.drawn.clear(renderer);
+        if let Some((_, clip)) = self.clip.take() {
+            renderer.remove_clip(clip);
+        }
+        self.base.dirty = true;
+    }
+
+    fn set_bounds(&mut self, bounds: Rect) {
+        self.base.bounds = bounds;
+        if let Some(node) = &self.base.node {
+            node.set_bounds(bounds);
+        }
+        let content = self.content_bounds();
+        for page in &mut self.pages {
+            page.content.set_bounds(content);
+        }
+        self.layout_strip();
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.base.dirty
+            || !self.removed.is_empty()
+            || self.selected.and_then(|index| self.pages.get(index)).is_some_and(|page| page.content.is_dirty())
+    }
+
+    fn set_enabled(&mut self, enabled: bool) {
+        if !enabled {
+            self.hovered = None;
+            self.hovered_close = None;
+            self.pressed = None;
+        }
+        self.base.state.disabled = !enabled;
+        if let Some(node) = &self.base.node {
+            node.set_enabled(enabled);
+        }
+        for page in &mut self.pages {
+            page.content.set_enabled(enabled);
+        }
+        self.base.mark_dirty();
+    }
+
+    fn set_theme(&mut self, theme: Theme) {
+        self.base.theme = theme;
+        let content = self.content_bounds();
+        for page in &mut self.pages {
+            page.content.set_bounds(content);
+        }
+        self.layout_strip();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::text::font::test_font;
+    use crate::widgets::button::Button;
+
+    fn tabs(count: usize) -> Tabs<Button> {
+        let font = test_font();
+        let mut tabs = Tabs::new(font.clone(), Rect::new(0.0, 0.0, 400.0, 300.0), Theme::default());
+        for index in 0..count {
+            tabs.add_closable(&format!("page {}", index), Button::new(font.clone(), "ok", Rect::default(), Theme::default()));
+        }
+        tabs
+    }
+
+    #[test]
+    fn tabs_share_the_strip_when_they_do_not_fit() {
+        let font = test_font();
+        let theme = Theme::default();
+        let strip = Rect::new(0.0, 0.0, 100.0, strip_height(&theme));
+        let rects = layout_tabs(&font, &theme, &[("a", false), ("b", true)], strip);
+        assert_eq!(rects.iter().map(|rect| rect.tab.width).collect::<Vec<_>>(), vec![50.0, 50.0]);
+        assert_eq!(rects[1].tab.x, 50.0);
+        assert!(rects[0].close.is_none());
+        assert!(rects[1].close.is_some_and(|close| close.right() <= 100.0));
+
+        let wide = layout_tabs(&font, &theme, &[("a", false)], Rect::new(0.0, 0.0, 1000.0, strip.height));
+        assert_eq!(wide[0].tab.width, MIN_TAB_WIDTH);
+    }
+
+    #[test]
+    fn pages_are_laid_out_below_the_strip() {
+        let tabs = tabs(1);
+        let strip = strip_height(&Theme::default());
+        assert_eq!(tabs.content_bounds(), Rect::new(0.0, strip, 400.0, 300.0 - strip));
+        assert_eq!(tabs.page(0).unwrap().bounds(), tabs.content_bounds());
+    }
+
+    #[test]
+    fn removing_the_selected_tab_selects_the_next_one() {
+        let mut tabs = tabs(3);
+        assert_eq!(tabs.selected(), Some(0));
+
+        tabs.select(1);
+        tabs.remove(1);
+        assert_eq!(tabs.selected(), Some(1));
+        assert_eq!(tabs.title(1), Some("page 2"));
+
+        tabs.remove(1);
+        assert_eq!(tabs.selected(), Some(0));
+        tabs.insert(0, "first", Button::new(test_font(), "ok", Rect::default(), Theme::default()), false);
+        assert_eq!(tabs.selected(), Some(1));
+
+        tabs.remove(0);
+        tabs.remove(0);
+        assert_eq!(tabs.selected(), None);
+    }
+}
\ No newline at end of file
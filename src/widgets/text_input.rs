@@ -0,0 +1,829 @@
+use winit::dpi::PhysicalSize;
+use winit::event::{ElementState, Ime, KeyEvent};
+use winit::keyboard::{Key, ModifiersState, NamedKey};
+use crate::designer::point::Measurement;
+use crate::designer::Designer;
+use crate::graphics::clip::ClipId;
+use crate::graphics::color::Color;
+use crate::graphics::path::PathError;
+use crate::input::clipboard::SharedClipboard;
+use crate::render::wgpu::WgpuRenderer;
+use crate::scene::Rect;
+use crate::text::font::Font;
+use crate::text::layout::{layout_text, TextLayout, TextStyle};
+use crate::widgets::theme::Theme;
+use crate::widgets::{Drawn, Widget, WidgetBase};
+
+const UNDO_LIMIT: usize = 100;
+
+pub type ChangeHandler = Box<dyn FnMut(&str) + Send>;
+
+#[derive(Clone, Debug)]
+pub struct TextInputStyle {
+    pub text: TextStyle,
+    pub text_color: Color,
+    pub background_color: Color,
+    pub selection_color: Color,
+    pub caret_color: Color,
+    pub caret_width: f32,
+    pub padding: f32
+}
+
+impl TextInputStyle {
+    pub fn new(size: f32) -> Self {
+        TextInputStyle {
+            text: TextStyle::new(size),
+            text_color: Color::BLACK,
+            background_color: Color::WHITE,
+            selection_color: Color::rgb(179, 204, 255),
+            caret_color: Color::BLACK,
+            caret_width: 1.0,
+            padding: 4.0
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Motion {
+    Left,
+    Right,
+    WordLeft,
+    WordRight,
+    Up,
+    Down,
+    LineStart,
+    LineEnd,
+    DocumentStart,
+    DocumentEnd
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum EditCommand {
+    Insert(String),
+    NewLine,
+    DeleteBackward,
+    DeleteForward,
+    DeleteWordBackward,
+    DeleteWordForward,
+    // the flag extends the selection instead of collapsing it
+    Move(Motion, bool),
+    SelectAll,
+    Copy,
+    Cut,
+    Paste,
+    Undo,
+    Redo
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum EditKind {
+    Typing,
+    Deleting,
+    Other
+}
+
+#[derive(Clone, Debug)]
+struct Snapshot {
+    text: String,
+    caret: usize,
+    anchor: usize
+}
+
+#[derive(Clone, Debug)]
+struct Composition {
+    text: String,
+    // byte offset of the ime cursor inside the composed text
+    cursor: usize
+}
+
+pub struct TextInput {
+    text: String,
+    multiline: bool,
+    // caret and anchor are byte offsets, the selection spans between them
+    caret: usize,
+    anchor: usize,
+    preferred_x: Option<f32>,
+    composition: Option<Composition>,
+    undo_stack: Vec<Snapshot>,
+    redo_stack: Vec<Snapshot>,
+    last_edit: Option<EditKind>,
+    clipboard: SharedClipboard,
+    base: WidgetBase,
+    font: Font,
+    style: TextInputStyle,
+    screen_size: PhysicalSize<u32>,
+    layout: TextLayout,
+    scroll: [f32; 2],
+    dragging: bool,
+    // the text is drawn under a clip of the field, made again when the bounds change
+    clip: Option<(Rect, ClipId)>,
+    on_change: Option<ChangeHandler>
+}
+
+impl TextInput {
+    pub fn new(font: Font, style: TextInputStyle, bounds: Rect, multiline: bool, clipboard: SharedClipboard) -> Self {
+        let mut input = TextInput {
+            text: String::new(),
+            multiline,
+            caret: 0,
+            anchor: 0,
+            preferred_x: None,
+            composition: None,
+            undo_stack: vec![],
+            redo_stack: vec![],
+            last_edit: None,
+            clipboard,
+            base: WidgetBase::new(bounds, Theme::default()),
+            font,
+            style,
+            screen_size: PhysicalSize::new(1, 1),
+            layout: TextLayout::default(),
+            scroll: [0.0, 0.0],
+            dragging: false,
+            clip: None,
+            on_change: None
+        };
+        input.relayout();
+        input
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn set_text(&mut self, text: &str) {
+        self.text = self.sanitize(text);
+        self.caret = self.text.len();
+        self.anchor = self.caret;
+        self.composition = None;
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.last_edit = None;
+        self.changed();
+    }
+
+    pub fn caret(&self) -> usize {
+        self.caret
+    }
+
+    pub fn selection(&self) -> (usize, usize) {
+        (self.caret.min(self.anchor), self.caret.max(self.anchor))
+    }
+
+    pub fn selected_text(&self) -> &str {
+        let (start, end) = self.selection();
+        &self.text[start..end]
+    }
+
+    pub fn set_selection(&mut self, anchor: usize, caret: usize) {
+        self.anchor = self.clamp_index(anchor);
+        self.caret = self.clamp_index(caret);
+        self.preferred_x = None;
+        self.scroll_to_caret();
+        self.base.dirty = true;
+    }
+
+    pub fn set_screen_size(&mut self, screen_size: PhysicalSize<u32>) {
+        self.screen_size = screen_size;
+        self.relayout();
+    }
+
+    pub fn is_focused(&self) -> bool {
+        self.base.state.focused
+    }
+
+    pub fn on_change(&mut self, handler: impl FnMut(&str) + Send + 'static) {
+        self.on_change = Some(Box::new(handler));
+    }
+
+    // the area the platform should place its candidate window next to, in window pixels
+    pub fn ime_cursor_area(&self) -> Rect {
+        let [x, y] = self.layout.caret_position(self.display_caret());
+        let [origin_x, origin_y] = self.text_origin();
+        let height = self.layout.lines.first().map(|l| l.height).unwrap_or(self.style.text.size);
+
+        Rect::new(origin_x + x, origin_y + y, self.style.caret_width, height)
+    }
+
+    // places the caret at the point, a shift click keeps the anchor to extend the selection
+    pub fn select_at(&mut self, x: f32, y: f32, extend: bool) -> bool {
+        if !self.base.bounds.contains(x, y) {
+            return false;
+        }
+
+        // clicking ends the composition, the platform commits or drops it when the caret moves
+        self.end_composition();
+        let index = self.index_at(x, y);
+        self.caret = index;
+        if !extend {
+            self.anchor = index;
+        }
+        self.preferred_x = None;
+        self.last_edit = None;
+        self.dragging = true;
+        self.scroll_to_caret();
+        self.base.dirty = true;
+        true
+    }
+
+    pub fn apply(&mut self, command: EditCommand) {
+        // motions read the layout, which has to match the text without the composition
+        self.end_composition();
+        match command {
+            EditCommand::Insert(text) => {
+                let text = self.sanitize(&text);
+                self.record(EditKind::Typing);
+                self.replace_selection(&text);
+
+                // whitespace closes the current undo group so words are undone one at a time
+                if text.chars().all(char::is_whitespace) {
+                    self.last_edit = None;
+                }
+            }
+            EditCommand::NewLine => {
+                if self.multiline {
+                    self.record(EditKind::Other);
+                    self.replace_selection("\n");
+                }
+            }
+            EditCommand::DeleteBackward => self.delete(Motion::Left),
+            EditCommand::DeleteForward => self.delete(Motion::Right),
+            EditCommand::DeleteWordBackward => self.delete(Motion::WordLeft),
+            EditCommand::DeleteWordForward => self.delete(Motion::WordRight),
+            EditCommand::Move(motion, extend) => {
+                let (start, end) = self.selection();
+                let collapse = !extend && start != end;
+
+                self.caret = match motion {
+                    Motion::Left if collapse => start,
+                    Motion::Right if collapse => end,
+                    _ => self.motion_target(motion)
+                };
+                if !extend {
+                    self.anchor = self.caret;
+                }
+                if !matches!(motion, Motion::Up | Motion::Down) {
+                    self.preferred_x = None;
+                }
+                self.last_edit = None;
+                self.scroll_to_caret();
+            }
+            EditCommand::SelectAll => {
+                self.anchor = 0;
+                self.caret = self.text.len();
+            }
+            EditCommand::Copy => self.copy(),
+            EditCommand::Cut => {
+                if self.caret != self.anchor {
+                    self.copy();
+                    self.record(EditKind::Other);
+                    self.replace_selection("");
+                }
+            }
+            EditCommand::Paste => {
+                let contents = self.clipboard.lock().unwrap().read();
+                if let Some(contents) = contents {
+                    let contents = self.sanitize(&contents);
+                    self.record(EditKind::Other);
+                    self.replace_selection(&contents);
+                }
+            }
+            EditCommand::Undo => self.undo(),
+            EditCommand::Redo => self.redo()
+        }
+
+        self.base.dirty = true;
+    }
+
+    pub fn undo(&mut self) {
+        if let Some(snapshot) = self.undo_stack.pop() {
+            let current = self.snapshot();
+            self.redo_stack.push(current);
+            self.restore(snapshot);
+        }
+    }
+
+    pub fn redo(&mut self) {
+        if let Some(snapshot) = self.redo_stack.pop() {
+            let current = self.snapshot();
+            self.undo_stack.push(current);
+            self.restore(snapshot);
+        }
+    }
+
+    // selection highlight rectangles relative to the text origin, one per line;
+    // selections inside mixed direction lines are approximated by a single span
+    pub fn selection_rects(&self) -> Vec<Rect> {
+        let (start, end) = self.selection();
+        if start == end || self.composition.is_some() {
+            return vec![];
+        }
+
+        self.layout.lines
+            .iter()
+            .filter(|l| l.text.start < end && start <= l.text.end)
+            .filter_map(|line| {
+                let from = self.layout.caret_position(start.max(line.text.start))[0];
+                let to = if end > line.text.end {
+                    if line.rtl { line.x } else { line.x + line.width }
+                } else {
+                    self.layout.caret_position(end)[0]
+                };
+
+                let (left, right) = (from.min(to), from.max(to));
+                if right - left <= 0.0 {
+                    return None;
+                }
+                Some(Rect::new(left, line.top, right - left, line.height))
+            })
+            .collect()
+    }
+
+    fn text_origin(&self) -> [f32; 2] {
+        [
+            self.base.bounds.x + self.style.padding - self.scroll[0],
+            self.base.bounds.y + self.style.padding - self.scroll[1]
+        ]
+    }
+
+    fn index_at(&self, x: f32, y: f32) -> usize {
+        let [origin_x, origin_y] = self.text_origin();
+        let Some(composition) = &self.composition else {
+            return self.layout.hit_test(&self.text, x - origin_x, y - origin_y);
+        };
+
+        // the layout holds the composed text, so hits inside it land on the caret and later ones shift back
+        let mut display = self.text.clone();
+        display.insert_str(self.caret, &composition.text);
+        let index = self.layout.hit_test(&display, x - origin_x, y - origin_y);
+        match index {
+            _ if index <= self.caret => index,
+            _ if index < self.caret + composition.text.len() => self.caret,
+            _ => index - composition.text.len()
+        }
+    }
+
+    fn end_composition(&mut self) {
+        if self.composition.take().is_some() {
+            self.relayout();
+        }
+    }
+
+    fn clamp_index(&self, index: usize) -> usize {
+        let mut index = index.min(self.text.len());
+        while !self.text.is_char_boundary(index) {
+            index -= 1;
+        }
+        index
+    }
+
+    fn sanitize(&self, text: &str) -> String {
+        if self.multiline {
+            text.replace("\r\n", "\n")
+        } else {
+            text.replace("\r\n", " ").replace(['\n', '\r'], " ")
+        }
+    }
+
+    // the caret inside the displayed text, which includes an active ime composition
+    fn display_caret(&self) -> usize {
+        match &self.composition {
+            Some(composition) => self.caret + composition.cursor,
+            None => self.caret
+        }
+    }
+
+    fn relayout(&mut self) {
+        let mut style = self.style.text.clone();
+        style.wrap = self.multiline;
+        style.max_width = if self.multiline {
+            Some(Measurement::Pixels(self.base.bounds.width - self.style.padding * 2.0))
+        } else {
+            None
+        };
+
+        self.layout = match &self.composition {
+            Some(composition) => {
+                let mut display = self.text.clone();
+                display.insert_str(self.caret, &composition.text);
+                layout_text(&self.font, &display, &style, self.screen_size)
+            }
+            None => layout_text(&self.font, &self.text, &style, self.screen_size)
+        };
+        self.base.dirty = true;
+    }
+
+    fn scroll_to_caret(&mut self) {
+        let [x, y] = self.layout.caret_position(self.display_caret());
+        let line_height = self.layout.lines.first().map(|l| l.height).unwrap_or(self.style.text.size);
+        let visible_width = self.base.bounds.width - self.style.padding * 2.0;
+        let visible_height = self.base.bounds.height - self.style.padding * 2.0;
+
+        if x < self.scroll[0] {
+            self.scroll[0] = x;
+        } else if x + self.style.caret_width > self.scroll[0] + visible_width {
+            self.scroll[0] = x + self.style.caret_width - visible_width;
+        }
+
+        if y < self.scroll[1] {
+            self.scroll[1] = y;
+        } else if y + line_height > self.scroll[1] + visible_height {
+            self.scroll[1] = y + line_height - visible_height;
+        }
+    }
+
+    fn changed(&mut self) {
+        self.relayout();
+        self.scroll_to_caret();
+        if let Some(handler) = &mut self.on_change {
+            handler(&self.text);
+        }
+    }
+
+    fn replace_selection(&mut self, text: &str) {
+        let (start, end) = self.selection();
+        self.text.replace_range(start..end, text);
+        self.caret = start + text.len();
+        self.anchor = self.caret;
+        self.preferred_x = None;
+        self.changed();
+    }
+
+    fn delete(&mut self, motion: Motion) {
+        if self.caret == self.anchor {
+            self.caret = self.motion_target(motion);
+            if self.caret == self.anchor {
+                return;
+            }
+        }
+
+        self.record(EditKind::Deleting);
+        self.replace_selection("");
+    }
+
+    fn copy(&mut self) {
+        if self.caret != self.anchor {
+            let selected = self.selected_text().to_string();
+            self.clipboard.lock().unwrap().write(selected);
+        }
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            text: self.text.clone(),
+            caret: self.caret,
+            anchor: self.anchor
+        }
+    }
+
+    fn restore(&mut self, snapshot: Snapshot) {
+        self.text = snapshot.text;
+        self.caret = snapshot.caret;
+        self.anchor = snapshot.anchor;
+        self.composition = None;
+        self.last_edit = None;
+        self.changed();
+    }
+
+    // consecutive edits of the same kind share a single undo step
+    fn record(&mut self, kind: EditKind) {
+        if kind == EditKind::Other || self.last_edit != Some(kind) {
+            let snapshot = self.snapshot();
+            self.undo_stack.push(snapshot);
+            if self.undo_stack.len() > UNDO_LIMIT {
+                self.undo_stack.remove(0);
+            }
+        }
+
+        self.redo_stack.clear();
+        self.last_edit = Some(kind);
+    }
+
+    fn motion_target(&mut self, motion: Motion) -> usize {
+        let text = &self.text;
+        match motion {
+            Motion::Left => text[..self.caret].char_indices().next_back().map(|(i, _)| i).unwrap_or(0),
+            Motion::Right => text[self.caret..].chars().next().map(|c| self.caret + c.len_utf8()).unwrap_or(self.caret),
+            Motion::WordLeft => {
+                let before = text[..self.caret].trim_end();
+                before
+                    .char_indices()
+                    .rev()
+                    .find(|(_, c)| c.is_whitespace())
+                    .map(|(i, c)| i + c.len_utf8())
+                    .unwrap_or(0)
+            }
+            Motion::WordRight => {
+                let after = &text[self.caret..];
+                let word_start = after.len() - after.trim_start().len();
+                after[word_start..]
+                    .find(char::is_whitespace)
+                    .map(|i| self.caret + word_start + i)
+                    .unwrap_or(text.len())
+            }
+            Motion::LineStart | Motion::LineEnd => {
+                let index = self.layout.line_at_index(self.caret);
+                let Some(line) = self.layout.lines.get(index) else {
+                    return self.caret;
+                };
+
+                if motion == Motion::LineStart {
+                    return line.text.start;
+                }
+
+                // wrapped lines end before their trailing whitespace so the caret stays on them
+                let wraps = self.layout.lines.get(index + 1).is_some_and(|n| n.text.start == line.text.end);
+                if wraps {
+                    line.text.start + text[line.text.clone()].trim_end().len()
+                } else {
+                    line.text.end
+                }
+            }
+            Motion::Up | Motion::Down => {
+                let [x, _] = self.layout.caret_position(self.caret);
+                let x = *self.preferred_x.get_or_insert(x);
+                let line = self.layout.line_at_index(self.caret);
+
+                let target = match motion {
+                    Motion::Up if line == 0 => return 0,
+                    Motion::Up => line - 1,
+                    _ if line + 1 >= self.layout.lines.len() => return text.len(),
+                    _ => line + 1
+                };
+
+                let target = &self.layout.lines[target];
+                self.layout.hit_test(text, x, target.top + target.height / 2.0)
+            }
+            Motion::DocumentStart => 0,
+            Motion::DocumentEnd => text.len()
+        }
+    }
+}
+
+impl Widget for TextInput {
+    fn base(&self) -> &WidgetBase {
+        &self.base
+    }
+
+    fn base_mut(&mut self) -> &mut WidgetBase {
+        &mut self.base
+    }
+
+    fn handle_key(&mut self, event: &KeyEvent, modifiers: ModifiersState) -> bool {
+        if self.base.state.disabled || event.state != ElementState::Pressed {
+            return false;
+        }
+
+        let command = modifiers.control_key() || modifiers.super_key();
+        let extend = modifiers.shift_key();
+
+        let edit = match &event.logical_key {
+            Key::Named(NamedKey::ArrowLeft) if command => EditCommand::Move(Motion::WordLeft, extend),
+            Key::Named(NamedKey::ArrowRight) if command => EditCommand::Move(Motion::WordRight, extend),
+            Key::Named(NamedKey::Home) if command => EditCommand::Move(Motion::DocumentStart, extend),
+            Key::Named(NamedKey::End) if command => EditCommand::Move(Motion::DocumentEnd, extend),
+            Key::Named(NamedKey::Backspace) if command => EditCommand::DeleteWordBackward,
+            Key::Named(NamedKey::Delete) if command => EditCommand::DeleteWordForward,
+            Key::Named(NamedKey::ArrowLeft) => EditCommand::Move(Motion::Left, extend),
+            Key::Named(NamedKey::ArrowRight) => EditCommand::Move(Motion::Right, extend),
+            Key::Named(NamedKey::ArrowUp) if self.multiline => EditCommand::Move(Motion::Up, extend),
+            Key::Named(NamedKey::ArrowDown) if self.multiline => EditCommand::Move(Motion::Down, extend),
+            Key::Named(NamedKey::Home) => EditCommand::Move(Motion::LineStart, extend),
+            Key::Named(NamedKey::End) => EditCommand::Move(Motion::LineEnd, extend),
+            Key::Named(NamedKey::Backspace) => EditCommand::DeleteBackward,
+            Key::Named(NamedKey::Delete) => EditCommand::DeleteForward,
+            Key::Named(NamedKey::Enter) if self.multiline => EditCommand::NewLine,
+            Key::Character(c) if command => match c.to_lowercase().as_str() {
+                "a" => EditCommand::SelectAll,
+                "c" => EditCommand::Copy,
+                "x" => EditCommand::Cut,
+                "v" => EditCommand::Paste,
+                "z" if extend => EditCommand::Redo,
+                "z" => EditCommand::Undo,
+                "y" => EditCommand::Redo,
+                _ => return false
+            },
+            _ => match &event.text {
+                Some(text) if !text.is_empty() && !text.chars().any(char::is_control) => {
+                    EditCommand::Insert(text.to_string())
+                }
+                _ => return false
+            }
+        };
+
+        self.apply(edit);
+        true
+    }
+
+    fn handle_mouse_down(&mut self, x: f32, y: f32) -> bool {
+        if self.base.state.disabled || !self.select_at(x, y, false) {
+            return false;
+        }
+        self.base.take_focus();
+        true
+    }
+
+    fn handle_mouse_move(&mut self, x: f32, y: f32) -> bool {
+        if !self.dragging {
+            return false;
+        }
+
+        self.caret = self.index_at(x, y);
+        self.scroll_to_caret();
+        self.base.dirty = true;
+        true
+    }
+
+    fn handle_mouse_up(&mut self, _x: f32, _y: f32) -> bool {
+        std::mem::replace(&mut self.dragging, false)
+    }
+
+    fn draw_content(&mut self, designer: &Designer, renderer: &mut WgpuRenderer, drawn: &mut Drawn) -> Result<(), PathError> {
+        if renderer.size != self.screen_size {
+            self.set_screen_size(renderer.size);
+        }
+
+        let bounds = self.base.bounds;
+        drawn.instances.push(designer.create_rect(renderer, bounds, self.style.background_color));
+
+        // text scrolled past the padding is cut off at the edges of the field
+        let clip = match self.clip.take() {
+            Some((clipped, clip)) if clipped == bounds => clip,
+            previous => {
+                if let Some((_, clip)) = previous {
+                    renderer.remove_clip(clip);
+                }
+                let clip = renderer.push_rect_clip(bounds);
+                renderer.pop_clip();
+                clip
+            }
+        };
+        renderer.reenter_clip(clip);
+
+        let origin = self.text_origin();
+        for rect in self.selection_rects() {
+            let rect = Rect::new(rect.x + origin[0], rect.y + origin[1], rect.width, rect.height);
+            drawn.instances.push(designer.create_rect(renderer, rect, self.style.selection_color));
+        }
+
+        let text = renderer.add_text(
+            &self.font,
+            &self.layout.glyphs,
+            origin,
+            self.style.text.size,
+            self.style.text_color.to_linear_rgb()
+        );
+        drawn.texts.extend(text);
+
+        if self.base.state.focused {
+            let caret = self.ime_cursor_area();
+            drawn.instances.push(designer.create_rect(renderer, caret, self.style.caret_color));
+        }
+
+        renderer.pop_clip();
+        self.clip = Some((bounds, clip));
+        Ok(())
+    }
+
+    fn accepts_ime(&self) -> bool {
+        true
+    }
+
+    fn handle_ime(&mut self, ime: &Ime) -> bool {
+        if !self.base.state.focused {
+            return false;
+        }
+
+        match ime {
+            Ime::Enabled => {}
+            Ime::Preedit(text, cursor) => {
+                if text.is_empty() {
+                    self.composition = None;
+                } else {
+                    if self.composition.is_none() && self.caret != self.anchor {
+                        self.record(EditKind::Other);
+                        self.replace_selection("");
+                    }
+
+                    self.composition = Some(Composition {
+                        text: text.clone(),
+                        cursor: cursor.map(|(_, end)| end).unwrap_or(text.len())
+                    });
+                }
+                self.relayout();
+                self.scroll_to_caret();
+            }
+            Ime::Commit(text) => {
+                self.composition = None;
+                self.apply(EditCommand::Insert(text.clone()));
+            }
+            Ime::Disabled => {
+                self.composition = None;
+                self.relayout();
+            }
+        }
+
+        self.base.dirty = true;
+        true
+    }
+
+    fn clear(&mut self, renderer: &mut WgpuRenderer) {
+        self.base.drawn.clear(renderer);
+        if let Some((_, clip)) = self.clip.take() {
+            renderer.remove_clip(clip);
+        }
+        self.base.dirty = true;
+    }
+
+    fn set_bounds(&mut self, bounds: Rect) {
+        self.base.bounds = bounds;
+        if let Some(node) = &self.base.node {
+            node.set_bounds(bounds);
+        }
+        self.relayout();
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.base.state.focused = focused;
+        if !focused {
+            self.composition = None;
+            self.dragging = false;
+            self.relayout();
+        }
+        self.base.dirty = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use crate::input::clipboard::InMemoryClipboard;
+    use crate::input::focus::FocusManager;
+    use crate::text::font::test_font;
+    use crate::widgets::attach;
+
+    fn input(text: &str) -> TextInput {
+        let font = test_font();
+        let bounds = Rect::new(0.0, 0.0, 400.0, 40.0);
+        let mut input = TextInput::new(font, TextInputStyle::new(16.0), bounds, false, InMemoryClipboard::shared());
+        input.set_screen_size(PhysicalSize::new(800, 600));
+        input.set_focused(true);
+        input.set_text(text);
+        input
+    }
+
+    #[test]
+    fn clicking_during_composition_ends_it() {
+        let mut input = input("ab");
+        input.set_selection(0, 0);
+        input.handle_ime(&Ime::Preedit("かんじかんじかんじ".to_string(), None));
+
+        assert!(input.select_at(390.0, 20.0, false));
+        assert_eq!(input.caret(), 2);
+        assert_eq!(input.text(), "ab");
+    }
+
+    #[test]
+    fn moving_during_composition_uses_the_committed_text() {
+        let mut input = input("ab");
+        input.handle_ime(&Ime::Preedit("かんじかんじ".to_string(), None));
+        input.apply(EditCommand::Move(Motion::LineEnd, false));
+        assert_eq!(input.caret(), 2);
+    }
+
+    #[test]
+    fn cut_and_paste_go_through_the_clipboard() {
+        let mut input = input("hello world");
+        input.set_selection(0, 5);
+        input.apply(EditCommand::Cut);
+        assert_eq!(input.text(), " world");
+
+        input.set_selection(6, 6);
+        input.apply(EditCommand::Paste);
+        assert_eq!(input.text(), " worldhello");
+        assert_eq!(input.clipboard.lock().unwrap().read().as_deref(), Some("hello"));
+    }
+
+    #[test]
+    fn pasted_newlines_become_spaces_on_one_line() {
+        let mut input = input("");
+        input.clipboard.lock().unwrap().write("a\r\nb\nc".to_string());
+        input.apply(EditCommand::Paste);
+        assert_eq!(input.text(), "a b c");
+    }
+
+    #[test]
+    fn attached_inputs_take_ime_while_focused() {
+        let focus = FocusManager::new();
+        let input = Arc::new(Mutex::new(input("")));
+        input.lock().unwrap().set_focused(false);
+
+        let handle = attach(&input, &focus, 0);
+        assert!(!focus.accepts_ime());
+        assert!(handle.focus());
+        focus.deliver_notifications();
+        assert!(focus.accepts_ime());
+        assert!(input.lock().unwrap().is_focused());
+    }
+}
@@ -0,0 +1,125 @@
+use crate::graphics::color::Color;
+use crate::text::layout::TextStyle;
+use crate::widgets::WidgetState;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum WidgetKind {
+    Button,
+    Label,
+    Checkbox,
+    Radio,
+    Toggle,
+    Slider,
+    ScrollView,
+    // header cells and rows of a table
+    Table
+}
+
+// the colors and sizes a widget draws with in its current state
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct WidgetStyle {
+    pub background: Color,
+    pub border: Color,
+    pub border_width: f32,
+    pub foreground: Color,
+    // checked boxes, selected radios, toggles that are on and the filled part of sliders
+    pub accent: Color,
+    // drawn on top of the accent, like the check mark
+    pub on_accent: Color,
+    pub corner_radius: f32
+}
+
+// lets an application restyle widgets per kind and state after the theme is applied
+pub type StyleHook = Box<dyn Fn(WidgetKind, WidgetState, &mut WidgetStyle) + Send>;
+
+#[derive(Clone, Debug)]
+pub struct Theme {
+    pub text: TextStyle,
+    pub foreground: Color,
+    pub background: Color,
+    // raised surfaces like buttons
+    pub surface: Color,
+    pub border: Color,
+    pub accent: Color,
+    pub on_accent: Color,
+    pub disabled: Color,
+    pub corner_radius: f32,
+    pub border_width: f32,
+    // space between a control and its label, and inside buttons
+    pub padding: f32
+}
+
+impl Theme {
+    pub fn light() -> Self {
+        Theme {
+            text: TextStyle::new(16.0),
+            foreground: Color::rgb(28, 28, 30),
+            background: Color::WHITE,
+            surface: Color::rgb(242, 242, 247),
+            border: Color::rgb(199, 199, 204),
+            accent: Color::rgb(0, 122, 255),
+            on_accent: Color::WHITE,
+            disabled: Color::rgb(174, 174, 178),
+            corner_radius: 6.0,
+            border_width: 1.0,
+            padding: 8.0
+        }
+    }
+
+    pub fn dark() -> Self {
+        Theme {
+            foreground: Color::rgb(242, 242, 247),
+            background: Color::rgb(28, 28, 30),
+            surface: Color::rgb(44, 44, 46),
+            border: Color::rgb(72, 72, 74),
+            accent: Color::rgb(10, 132, 255),
+            disabled: Color::rgb(99, 99, 102),
+            ..Theme::light()
+        }
+    }
+
+    pub fn resolve(&self, kind: WidgetKind, state: WidgetState) -> WidgetStyle {
+        let mut style = WidgetStyle {
+            background: match kind {
+                WidgetKind::Button | WidgetKind::Table => self.surface,
+                WidgetKind::Label | WidgetKind::ScrollView => Color::TRANSPARENT,
+                _ => self.background
+            },
+            border: match kind {
+                WidgetKind::Label => Color::TRANSPARENT,
+                _ => self.border
+            },
+            border_width: self.border_width,
+            foreground: self.foreground,
+            accent: self.accent,
+            on_accent: self.on_accent,
+            corner_radius: self.corner_radius
+        };
+
+        if state.disabled {
+            style.foreground = self.disabled;
+            style.accent = self.disabled;
+            style.border = style.border.lerp(style.background, 0.5);
+            return style;
+        }
+
+        // hover and press shade towards the foreground so they work on light and dark themes
+        if state.pressed {
+            style.background = style.background.lerp(self.foreground, 0.12);
+            style.accent = style.accent.lerp(self.foreground, 0.2);
+        } else if state.hovered {
+            style.background = style.background.lerp(self.foreground, 0.06);
+            style.accent = style.accent.lerp(self.foreground, 0.1);
+        }
+        if state.focused && kind != WidgetKind::Label {
+            style.border = self.accent;
+        }
+        style
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::light()
+    }
+}
@@ -0,0 +1,135 @@
+use winit::event::KeyEvent;
+use winit::keyboard::ModifiersState;
+use crate::designer::Designer;
+use crate::graphics::path::PathError;
+use crate::graphics::sdf::{Border, ShapeStyle};
+use crate::render::wgpu::WgpuRenderer;
+use crate::scene::Rect;
+use crate::text::font::Font;
+use crate::widgets::theme::{Theme, WidgetKind};
+use crate::widgets::{is_activation_key, Drawn, ToggleHandler, Widget, WidgetBase};
+
+// a switch with a sliding knob, the label sits to the right of the track
+pub struct Toggle {
+    base: WidgetBase,
+    font: Font,
+    label: String,
+    on: bool,
+    on_toggle: Option<ToggleHandler>
+}
+
+impl Toggle {
+    pub fn new(font: Font, label: &str, bounds: Rect, theme: Theme) -> Self {
+        Toggle {
+            base: WidgetBase::new(bounds, theme),
+            font,
+            label: label.to_string(),
+            on: false,
+            on_toggle: None
+        }
+    }
+
+    pub fn is_on(&self) -> bool {
+        self.on
+    }
+
+    // changes the state without calling the handler
+    pub fn set_on(&mut self, on: bool) {
+        if self.on != on {
+            self.on = on;
+            self.base.mark_dirty();
+        }
+    }
+
+    pub fn on_toggle(&mut self, handler: impl FnMut(bool) + Send + 'static) {
+        self.on_toggle = Some(Box::new(handler));
+    }
+
+    pub fn toggle(&mut self) {
+        if self.base.state.disabled {
+            return;
+        }
+        self.set_on(!self.on);
+        if let Some(handler) = &mut self.on_toggle {
+            handler(self.on);
+        }
+    }
+
+    fn track_bounds(&self) -> Rect {
+        let bounds = self.base.bounds;
+        let height = (self.base.theme().text.size * 1.4).min(bounds.height);
+        Rect::new(bounds.x, bounds.y + (bounds.height - height) / 2.0, height * 1.75, height)
+    }
+}
+
+impl Widget for Toggle {
+    fn base(&self) -> &WidgetBase {
+        &self.base
+    }
+
+    fn base_mut(&mut self) -> &mut WidgetBase {
+        &mut self.base
+    }
+
+    fn handle_key(&mut self, event: &KeyEvent, _modifiers: ModifiersState) -> bool {
+        if self.base.state.disabled || !is_activation_key(event) {
+            return false;
+        }
+        self.toggle();
+        true
+    }
+
+    fn handle_mouse_down(&mut self, x: f32, y: f32) -> bool {
+        if self.base.state.disabled || !self.base.bounds.contains(x, y) {
+            return false;
+        }
+        self.base.set_pressed(true);
+        self.base.take_focus();
+        true
+    }
+
+    fn handle_mouse_move(&mut self, x: f32, y: f32) -> bool {
+        self.base.set_hovered(self.base.bounds.contains(x, y))
+    }
+
+    fn handle_mouse_up(&mut self, x: f32, y: f32) -> bool {
+        if !self.base.state.pressed {
+            return false;
+        }
+        self.base.set_pressed(false);
+        if self.base.bounds.contains(x, y) {
+            self.toggle();
+        }
+        true
+    }
+
+    fn draw_content(&mut self, designer: &Designer, renderer: &mut WgpuRenderer, drawn: &mut Drawn) -> Result<(), PathError> {
+        let style = self.base.style(WidgetKind::Toggle);
+        let track = self.track_bounds();
+        let radius = track.height / 2.0;
+
+        if self.on {
+            drawn.rounded_rect(designer, renderer, track, radius, style.accent, None);
+        } else {
+            drawn.rounded_rect(
+                designer,
+                renderer,
+                track,
+                radius,
+                style.border.lerp(style.background, 0.4),
+                Some(Border::new(style.border_width, style.border))
+            );
+        }
+
+        let inset = 2.0;
+        let knob_radius = radius - inset;
+        let knob_x = if self.on { track.right() - radius } else { track.x + radius };
+        let knob_color = if self.on { style.on_accent } else { style.background };
+        drawn.circle(designer, renderer, [knob_x, track.y + radius], knob_radius, &ShapeStyle::fill(knob_color));
+
+        let bounds = self.base.bounds;
+        let x = track.right() + self.base.theme().padding;
+        drawn.text(renderer, &self.font, &self.label, &self.base.theme().text, Rect::new(x, bounds.y, bounds.right() - x, bounds.height), style.foreground);
+        Ok(())
+    }
+}
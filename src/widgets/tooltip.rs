@@ -0,0 +1,298 @@
+use std::sync::{Arc, Mutex};
+use crate::designer::point::Measurement;
+use crate::designer::Designer;
+use crate::graphics::layer::{LayerId, LayerOptions};
+use crate::render::wgpu::WgpuRenderer;
+use crate::scene::Rect;
+use crate::text::font::Font;
+use crate::text::layout::{layout_text, TextStyle};
+use crate::widgets::popup::place_below;
+use crate::widgets::theme::Theme;
+use crate::widgets::Drawn;
+
+// seconds the pointer has to rest on a region before its tooltip shows
+pub const DEFAULT_DELAY: f32 = 0.5;
+// after a tooltip hides, the next one shows without delay for this long, like moving along a toolbar
+const WARM_TIME: f32 = 0.5;
+// the tooltip shows below the pointer, clear of the cursor
+const CURSOR_HEIGHT: f32 = 20.0;
+const MAX_WIDTH: f32 = 280.0;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TooltipId(pub u32);
+
+struct TooltipEntry {
+    id: TooltipId,
+    bounds: Rect,
+    text: String
+}
+
+struct TooltipState {
+    font: Font,
+    theme: Theme,
+    delay: f32,
+    entries: Vec<TooltipEntry>,
+    hovered: Option<TooltipId>,
+    pointer: [f32; 2],
+    hover_time: f32,
+    warm: f32,
+    shown: Option<TooltipId>,
+    // a press hides the tooltip until the pointer leaves its region
+    suppressed: Option<TooltipId>,
+    overlay: Option<LayerId>,
+    drawn: Drawn,
+    dirty: bool,
+    next_id: u32
+}
+
+impl TooltipState {
+    fn hide(&mut self) -> bool {
+        if self.shown.take().is_none() {
+            return false;
+        }
+        self.warm = WARM_TIME;
+        self.dirty = true;
+        true
+    }
+}
+
+// text shown near the pointer after it rests on a region, drawn in an overlay above everything
+#[derive(Clone)]
+pub struct Tooltips {
+    state: Arc<Mutex<TooltipState>>
+}
+
+impl Tooltips {
+    pub fn new(font: Font, theme: Theme) -> Self {
+        Tooltips {
+            state: Arc::new(Mutex::new(TooltipState {
+                font,
+                theme,
+                delay: DEFAULT_DELAY,
+                entries: vec![],
+                hovered: None,
+                pointer: [0.0, 0.0],
+                hover_time: 0.0,
+                warm: 0.0,
+                shown: None,
+                suppressed: None,
+                overlay: None,
+                drawn: Drawn::default(),
+                dirty: false,
+                next_id: 0
+            }))
+        }
+    }
+
+    // regions registered later win where they overlap
+    pub fn register(&self, bounds: Rect, text: &str) -> TooltipId {
+        let mut state = self.state.lock().unwrap();
+        let id = TooltipId(state.next_id);
+        state.next_id += 1;
+        state.entries.push(TooltipEntry { id, bounds, text: text.to_string() });
+        id
+    }
+
+    pub fn unregister(&self, id: TooltipId) {
+        let mut state = self.state.lock().unwrap();
+        state.entries.retain(|e| e.id != id);
+        if state.hovered == Some(id) {
+            state.hovered = None;
+        }
+        if state.shown == Some(id) {
+            state.hide();
+        }
+    }
+
+    pub fn set_bounds(&self, id: TooltipId, bounds: Rect) {
+        if let Some(entry) = self.state.lock().unwrap().entries.iter_mut().find(|e| e.id == id) {
+            entry.bounds = bounds;
+        }
+    }
+
+    pub fn set_text(&self, id: TooltipId, text: &str) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(entry) = state.entries.iter_mut().find(|e| e.id == id) {
+            entry.text = text.to_string();
+        }
+        if state.shown == Some(id) {
+            state.dirty = true;
+        }
+    }
+
+    pub fn set_delay(&self, delay: f32) {
+        self.state.lock().unwrap().delay = delay.max(0.0);
+    }
+
+    pub fn set_theme(&self, theme: Theme) {
+        let mut state = self.state.lock().unwrap();
+        state.theme = theme;
+        state.dirty = true;
+    }
+
+    pub fn shown(&self) -> Option<TooltipId> {
+        self.state.lock().unwrap().shown
+    }
+
+    pub fn hide(&self) -> bool {
+        self.state.lock().unwrap().hide()
+    }
+
+    // the tooltip stays where it showed while the pointer moves inside its region
+    pub fn handle_mouse_move(&self, x: f32, y: f32) -> bool {
+        let mut state = self.state.lock().unwrap();
+        state.pointer = [x, y];
+        let over = state.entries.iter().rev().find(|e| e.bounds.contains(x, y)).map(|e| e.id);
+        if over == state.hovered {
+            return false;
+        }
+
+        if state.suppressed != over {
+            state.suppressed = None;
+        }
+        state.hovered = over;
+        state.hover_time = 0.0;
+        state.hide()
+    }
+
+    pub fn handle_mouse_down(&self, _x: f32, _y: f32) -> bool {
+        let mut state = self.state.lock().unwrap();
+        state.suppressed = state.hovered;
+        state.hover_time = 0.0;
+        let hidden = state.hide();
+        // a press is not a move along a toolbar, the next tooltip waits for the delay again
+        state.warm = 0.0;
+        hidden
+    }
+
+    // counts the hover time, returns whether a tooltip showed
+    pub fn tick(&self, elapsed: f32) -> bool {
+        let mut state = self.state.lock().unwrap();
+        state.warm = (state.warm - elapsed).max(0.0);
+
+        let Some(hovered) = state.hovered else {
+            return false;
+        };
+        if state.shown.is_some() || state.suppressed == Some(hovered) {
+            return false;
+        }
+        state.hover_time += elapsed;
+        if state.hover_time < state.delay && state.warm <= 0.0 {
+            return false;
+        }
+        state.shown = Some(hovered);
+        state.dirty = true;
+        true
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.state.lock().unwrap().dirty
+    }
+
+    // draw after everything else, every tooltip gets a new overlay so it shows above popups opened before it
+    pub fn draw(&self, designer: &Designer, renderer: &mut WgpuRenderer) {
+        let mut state = self.state.lock().unwrap();
+        if !state.dirty {
+            return;
+        }
+        state.dirty = false;
+        state.drawn.clear(renderer);
+        if let Some(overlay) = state.overlay.take() {
+            renderer.remove_layer(overlay);
+        }
+
+        let Some(text) = state.shown.and_then(|id| state.entries.iter().find(|e| e.id == id)).map(|e| e.text.clone()) else {
+            return;
+        };
+        let theme = state.theme.clone();
+        let padding = theme.padding * 0.75;
+        let style = TextStyle {
+            size: theme.text.size * 0.875,
+            max_width: Some(Measurement::Pixels(MAX_WIDTH - padding * 2.0)),
+            wrap: true,
+            ..theme.text.clone()
+        };
+        let layout = layout_text(&state.font, &text, &style, renderer.size);
+
+        let size = [layout.width + padding * 2.0, layout.height + padding * 2.0];
+        let window = Rect::new(0.0, 0.0, renderer.size.width as f32, renderer.size.height as f32);
+        let [x, y] = state.pointer;
+        let bounds = place_below(Rect::new(x, y, 0.0, CURSOR_HEIGHT), size, window);
+
+        let overlay = renderer.add_overlay(LayerOptions::new(1.0));
+        state.overlay = Some(overlay);
+        renderer.enter_overlay(overlay);
+        state.drawn.rounded_rect(designer, renderer, bounds, theme.corner_radius, theme.foreground.lerp(theme.background, 0.1), None);
+        let origin = [bounds.x + padding, bounds.y + padding];
+        if let Some(id) = renderer.add_text(&state.font, &layout.glyphs, origin, style.size, theme.background.to_linear_rgb()) {
+            state.drawn.texts.push(id);
+        }
+        renderer.exit_overlay();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::text::font::test_font;
+
+    fn tooltips() -> Tooltips {
+        let tooltips = Tooltips::new(test_font(), Theme::default());
+        tooltips.register(Rect::new(0.0, 0.0, 100.0, 20.0), "first");
+        tooltips.register(Rect::new(100.0, 0.0, 100.0, 20.0), "second");
+        tooltips
+    }
+
+    #[test]
+    fn shows_once_the_pointer_rests_for_the_delay() {
+        let tooltips = tooltips();
+        tooltips.handle_mouse_move(10.0, 10.0);
+        assert!(!tooltips.tick(DEFAULT_DELAY / 2.0));
+        tooltips.handle_mouse_move(20.0, 10.0);
+        assert!(tooltips.tick(DEFAULT_DELAY / 2.0));
+        assert_eq!(tooltips.shown(), Some(TooltipId(0)));
+        assert!(tooltips.is_dirty());
+    }
+
+    #[test]
+    fn the_next_tooltip_shows_without_delay_right_after_one_hides() {
+        let tooltips = tooltips();
+        tooltips.handle_mouse_move(10.0, 10.0);
+        tooltips.tick(DEFAULT_DELAY);
+
+        assert!(tooltips.handle_mouse_move(110.0, 10.0));
+        assert!(tooltips.tick(0.01));
+        assert_eq!(tooltips.shown(), Some(TooltipId(1)));
+
+        tooltips.handle_mouse_move(300.0, 10.0);
+        tooltips.tick(WARM_TIME);
+        tooltips.handle_mouse_move(10.0, 10.0);
+        assert!(!tooltips.tick(0.01));
+    }
+
+    #[test]
+    fn a_press_hides_the_tooltip_until_the_pointer_leaves() {
+        let tooltips = tooltips();
+        tooltips.handle_mouse_move(10.0, 10.0);
+        tooltips.tick(DEFAULT_DELAY);
+
+        assert!(tooltips.handle_mouse_down(10.0, 10.0));
+        assert!(!tooltips.tick(DEFAULT_DELAY * 2.0));
+
+        tooltips.handle_mouse_move(110.0, 10.0);
+        tooltips.handle_mouse_move(10.0, 10.0);
+        assert!(tooltips.tick(DEFAULT_DELAY));
+    }
+
+    #[test]
+    fn later_regions_win_where_they_overlap() {
+        let tooltips = tooltips();
+        let top = tooltips.register(Rect::new(50.0, 0.0, 100.0, 20.0), "top");
+        tooltips.handle_mouse_move(60.0, 10.0);
+        tooltips.tick(DEFAULT_DELAY);
+        assert_eq!(tooltips.shown(), Some(top));
+
+        tooltips.unregister(top);
+        assert_eq!(tooltips.shown(), None);
+    }
+}
\ No newline at end of file